@@ -4,7 +4,8 @@
 //!
 //! This module provides a [`Parser`] type, which processes raw Markdown input into a [`Vec`] of
 //! [`Node`]s. These [`Node`]s represent semantic elements such as headings, paragraphs, block
-//! quotes, and code blocks.
+//! quotes, code blocks, lists, tables, horizontal rules, footnote definitions, HTML blocks and
+//! definition lists.
 //!
 //! The parser is built on top of [`pulldown_cmark`].
 //!
@@ -37,24 +38,44 @@
 //!
 //! ## Implementation details
 //!
-//! The [`Parser`] processes [`pulldown_cmark::Event`]s one by one, building up the current
-//! [`Node`] in `current_node`. When an event indicates the start of a new structure (e.g.,
-//! `Event::Start(Tag::Heading {..})`), the [`Parser`] pushes or replaces the current node
-//! with a new one. When an event indicates the end of that structure, the node is finalized
-//! and pushed into [`Parser::output`].
+//! The [`Parser`] processes [`pulldown_cmark::Event`]s one by one, keeping a stack of the blocks
+//! it is nested in. When an event indicates the start of a new structure (e.g.,
+//! `Event::Start(Tag::Heading {..})`), the [`Parser`] opens a new block on top of the stack. When
+//! an event indicates the end of that structure, the block is closed and pushed into its parent
+//! block, or into [`Parser::output`] at the top level.
+//!
+//! YAML frontmatter at the start of the input is parsed into a [`Frontmatter`] and emitted as a
+//! [`MarkdownNode::Frontmatter`] node, see the [`frontmatter`] module.
+//!
+//! Inline styles (emphasis, strong, strikethrough, `==highlight==`, links, wiki-links and images)
+//! are tracked on a stack while the text of a block is parsed, so every [`TextNode`] carries all
+//! the [`Style`]s it is nested in. Inline and display math, footnote references and hard line
+//! breaks are [`TextNode`]s with their own [`Style`].
 //!
 //! Unrecognized events (such as [`InlineHtml`](pulldown_cmark::Event::InlineHtml)) are simply
 //! ignored for the time being.
 //!
+//! The language of a fenced code block is kept as written after the opening fence, and the
+//! `highlight` module, enabled by the `highlight` feature, splits the code into tokens for syntax
+//! highlighting, and the [`math`] module approximates the LaTeX of math with unicode.
+//!
 //! ## Not yet implemented
 //!
-//! - Handling of inline HTML, math blocks, etc.
-//! - Tracking code block language (`lang`) properly (currently set to [`None`]).
+//! - Inline HTML.
 use std::vec::IntoIter;
 
-use pulldown_cmark::{Event, Options, Tag, TagEnd};
+use pulldown_cmark::{CodeBlockKind, Event, LinkType, Tag, TagEnd};
+
+pub mod frontmatter;
+#[cfg(feature = "highlight")]
+pub mod highlight;
+pub mod math;
 
-/// A style that can be applied to [`TextNode`] (code, emphasis, strikethrough, strong).
+pub use frontmatter::Frontmatter;
+pub use pulldown_cmark::Options;
+
+/// A style that can be applied to [`TextNode`] (code, emphasis, strikethrough, strong, highlight,
+/// links, images, math, footnote references and hard line breaks).
 #[derive(Clone, Debug, PartialEq)]
 pub enum Style {
     /// Inline code style (e.g. `code`).
@@ -65,6 +86,29 @@ pub enum Style {
     Strikethrough,
     /// Bold/strong style (e.g. `**strong**`).
     Strong,
+    /// Highlight style (e.g. `==highlight==`).
+    Highlight,
+    /// Superscript style (e.g. `^superscript^`).
+    Superscript,
+    /// Subscript style (e.g. `~subscript~`).
+    Subscript,
+    /// Link text (e.g. `[text](https://example.com)`), holding the destination URL.
+    Link(String),
+    /// Wiki-link text (e.g. `[[Note#Heading|alias]]`), holding the link target (`Note#Heading`).
+    WikiLink(String),
+    /// Alt text of an image (e.g. `![alt](image.png)`), holding the image URL. An image without
+    /// alt text is an empty [`TextNode`] with this style.
+    Image(String),
+    /// Text of an embed (e.g. `![[Note]]` or `![[image.png]]`), holding the embedded target.
+    Embed(String),
+    /// Inline math (e.g. `$e^{i\pi}$`), the text being the LaTeX source.
+    InlineMath,
+    /// Display math (e.g. `$$\sum_i x_i$$`), the text being the LaTeX source.
+    DisplayMath,
+    /// Footnote reference (e.g. `[^1]`), the text being the footnote label.
+    FootnoteReference,
+    /// Hard line break, a trailing backslash or two trailing spaces, the text being `"\n"`.
+    HardBreak,
 }
 
 /// Represents the variant of a list or task item (checked, unchecked, etc.).
@@ -77,10 +121,9 @@ pub enum ItemKind {
     Checked,
     /// A checkbox item that is unchecked using `- [ ]`.
     Unchecked,
-    // TODO: Remove in favor of using List node that has children of nodes
-    /// An ordered list item (e.g., `1. item`), storing the numeric index.
+    /// An item of an ordered list (e.g., `1. item`), storing its number.
     Ordered(u64),
-    /// An unordered list item (e.g., `- item`).
+    /// An item of an unordered list (e.g., `- item`).
     Unordered,
 }
 
@@ -137,24 +180,53 @@ impl From<pulldown_cmark::BlockQuoteKind> for BlockQuoteKind {
 /// Denotes whether a list is ordered or unordered.
 #[derive(Clone, Debug, PartialEq)]
 pub enum ListKind {
-    /// An ordered list item (e.g., `1. item`), storing the numeric index.
+    /// An ordered list (e.g., `1. item`), storing the number of its first item.
     Ordered(u64),
-    /// An unordered list item (e.g., `- item`).
+    /// An unordered list (e.g., `- item`).
     Unordered,
 }
 
+/// The alignment of a table column, set by the colons of the delimiter row.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum Alignment {
+    /// No alignment (e.g. `---`).
+    #[default]
+    None,
+    /// Left alignment (e.g. `:--`).
+    Left,
+    /// Center alignment (e.g. `:-:`).
+    Center,
+    /// Right alignment (e.g. `--:`).
+    Right,
+}
+
+impl From<pulldown_cmark::Alignment> for Alignment {
+    fn from(value: pulldown_cmark::Alignment) -> Self {
+        match value {
+            pulldown_cmark::Alignment::None => Alignment::None,
+            pulldown_cmark::Alignment::Left => Alignment::Left,
+            pulldown_cmark::Alignment::Center => Alignment::Center,
+            pulldown_cmark::Alignment::Right => Alignment::Right,
+        }
+    }
+}
+
 /// A single unit of text that is optionally styled (e.g., code).
 ///
 /// [`TextNode`] can be any combination of sentence, words or characters.
 ///
-/// Usually styled text will be contained in a single [`TextNode`] with the given [`Style`]
-/// property.
+/// Usually styled text will be contained in a single [`TextNode`] with the given [`Style`]s.
+/// Nested styles are all present, e.g. `**bold _and italic_**` yields a node `"and italic"` with
+/// both [`Style::Strong`] and [`Style::Emphasis`].
+///
+/// A soft line break within a block is a [`TextNode`] holding `"\n"`, and a hard line break one
+/// holding `"\n"` with [`Style::HardBreak`].
 #[derive(Clone, Debug, PartialEq, Default)]
 pub struct TextNode {
     /// The literal text content.
     pub content: String,
-    /// Optional inline style of the text.
-    pub style: Option<Style>,
+    /// The inline styles of the text, outermost first. Empty for plain text.
+    pub styles: Vec<Style>,
 }
 
 impl From<&str> for TextNode {
@@ -173,9 +245,25 @@ impl From<String> for TextNode {
 }
 
 impl TextNode {
-    /// Creates a new [`TextNode`] from `content` and optional [`Style`].
-    pub fn new(content: String, style: Option<Style>) -> Self {
-        Self { content, style }
+    /// Creates a new [`TextNode`] from `content` and its [`Style`]s.
+    pub fn new(content: String, styles: Vec<Style>) -> Self {
+        Self { content, styles }
+    }
+
+    /// Returns `true` if the text has the given style.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::markdown::{Style, TextNode};
+    ///
+    /// let node = TextNode::new("bold".into(), vec![Style::Strong]);
+    ///
+    /// assert!(node.has_style(&Style::Strong));
+    /// assert!(!node.has_style(&Style::Emphasis));
+    /// ```
+    pub fn has_style(&self, style: &Style) -> bool {
+        self.styles.contains(style)
     }
 }
 
@@ -221,11 +309,29 @@ impl IntoIterator for Text {
     }
 }
 
+impl<'a> IntoIterator for &'a Text {
+    type Item = &'a TextNode;
+    type IntoIter = std::slice::Iter<'a, TextNode>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
 impl Text {
     /// Appends a [`TextNode`] to the inner text list.
     fn push(&mut self, node: TextNode) {
         self.0.push(node);
     }
+
+    /// Returns `true` if the text has no [`TextNode`]s.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns an iterator over the [`TextNode`]s.
+    pub fn iter(&self) -> std::slice::Iter<'_, TextNode> {
+        self.0.iter()
+    }
 }
 
 /// A [`std::ops::Range`] type for depicting range in [`crate::markdown`].
@@ -283,22 +389,40 @@ impl Node {
         }
     }
 
-    /// Pushes a [`TextNode`] into the markdown node, if it contains a text buffer.
-    ///
-    /// If the markdown node is a [`MarkdownNode::BlockQuote`], the [`TextNode`] will be pushed
-    /// into the last child [`Node`], if any.
-    /// ```
-    pub(crate) fn push_text_node(&mut self, node: TextNode) {
+    /// Returns the child nodes of a block that contains other blocks (block quotes, lists,
+    /// items, footnote definitions and definition lists), or `None` for any other node.
+    pub fn nodes(&self) -> Option<&[Node]> {
+        match &self.markdown_node {
+            MarkdownNode::BlockQuote { nodes, .. }
+            | MarkdownNode::List { nodes, .. }
+            | MarkdownNode::Item { nodes, .. }
+            | MarkdownNode::FootnoteDefinition { nodes, .. }
+            | MarkdownNode::DefinitionList { nodes }
+            | MarkdownNode::Definition { nodes } => Some(nodes),
+            _ => None,
+        }
+    }
+
+    fn nodes_mut(&mut self) -> Option<&mut Vec<Node>> {
+        match &mut self.markdown_node {
+            MarkdownNode::BlockQuote { nodes, .. }
+            | MarkdownNode::List { nodes, .. }
+            | MarkdownNode::Item { nodes, .. }
+            | MarkdownNode::FootnoteDefinition { nodes, .. }
+            | MarkdownNode::DefinitionList { nodes }
+            | MarkdownNode::Definition { nodes } => Some(nodes),
+            _ => None,
+        }
+    }
+
+    fn text_mut(&mut self) -> Option<&mut Text> {
         match &mut self.markdown_node {
-            MarkdownNode::Paragraph { text, .. }
+            MarkdownNode::Paragraph { text }
             | MarkdownNode::Heading { text, .. }
             | MarkdownNode::CodeBlock { text, .. }
-            | MarkdownNode::Item { text, .. } => text.push(node),
-            MarkdownNode::BlockQuote { nodes, .. } => {
-                if let Some(last_node) = nodes.last_mut() {
-                    last_node.push_text_node(node);
-                }
-            }
+            | MarkdownNode::HtmlBlock { text }
+            | MarkdownNode::DefinitionTitle { text } => Some(text),
+            _ => None,
         }
     }
 }
@@ -331,31 +455,65 @@ pub enum MarkdownNode {
         lang: Option<String>,
         text: Text,
     },
+    /// An ordered or unordered list, holding its [`MarkdownNode::Item`]s.
+    List {
+        kind: ListKind,
+        nodes: Vec<Node>,
+    },
     /// A list item node that represents different list item variants including task items.
     ///
-    /// The variant is controlled with the [`ItemKind`] definition. When [`ItemKind`] is [`None`]
-    /// the item should be interpreted as unordered list item: `"- Item"`.
+    /// The variant is controlled with the [`ItemKind`] definition. The text of an item in a tight
+    /// list has no paragraph of its own, so it is given as a [`MarkdownNode::Paragraph`] covering
+    /// the whole item, before any nested blocks.
     Item {
-        kind: Option<ItemKind>,
+        kind: ItemKind,
+        nodes: Vec<Node>,
+    },
+    /// A table with the cells of its header row and of each body row, and the [`Alignment`] of
+    /// each column.
+    Table {
+        alignments: Vec<Alignment>,
+        head: Vec<Text>,
+        rows: Vec<Vec<Text>>,
+    },
+    /// A thematic break, i.e. a horizontal rule (e.g. `---`).
+    Rule,
+    /// A footnote definition (e.g. `[^1]: Text`). References to it are [`TextNode`]s with
+    /// [`Style::FootnoteReference`].
+    FootnoteDefinition {
+        label: String,
+        nodes: Vec<Node>,
+    },
+    /// A block of raw HTML, kept as written.
+    HtmlBlock {
         text: Text,
     },
-}
-
-/// Returns `true` if the [`MarkdownNode`] should be closed upon encountering the given [`TagEnd`].
-fn matches_tag_end(node: &Node, tag_end: &TagEnd) -> bool {
-    matches!(
-        (&node.markdown_node, tag_end),
-        (MarkdownNode::Paragraph { .. }, TagEnd::Paragraph)
-            | (MarkdownNode::Heading { .. }, TagEnd::Heading(..))
-            | (MarkdownNode::BlockQuote { .. }, TagEnd::BlockQuote(..))
-            | (MarkdownNode::CodeBlock { .. }, TagEnd::CodeBlock)
-            | (MarkdownNode::Item { .. }, TagEnd::Item)
-    )
+    /// A definition list, holding [`MarkdownNode::DefinitionTitle`]s each followed by their
+    /// [`MarkdownNode::Definition`]s.
+    DefinitionList {
+        nodes: Vec<Node>,
+    },
+    /// The term of a definition list.
+    DefinitionTitle {
+        text: Text,
+    },
+    /// A definition of the preceding term (e.g. `: Definition`). Like the text of a tight list
+    /// item, text without a paragraph of its own is given as a [`MarkdownNode::Paragraph`].
+    Definition {
+        nodes: Vec<Node>,
+    },
+    /// The YAML frontmatter (note properties) at the start of a note, delimited by `---` lines.
+    ///
+    /// Malformed frontmatter, and metadata blocks further down the note, are emitted as a
+    /// [`MarkdownNode::Paragraph`] holding their source.
+    Frontmatter {
+        frontmatter: Frontmatter,
+    },
 }
 
 /// Parses the given Markdown input into a list of [`Node`]s.
 ///
-/// This is a convenience function for constructing a [`Parser`] and calling [`Parser::parse`].  
+/// This is a convenience function for constructing a [`Parser`] and calling [`Parser::parse`].
 ///
 /// # Examples
 ///
@@ -385,6 +543,14 @@ pub fn from_str(text: &str) -> Vec<Node> {
     Parser::new(text).parse()
 }
 
+/// A block the [`Parser`] is nested in.
+struct Block {
+    node: Node,
+    /// Text directly inside a block of blocks, e.g. the text of an item in a tight list, or the
+    /// text of the current cell of a table.
+    text: Text,
+}
+
 /// A parser that consumes [`pulldown_cmark::Event`]s and produces a [`Vec`] of [`Node`].
 ///
 /// # Examples
@@ -416,7 +582,16 @@ pub struct Parser<'a> {
     /// Contains the completed AST [`Node`]s.
     pub output: Vec<Node>,
     inner: pulldown_cmark::TextMergeWithOffset<'a, pulldown_cmark::OffsetIter<'a>>,
-    current_node: Option<Node>,
+    source: &'a str,
+    /// The blocks the parser is nested in, outermost first.
+    blocks: Vec<Block>,
+    /// The inline styles the parser is currently nested in, outermost first. An open `==`
+    /// highlight is kept here as [`Style::Highlight`] at the position it was opened at.
+    styles: Vec<Style>,
+    /// The style of the image the parser is in, until its alt text is pushed.
+    image: Option<Style>,
+    /// Whether the parser is in the header row of a table.
+    table_head: bool,
 }
 
 impl<'a> Iterator for Parser<'a> {
@@ -434,112 +609,399 @@ impl<'a> Parser<'a> {
     ///
     /// The offset is required to know where the node appears in the provided source text.
     pub fn new(text: &'a str) -> Self {
+        Self::new_ext(text, Options::all())
+    }
+
+    /// Creates a new [`Parser`] from a Markdown input string with the given [`Options`] of the
+    /// underlying [`pulldown_cmark::Parser`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::markdown::{MarkdownNode, Options, Parser, Text};
+    ///
+    /// let mut options = Options::all();
+    /// options.remove(Options::ENABLE_SMART_PUNCTUATION);
+    ///
+    /// let nodes = Parser::new_ext("\"Quoted\"", options).parse();
+    ///
+    /// assert_eq!(
+    ///   nodes[0].markdown_node,
+    ///   MarkdownNode::Paragraph { text: Text::from("\"Quoted\"") }
+    /// );
+    /// ```
+    pub fn new_ext(text: &'a str, options: Options) -> Self {
         let parser = pulldown_cmark::TextMergeWithOffset::new(
-            pulldown_cmark::Parser::new_ext(text, Options::all()).into_offset_iter(),
+            pulldown_cmark::Parser::new_ext(text, options).into_offset_iter(),
         );
 
         Self {
             inner: parser,
+            source: text,
             output: vec![],
-            current_node: None,
+            blocks: vec![],
+            styles: vec![],
+            image: None,
+            table_head: false,
+        }
+    }
+
+    /// Opens a new block, which the following events are parsed into until it is closed.
+    fn open(&mut self, markdown_node: MarkdownNode, range: Range<usize>) {
+        self.styles.retain(|style| *style != Style::Highlight);
+        self.blocks.push(Block {
+            node: Node::new(markdown_node, range),
+            text: Text::default(),
+        });
+    }
+
+    /// Closes the innermost block and pushes it into its parent block, or into the output.
+    fn close(&mut self) {
+        let Some(Block { mut node, text }) = self.blocks.pop() else {
+            return;
+        };
+
+        let range = node.source_range.clone();
+        if let Some(nodes) = node.nodes_mut().filter(|_| !text.is_empty()) {
+            nodes.insert(0, Node::new(MarkdownNode::Paragraph { text }, range));
         }
+
+        self.push_node(node);
     }
 
-    /// Pushes a [`Node`] as a child if the current node is a [`BlockQuote`], otherwise sets it as
-    /// the `current_node`.
+    /// Pushes a completed [`Node`] as a child of the innermost block, or into the output.
     fn push_node(&mut self, node: Node) {
-        if let Some(Node {
-            markdown_node: MarkdownNode::BlockQuote { nodes, .. },
-            ..
-        }) = &mut self.current_node
+        match self
+            .blocks
+            .last_mut()
+            .and_then(|block| block.node.nodes_mut())
         {
-            nodes.push(node);
-        } else {
-            self.set_node(&node);
+            Some(nodes) => nodes.push(node),
+            None => self.output.push(node),
         }
     }
 
-    /// Pushes a [`TextNode`] into the `current_node` if it exists.
+    /// Pushes a [`TextNode`] into the innermost block.
     fn push_text_node(&mut self, node: TextNode) {
-        if let Some(ref mut current) = self.current_node {
-            current.push_text_node(node);
+        if let Some(block) = self.blocks.last_mut() {
+            match block.node.text_mut() {
+                Some(text) => text.push(node),
+                None => block.text.push(node),
+            }
+        }
+    }
+
+    /// Pushes the text with the currently open styles, plus the given extra style.
+    fn push_styled_text(&mut self, content: &str, style: Option<Style>) {
+        if content.is_empty() {
+            return;
+        }
+
+        self.image = None;
+
+        let mut styles = self.styles.clone();
+        styles.extend(style);
+
+        self.push_text_node(TextNode::new(content.to_string(), styles));
+    }
+
+    /// Pushes a text event, splitting it on `==` highlight delimiters.
+    ///
+    /// [`pulldown_cmark`] has no notion of highlights, so an opening `==` is only treated as one
+    /// when a closing `==` follows it within the current block. Otherwise it is kept as text.
+    fn text(&mut self, text: &str, range: Range<usize>) {
+        let Some(block) = self.blocks.last() else {
+            return;
+        };
+
+        if matches!(
+            block.node.markdown_node,
+            MarkdownNode::CodeBlock { .. } | MarkdownNode::HtmlBlock { .. }
+        ) {
+            return self.push_styled_text(text, None);
+        }
+
+        let block_end = block.node.source_range.end;
+
+        // The text may be shorter than its source because of escapes and entities, so the rest
+        // of the block is only looked up in the source after the end of this text event.
+        let closed_after = self
+            .source
+            .get(range.end..block_end)
+            .is_some_and(|after| after.contains("=="));
+
+        let mut start = 0;
+        let mut search = 0;
+        while let Some(index) = text[search..].find("==").map(|index| search + index) {
+            search = index + 2;
+            let highlighted = self.styles.contains(&Style::Highlight);
+
+            if highlighted || closed_after || text[search..].contains("==") {
+                self.push_styled_text(&text[start..index], None);
+                match self
+                    .styles
+                    .iter()
+                    .rposition(|style| *style == Style::Highlight)
+                {
+                    Some(index) => _ = self.styles.remove(index),
+                    None => self.styles.push(Style::Highlight),
+                }
+                start = search;
+            }
+        }
+        self.push_styled_text(&text[start..], None);
+    }
+
+    /// Closes the innermost inline style other than an open highlight, which may have been
+    /// opened inside of it and closed after it.
+    fn pop_style(&mut self) {
+        if let Some(index) = self
+            .styles
+            .iter()
+            .rposition(|style| *style != Style::Highlight)
+        {
+            self.styles.remove(index);
+        }
+    }
+
+    /// Consumes the events of a metadata block up to its end and pushes the parsed frontmatter
+    /// into the output.
+    ///
+    /// [`pulldown_cmark`] also reports `---` delimited blocks further down the input as metadata,
+    /// but like Obsidian only the block at the very start of the input counts as frontmatter.
+    /// Those blocks, like malformed frontmatter, are kept as a paragraph of their source.
+    fn frontmatter(&mut self, range: Range<usize>) {
+        let mut yaml = String::new();
+        for (event, _) in self.by_ref() {
+            match event {
+                Event::Text(text) => yaml.push_str(&text),
+                Event::End(TagEnd::MetadataBlock(_)) => break,
+                _ => {}
+            }
+        }
+
+        let markdown_node = match Frontmatter::parse(&yaml) {
+            Some(frontmatter) if range.start == 0 => MarkdownNode::Frontmatter { frontmatter },
+            _ => MarkdownNode::Paragraph {
+                text: Text::from(
+                    self.source
+                        .get(range.clone())
+                        .unwrap_or_default()
+                        .trim_end_matches('\n'),
+                ),
+            },
+        };
+
+        self.push_node(Node::new(markdown_node, range));
+    }
+
+    /// Returns the kind of a new item of the innermost list, numbering the items of an ordered
+    /// list from its start.
+    fn item_kind(&self) -> ItemKind {
+        match self.blocks.last().map(|block| &block.node.markdown_node) {
+            Some(MarkdownNode::List {
+                kind: ListKind::Ordered(start),
+                nodes,
+            }) => ItemKind::Ordered(start + nodes.len() as u64),
+            _ => ItemKind::Unordered,
         }
     }
 
-    /// Sets (or replaces) the `current_node` with a new one, discarding any old node.
-    fn set_node(&mut self, block: &Node) {
-        self.current_node.replace(block.clone());
+    /// Marks the innermost item as a task.
+    fn task_list_marker(&mut self, checked: bool) {
+        let item =
+            self.blocks
+                .iter_mut()
+                .rev()
+                .find_map(|block| match &mut block.node.markdown_node {
+                    MarkdownNode::Item { kind, .. } => Some(kind),
+                    _ => None,
+                });
+
+        if let Some(kind) = item {
+            *kind = if checked {
+                ItemKind::HardChecked
+            } else {
+                ItemKind::Unchecked
+            };
+        }
     }
 
-    /// Handles the start of a [`Tag`]. Pushes the matching semantic node to be processed.
+    /// Handles the start of a [`Tag`]. Opens the matching block or inline style.
     fn tag(&mut self, tag: Tag<'a>, range: Range<usize>) {
         match tag {
-            Tag::Paragraph => self.push_node(Node::new(
+            Tag::MetadataBlock(_) => self.frontmatter(range),
+            Tag::Paragraph => self.open(
                 MarkdownNode::Paragraph {
                     text: Text::default(),
                 },
                 range,
-            )),
-            Tag::Heading { level, .. } => self.push_node(Node::new(
+            ),
+            Tag::Heading { level, .. } => self.open(
                 MarkdownNode::Heading {
                     level: level.into(),
                     text: Text::default(),
                 },
                 range,
-            )),
-            Tag::BlockQuote(kind) => self.push_node(Node::new(
+            ),
+            Tag::BlockQuote(kind) => self.open(
                 MarkdownNode::BlockQuote {
                     kind: kind.map(|kind| kind.into()),
                     nodes: vec![],
                 },
                 range,
-            )),
-            Tag::CodeBlock(_) => self.push_node(Node::new(
+            ),
+            Tag::CodeBlock(kind) => self.open(
                 MarkdownNode::CodeBlock {
-                    lang: None,
+                    lang: match kind {
+                        CodeBlockKind::Fenced(lang) if !lang.is_empty() => Some(lang.to_string()),
+                        _ => None,
+                    },
                     text: Text::default(),
                 },
                 range,
-            )),
-            Tag::Item => self.push_node(Node::new(
+            ),
+            Tag::HtmlBlock => self.open(
+                MarkdownNode::HtmlBlock {
+                    text: Text::default(),
+                },
+                range,
+            ),
+            Tag::List(start) => self.open(
+                MarkdownNode::List {
+                    kind: start.map_or(ListKind::Unordered, ListKind::Ordered),
+                    nodes: vec![],
+                },
+                range,
+            ),
+            Tag::Item => self.open(
                 MarkdownNode::Item {
-                    kind: None,
+                    kind: self.item_kind(),
+                    nodes: vec![],
+                },
+                range,
+            ),
+            Tag::FootnoteDefinition(label) => self.open(
+                MarkdownNode::FootnoteDefinition {
+                    label: label.to_string(),
+                    nodes: vec![],
+                },
+                range,
+            ),
+            Tag::Table(alignments) => self.open(
+                MarkdownNode::Table {
+                    alignments: alignments.into_iter().map(Into::into).collect(),
+                    head: vec![],
+                    rows: vec![],
+                },
+                range,
+            ),
+            Tag::TableHead => self.table_head = true,
+            Tag::TableRow => {
+                if let Some(Block {
+                    node:
+                        Node {
+                            markdown_node: MarkdownNode::Table { rows, .. },
+                            ..
+                        },
+                    ..
+                }) = self.blocks.last_mut()
+                {
+                    rows.push(vec![]);
+                }
+            }
+            Tag::TableCell => {}
+            Tag::DefinitionList => self.open(MarkdownNode::DefinitionList { nodes: vec![] }, range),
+            Tag::DefinitionListTitle => self.open(
+                MarkdownNode::DefinitionTitle {
                     text: Text::default(),
                 },
                 range,
-            )),
-            // For now everything below this comment are defined as paragraph nodes
-            Tag::HtmlBlock
-            | Tag::List(_)
-            | Tag::FootnoteDefinition(_)
-            | Tag::Table(_)
-            | Tag::TableHead
-            | Tag::TableRow
-            | Tag::TableCell
-            | Tag::Emphasis
-            | Tag::Strong
-            | Tag::Strikethrough
-            | Tag::Link { .. }
-            | Tag::Image { .. }
-            | Tag::MetadataBlock(_)
-            | Tag::DefinitionList
-            | Tag::DefinitionListTitle
-            | Tag::Subscript
-            | Tag::Superscript
-            | Tag::DefinitionListDefinition => {}
+            ),
+            Tag::DefinitionListDefinition => {
+                self.open(MarkdownNode::Definition { nodes: vec![] }, range)
+            }
+            Tag::Emphasis => self.styles.push(Style::Emphasis),
+            Tag::Strong => self.styles.push(Style::Strong),
+            Tag::Strikethrough => self.styles.push(Style::Strikethrough),
+            Tag::Superscript => self.styles.push(Style::Superscript),
+            Tag::Subscript => self.styles.push(Style::Subscript),
+            Tag::Link {
+                link_type: LinkType::WikiLink { .. },
+                dest_url,
+                ..
+            } => self.styles.push(Style::WikiLink(dest_url.to_string())),
+            Tag::Link { dest_url, .. } => self.styles.push(Style::Link(dest_url.to_string())),
+            Tag::Image {
+                link_type,
+                dest_url,
+                ..
+            } => {
+                let style = match link_type {
+                    LinkType::WikiLink { .. } => Style::Embed(dest_url.to_string()),
+                    _ => Style::Image(dest_url.to_string()),
+                };
+                self.styles.push(style.clone());
+                self.image = Some(style);
+            }
         }
     }
 
-    /// Handles the end of a [`Tag`], finalizing a node if matching.
-    fn tag_end(&mut self, tag_end: TagEnd) {
-        let Some(node) = self.current_node.take() else {
+    /// Handles the end of the image the parser is in. An image without alt text is kept as an
+    /// empty [`TextNode`].
+    fn image_end(&mut self) {
+        self.pop_style();
+
+        if let Some(style) = self.image.take() {
+            let mut styles = self.styles.clone();
+            styles.push(style);
+            self.push_text_node(TextNode::new(String::new(), styles));
+        }
+    }
+
+    /// Handles the end of a table cell, moving its text into the header or the current row.
+    fn table_cell_end(&mut self) {
+        let table_head = self.table_head;
+        let Some(Block { node, text }) = self.blocks.last_mut() else {
             return;
         };
 
-        if matches_tag_end(&node, &tag_end) {
-            self.output.push(node);
-        } else {
-            self.set_node(&node);
+        let cell = std::mem::take(text);
+        match &mut node.markdown_node {
+            MarkdownNode::Table { head, .. } if table_head => head.push(cell),
+            MarkdownNode::Table { rows, .. } => match rows.last_mut() {
+                Some(row) => row.push(cell),
+                None => rows.push(vec![cell]),
+            },
+            _ => {}
+        }
+    }
+
+    /// Handles the end of a [`Tag`], closing the matching block or inline style.
+    fn tag_end(&mut self, tag_end: TagEnd) {
+        match tag_end {
+            TagEnd::Emphasis
+            | TagEnd::Strong
+            | TagEnd::Strikethrough
+            | TagEnd::Superscript
+            | TagEnd::Subscript
+            | TagEnd::Link => self.pop_style(),
+            TagEnd::Image => self.image_end(),
+            TagEnd::TableHead => self.table_head = false,
+            TagEnd::TableCell => self.table_cell_end(),
+            TagEnd::TableRow | TagEnd::MetadataBlock(_) => {}
+            TagEnd::Paragraph
+            | TagEnd::Heading(_)
+            | TagEnd::BlockQuote(_)
+            | TagEnd::CodeBlock
+            | TagEnd::HtmlBlock
+            | TagEnd::List(_)
+            | TagEnd::Item
+            | TagEnd::FootnoteDefinition
+            | TagEnd::Table
+            | TagEnd::DefinitionList
+            | TagEnd::DefinitionListTitle
+            | TagEnd::DefinitionListDefinition => self.close(),
         }
     }
 
@@ -548,40 +1010,18 @@ impl<'a> Parser<'a> {
         match event {
             Event::Start(tag) => self.tag(tag, range),
             Event::End(tag_end) => self.tag_end(tag_end),
-            Event::Text(text) => self.push_text_node(TextNode::new(text.to_string(), None)),
-            Event::Code(text) => {
-                self.push_text_node(TextNode::new(text.to_string(), Some(Style::Code)))
-            }
-            Event::TaskListMarker(checked) => {
-                // The range for these markdown items only applies to the `[ ]` portion.
-                // TODO: Add implementation for ListBlock, which will retain the complete source
-                // range.
-                if checked {
-                    self.set_node(&Node::new(
-                        MarkdownNode::Item {
-                            kind: Some(ItemKind::HardChecked),
-                            text: Text::default(),
-                        },
-                        range,
-                    ));
-                } else {
-                    self.set_node(&Node::new(
-                        MarkdownNode::Item {
-                            kind: Some(ItemKind::Unchecked),
-                            text: Text::default(),
-                        },
-                        range,
-                    ));
-                }
+            Event::Text(text) | Event::Html(text) => self.text(&text, range),
+            Event::Code(text) => self.push_styled_text(&text, Some(Style::Code)),
+            Event::InlineMath(text) => self.push_styled_text(&text, Some(Style::InlineMath)),
+            Event::DisplayMath(text) => self.push_styled_text(&text, Some(Style::DisplayMath)),
+            Event::FootnoteReference(label) => {
+                self.push_styled_text(&label, Some(Style::FootnoteReference))
             }
-            Event::InlineMath(_)
-            | Event::DisplayMath(_)
-            | Event::Html(_)
-            | Event::InlineHtml(_)
-            | Event::SoftBreak
-            | Event::HardBreak
-            | Event::Rule
-            | Event::FootnoteReference(_) => {
+            Event::SoftBreak => self.push_styled_text("\n", None),
+            Event::HardBreak => self.push_styled_text("\n", Some(Style::HardBreak)),
+            Event::TaskListMarker(checked) => self.task_list_marker(checked),
+            Event::Rule => self.push_node(Node::new(MarkdownNode::Rule, range)),
+            Event::InlineHtml(_) => {
                 // TODO: Not yet implemented
             }
         }
@@ -612,8 +1052,8 @@ impl<'a> Parser<'a> {
             self.handle_event(event, range);
         }
 
-        if let Some(node) = self.current_node.take() {
-            self.output.push(node);
+        while !self.blocks.is_empty() {
+            self.close();
         }
 
         self.output
@@ -633,34 +1073,12 @@ mod tests {
         Node::new(MarkdownNode::BlockQuote { kind: None, nodes }, range)
     }
 
-    fn item(str: &str, range: Range<usize>) -> Node {
-        Node::new(
-            MarkdownNode::Item {
-                kind: None,
-                text: str.into(),
-            },
-            range,
-        )
-    }
-
-    fn task(str: &str, range: Range<usize>) -> Node {
-        Node::new(
-            MarkdownNode::Item {
-                kind: Some(ItemKind::Unchecked),
-                text: str.into(),
-            },
-            range,
-        )
+    fn list(kind: ListKind, nodes: Vec<Node>, range: Range<usize>) -> Node {
+        Node::new(MarkdownNode::List { kind, nodes }, range)
     }
 
-    fn completed_task(str: &str, range: Range<usize>) -> Node {
-        Node::new(
-            MarkdownNode::Item {
-                kind: Some(ItemKind::HardChecked),
-                text: str.into(),
-            },
-            range,
-        )
+    fn item(kind: ItemKind, nodes: Vec<Node>, range: Range<usize>) -> Node {
+        Node::new(MarkdownNode::Item { kind, nodes }, range)
     }
 
     fn heading(level: HeadingLevel, str: &str, range: Range<usize>) -> Node {
@@ -699,6 +1117,231 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_parse_frontmatter() {
+        let markdown = indoc! {r#"---
+            tags: [recipe]
+            servings: 4
+            ---
+            # Pancakes
+            "#};
+
+        let nodes = from_str(markdown);
+        let MarkdownNode::Frontmatter { frontmatter } = &nodes[0].markdown_node else {
+            panic!("expected frontmatter, got {:?}", nodes[0]);
+        };
+        assert_eq!(frontmatter.tags, ["recipe"]);
+        assert_eq!(nodes[0].source_range, 0..34);
+        assert_eq!(nodes[1], h1("Pancakes", 35..46));
+
+        // Malformed frontmatter is dropped, and a rule later in the note is not frontmatter.
+        let nodes = from_str("---\n[unclosed\n---\n\nText\n\n---\nkey: value\n---\n");
+        assert!(nodes
+            .iter()
+            .all(|node| !matches!(node.markdown_node, MarkdownNode::Frontmatter { .. })));
+    }
+
+    #[test]
+    fn test_parse_inline_styles() {
+        let text = |markdown: &str| match from_str(markdown).remove(0).markdown_node {
+            MarkdownNode::Paragraph { text } => text.into_iter().collect::<Vec<_>>(),
+            node => panic!("expected a paragraph, got {node:?}"),
+        };
+        let node = |content: &str, styles: Vec<Style>| TextNode::new(content.into(), styles);
+
+        assert_eq!(
+            text("**bold _both_** ~~gone~~"),
+            [
+                node("bold ", vec![Style::Strong]),
+                node("both", vec![Style::Strong, Style::Emphasis]),
+                node(" ", vec![]),
+                node("gone", vec![Style::Strikethrough]),
+            ]
+        );
+
+        assert_eq!(
+            text("See [docs](https://example.com) and [[Note#Heading|the `note`]]"),
+            [
+                node("See ", vec![]),
+                node("docs", vec![Style::Link("https://example.com".into())]),
+                node(" and ", vec![]),
+                node("the ", vec![Style::WikiLink("Note#Heading".into())]),
+                node(
+                    "note",
+                    vec![Style::WikiLink("Note#Heading".into()), Style::Code]
+                ),
+            ]
+        );
+
+        assert_eq!(
+            text("a ==marked **text**== and a == b"),
+            [
+                node("a ", vec![]),
+                node("marked ", vec![Style::Highlight]),
+                node("text", vec![Style::Highlight, Style::Strong]),
+                node(" and a == b", vec![]),
+            ]
+        );
+
+        assert_eq!(
+            text("**a ==b== c**"),
+            [
+                node("a ", vec![Style::Strong]),
+                node("b", vec![Style::Strong, Style::Highlight]),
+                node(" c", vec![Style::Strong]),
+            ]
+        );
+
+        assert_eq!(
+            text("==a **b== c**"),
+            [
+                node("a ", vec![Style::Highlight]),
+                node("b", vec![Style::Highlight, Style::Strong]),
+                node(" c", vec![Style::Strong]),
+            ]
+        );
+
+        assert_eq!(text("a &amp; == b"), [node("a & == b", vec![])]);
+
+        assert_eq!(
+            text("soft\nhard\\\n**two spaces**  \nend"),
+            [
+                node("soft", vec![]),
+                node("\n", vec![]),
+                node("hard", vec![]),
+                node("\n", vec![Style::HardBreak]),
+                node("two spaces", vec![Style::Strong]),
+                node("\n", vec![Style::HardBreak]),
+                node("end", vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_blocks() {
+        let markdown = indoc! {r#"
+            1. One
+            2. Two
+               - [x] Nested
+
+            | A | B |
+            |:--|--:|
+            | *a* | `b` |
+
+            ---
+
+            [^1]: The note.
+
+            <div>
+            </div>
+
+            Term
+            : Definition
+            "#};
+
+        assert_eq!(
+            from_str(markdown),
+            [
+                list(
+                    ListKind::Ordered(1),
+                    vec![
+                        item(ItemKind::Ordered(1), vec![p("One", 0..7)], 0..7),
+                        item(
+                            ItemKind::Ordered(2),
+                            vec![
+                                p("Two", 7..31),
+                                list(
+                                    ListKind::Unordered,
+                                    vec![item(
+                                        ItemKind::HardChecked,
+                                        vec![p("Nested", 17..31)],
+                                        17..31
+                                    )],
+                                    17..31,
+                                ),
+                            ],
+                            7..31,
+                        ),
+                    ],
+                    0..31,
+                ),
+                Node::new(
+                    MarkdownNode::Table {
+                        alignments: vec![Alignment::Left, Alignment::Right],
+                        head: vec!["A".into(), "B".into()],
+                        rows: vec![vec![
+                            TextNode::new("a".into(), vec![Style::Emphasis]).into(),
+                            TextNode::new("b".into(), vec![Style::Code]).into(),
+                        ]],
+                    },
+                    31..65,
+                ),
+                Node::new(MarkdownNode::Rule, 66..70),
+                Node::new(
+                    MarkdownNode::FootnoteDefinition {
+                        label: "1".into(),
+                        nodes: vec![p("The note.", 77..87)],
+                    },
+                    71..88,
+                ),
+                Node::new(
+                    MarkdownNode::HtmlBlock {
+                        text: vec![TextNode::from("<div>\n"), TextNode::from("</div>\n")].into(),
+                    },
+                    88..101,
+                ),
+                Node::new(
+                    MarkdownNode::DefinitionList {
+                        nodes: vec![
+                            Node::new(
+                                MarkdownNode::DefinitionTitle {
+                                    text: "Term".into(),
+                                },
+                                102..107,
+                            ),
+                            Node::new(
+                                MarkdownNode::Definition {
+                                    nodes: vec![p("Definition", 107..120)],
+                                },
+                                107..120,
+                            ),
+                        ],
+                    },
+                    102..120,
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_inline_nodes() {
+        let text = |markdown: &str| match from_str(markdown).remove(0).markdown_node {
+            MarkdownNode::Paragraph { text } => text.into_iter().collect::<Vec<_>>(),
+            node => panic!("expected a paragraph, got {node:?}"),
+        };
+        let node = |content: &str, styles: Vec<Style>| TextNode::new(content.into(), styles);
+
+        assert_eq!(
+            text("Text[^1] with $x^2$ and\n![[Note]] ![](img.png)"),
+            [
+                node("Text", vec![]),
+                node("1", vec![Style::FootnoteReference]),
+                node(" with ", vec![]),
+                node("x^2", vec![Style::InlineMath]),
+                node(" and", vec![]),
+                node("\n", vec![]),
+                node("Note", vec![Style::Embed("Note".into())]),
+                node(" ", vec![]),
+                node("", vec![Style::Image("img.png".into())]),
+            ]
+        );
+
+        assert_eq!(
+            text("$$\\sum x$$"),
+            [node("\\sum x", vec![Style::DisplayMath])]
+        );
+    }
+
     #[test]
     fn test_parse() {
         let tests = [
@@ -737,9 +1380,23 @@ mod tests {
                 "#},
                 vec![
                     h2("Tasks", 0..9),
-                    task("Task", 12..15),
-                    completed_task("Completed task", 24..27),
-                    p("[?] Completed task", 46..65),
+                    list(
+                        ListKind::Unordered,
+                        vec![
+                            item(ItemKind::Unchecked, vec![p("Task", 16..21)], 10..22),
+                            item(
+                                ItemKind::HardChecked,
+                                vec![p("Completed task", 28..43)],
+                                22..44,
+                            ),
+                            item(
+                                ItemKind::Unordered,
+                                vec![p("[?] Completed task", 46..65)],
+                                44..65,
+                            ),
+                        ],
+                        10..65,
+                    ),
                 ],
             ),
             (
@@ -755,17 +1412,25 @@ mod tests {
                     h2("Quotes", 0..10),
                     Node::new(MarkdownNode::Paragraph {
                         text: vec![
-                            TextNode::new("You ".into(), None),
-                            TextNode::new("can".into(),None),
-                            TextNode::new(" quote text by adding a ".into(), None),
-                            TextNode::new(">".into(), Some(Style::Code)),
-                            TextNode::new(" symbols before the text.".into(), None),
+                            TextNode::new("You ".into(), vec![]),
+                            TextNode::new("can".into(), vec![Style::Emphasis]),
+                            TextNode::new(" quote text by adding a ".into(), vec![]),
+                            TextNode::new(">".into(), vec![Style::Code]),
+                            TextNode::new(" symbols before the text.".into(), vec![]),
                         ]
                         .into(),
                     }, 11..73),
                     blockquote(vec![
                         p("Human beings face ever more complex and urgent problems, and their effectiveness in dealing with these problems is a matter that is critical to the stability and continued progress of society.", 76..269),
-                        item("Doug Engelbart, 1961", 272..295)
+                        list(
+                            ListKind::Unordered,
+                            vec![item(
+                                ItemKind::Unordered,
+                                vec![p("Doug Engelbart, 1961", 272..295)],
+                                272..295,
+                            )],
+                            272..295,
+                        ),
                     ], 74..295),
                 ],
             ),
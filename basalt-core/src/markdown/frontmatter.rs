@@ -0,0 +1,240 @@
+//! This module provides parsing of YAML frontmatter, which Obsidian calls note properties.
+//!
+//! Frontmatter is the YAML block between two `---` lines at the very start of a note:
+//!
+//! ```markdown
+//! ---
+//! tags: [recipe, breakfast]
+//! aliases: Pancakes
+//! servings: 4
+//! ---
+//! ```
+//!
+//! The well-known `tags`, `aliases` and `cssclasses` properties are normalized into lists the same
+//! way Obsidian reads them, while every property, including those three, is kept in source order
+//! in [`Frontmatter::properties`].
+use std::fmt;
+
+use serde_yaml_ng as yaml;
+
+/// A single property value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// An empty property, e.g. `status:`.
+    Null,
+    /// A checkbox property, e.g. `draft: true`.
+    Bool(bool),
+    /// A number property, e.g. `servings: 4`.
+    Number(f64),
+    /// A text property. Dates are kept as text as well, e.g. `created: 2024-01-31`.
+    String(String),
+    /// A list property, e.g. `tags: [recipe, breakfast]`.
+    List(Vec<Value>),
+    /// A nested mapping, which Obsidian does not edit but keeps as is.
+    Map(Vec<(String, Value)>),
+}
+
+impl From<yaml::Value> for Value {
+    fn from(value: yaml::Value) -> Self {
+        match value {
+            yaml::Value::Null => Value::Null,
+            yaml::Value::Bool(value) => Value::Bool(value),
+            yaml::Value::Number(value) => Value::Number(value.as_f64().unwrap_or_default()),
+            yaml::Value::String(value) => Value::String(value),
+            yaml::Value::Sequence(values) => {
+                Value::List(values.into_iter().map(Value::from).collect())
+            }
+            yaml::Value::Mapping(mapping) => Value::Map(entries(mapping)),
+            yaml::Value::Tagged(tagged) => tagged.value.into(),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    /// Formats the value the way it reads in a properties view: lists are comma separated and
+    /// empty values are blank.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => Ok(()),
+            Value::Bool(value) => write!(f, "{value}"),
+            Value::Number(value) => write!(f, "{value}"),
+            Value::String(value) => write!(f, "{value}"),
+            Value::List(values) => {
+                let values: Vec<String> = values.iter().map(Value::to_string).collect();
+                write!(f, "{}", values.join(", "))
+            }
+            Value::Map(entries) => {
+                let entries: Vec<String> = entries
+                    .iter()
+                    .map(|(key, value)| format!("{key}: {value}"))
+                    .collect();
+                write!(f, "{{{}}}", entries.join(", "))
+            }
+        }
+    }
+}
+
+impl Value {
+    /// Returns the value as a list of strings. A list yields its non-empty items, and a string is
+    /// split on commas, as Obsidian accepts both `tags: a, b` and `tags: [a, b]`.
+    fn to_list(&self) -> Vec<String> {
+        match self {
+            Value::Null => vec![],
+            Value::List(values) => values
+                .iter()
+                .flat_map(Value::to_list)
+                .collect::<Vec<String>>(),
+            Value::String(value) => value
+                .split(',')
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+                .collect(),
+            value => vec![value.to_string()],
+        }
+    }
+}
+
+/// The parsed frontmatter of a note.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Frontmatter {
+    /// Tags from the `tags` (or legacy `tag`) property, without the leading `#`.
+    pub tags: Vec<String>,
+
+    /// Alternative note names from the `aliases` (or legacy `alias`) property.
+    pub aliases: Vec<String>,
+
+    /// CSS classes from the `cssclasses` (or legacy `cssclass`) property.
+    pub cssclasses: Vec<String>,
+
+    /// Every property in source order, including `tags`, `aliases` and `cssclasses`.
+    pub properties: Vec<(String, Value)>,
+}
+
+impl Frontmatter {
+    /// Parses the YAML between the `---` lines of a frontmatter block.
+    ///
+    /// Returns [`None`] when the YAML is malformed or is not a mapping of properties. An empty
+    /// block yields an empty [`Frontmatter`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::markdown::frontmatter::{Frontmatter, Value};
+    ///
+    /// let frontmatter = Frontmatter::parse("tags: [recipe, '#breakfast']\naliases: Pancakes\nservings: 4").unwrap();
+    ///
+    /// assert_eq!(frontmatter.tags, ["recipe", "breakfast"]);
+    /// assert_eq!(frontmatter.aliases, ["Pancakes"]);
+    /// assert_eq!(frontmatter.get("servings"), Some(&Value::Number(4.0)));
+    /// assert_eq!(frontmatter.properties.len(), 3);
+    ///
+    /// assert_eq!(Frontmatter::parse(""), Some(Frontmatter::default()));
+    /// assert_eq!(Frontmatter::parse("- not\n- a mapping"), None);
+    /// ```
+    pub fn parse(yaml: &str) -> Option<Frontmatter> {
+        let properties = match yaml::from_str(yaml).ok()? {
+            yaml::Value::Null => vec![],
+            yaml::Value::Mapping(mapping) => entries(mapping),
+            _ => return None,
+        };
+
+        let list = |keys: &[&str]| -> Vec<String> {
+            properties
+                .iter()
+                .filter(|(key, _)| keys.contains(&key.as_str()))
+                .flat_map(|(_, value)| value.to_list())
+                .collect()
+        };
+
+        Some(Frontmatter {
+            tags: list(&["tags", "tag"])
+                .into_iter()
+                .map(|tag| tag.trim_start_matches('#').to_string())
+                .filter(|tag| !tag.is_empty())
+                .collect(),
+            aliases: list(&["aliases", "alias"]),
+            cssclasses: list(&["cssclasses", "cssclass"]),
+            properties,
+        })
+    }
+
+    /// Returns the value of the given property, if present.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.properties
+            .iter()
+            .find(|(property, _)| property == key)
+            .map(|(_, value)| value)
+    }
+
+    /// Returns `true` if the frontmatter has no properties.
+    pub fn is_empty(&self) -> bool {
+        self.properties.is_empty()
+    }
+}
+
+/// Converts the YAML mapping into properties, keeping the source order. Non-string keys, e.g.
+/// `1: one`, are formatted into strings.
+fn entries(mapping: yaml::Mapping) -> Vec<(String, Value)> {
+    mapping
+        .into_iter()
+        .map(|(key, value)| {
+            let key = match key {
+                yaml::Value::String(key) => key,
+                key => Value::from(key).to_string(),
+            };
+            (key, value.into())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use similar_asserts::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let frontmatter = Frontmatter::parse(indoc! {r##"
+            tag: "#rust, tui"
+            alias:
+              - Basalt
+              - basalt-tui
+            cssclasses: wide
+            draft: false
+            created: 2024-01-31
+            rating:
+            links:
+              home: "[[Home]]"
+        "##})
+        .unwrap();
+
+        assert_eq!(frontmatter.tags, ["rust", "tui"]);
+        assert_eq!(frontmatter.aliases, ["Basalt", "basalt-tui"]);
+        assert_eq!(frontmatter.cssclasses, ["wide"]);
+        assert_eq!(
+            frontmatter
+                .properties
+                .iter()
+                .map(|(key, value)| format!("{key}={value}"))
+                .collect::<Vec<_>>(),
+            [
+                "tag=#rust, tui",
+                "alias=Basalt, basalt-tui",
+                "cssclasses=wide",
+                "draft=false",
+                "created=2024-01-31",
+                "rating=",
+                "links={home: [[Home]]}",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_malformed() {
+        assert_eq!(Frontmatter::parse("tags: [unclosed"), None);
+        assert_eq!(Frontmatter::parse("just text"), None);
+    }
+}
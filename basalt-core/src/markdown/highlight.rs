@@ -0,0 +1,267 @@
+//! This module provides syntax highlighting for fenced code blocks.
+//!
+//! Code is tokenized with the grammars bundled with [`syntect`], so highlighting works offline.
+//! Instead of colours, every token is classified into a [`TokenKind`], leaving the colours to the
+//! theme of the renderer.
+//!
+//! The module is only available with the `highlight` feature, which pulls in [`syntect`].
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    ops::Range,
+    sync::{Mutex, OnceLock},
+};
+
+use syntect::parsing::{ParseState, Scope, ScopeStack, SyntaxSet};
+
+/// The kind of a highlighted token, coarse enough to be coloured by a theme.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    /// Text without any particular kind, e.g. identifiers and punctuation.
+    Plain,
+    /// Comments.
+    Comment,
+    /// Keywords and storage modifiers, e.g. `fn`, `let` or `pub`.
+    Keyword,
+    /// String and character literals.
+    String,
+    /// Numbers, booleans and other constants.
+    Constant,
+    /// Function names, both in definitions and calls.
+    Function,
+    /// Type, class and trait names.
+    Type,
+}
+
+/// A highlighted token of a single line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Token {
+    /// The kind of the token.
+    pub kind: TokenKind,
+    /// The byte range of the token within its line.
+    pub range: Range<usize>,
+}
+
+/// The number of highlighted code blocks kept by [`highlight`], so that laying out the same
+/// blocks again does not re-run the grammars.
+const MAX_CACHED_BLOCKS: usize = 64;
+
+type Highlighted = Option<Vec<Vec<Token>>>;
+
+fn cache() -> &'static Mutex<HashMap<u64, Highlighted>> {
+    static CACHE: OnceLock<Mutex<HashMap<u64, Highlighted>>> = OnceLock::new();
+    CACHE.get_or_init(Mutex::default)
+}
+
+fn syntaxes() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+/// Scope prefixes and the kind of token they map to. More specific prefixes come first.
+fn rules() -> &'static [(Scope, TokenKind)] {
+    static RULES: OnceLock<Vec<(Scope, TokenKind)>> = OnceLock::new();
+    RULES.get_or_init(|| {
+        [
+            ("comment", TokenKind::Comment),
+            ("string", TokenKind::String),
+            ("constant", TokenKind::Constant),
+            ("entity.name.function", TokenKind::Function),
+            ("support.function", TokenKind::Function),
+            ("variable.function", TokenKind::Function),
+            ("support.macro", TokenKind::Function),
+            ("entity.name.type", TokenKind::Type),
+            ("entity.name.class", TokenKind::Type),
+            ("entity.name.struct", TokenKind::Type),
+            ("entity.name.enum", TokenKind::Type),
+            ("entity.name.trait", TokenKind::Type),
+            ("entity.other.inherited-class", TokenKind::Type),
+            ("support.type", TokenKind::Type),
+            ("support.class", TokenKind::Type),
+            ("keyword.operator", TokenKind::Plain),
+            ("keyword", TokenKind::Keyword),
+            ("storage", TokenKind::Keyword),
+        ]
+        .into_iter()
+        .filter_map(|(scope, kind)| Some((Scope::new(scope).ok()?, kind)))
+        .collect()
+    })
+}
+
+/// Classifies a scope stack by its innermost scope that matches a rule.
+fn token_kind(stack: &ScopeStack) -> TokenKind {
+    stack
+        .as_slice()
+        .iter()
+        .rev()
+        .find_map(|scope| {
+            rules()
+                .iter()
+                .find(|(prefix, _)| prefix.is_prefix_of(*scope))
+                .map(|(_, kind)| *kind)
+        })
+        .unwrap_or(TokenKind::Plain)
+}
+
+fn push_token(tokens: &mut Vec<Token>, kind: TokenKind, range: Range<usize>) {
+    if range.is_empty() {
+        return;
+    }
+
+    match tokens.last_mut() {
+        Some(last) if last.kind == kind && last.range.end == range.start => {
+            last.range.end = range.end
+        }
+        _ => tokens.push(Token { kind, range }),
+    }
+}
+
+/// Highlights the code of a fenced code block written in the given language.
+///
+/// The language is matched against the names and file extensions known to the bundled grammars,
+/// e.g. `rust`, `rs`, `py` or `sh`. Anything after the first word of the info string, such as
+/// `{.class}` or `,ignore`, is ignored.
+///
+/// Returns the tokens of every line of the code, as split by [`str::lines`], or [`None`] if the
+/// language is unknown. The tokens of recently highlighted code blocks are cached.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_core::markdown::highlight::{highlight, TokenKind};
+///
+/// let lines = highlight("let x = 1; // one", "rust").unwrap();
+///
+/// let kinds: Vec<(TokenKind, &str)> = lines[0]
+///     .iter()
+///     .map(|token| (token.kind, &"let x = 1; // one"[token.range.clone()]))
+///     .collect();
+///
+/// assert_eq!(kinds, [
+///     (TokenKind::Keyword, "let"),
+///     (TokenKind::Plain, " x = "),
+///     (TokenKind::Constant, "1"),
+///     (TokenKind::Plain, "; "),
+///     (TokenKind::Comment, "// one"),
+/// ]);
+///
+/// assert_eq!(highlight("x", "not-a-language"), None);
+/// ```
+pub fn highlight(code: &str, lang: &str) -> Option<Vec<Vec<Token>>> {
+    let mut hasher = DefaultHasher::new();
+    (code, lang).hash(&mut hasher);
+    let key = hasher.finish();
+
+    if let Some(highlighted) = cache().lock().ok()?.get(&key) {
+        return highlighted.clone();
+    }
+
+    let highlighted = tokenize(code, lang);
+
+    let mut cache = cache().lock().ok()?;
+    if cache.len() >= MAX_CACHED_BLOCKS {
+        cache.clear();
+    }
+    cache.insert(key, highlighted.clone());
+
+    highlighted
+}
+
+/// Tokenizes the code with the grammar of the given language.
+fn tokenize(code: &str, lang: &str) -> Option<Vec<Vec<Token>>> {
+    let lang = lang
+        .split([',', ' ', '{'])
+        .next()
+        .unwrap_or_default()
+        .trim();
+    if lang.is_empty() {
+        return None;
+    }
+
+    let syntaxes = syntaxes();
+    let syntax = syntaxes.find_syntax_by_token(lang)?;
+
+    let mut state = ParseState::new(syntax);
+    let mut stack = ScopeStack::new();
+
+    code.lines()
+        .map(|line| {
+            let ops = state.parse_line(&format!("{line}\n"), syntaxes).ok()?;
+
+            let mut tokens = Vec::new();
+            let mut start = 0;
+            for (offset, op) in ops {
+                let offset = offset.min(line.len());
+                push_token(&mut tokens, token_kind(&stack), start..offset);
+                start = offset;
+                stack.apply(&op).ok()?;
+            }
+            push_token(&mut tokens, token_kind(&stack), start..line.len());
+
+            Some(tokens)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use similar_asserts::assert_eq;
+
+    use super::*;
+
+    fn kinds<'a>(code: &'a str, lang: &str) -> Vec<Vec<(TokenKind, &'a str)>> {
+        let lines: Vec<&str> = code.lines().collect();
+        highlight(code, lang)
+            .unwrap()
+            .iter()
+            .zip(lines)
+            .map(|(tokens, line)| {
+                tokens
+                    .iter()
+                    .map(|token| (token.kind, &line[token.range.clone()]))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_highlight() {
+        let code = indoc! {r#"
+            fn main() {
+                println!("hi");
+            }
+        "#};
+
+        assert_eq!(
+            kinds(code, "rs"),
+            [
+                vec![
+                    (TokenKind::Keyword, "fn"),
+                    (TokenKind::Plain, " "),
+                    (TokenKind::Function, "main"),
+                    (TokenKind::Plain, "() {"),
+                ],
+                vec![
+                    (TokenKind::Plain, "    "),
+                    (TokenKind::Function, "println!"),
+                    (TokenKind::Plain, "("),
+                    (TokenKind::String, "\"hi\""),
+                    (TokenKind::Plain, ");"),
+                ],
+                vec![(TokenKind::Plain, "}")],
+            ]
+        );
+
+        assert_eq!(
+            kinds("# comment\necho $HOME", "sh {.numberLines}"),
+            [
+                vec![(TokenKind::Comment, "# comment")],
+                vec![(TokenKind::Function, "echo"), (TokenKind::Plain, " $HOME")],
+            ]
+        );
+
+        assert_eq!(highlight("", "rust"), Some(vec![]));
+        assert_eq!(highlight("plain", ""), None);
+    }
+}
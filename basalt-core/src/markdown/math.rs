@@ -0,0 +1,624 @@
+//! This module approximates LaTeX math with unicode, so that inline (`$...$`) and display
+//! (`$$...$$`) math reads well in a terminal.
+//!
+//! Greek letters, operators, relations and arrows become their unicode symbols, `^` and `_` become
+//! superscript and subscript characters where unicode has them, and fractions, roots and accents
+//! are written out on a single line. Commands without an approximation are kept as written, and
+//! runs of whitespace, which are not significant in LaTeX math, become a single space.
+//!
+//! ```
+//! use basalt_core::markdown::math::to_unicode;
+//!
+//! assert_eq!(to_unicode(r"\alpha^2 + \beta_1"), "α² + β₁");
+//! assert_eq!(to_unicode(r"\frac{1}{2} \leq x"), "½ ≤ x");
+//! assert_eq!(to_unicode(r"\sum_{i=1}^{n} x_i"), "∑ᵢ₌₁ⁿ xᵢ");
+//! assert_eq!(to_unicode(r"\int_0^\infty e^{-x} \, dx"), "∫₀^∞ e⁻ˣ dx");
+//! ```
+use std::{iter::Peekable, str::Chars};
+
+/// Commands that stand for a single symbol.
+const SYMBOLS: &[(&str, &str)] = &[
+    // Greek letters
+    ("alpha", "α"),
+    ("beta", "β"),
+    ("gamma", "γ"),
+    ("delta", "δ"),
+    ("epsilon", "ϵ"),
+    ("varepsilon", "ε"),
+    ("zeta", "ζ"),
+    ("eta", "η"),
+    ("theta", "θ"),
+    ("vartheta", "ϑ"),
+    ("iota", "ι"),
+    ("kappa", "κ"),
+    ("lambda", "λ"),
+    ("mu", "μ"),
+    ("nu", "ν"),
+    ("xi", "ξ"),
+    ("omicron", "ο"),
+    ("pi", "π"),
+    ("varpi", "ϖ"),
+    ("rho", "ρ"),
+    ("varrho", "ϱ"),
+    ("sigma", "σ"),
+    ("varsigma", "ς"),
+    ("tau", "τ"),
+    ("upsilon", "υ"),
+    ("phi", "ϕ"),
+    ("varphi", "φ"),
+    ("chi", "χ"),
+    ("psi", "ψ"),
+    ("omega", "ω"),
+    ("Gamma", "Γ"),
+    ("Delta", "Δ"),
+    ("Theta", "Θ"),
+    ("Lambda", "Λ"),
+    ("Xi", "Ξ"),
+    ("Pi", "Π"),
+    ("Sigma", "Σ"),
+    ("Upsilon", "Υ"),
+    ("Phi", "Φ"),
+    ("Psi", "Ψ"),
+    ("Omega", "Ω"),
+    // Big operators
+    ("sum", "∑"),
+    ("prod", "∏"),
+    ("coprod", "∐"),
+    ("int", "∫"),
+    ("iint", "∬"),
+    ("iiint", "∭"),
+    ("oint", "∮"),
+    ("bigcup", "⋃"),
+    ("bigcap", "⋂"),
+    // Operators
+    ("pm", "±"),
+    ("mp", "∓"),
+    ("times", "×"),
+    ("div", "÷"),
+    ("cdot", "⋅"),
+    ("ast", "∗"),
+    ("star", "⋆"),
+    ("circ", "∘"),
+    ("bullet", "•"),
+    ("oplus", "⊕"),
+    ("otimes", "⊗"),
+    ("cup", "∪"),
+    ("cap", "∩"),
+    ("setminus", "∖"),
+    ("land", "∧"),
+    ("wedge", "∧"),
+    ("lor", "∨"),
+    ("vee", "∨"),
+    ("neg", "¬"),
+    ("lnot", "¬"),
+    ("partial", "∂"),
+    ("nabla", "∇"),
+    // Relations
+    ("leq", "≤"),
+    ("le", "≤"),
+    ("geq", "≥"),
+    ("ge", "≥"),
+    ("neq", "≠"),
+    ("ne", "≠"),
+    ("ll", "≪"),
+    ("gg", "≫"),
+    ("approx", "≈"),
+    ("equiv", "≡"),
+    ("sim", "∼"),
+    ("simeq", "≃"),
+    ("cong", "≅"),
+    ("propto", "∝"),
+    ("in", "∈"),
+    ("notin", "∉"),
+    ("ni", "∋"),
+    ("subset", "⊂"),
+    ("subseteq", "⊆"),
+    ("supset", "⊃"),
+    ("supseteq", "⊇"),
+    ("perp", "⊥"),
+    ("parallel", "∥"),
+    ("mid", "∣"),
+    // Arrows
+    ("to", "→"),
+    ("rightarrow", "→"),
+    ("leftarrow", "←"),
+    ("gets", "←"),
+    ("leftrightarrow", "↔"),
+    ("Rightarrow", "⇒"),
+    ("Leftarrow", "⇐"),
+    ("Leftrightarrow", "⇔"),
+    ("implies", "⟹"),
+    ("impliedby", "⟸"),
+    ("iff", "⟺"),
+    ("mapsto", "↦"),
+    ("uparrow", "↑"),
+    ("downarrow", "↓"),
+    // Delimiters
+    ("langle", "⟨"),
+    ("rangle", "⟩"),
+    ("lfloor", "⌊"),
+    ("rfloor", "⌋"),
+    ("lceil", "⌈"),
+    ("rceil", "⌉"),
+    ("lvert", "|"),
+    ("rvert", "|"),
+    ("vert", "|"),
+    ("lVert", "‖"),
+    ("rVert", "‖"),
+    ("Vert", "‖"),
+    // Other symbols
+    ("infty", "∞"),
+    ("forall", "∀"),
+    ("exists", "∃"),
+    ("nexists", "∄"),
+    ("emptyset", "∅"),
+    ("varnothing", "∅"),
+    ("angle", "∠"),
+    ("triangle", "△"),
+    ("prime", "′"),
+    ("degree", "°"),
+    ("hbar", "ℏ"),
+    ("ell", "ℓ"),
+    ("Re", "ℜ"),
+    ("Im", "ℑ"),
+    ("aleph", "ℵ"),
+    ("ldots", "…"),
+    ("dots", "…"),
+    ("cdots", "⋯"),
+    ("vdots", "⋮"),
+    ("ddots", "⋱"),
+    // Spacing
+    ("quad", " "),
+    ("qquad", "  "),
+];
+
+/// Named functions, written upright in LaTeX and kept as their name.
+const FUNCTIONS: &[&str] = &[
+    "sin", "cos", "tan", "cot", "sec", "csc", "arcsin", "arccos", "arctan", "sinh", "cosh", "tanh",
+    "log", "ln", "lg", "exp", "lim", "liminf", "limsup", "max", "min", "sup", "inf", "det", "dim",
+    "gcd", "deg", "arg", "ker", "Pr", "mod", "bmod",
+];
+
+/// Commands that only change the size or the spacing of what follows.
+const IGNORED: &[&str] = &[
+    "left",
+    "right",
+    "big",
+    "Big",
+    "bigg",
+    "Bigg",
+    "bigl",
+    "bigr",
+    "Bigl",
+    "Bigr",
+    "displaystyle",
+    "textstyle",
+    "limits",
+    "nolimits",
+    "!",
+];
+
+/// Commands whose argument is text, kept as written.
+const TEXT: &[&str] = &["text", "textrm", "textit", "textbf", "mbox"];
+
+/// Commands that change the font of their argument, which is kept in the current font.
+const FONTS: &[&str] = &[
+    "mathrm",
+    "mathit",
+    "mathbf",
+    "mathsf",
+    "mathtt",
+    "mathcal",
+    "mathscr",
+    "mathfrak",
+    "boldsymbol",
+    "operatorname",
+];
+
+/// Accents and the combining characters they are drawn with.
+const ACCENTS: &[(&str, char)] = &[
+    ("hat", '\u{0302}'),
+    ("widehat", '\u{0302}'),
+    ("tilde", '\u{0303}'),
+    ("widetilde", '\u{0303}'),
+    ("bar", '\u{0304}'),
+    ("overline", '\u{0305}'),
+    ("dot", '\u{0307}'),
+    ("ddot", '\u{0308}'),
+    ("vec", '\u{20D7}'),
+];
+
+/// Fractions with a unicode character of their own.
+const VULGAR_FRACTIONS: &[(&str, &str, &str)] = &[
+    ("1", "2", "½"),
+    ("1", "3", "⅓"),
+    ("2", "3", "⅔"),
+    ("1", "4", "¼"),
+    ("3", "4", "¾"),
+    ("1", "5", "⅕"),
+    ("2", "5", "⅖"),
+    ("3", "5", "⅗"),
+    ("4", "5", "⅘"),
+    ("1", "6", "⅙"),
+    ("5", "6", "⅚"),
+    ("1", "7", "⅐"),
+    ("1", "8", "⅛"),
+    ("3", "8", "⅜"),
+    ("5", "8", "⅝"),
+    ("7", "8", "⅞"),
+    ("1", "9", "⅑"),
+    ("1", "10", "⅒"),
+];
+
+fn superscript_char(c: char) -> Option<char> {
+    Some(match c {
+        '0' => '⁰',
+        '1' => '¹',
+        '2' => '²',
+        '3' => '³',
+        '4'..='9' => char::from_u32(0x2070 + (c as u32 - '0' as u32))?,
+        '+' => '⁺',
+        '-' | '−' => '⁻',
+        '=' => '⁼',
+        '(' => '⁽',
+        ')' => '⁾',
+        'a' => 'ᵃ',
+        'b' => 'ᵇ',
+        'c' => 'ᶜ',
+        'd' => 'ᵈ',
+        'e' => 'ᵉ',
+        'f' => 'ᶠ',
+        'g' => 'ᵍ',
+        'h' => 'ʰ',
+        'i' => 'ⁱ',
+        'j' => 'ʲ',
+        'k' => 'ᵏ',
+        'l' => 'ˡ',
+        'm' => 'ᵐ',
+        'n' => 'ⁿ',
+        'o' => 'ᵒ',
+        'p' => 'ᵖ',
+        'r' => 'ʳ',
+        's' => 'ˢ',
+        't' => 'ᵗ',
+        'u' => 'ᵘ',
+        'v' => 'ᵛ',
+        'w' => 'ʷ',
+        'x' => 'ˣ',
+        'y' => 'ʸ',
+        'z' => 'ᶻ',
+        'A' => 'ᴬ',
+        'B' => 'ᴮ',
+        'D' => 'ᴰ',
+        'E' => 'ᴱ',
+        'G' => 'ᴳ',
+        'H' => 'ᴴ',
+        'I' => 'ᴵ',
+        'J' => 'ᴶ',
+        'K' => 'ᴷ',
+        'L' => 'ᴸ',
+        'M' => 'ᴹ',
+        'N' => 'ᴺ',
+        'O' => 'ᴼ',
+        'P' => 'ᴾ',
+        'R' => 'ᴿ',
+        'T' => 'ᵀ',
+        'U' => 'ᵁ',
+        'V' => 'ⱽ',
+        'W' => 'ᵂ',
+        'α' => 'ᵅ',
+        'β' => 'ᵝ',
+        'γ' => 'ᵞ',
+        'δ' => 'ᵟ',
+        'θ' => 'ᶿ',
+        'ι' => 'ᶥ',
+        'φ' | 'ϕ' => 'ᵠ',
+        'χ' => 'ᵡ',
+        '′' => '′',
+        _ => return None,
+    })
+}
+
+fn subscript_char(c: char) -> Option<char> {
+    Some(match c {
+        '0'..='9' => char::from_u32(0x2080 + (c as u32 - '0' as u32))?,
+        '+' => '₊',
+        '-' | '−' => '₋',
+        '=' => '₌',
+        '(' => '₍',
+        ')' => '₎',
+        'a' => 'ₐ',
+        'e' => 'ₑ',
+        'h' => 'ₕ',
+        'i' => 'ᵢ',
+        'j' => 'ⱼ',
+        'k' => 'ₖ',
+        'l' => 'ₗ',
+        'm' => 'ₘ',
+        'n' => 'ₙ',
+        'o' => 'ₒ',
+        'p' => 'ₚ',
+        'r' => 'ᵣ',
+        's' => 'ₛ',
+        't' => 'ₜ',
+        'u' => 'ᵤ',
+        'v' => 'ᵥ',
+        'x' => 'ₓ',
+        'β' => 'ᵦ',
+        'γ' => 'ᵧ',
+        'ρ' => 'ᵨ',
+        'φ' | 'ϕ' => 'ᵩ',
+        'χ' => 'ᵪ',
+        _ => return None,
+    })
+}
+
+fn double_struck_char(c: char) -> char {
+    match c {
+        'C' => 'ℂ',
+        'H' => 'ℍ',
+        'N' => 'ℕ',
+        'P' => 'ℙ',
+        'Q' => 'ℚ',
+        'R' => 'ℝ',
+        'Z' => 'ℤ',
+        'A'..='Z' => char::from_u32(0x1D538 + (c as u32 - 'A' as u32)).unwrap_or(c),
+        _ => c,
+    }
+}
+
+/// Converts the text into superscript characters, ignoring whitespace. Returns `None` if a
+/// character has no superscript variant.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_core::markdown::math::superscript;
+///
+/// assert_eq!(superscript("n+1"), Some("ⁿ⁺¹".to_string()));
+/// assert_eq!(superscript("q"), None);
+/// ```
+pub fn superscript(text: &str) -> Option<String> {
+    text.chars()
+        .filter(|c| !c.is_whitespace())
+        .map(superscript_char)
+        .collect()
+}
+
+/// Converts the text into subscript characters, ignoring whitespace. Returns `None` if a character
+/// has no subscript variant.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_core::markdown::math::subscript;
+///
+/// assert_eq!(subscript("i=0"), Some("ᵢ₌₀".to_string()));
+/// assert_eq!(subscript("y"), None);
+/// ```
+pub fn subscript(text: &str) -> Option<String> {
+    text.chars()
+        .filter(|c| !c.is_whitespace())
+        .map(subscript_char)
+        .collect()
+}
+
+/// Wraps the text in parentheses when it is more than a single number or word.
+fn group(text: &str) -> String {
+    if text.chars().all(char::is_alphanumeric) {
+        text.to_string()
+    } else {
+        format!("({text})")
+    }
+}
+
+/// Writes a superscript or subscript, falling back to the `^` or `_` marker when the script has
+/// no unicode variant.
+fn script(marker: char, text: &str) -> String {
+    let converted = match marker {
+        '^' => superscript(text),
+        _ => subscript(text),
+    };
+
+    match converted {
+        Some(converted) if !converted.is_empty() => converted,
+        _ if text.chars().count() == 1 => format!("{marker}{text}"),
+        _ => format!("{marker}({text})"),
+    }
+}
+
+fn fraction(numerator: &str, denominator: &str) -> String {
+    VULGAR_FRACTIONS
+        .iter()
+        .find(|(n, d, _)| *n == numerator.trim() && *d == denominator.trim())
+        .map(|(.., fraction)| fraction.to_string())
+        .unwrap_or_else(|| format!("{}/{}", group(numerator), group(denominator)))
+}
+
+fn root(index: Option<&str>, radicand: &str) -> String {
+    let symbol = match index.map(str::trim) {
+        None | Some("2") => "√".to_string(),
+        Some("3") => "∛".to_string(),
+        Some("4") => "∜".to_string(),
+        Some(index) => format!("{}√", script('^', index)),
+    };
+    format!("{symbol}{}", group(radicand))
+}
+
+struct Converter<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl Converter<'_> {
+    /// Converts up to the end of the input, or up to the closing brace of the current group.
+    fn convert(&mut self, in_group: bool) -> String {
+        let mut output = String::new();
+
+        while let Some(c) = self.chars.next() {
+            match c {
+                '}' if in_group => break,
+                '{' => output.push_str(&self.convert(true)),
+                '\\' => output.push_str(&self.command()),
+                '^' | '_' => {
+                    let argument = self.argument();
+                    output.push_str(&script(c, &argument));
+                }
+                c => output.push(c),
+            }
+        }
+
+        output
+    }
+
+    /// Reads the text of a group as written, up to its closing brace.
+    fn raw_group(&mut self) -> String {
+        let mut output = String::new();
+        let mut depth = 0;
+
+        for c in self.chars.by_ref() {
+            match c {
+                '{' => depth += 1,
+                '}' if depth == 0 => break,
+                '}' => depth -= 1,
+                _ => {}
+            }
+            output.push(c);
+        }
+
+        output
+    }
+
+    /// Reads the argument of a command or a script: a group, a command or a single character.
+    fn argument(&mut self) -> String {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        match self.chars.next() {
+            Some('{') => self.convert(true),
+            Some('\\') => self.command(),
+            Some(c) => c.to_string(),
+            None => String::new(),
+        }
+    }
+
+    /// Reads an argument whose text is kept as written, e.g. of `\text`.
+    fn text_argument(&mut self) -> String {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        match self.chars.next() {
+            Some('{') => self.raw_group(),
+            Some(c) => c.to_string(),
+            None => String::new(),
+        }
+    }
+
+    /// Reads an optional argument in brackets, e.g. the index of `\sqrt[3]{x}`.
+    fn optional_argument(&mut self) -> Option<String> {
+        self.chars.next_if_eq(&'[')?;
+        let mut output = String::new();
+        for c in self.chars.by_ref() {
+            if c == ']' {
+                break;
+            }
+            output.push(c);
+        }
+        Some(output)
+    }
+
+    fn command(&mut self) -> String {
+        let mut name = String::new();
+        while let Some(c) = self.chars.next_if(|c| c.is_ascii_alphabetic()) {
+            name.push(c);
+        }
+        if name.is_empty() {
+            match self.chars.next() {
+                Some(c) => name.push(c),
+                None => return "\\".to_string(),
+            }
+        }
+
+        let name = name.as_str();
+
+        if let Some((_, symbol)) = SYMBOLS.iter().find(|(command, _)| *command == name) {
+            return symbol.to_string();
+        }
+
+        if let Some((_, accent)) = ACCENTS.iter().find(|(command, _)| *command == name) {
+            return self.argument().chars().flat_map(|c| [c, *accent]).collect();
+        }
+
+        match name {
+            "frac" | "dfrac" | "tfrac" | "cfrac" => {
+                let numerator = self.argument();
+                let denominator = self.argument();
+                fraction(&numerator, &denominator)
+            }
+            "sqrt" => {
+                let index = self.optional_argument();
+                let radicand = self.argument();
+                root(index.as_deref(), &radicand)
+            }
+            "mathbb" => self.argument().chars().map(double_struck_char).collect(),
+            "," | ";" | ":" | ">" | " " | "\\" => " ".to_string(),
+            "{" | "}" | "%" | "$" | "&" | "#" | "_" | "|" => name.to_string(),
+            _ if TEXT.contains(&name) => self.text_argument(),
+            _ if FONTS.contains(&name) => self.argument(),
+            _ if IGNORED.contains(&name) => String::new(),
+            _ if FUNCTIONS.contains(&name) => name.to_string(),
+            _ => format!("\\{name}"),
+        }
+    }
+}
+
+/// Approximates the LaTeX math with unicode, on a single line.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_core::markdown::math::to_unicode;
+///
+/// assert_eq!(to_unicode(r"\sqrt{x^2 + y^2}"), "√(x² + y²)");
+/// assert_eq!(to_unicode(r"\mathbb{R}^n \to \mathbb{R}"), "ℝⁿ → ℝ");
+/// ```
+pub fn to_unicode(latex: &str) -> String {
+    let converted = Converter {
+        chars: latex.chars().peekable(),
+    }
+    .convert(false);
+
+    converted.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use similar_asserts::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_to_unicode() {
+        let tests = [
+            (r"\alpha + \beta = \Gamma", "α + β = Γ"),
+            (r"x^2 + y_1", "x² + y₁"),
+            (r"x^{n+1} - x_{i,j}", "xⁿ⁺¹ - x_(i,j)"),
+            (r"e^{i\pi} + 1 = 0", "e^(iπ) + 1 = 0"),
+            (
+                r"\frac{1}{2} + \frac{a+b}{c} + \frac{dy}{dx}",
+                "½ + (a+b)/c + dy/dx",
+            ),
+            (r"\sqrt{2} \sqrt[3]{x} \sqrt[n]{y+1}", "√2 ∛x ⁿ√(y+1)"),
+            (r"\sum_{k=0}^{\infty} \frac{x^k}{k!}", "∑ₖ₌₀^∞ xᵏ/(k!)"),
+            (r"\int_a^b f(x) \, dx", "∫ₐᵇ f(x) dx"),
+            (r"\lim_{x \to 0} \frac{\sin x}{x}", "lim_(x → 0) (sin x)/x"),
+            (r"\left( \vec{v} \cdot \hat{n} \right)", "( v⃗ ⋅ n̂ )"),
+            (r"\text{if } x \in \mathbb{N}", "if x ∈ ℕ"),
+            ("a \\\\\n b", "a b"),
+            (r"\forall \epsilon > 0 \exists \delta", "∀ ϵ > 0 ∃ δ"),
+            (r"50\% \{a\}", "50% {a}"),
+            (r"\unknown x", r"\unknown x"),
+        ];
+
+        tests
+            .iter()
+            .for_each(|(latex, expected)| assert_eq!(to_unicode(latex), *expected, "{latex}"));
+    }
+}
@@ -15,11 +15,18 @@
 //! ```
 use std::{io, path::PathBuf, result};
 
+pub mod backlinks;
 pub mod config;
+pub mod daily_notes;
+pub mod date_format;
 pub mod directory;
 pub mod note;
+pub mod search;
+pub mod tags;
+pub mod templates;
 pub mod vault;
 mod vault_entry;
+pub mod wiki_link;
 
 pub use config::ObsidianConfig;
 pub use note::Note;
@@ -60,6 +67,10 @@ pub enum Error {
     #[error("Empty filename for path: {0}")]
     EmptyFileName(PathBuf),
 
+    /// Path given relative to the vault leads outside of it, or is absolute.
+    #[error("Path leads outside the vault: {0}")]
+    PathOutsideVault(PathBuf),
+
     /// JSON (de)serialization error, from [`serde_json::Error`].
     #[error("JSON (de)serialization error: {0}")]
     Json(#[from] serde_json::Error),
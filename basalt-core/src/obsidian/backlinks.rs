@@ -0,0 +1,117 @@
+//! This module provides lookup of backlinks, the wiki-links in other notes of the vault that
+//! point to a given note.
+use std::{fs, ops::Range};
+
+use crate::obsidian::{vault::wiki_link_patterns, Note, Vault, VaultEntry};
+
+/// A single wiki-link pointing to a note.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backlink {
+    /// The note containing the link.
+    pub note: Note,
+
+    /// Byte range of the whole link, from `[[` to `]]`, within the linking note content.
+    pub range: Range<usize>,
+
+    /// Zero-based line of the link within the linking note.
+    pub line: usize,
+
+    /// The trimmed line containing the link.
+    pub text: String,
+}
+
+/// Finds every wiki-link in the vault that points to the given note.
+///
+/// Handles simple links (`[[name]]`), links with aliases (`[[name|alias]]`), and links with
+/// headings (`[[name#heading]]`). Only Markdown (`.md`) files are scanned and links from the
+/// note to itself are skipped.
+///
+/// Backlinks are ordered by the path of the linking note and the position of the link.
+///
+/// # Examples
+///
+/// ```
+/// # use std::fs;
+/// # use tempfile::tempdir;
+/// # use basalt_core::obsidian::{self, Vault, Error};
+/// use basalt_core::obsidian::backlinks;
+///
+/// # let tmp_dir = tempdir()?;
+/// let vault = Vault { path: tmp_dir.path().to_path_buf(), ..Default::default() };
+/// let recipes = obsidian::vault::create_note(&vault.path, "Recipes")?;
+/// let groceries = obsidian::vault::create_note(&vault.path, "Groceries")?;
+/// fs::write(recipes.path(), "# Pancakes\n\nBuy [[Groceries|the usual]] first.")?;
+/// fs::write(groceries.path(), "- Eggs\n- See [[Groceries#Dairy]]")?;
+///
+/// let links = backlinks::backlinks(&vault, &groceries);
+/// assert_eq!(links.len(), 1);
+/// assert_eq!(links[0].note.name(), "Recipes");
+/// assert_eq!(links[0].line, 2);
+/// assert_eq!(links[0].range, 16..39);
+/// assert_eq!(links[0].text, "Buy [[Groceries|the usual]] first.");
+///
+/// assert!(backlinks::backlinks(&vault, &recipes).is_empty());
+/// # Ok::<(), Error>(())
+/// ```
+pub fn backlinks(vault: &Vault, note: &Note) -> Vec<Backlink> {
+    let patterns = wiki_link_patterns(note.name());
+
+    let mut backlinks: Vec<Backlink> = vault
+        .entries()
+        .into_iter()
+        .flat_map(VaultEntry::into_notes)
+        .filter(|linking_note| linking_note.path() != note.path())
+        .filter(|linking_note| {
+            linking_note
+                .path()
+                .extension()
+                .is_some_and(|ext| ext == "md")
+        })
+        .flat_map(|linking_note| find_links(linking_note, &patterns))
+        .collect();
+
+    backlinks.sort_by(|a, b| {
+        a.note
+            .path()
+            .cmp(b.note.path())
+            .then_with(|| a.range.start.cmp(&b.range.start))
+    });
+
+    backlinks
+}
+
+/// Finds the links matching any of the patterns in a single note.
+fn find_links(note: Note, patterns: &[String]) -> Vec<Backlink> {
+    let Ok(content) = fs::read_to_string(note.path()) else {
+        return vec![];
+    };
+
+    let mut links = Vec::new();
+    let mut line_start = 0;
+
+    for (line_number, line) in content.split_inclusive('\n').enumerate() {
+        let text = line.trim_end_matches(['\n', '\r']);
+
+        let mut starts: Vec<usize> = patterns
+            .iter()
+            .flat_map(|pattern| text.match_indices(pattern.as_str()))
+            .map(|(start, _)| start)
+            .collect();
+        starts.sort_unstable();
+
+        links.extend(starts.into_iter().filter_map(|start| {
+            // A link has to be closed on the same line.
+            let end = start + text[start..].find("]]")? + 2;
+            Some(Backlink {
+                note: note.clone(),
+                range: line_start + start..line_start + end,
+                line: line_number,
+                text: text.trim().to_string(),
+            })
+        }));
+
+        line_start += line.len();
+    }
+
+    links
+}
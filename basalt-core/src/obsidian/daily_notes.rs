@@ -0,0 +1,290 @@
+//! This module provides daily notes, configured like the daily notes core plugin of Obsidian in
+//! `.obsidian/daily-notes.json`:
+//!
+//! ```json
+//! {
+//!   "folder": "Journal",
+//!   "format": "YYYY/MM/YYYY-MM-DD",
+//!   "template": "Templates/Daily"
+//! }
+//! ```
+//!
+//! The name of a daily note is its date written in the [`date_format`] of the config, so a `/` in
+//! the format puts the notes into subfolders.
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+use time::{Date, PrimitiveDateTime};
+
+use crate::obsidian::{self, date_format, templates, Error, Note, Result, Vault, VaultEntry};
+
+/// The date format of daily notes when the config leaves it empty.
+pub const DEFAULT_FORMAT: &str = "YYYY-MM-DD";
+
+/// The daily notes config of a vault.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct DailyNotesConfig {
+    /// Folder of the daily notes relative to the vault root. Empty for the vault root.
+    pub folder: String,
+
+    /// Date format of the note names. Empty for [`DEFAULT_FORMAT`].
+    pub format: String,
+
+    /// Path of the template note relative to the vault root, with or without the `.md`
+    /// extension. Empty for no template.
+    pub template: String,
+}
+
+/// Reads the daily notes config of the vault from `.obsidian/daily-notes.json`. A vault without
+/// the file uses the defaults of Obsidian.
+///
+/// Returns an [`Error`] if the file cannot be read or parsed.
+///
+/// # Examples
+///
+/// ```
+/// # use std::fs;
+/// # use tempfile::tempdir;
+/// use basalt_core::obsidian::{daily_notes, Error, Vault};
+///
+/// # let tmp_dir = tempdir()?;
+/// let vault = Vault { path: tmp_dir.path().to_path_buf(), ..Default::default() };
+/// assert_eq!(daily_notes::load(&vault)?.date_format(), "YYYY-MM-DD");
+///
+/// fs::create_dir(vault.path.join(".obsidian"))?;
+/// fs::write(
+///     vault.path.join(".obsidian/daily-notes.json"),
+///     r#"{ "folder": "Journal", "format": "DD.MM.YYYY" }"#,
+/// )?;
+/// let config = daily_notes::load(&vault)?;
+/// assert_eq!(config.folder, "Journal");
+/// assert_eq!(config.date_format(), "DD.MM.YYYY");
+/// # Ok::<(), Error>(())
+/// ```
+pub fn load(vault: &Vault) -> Result<DailyNotesConfig> {
+    let path = vault.path.join(".obsidian").join("daily-notes.json");
+
+    if !path.try_exists()? {
+        return Ok(DailyNotesConfig::default());
+    }
+
+    let contents = fs::read_to_string(path)?;
+    serde_json::from_str(&contents).map_err(Error::Json)
+}
+
+impl DailyNotesConfig {
+    /// Returns the date format of the note names.
+    pub fn date_format(&self) -> &str {
+        match self.format.trim() {
+            "" => DEFAULT_FORMAT,
+            format => format,
+        }
+    }
+
+    fn folder_path(&self, vault: &Vault) -> PathBuf {
+        vault.path.join(self.folder.trim_matches('/'))
+    }
+
+    /// Returns the name of the daily note of the date, relative to the daily notes folder.
+    pub fn note_name(&self, date: Date) -> String {
+        date_format::format(date.midnight(), self.date_format())
+    }
+
+    /// Returns the path of the daily note of the date.
+    pub fn note_path(&self, vault: &Vault, date: Date) -> PathBuf {
+        self.folder_path(vault)
+            .join(format!("{}.md", self.note_name(date)))
+    }
+
+    /// Returns the date of the note at the path, or [`None`] if it is not a daily note.
+    pub fn date_of(&self, vault: &Vault, path: &Path) -> Option<Date> {
+        let name = path
+            .strip_prefix(self.folder_path(vault))
+            .ok()?
+            .to_str()?
+            .strip_suffix(".md")?
+            .replace('\\', "/");
+
+        date_format::parse_date(&name, self.date_format())
+    }
+
+    /// Returns every daily note of the vault with its date, ordered by date.
+    pub fn notes(&self, vault: &Vault) -> Vec<(Date, Note)> {
+        let Ok(VaultEntry::Directory { entries, .. }) =
+            VaultEntry::try_from(self.folder_path(vault).as_path())
+        else {
+            return vec![];
+        };
+
+        let mut notes: Vec<(Date, Note)> = entries
+            .into_iter()
+            .filter(|entry| !entry.name().starts_with('.'))
+            .flat_map(VaultEntry::into_notes)
+            .filter_map(|note| Some((self.date_of(vault, note.path())?, note)))
+            .collect();
+
+        notes.sort_by_key(|(date, _)| *date);
+        notes
+    }
+}
+
+/// Opens the daily note of today, creating it if it does not exist yet.
+///
+/// A new note is created with [`obsidian::vault::create_note_with_content`] and filled with the
+/// template of the config, if any, with its variables expanded by [`templates::expand`]. Like in
+/// Obsidian, `{{date}}` is written in the date format of the daily notes.
+///
+/// Returns an [`Error`] if the template cannot be read or the note cannot be created. The note is
+/// not created when the template is missing.
+///
+/// # Examples
+///
+/// ```
+/// # use std::fs;
+/// # use tempfile::tempdir;
+/// use basalt_core::obsidian::{daily_notes::{self, DailyNotesConfig}, Error, Vault};
+/// use time::macros::datetime;
+///
+/// # let tmp_dir = tempdir()?;
+/// let vault = Vault { path: tmp_dir.path().to_path_buf(), ..Default::default() };
+/// # fs::create_dir(vault.path.join("Templates"))?;
+/// fs::write(vault.path.join("Templates/Daily.md"), "# {{title}}\n\nWritten at {{time}}")?;
+///
+/// let config = DailyNotesConfig {
+///     folder: "Journal".into(),
+///     template: "Templates/Daily".into(),
+///     ..Default::default()
+/// };
+///
+/// let note = daily_notes::open_today(&vault, &config, datetime!(2024-03-01 09:30))?;
+/// assert_eq!(note.path(), vault.path.join("Journal/2024-03-01.md"));
+/// assert_eq!(fs::read_to_string(note.path())?, "# 2024-03-01\n\nWritten at 09:30");
+///
+/// // An existing daily note is opened as is.
+/// fs::write(note.path(), "Edited")?;
+/// let note = daily_notes::open_today(&vault, &config, datetime!(2024-03-01 18:00))?;
+/// assert_eq!(fs::read_to_string(note.path())?, "Edited");
+/// # Ok::<(), Error>(())
+/// ```
+pub fn open_today(
+    vault: &Vault,
+    config: &DailyNotesConfig,
+    now: PrimitiveDateTime,
+) -> Result<Note> {
+    let date = now.date();
+    let path = config.note_path(vault, date);
+    let name = config.note_name(date);
+    let title = name.rsplit('/').next().unwrap_or(&name).to_string();
+
+    if path.try_exists()? {
+        return Note::try_from((title, path));
+    }
+
+    let content = match config.template.trim().trim_matches('/') {
+        "" => String::new(),
+        template => {
+            let template_path = match template.ends_with(".md") {
+                true => vault.path.join(template),
+                false => vault.path.join(format!("{template}.md")),
+            };
+            if !template_path.try_exists()? {
+                return Err(Error::PathNotFound(
+                    template_path.to_string_lossy().to_string(),
+                ));
+            }
+            let template = fs::read_to_string(template_path)?;
+            templates::expand(
+                &template,
+                &title,
+                now,
+                config.date_format(),
+                templates::DEFAULT_TIME_FORMAT,
+            )
+        }
+    };
+
+    let note =
+        obsidian::vault::create_note_with_content(config.folder_path(vault), &name, &content)?;
+    Ok(note)
+}
+
+/// Returns the closest daily note before the date, if any.
+pub fn previous(vault: &Vault, config: &DailyNotesConfig, date: Date) -> Option<Note> {
+    config
+        .notes(vault)
+        .into_iter()
+        .rev()
+        .find(|(note_date, _)| *note_date < date)
+        .map(|(_, note)| note)
+}
+
+/// Returns the closest daily note after the date, if any.
+pub fn next(vault: &Vault, config: &DailyNotesConfig, date: Date) -> Option<Note> {
+    config
+        .notes(vault)
+        .into_iter()
+        .find(|(note_date, _)| *note_date > date)
+        .map(|(_, note)| note)
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+    use time::macros::{date, datetime};
+
+    use super::*;
+
+    #[test]
+    fn test_previous_and_next_skip_missing_days() {
+        let tmp_dir = tempdir().unwrap();
+        let vault = Vault {
+            path: tmp_dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        let config = DailyNotesConfig {
+            folder: "Journal/".into(),
+            format: "YYYY/YYYY-MM-DD".into(),
+            ..Default::default()
+        };
+
+        for name in ["2023/2023-12-30", "2024/2024-01-02", "2024/Ideas"] {
+            let path = vault.path.join("Journal").join(format!("{name}.md"));
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+        fs::write(vault.path.join("2024-01-01.md"), "").unwrap();
+
+        let dates: Vec<Date> = config.notes(&vault).into_iter().map(|(d, _)| d).collect();
+        assert_eq!(dates, [date!(2023 - 12 - 30), date!(2024 - 01 - 02)]);
+
+        let today = date!(2024 - 01 - 01);
+        let name = |note: Option<Note>| note.map(|note| note.name().to_string());
+        assert_eq!(
+            name(previous(&vault, &config, today)),
+            Some("2023-12-30".into())
+        );
+        assert_eq!(
+            name(next(&vault, &config, today)),
+            Some("2024-01-02".into())
+        );
+        assert_eq!(name(next(&vault, &config, date!(2024 - 01 - 02))), None);
+
+        let note = open_today(&vault, &config, today.midnight()).unwrap();
+        assert_eq!(note.path(), vault.path.join("Journal/2024/2024-01-01.md"));
+        assert_eq!(config.date_of(&vault, note.path()), Some(today));
+
+        let config = DailyNotesConfig {
+            template: "Missing".into(),
+            ..config
+        };
+        assert!(matches!(
+            open_today(&vault, &config, datetime!(2024-01-03 00:00)),
+            Err(Error::PathNotFound(_))
+        ));
+        assert!(!config.note_path(&vault, date!(2024 - 01 - 03)).exists());
+    }
+}
@@ -0,0 +1,366 @@
+//! This module provides the [Moment.js](https://momentjs.com/docs/#/displaying/format/) date
+//! formats that Obsidian uses for the names of daily notes and the `{{date}}` template variable,
+//! e.g. `YYYY-MM-DD` or `dddd, MMMM Do YYYY`.
+//!
+//! Supported tokens:
+//!
+//! | Token                         | Output                               |
+//! | ----------------------------- | ------------------------------------ |
+//! | `YYYY`, `YY`                  | `2024`, `24`                         |
+//! | `Q`                           | Quarter, `1` to `4`                  |
+//! | `MMMM`, `MMM`, `MM`, `M`      | `January`, `Jan`, `01`, `1`          |
+//! | `DDDD`, `DDD`                 | Day of year, `001`, `1`              |
+//! | `DD`, `D`, `Do`               | `01`, `1`, `1st`                     |
+//! | `dddd`, `ddd`, `dd`, `d`      | `Monday`, `Mon`, `Mo`, `1`           |
+//! | `GGGG`, `WW`, `W`             | ISO week year, `01`, `1`             |
+//! | `HH`, `H`, `hh`, `h`          | `09`, `9` (24-hour and 12-hour)      |
+//! | `mm`, `m`, `ss`, `s`          | Minutes and seconds                  |
+//! | `A`, `a`                      | `AM`, `am`                           |
+//!
+//! Text within square brackets is escaped, e.g. `[Week] W`. Any other character is copied as is.
+use time::{Date, Month, PrimitiveDateTime};
+
+/// The tokens, longest first among tokens sharing a start.
+const TOKENS: &[&str] = &[
+    "YYYY", "YY", "Q", "MMMM", "MMM", "MM", "M", "DDDD", "DDD", "Do", "DD", "D", "dddd", "ddd",
+    "dd", "d", "GGGG", "WW", "W", "HH", "H", "hh", "h", "mm", "m", "ss", "s", "A", "a",
+];
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+const WEEKDAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+#[derive(Debug, PartialEq)]
+enum Part<'a> {
+    Token(&'static str),
+    Literal(&'a str),
+}
+
+fn parts(format: &str) -> Vec<Part<'_>> {
+    let mut parts = Vec::new();
+    let mut rest = format;
+
+    while let Some(c) = rest.chars().next() {
+        if c == '[' {
+            if let Some(end) = rest.find(']') {
+                parts.push(Part::Literal(&rest[1..end]));
+                rest = &rest[end + 1..];
+                continue;
+            }
+        }
+
+        match TOKENS.iter().find(|token| rest.starts_with(*token)) {
+            Some(token) => {
+                parts.push(Part::Token(token));
+                rest = &rest[token.len()..];
+            }
+            None => {
+                parts.push(Part::Literal(&rest[..c.len_utf8()]));
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+
+    parts
+}
+
+fn ordinal(n: u8) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{n}{suffix}")
+}
+
+/// Formats the date and time with the given format.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_core::obsidian::date_format;
+/// use time::macros::datetime;
+///
+/// let date_time = datetime!(2024-03-01 14:05);
+///
+/// assert_eq!(date_format::format(date_time, "YYYY-MM-DD"), "2024-03-01");
+/// assert_eq!(date_format::format(date_time, "dddd, MMMM Do YYYY"), "Friday, March 1st 2024");
+/// assert_eq!(date_format::format(date_time, "[Week] W, h:mm a"), "Week 9, 2:05 pm");
+/// ```
+pub fn format(date_time: PrimitiveDateTime, format: &str) -> String {
+    let date = date_time.date();
+    let month = u8::from(date.month());
+    let weekday = date.weekday().number_days_from_sunday() as usize;
+    let hour_12 = match date_time.hour() % 12 {
+        0 => 12,
+        hour => hour,
+    };
+    let am = date_time.hour() < 12;
+
+    parts(format)
+        .into_iter()
+        .map(|part| match part {
+            Part::Literal(text) => text.to_string(),
+            Part::Token("YYYY") => format!("{:04}", date.year()),
+            Part::Token("YY") => format!("{:02}", date.year().rem_euclid(100)),
+            Part::Token("Q") => ((month - 1) / 3 + 1).to_string(),
+            Part::Token("MMMM") => MONTHS[month as usize - 1].to_string(),
+            Part::Token("MMM") => MONTHS[month as usize - 1][..3].to_string(),
+            Part::Token("MM") => format!("{month:02}"),
+            Part::Token("M") => month.to_string(),
+            Part::Token("DDDD") => format!("{:03}", date.ordinal()),
+            Part::Token("DDD") => date.ordinal().to_string(),
+            Part::Token("Do") => ordinal(date.day()),
+            Part::Token("DD") => format!("{:02}", date.day()),
+            Part::Token("D") => date.day().to_string(),
+            Part::Token("dddd") => WEEKDAYS[weekday].to_string(),
+            Part::Token("ddd") => WEEKDAYS[weekday][..3].to_string(),
+            Part::Token("dd") => WEEKDAYS[weekday][..2].to_string(),
+            Part::Token("d") => weekday.to_string(),
+            Part::Token("GGGG") => format!("{:04}", date.to_iso_week_date().0),
+            Part::Token("WW") => format!("{:02}", date.iso_week()),
+            Part::Token("W") => date.iso_week().to_string(),
+            Part::Token("HH") => format!("{:02}", date_time.hour()),
+            Part::Token("H") => date_time.hour().to_string(),
+            Part::Token("hh") => format!("{hour_12:02}"),
+            Part::Token("h") => hour_12.to_string(),
+            Part::Token("mm") => format!("{:02}", date_time.minute()),
+            Part::Token("m") => date_time.minute().to_string(),
+            Part::Token("ss") => format!("{:02}", date_time.second()),
+            Part::Token("s") => date_time.second().to_string(),
+            Part::Token("A") => if am { "AM" } else { "PM" }.to_string(),
+            Part::Token("a") => if am { "am" } else { "pm" }.to_string(),
+            Part::Token(token) => token.to_string(),
+        })
+        .collect()
+}
+
+/// Splits off a number of `min..=max` digits from the start of the text.
+fn number(text: &str, min: usize, max: usize) -> Option<(u32, &str)> {
+    let len = text
+        .bytes()
+        .take(max)
+        .take_while(u8::is_ascii_digit)
+        .count();
+    if len < min {
+        return None;
+    }
+    Some((text[..len].parse().ok()?, &text[len..]))
+}
+
+/// Splits off one of the names, or its abbreviation of the given length, from the start of the
+/// text. Returns the index of the name. Matching ignores case.
+fn name<'a>(text: &'a str, names: &[&str], len: Option<usize>) -> Option<(usize, &'a str)> {
+    names.iter().enumerate().find_map(|(index, name)| {
+        let name = len.map_or(*name, |len| &name[..len]);
+        let head = text.get(..name.len())?;
+        head.eq_ignore_ascii_case(name)
+            .then(|| (index, &text[name.len()..]))
+    })
+}
+
+/// Parses the date of a text written in the given format, the reverse of [`format`].
+///
+/// The year, month, day and day of year tokens make up the date; a missing month or day defaults
+/// to the first. Weekday names and time tokens have to match their form but are otherwise
+/// ignored. Returns [`None`] if the text does not match the format or the date is invalid.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_core::obsidian::date_format;
+/// use time::macros::date;
+///
+/// assert_eq!(date_format::parse_date("2024-03-01", "YYYY-MM-DD"), Some(date!(2024-03-01)));
+/// assert_eq!(
+///     date_format::parse_date("Friday, March 1st 2024", "dddd, MMMM Do YYYY"),
+///     Some(date!(2024-03-01))
+/// );
+/// assert_eq!(date_format::parse_date("2024-02-30", "YYYY-MM-DD"), None);
+/// assert_eq!(date_format::parse_date("Meeting notes", "YYYY-MM-DD"), None);
+/// ```
+pub fn parse_date(text: &str, format: &str) -> Option<Date> {
+    let mut year = None;
+    let mut month = 1;
+    let mut day = 1;
+    let mut day_of_year = None;
+
+    let mut rest = text;
+    for part in parts(format) {
+        rest = match part {
+            Part::Literal(literal) => rest.strip_prefix(literal)?,
+            Part::Token("YYYY") => {
+                let (value, rest) = number(rest, 4, 4)?;
+                year = Some(value as i32);
+                rest
+            }
+            Part::Token("YY") => {
+                // Moment.js puts two-digit years above 68 into the 1900s.
+                let (value, rest) = number(rest, 2, 2)?;
+                year = Some(if value > 68 { 1900 } else { 2000 } + value as i32);
+                rest
+            }
+            Part::Token("MMMM") => {
+                let (index, rest) = name(rest, &MONTHS, None)?;
+                month = index as u8 + 1;
+                rest
+            }
+            Part::Token("MMM") => {
+                let (index, rest) = name(rest, &MONTHS, Some(3))?;
+                month = index as u8 + 1;
+                rest
+            }
+            Part::Token("MM") => {
+                let (value, rest) = number(rest, 2, 2)?;
+                month = value as u8;
+                rest
+            }
+            Part::Token("M") => {
+                let (value, rest) = number(rest, 1, 2)?;
+                month = value as u8;
+                rest
+            }
+            Part::Token("DDDD") => {
+                let (value, rest) = number(rest, 3, 3)?;
+                day_of_year = Some(value as u16);
+                rest
+            }
+            Part::Token("DDD") => {
+                let (value, rest) = number(rest, 1, 3)?;
+                day_of_year = Some(value as u16);
+                rest
+            }
+            Part::Token("Do") => {
+                let (value, rest) = number(rest, 1, 2)?;
+                day = value as u8;
+                ["st", "nd", "rd", "th"]
+                    .iter()
+                    .find_map(|suffix| rest.strip_prefix(suffix))?
+            }
+            Part::Token("DD") => {
+                let (value, rest) = number(rest, 2, 2)?;
+                day = value as u8;
+                rest
+            }
+            Part::Token("D") => {
+                let (value, rest) = number(rest, 1, 2)?;
+                day = value as u8;
+                rest
+            }
+            Part::Token("dddd") => name(rest, &WEEKDAYS, None)?.1,
+            Part::Token("ddd") => name(rest, &WEEKDAYS, Some(3))?.1,
+            Part::Token("dd") => name(rest, &WEEKDAYS, Some(2))?.1,
+            Part::Token("A" | "a") => ["AM", "PM", "am", "pm"]
+                .iter()
+                .find_map(|suffix| rest.strip_prefix(suffix))?,
+            Part::Token("GGGG") => number(rest, 4, 4)?.1,
+            Part::Token("WW" | "HH" | "hh" | "mm" | "ss") => number(rest, 2, 2)?.1,
+            Part::Token(_) => number(rest, 1, 2)?.1,
+        };
+    }
+
+    if !rest.is_empty() {
+        return None;
+    }
+
+    let year = year?;
+    match day_of_year {
+        Some(ordinal) => Date::from_ordinal_date(year, ordinal).ok(),
+        None => Date::from_calendar_date(year, Month::try_from(month).ok()?, day).ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::{date, datetime};
+
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        let date_time = datetime!(2024-12-30 00:07:09);
+
+        let cases = [
+            ("YYYY-MM-DD", "2024-12-30"),
+            ("YY/M/D", "24/12/30"),
+            ("DDDD DDD Q", "365 365 4"),
+            ("ddd dd d", "Mon Mo 1"),
+            ("GGGG-[W]WW", "2025-W01"),
+            ("HH:mm:ss hh A", "00:07:09 12 AM"),
+            ("[YYYY] YYYY", "YYYY 2024"),
+            ("MMM Do, [open", "Dec 30th, [open"),
+        ];
+
+        for (format_str, expected) in cases {
+            assert_eq!(format(date_time, format_str), expected, "{format_str}");
+        }
+
+        assert_eq!(
+            (1..=4).map(ordinal).collect::<Vec<_>>(),
+            ["1st", "2nd", "3rd", "4th"]
+        );
+        assert_eq!(
+            [11, 12, 13, 21, 22, 111].map(ordinal),
+            ["11th", "12th", "13th", "21st", "22nd", "111th"]
+        );
+    }
+
+    #[test]
+    fn test_parse_date() {
+        let cases = [
+            ("2024-12-30", "YYYY-MM-DD", Some(date!(2024 - 12 - 30))),
+            (
+                "2024/12/2024-12-30",
+                "YYYY/MM/YYYY-MM-DD",
+                Some(date!(2024 - 12 - 30)),
+            ),
+            ("30.1.99", "D.M.YY", Some(date!(1999 - 01 - 30))),
+            (
+                "Mon, Dec 30th 2024",
+                "ddd, MMM Do YYYY",
+                Some(date!(2024 - 12 - 30)),
+            ),
+            ("2024 365", "YYYY DDDD", Some(date!(2024 - 12 - 30))),
+            (
+                "Journal 2024-12",
+                "[Journal] YYYY-MM",
+                Some(date!(2024 - 12 - 01)),
+            ),
+            ("2024-12-30 extra", "YYYY-MM-DD", None),
+            ("24-12-30", "YYYY-MM-DD", None),
+            ("2024-13-01", "YYYY-MM-DD", None),
+            ("12-30", "MM-DD", None),
+        ];
+
+        for (text, format_str, expected) in cases {
+            assert_eq!(
+                parse_date(text, format_str),
+                expected,
+                "{text} as {format_str}"
+            );
+        }
+    }
+}
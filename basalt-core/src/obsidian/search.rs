@@ -0,0 +1,253 @@
+//! This module provides full-text search over the notes of an Obsidian vault.
+//!
+//! The query is matched case-insensitively against both note names and note contents. Each
+//! matching line yields one [`SearchHit`], and the hits are ranked so that name matches and
+//! whole-word matches come first.
+use std::{fs, ops::Range};
+
+use crate::obsidian::{Note, Vault, VaultEntry};
+
+/// Maximum number of characters kept in a [`SearchHit::snippet`].
+const SNIPPET_WIDTH: usize = 80;
+
+/// Number of characters kept before the match when a snippet has to be shortened.
+const SNIPPET_LEADING_CONTEXT: usize = 20;
+
+/// Where in the note a [`SearchHit`] matched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchKind {
+    /// The query matched the note name.
+    Name,
+    /// The query matched a line of the note content.
+    Content,
+}
+
+/// A single search result.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    /// The note that matched.
+    pub note: Note,
+
+    /// Whether the note name or its content matched.
+    pub kind: MatchKind,
+
+    /// Zero-based line of the match within the note. Always `0` for [`MatchKind::Name`].
+    pub line: usize,
+
+    /// Byte offset of the match within the note content. Always `0` for [`MatchKind::Name`].
+    pub offset: usize,
+
+    /// The matching line (or note name), trimmed and shortened around the match.
+    pub snippet: String,
+
+    /// Byte range of the match within [`SearchHit::snippet`].
+    pub highlight: Range<usize>,
+
+    /// Relevance of the hit. Higher is better.
+    pub score: usize,
+}
+
+/// Searches every note in the vault for the given query.
+///
+/// Both the note names and the note contents are matched case-insensitively. Contents are only
+/// read from Markdown (`.md`) files. Each matching line produces a single hit, pointing at the
+/// first match on that line. An empty (or whitespace only) query returns no hits.
+///
+/// Hits are ordered by [`SearchHit::score`], with ties ordered by note path and offset.
+///
+/// # Examples
+///
+/// ```
+/// # use std::fs;
+/// # use tempfile::tempdir;
+/// # use basalt_core::obsidian::{self, Vault, Error};
+/// use basalt_core::obsidian::search::{self, MatchKind};
+///
+/// # let tmp_dir = tempdir()?;
+/// let vault = Vault { path: tmp_dir.path().to_path_buf(), ..Default::default() };
+/// let recipes = obsidian::vault::create_note(&vault.path, "Recipes")?;
+/// let groceries = obsidian::vault::create_note(&vault.path, "Groceries")?;
+/// fs::write(recipes.path(), "# Pancakes\n\nWhisk the eggs and milk.")?;
+/// fs::write(groceries.path(), "- Eggs\n- Milk\n- Flour")?;
+///
+/// let hits = search::search(&vault, "milk");
+/// assert_eq!(hits.len(), 2);
+/// assert!(hits.iter().all(|hit| hit.kind == MatchKind::Content));
+///
+/// let hit = hits.iter().find(|hit| hit.note.name() == "Recipes").unwrap();
+/// assert_eq!(hit.line, 2);
+/// assert_eq!(hit.offset, 31);
+/// assert_eq!(hit.snippet, "Whisk the eggs and milk.");
+/// assert_eq!(&hit.snippet[hit.highlight.clone()], "milk");
+///
+/// let hits = search::search(&vault, "recipe");
+/// assert_eq!(hits[0].kind, MatchKind::Name);
+/// assert_eq!(hits[0].note.name(), "Recipes");
+///
+/// assert!(search::search(&vault, "  ").is_empty());
+/// # Ok::<(), Error>(())
+/// ```
+pub fn search(vault: &Vault, query: &str) -> Vec<SearchHit> {
+    let query = query.trim();
+    if query.is_empty() {
+        return vec![];
+    }
+
+    let mut hits: Vec<SearchHit> = vault
+        .entries()
+        .into_iter()
+        .flat_map(VaultEntry::into_notes)
+        .flat_map(|note| search_note(note, query))
+        .collect();
+
+    hits.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.note.path().cmp(b.note.path()))
+            .then_with(|| a.offset.cmp(&b.offset))
+    });
+
+    hits
+}
+
+/// Matches a single note name and content against the query.
+fn search_note(note: Note, query: &str) -> Vec<SearchHit> {
+    let mut hits = Vec::new();
+
+    if let Some(start) = find_ignore_case(note.name(), query) {
+        let end = start + matched_len(note.name(), start, query);
+        let score = if end - start == note.name().len() {
+            300
+        } else if start == 0 {
+            200
+        } else {
+            100
+        };
+        let (snippet, highlight) = snippet(note.name(), start..end);
+        hits.push(SearchHit {
+            note: note.clone(),
+            kind: MatchKind::Name,
+            line: 0,
+            offset: 0,
+            snippet,
+            highlight,
+            score,
+        });
+    }
+
+    let is_markdown = note.path().extension().is_some_and(|ext| ext == "md");
+    let Some(content) = is_markdown
+        .then(|| fs::read_to_string(note.path()).ok())
+        .flatten()
+    else {
+        return hits;
+    };
+
+    let mut line_start = 0;
+    for (line_number, line) in content.split_inclusive('\n').enumerate() {
+        let text = line.trim_end_matches(['\n', '\r']);
+        if let Some(start) = find_ignore_case(text, query) {
+            let end = start + matched_len(text, start, query);
+            let (snippet, highlight) = snippet(text, start..end);
+            hits.push(SearchHit {
+                note: note.clone(),
+                kind: MatchKind::Content,
+                line: line_number,
+                offset: line_start + start,
+                snippet,
+                highlight,
+                score: content_score(text, start..end, query),
+            });
+        }
+        line_start += line.len();
+    }
+
+    hits
+}
+
+/// Scores a content match. Whole-word matches rank above partial ones, and an exact case match
+/// breaks ties between otherwise equal hits.
+fn content_score(text: &str, range: Range<usize>, query: &str) -> usize {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    let starts_word = !text[..range.start]
+        .chars()
+        .next_back()
+        .is_some_and(is_word_char);
+    let ends_word = !text[range.end..].chars().next().is_some_and(is_word_char);
+
+    let mut score = 10;
+    if starts_word {
+        score += 5;
+    }
+    if ends_word {
+        score += 5;
+    }
+    if &text[range] == query {
+        score += 2;
+    }
+    score
+}
+
+/// Returns the byte offset of the first case-insensitive occurrence of `needle` in `haystack`.
+///
+/// Comparison is done per lowercased character, so offsets always land on character boundaries
+/// of the original `haystack`, even when lowercasing changes the byte length of a character.
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+    let needle: Vec<char> = needle.chars().flat_map(char::to_lowercase).collect();
+
+    haystack
+        .char_indices()
+        .find(|(index, _)| {
+            let mut rest = haystack[*index..].chars().flat_map(char::to_lowercase);
+            needle.iter().all(|c| rest.next() == Some(*c))
+        })
+        .map(|(index, _)| index)
+}
+
+/// Byte length of the match starting at `start` in `haystack`, measured in characters of the
+/// original text so that the range can be used to slice it.
+fn matched_len(haystack: &str, start: usize, needle: &str) -> usize {
+    let needle_len = needle.chars().flat_map(char::to_lowercase).count();
+    let mut lowered = 0;
+
+    haystack[start..]
+        .chars()
+        .take_while(|c| {
+            let take = lowered < needle_len;
+            lowered += c.to_lowercase().count();
+            take
+        })
+        .map(char::len_utf8)
+        .sum()
+}
+
+/// Trims the text and shortens it around the match so that it fits into [`SNIPPET_WIDTH`]
+/// characters. Returns the snippet and the match range within it.
+fn snippet(text: &str, range: Range<usize>) -> (String, Range<usize>) {
+    let leading = text.len() - text.trim_start().len();
+    let text = text.trim();
+    let range = range.start.saturating_sub(leading).min(text.len())
+        ..range.end.saturating_sub(leading).min(text.len());
+
+    let match_char_start = text[..range.start].chars().count();
+    let skip = match_char_start.saturating_sub(SNIPPET_LEADING_CONTEXT);
+    let start = text
+        .char_indices()
+        .nth(skip)
+        .map(|(index, _)| index)
+        .unwrap_or(0);
+    let end = text[start..]
+        .char_indices()
+        .nth(SNIPPET_WIDTH)
+        .map(|(index, _)| start + index)
+        .unwrap_or(text.len())
+        .max(range.end);
+
+    let prefix = if start > 0 { "…" } else { "" };
+    let suffix = if end < text.len() { "…" } else { "" };
+
+    let snippet = format!("{prefix}{}{suffix}", &text[start..end]);
+    let highlight = (range.start - start + prefix.len())..(range.end - start + prefix.len());
+
+    (snippet, highlight)
+}
@@ -0,0 +1,381 @@
+//! This module provides the tag index of a vault, built from the inline `#tags` of the notes and
+//! the `tags` property of their frontmatter.
+//!
+//! Tags follow the Obsidian rules: a tag starts with `#` at the start of a line or after
+//! whitespace, consists of letters, numbers, `_`, `-` and `/`, and has at least one character
+//! that is not a number. A `/` nests a tag under another, e.g. `#project/alpha` is a child of
+//! `#project`. Tags are matched case-insensitively.
+use std::{collections::BTreeMap, fs, ops::Range, path::PathBuf, time::SystemTime};
+
+use pulldown_cmark::{Event, MetadataBlockKind, Options, Parser, Tag as MarkdownTag, TagEnd};
+
+use crate::{
+    markdown::Frontmatter,
+    obsidian::{Note, Vault, VaultEntry},
+};
+
+/// A tag and the notes using it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tag {
+    /// The tag without the leading `#`, as first seen in the vault, e.g. `project/alpha`.
+    pub name: String,
+
+    /// The notes using the tag, ordered by path.
+    pub notes: Vec<Note>,
+}
+
+impl Tag {
+    /// Returns `true` if this tag is the given tag or nested under it, e.g. `project/alpha` is
+    /// within `project`. The comparison ignores case.
+    pub fn is_within(&self, tag: &str) -> bool {
+        is_within(&self.name, tag)
+    }
+}
+
+/// Every tag of a vault, ordered by name.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TagIndex {
+    tags: Vec<Tag>,
+}
+
+impl TagIndex {
+    /// Returns every tag, ordered by name ignoring case.
+    pub fn tags(&self) -> &[Tag] {
+        &self.tags
+    }
+
+    /// Returns `true` if no note of the vault has a tag.
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+    }
+
+    /// Returns the tag with the given name, ignoring case.
+    pub fn get(&self, name: &str) -> Option<&Tag> {
+        let name = name.to_lowercase();
+        self.tags.iter().find(|tag| tag.name.to_lowercase() == name)
+    }
+
+    /// Returns the notes using the given tag or any tag nested under it, ordered by path and
+    /// without duplicates.
+    pub fn notes(&self, name: &str) -> Vec<&Note> {
+        let mut notes: Vec<&Note> = self
+            .tags
+            .iter()
+            .filter(|tag| tag.is_within(name))
+            .flat_map(|tag| &tag.notes)
+            .collect();
+        notes.sort_by(|a, b| a.path().cmp(b.path()));
+        notes.dedup_by(|a, b| a.path() == b.path());
+        notes
+    }
+}
+
+impl From<Vec<Tag>> for TagIndex {
+    /// Creates an index of the given tags, ordering them by name and their notes by path.
+    fn from(mut tags: Vec<Tag>) -> Self {
+        tags.sort_by_key(|tag| tag.name.to_lowercase());
+        tags.iter_mut()
+            .for_each(|tag| tag.notes.sort_by(|a, b| a.path().cmp(b.path())));
+        Self { tags }
+    }
+}
+
+/// Builds the tag index of the vault by reading every Markdown (`.md`) note.
+///
+/// # Examples
+///
+/// ```
+/// # use std::fs;
+/// # use tempfile::tempdir;
+/// # use basalt_core::obsidian::{self, Vault, Error};
+/// use basalt_core::obsidian::tags;
+///
+/// # let tmp_dir = tempdir()?;
+/// let vault = Vault { path: tmp_dir.path().to_path_buf(), ..Default::default() };
+/// let alpha = obsidian::vault::create_note(&vault.path, "Alpha")?;
+/// let beta = obsidian::vault::create_note(&vault.path, "Beta")?;
+/// fs::write(alpha.path(), "---\ntags: [project]\n---\nKick-off #project/alpha")?;
+/// fs::write(beta.path(), "Notes on #Project/beta and `#not-a-tag`")?;
+///
+/// let index = tags::index(&vault);
+/// let names: Vec<&str> = index.tags().iter().map(|tag| tag.name.as_str()).collect();
+/// assert_eq!(names, ["project", "project/alpha", "Project/beta"]);
+/// assert_eq!(index.notes("project").len(), 2);
+/// assert_eq!(index.notes("project/alpha")[0].name(), "Alpha");
+/// # Ok::<(), Error>(())
+/// ```
+pub fn index(vault: &Vault) -> TagIndex {
+    TagCache::default().index(vault)
+}
+
+/// Remembers the tags of every note between index builds, so that rebuilding the index only
+/// reads the notes that changed since. A note counts as changed when its modification time or
+/// size differs from the last build.
+///
+/// # Examples
+///
+/// ```
+/// # use std::fs;
+/// # use tempfile::tempdir;
+/// # use basalt_core::obsidian::{self, Vault, Error};
+/// use basalt_core::obsidian::tags::TagCache;
+///
+/// # let tmp_dir = tempdir()?;
+/// let vault = Vault { path: tmp_dir.path().to_path_buf(), ..Default::default() };
+/// let note = obsidian::vault::create_note(&vault.path, "Alpha")?;
+/// fs::write(note.path(), "#draft")?;
+///
+/// let mut cache = TagCache::default();
+/// assert!(cache.index(&vault).get("draft").is_some());
+///
+/// fs::write(note.path(), "#published")?;
+/// let index = cache.index(&vault);
+/// assert!(index.get("draft").is_none());
+/// assert!(index.get("published").is_some());
+/// # Ok::<(), Error>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TagCache {
+    notes: BTreeMap<PathBuf, CachedTags>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct CachedTags {
+    modified: Option<SystemTime>,
+    len: u64,
+    tags: Vec<String>,
+}
+
+impl TagCache {
+    /// Builds the tag index of the vault, reading only the notes that are new or changed since
+    /// the last build. Notes that no longer exist are forgotten.
+    pub fn index(&mut self, vault: &Vault) -> TagIndex {
+        let mut tags: Vec<Tag> = Vec::new();
+        let mut notes = BTreeMap::new();
+
+        let vault_notes = vault
+            .entries()
+            .into_iter()
+            .flat_map(VaultEntry::into_notes)
+            .filter(|note| note.path().extension().is_some_and(|ext| ext == "md"));
+
+        for note in vault_notes {
+            let Ok(metadata) = fs::metadata(note.path()) else {
+                continue;
+            };
+            let (modified, len) = (metadata.modified().ok(), metadata.len());
+
+            let cached = match self.notes.remove(note.path()) {
+                Some(cached) if cached.modified == modified && cached.len == len => cached,
+                _ => {
+                    let Ok(content) = fs::read_to_string(note.path()) else {
+                        continue;
+                    };
+                    CachedTags {
+                        modified,
+                        len,
+                        tags: note_tags(&content),
+                    }
+                }
+            };
+
+            for name in &cached.tags {
+                let lowercase = name.to_lowercase();
+                match tags
+                    .iter_mut()
+                    .find(|tag| tag.name.to_lowercase() == lowercase)
+                {
+                    Some(tag) => tag.notes.push(note.clone()),
+                    None => tags.push(Tag {
+                        name: name.clone(),
+                        notes: vec![note.clone()],
+                    }),
+                }
+            }
+
+            notes.insert(note.path().to_path_buf(), cached);
+        }
+
+        self.notes = notes;
+        TagIndex::from(tags)
+    }
+}
+
+/// Extracts the tags of a note, without the leading `#`, in order of appearance and without
+/// duplicates. Frontmatter tags come first, followed by the inline tags of the note body.
+///
+/// Tags inside code, math and HTML are skipped.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_core::obsidian::tags;
+///
+/// let content = "---\ntags: recipe\n---\n# Pancakes #breakfast\n\nIssue #1 and #recipe, see `#code`.";
+///
+/// assert_eq!(tags::note_tags(content), ["recipe", "breakfast"]);
+/// ```
+pub fn note_tags(content: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    let mut skipped: Vec<Range<usize>> = Vec::new();
+
+    let mut frontmatter = None;
+    let mut parser = Parser::new_ext(content, Options::all()).into_offset_iter();
+
+    while let Some((event, range)) = parser.next() {
+        match event {
+            Event::Start(MarkdownTag::MetadataBlock(kind)) => {
+                let mut text = String::new();
+                for (event, _) in parser.by_ref() {
+                    match event {
+                        Event::Text(value) => text.push_str(&value),
+                        Event::End(TagEnd::MetadataBlock(_)) => break,
+                        _ => {}
+                    }
+                }
+                if kind == MetadataBlockKind::YamlStyle && range.start == 0 {
+                    frontmatter = Frontmatter::parse(&text);
+                }
+                skipped.push(range);
+            }
+            Event::Start(MarkdownTag::CodeBlock(_) | MarkdownTag::HtmlBlock)
+            | Event::Code(_)
+            | Event::InlineMath(_)
+            | Event::DisplayMath(_)
+            | Event::Html(_)
+            | Event::InlineHtml(_) => skipped.push(range),
+            _ => {}
+        }
+    }
+
+    let mut push = |tag: &str| {
+        let tag = tag.trim_end_matches('/');
+        if is_valid(tag)
+            && !tags
+                .iter()
+                .any(|seen| seen.to_lowercase() == tag.to_lowercase())
+        {
+            tags.push(tag.to_string());
+        }
+    };
+
+    if let Some(frontmatter) = frontmatter {
+        frontmatter.tags.iter().for_each(|tag| push(tag));
+    }
+
+    let mut previous = None;
+    for (offset, c) in content.char_indices() {
+        let at_boundary = previous.is_none_or(char::is_whitespace);
+        previous = Some(c);
+
+        if c != '#' || !at_boundary || skipped.iter().any(|range| range.contains(&offset)) {
+            continue;
+        }
+
+        let rest = &content[offset + 1..];
+        let end = rest.find(|c| !is_tag_char(c)).unwrap_or(rest.len());
+        push(&rest[..end]);
+    }
+
+    tags
+}
+
+fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '/')
+}
+
+/// A tag needs at least one character that is not a number, so that e.g. `#1` is not a tag.
+fn is_valid(tag: &str) -> bool {
+    !tag.is_empty()
+        && !tag.starts_with('/')
+        && tag.chars().all(is_tag_char)
+        && tag.chars().any(|c| !c.is_numeric() && c != '/')
+}
+
+fn is_within(name: &str, tag: &str) -> bool {
+    let name = name.to_lowercase();
+    let tag = tag.to_lowercase();
+    name == tag
+        || name
+            .strip_prefix(&tag)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use similar_asserts::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_note_tags() {
+        let content = indoc! {r##"
+            ---
+            tags:
+              - "#rust"
+              - project/basalt
+            ---
+            # Tags #heading
+
+            #project/basalt/tui, #rust and #Rust. Not a tag: #1, a#b, [[Note#Heading]].
+
+            ```sh
+            # comment #code
+            ```
+
+            Trailing slash #nested/ and $#math$ <span>#html</span>
+        "##};
+
+        assert_eq!(
+            note_tags(content),
+            [
+                "rust",
+                "project/basalt",
+                "heading",
+                "project/basalt/tui",
+                "nested"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tag_cache_reads_only_changed_notes() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let vault = Vault {
+            path: tmp_dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        let alpha = crate::obsidian::vault::create_note(&vault.path, "Alpha").unwrap();
+        let beta = crate::obsidian::vault::create_note(&vault.path, "Beta").unwrap();
+        fs::write(alpha.path(), "#alpha").unwrap();
+        fs::write(beta.path(), "#beta").unwrap();
+
+        let mut cache = TagCache::default();
+        assert_eq!(cache.index(&vault).tags().len(), 2);
+
+        // Same size and modification time: the cached tags are used.
+        let modified = fs::metadata(alpha.path()).unwrap().modified().unwrap();
+        fs::write(alpha.path(), "#gamma").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(alpha.path())
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        fs::remove_file(beta.path()).unwrap();
+
+        let index = cache.index(&vault);
+        let names: Vec<&str> = index.tags().iter().map(|tag| tag.name.as_str()).collect();
+        assert_eq!(names, ["alpha"]);
+        assert_eq!(cache.notes.len(), 1);
+    }
+
+    #[test]
+    fn test_is_within() {
+        assert!(is_within("project/alpha", "Project"));
+        assert!(is_within("project", "project"));
+        assert!(!is_within("projects", "project"));
+        assert!(!is_within("project", "project/alpha"));
+    }
+}
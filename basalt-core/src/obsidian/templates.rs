@@ -0,0 +1,292 @@
+//! This module provides note templates, configured like the templates core plugin of Obsidian in
+//! `.obsidian/templates.json`:
+//!
+//! ```json
+//! {
+//!   "folder": "Templates",
+//!   "dateFormat": "DD.MM.YYYY",
+//!   "timeFormat": "HH:mm"
+//! }
+//! ```
+//!
+//! Every note in the templates folder is a template. The `{{title}}`, `{{date}}` and `{{time}}`
+//! variables of a template are expanded when it is applied, see [`expand`].
+use std::{fs, path::Path};
+
+use serde::Deserialize;
+use time::PrimitiveDateTime;
+
+use crate::obsidian::{self, date_format, Error, Note, Result, Vault, VaultEntry};
+
+/// The format of `{{date}}` when the config leaves it empty.
+pub const DEFAULT_DATE_FORMAT: &str = "YYYY-MM-DD";
+
+/// The format of `{{time}}` when the config leaves it empty.
+pub const DEFAULT_TIME_FORMAT: &str = "HH:mm";
+
+/// The templates config of a vault.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TemplatesConfig {
+    /// Folder of the templates relative to the vault root. Empty for no templates.
+    pub folder: String,
+
+    /// Format of `{{date}}`. Empty for [`DEFAULT_DATE_FORMAT`].
+    pub date_format: String,
+
+    /// Format of `{{time}}`. Empty for [`DEFAULT_TIME_FORMAT`].
+    pub time_format: String,
+}
+
+/// Reads the templates config of the vault from `.obsidian/templates.json`. A vault without the
+/// file has no templates folder.
+///
+/// Returns an [`Error`] if the file cannot be read or parsed.
+///
+/// # Examples
+///
+/// ```
+/// # use std::fs;
+/// # use tempfile::tempdir;
+/// use basalt_core::obsidian::{templates, Error, Vault};
+///
+/// # let tmp_dir = tempdir()?;
+/// let vault = Vault { path: tmp_dir.path().to_path_buf(), ..Default::default() };
+/// assert_eq!(templates::load(&vault)?.folder, "");
+///
+/// fs::create_dir(vault.path.join(".obsidian"))?;
+/// fs::write(
+///     vault.path.join(".obsidian/templates.json"),
+///     r#"{ "folder": "Templates", "dateFormat": "DD.MM.YYYY" }"#,
+/// )?;
+/// let config = templates::load(&vault)?;
+/// assert_eq!(config.folder, "Templates");
+/// assert_eq!(config.date_format(), "DD.MM.YYYY");
+/// assert_eq!(config.time_format(), "HH:mm");
+/// # Ok::<(), Error>(())
+/// ```
+pub fn load(vault: &Vault) -> Result<TemplatesConfig> {
+    let path = vault.path.join(".obsidian").join("templates.json");
+
+    if !path.try_exists()? {
+        return Ok(TemplatesConfig::default());
+    }
+
+    let contents = fs::read_to_string(path)?;
+    serde_json::from_str(&contents).map_err(Error::Json)
+}
+
+impl TemplatesConfig {
+    /// Returns the format of `{{date}}`.
+    pub fn date_format(&self) -> &str {
+        match self.date_format.trim() {
+            "" => DEFAULT_DATE_FORMAT,
+            format => format,
+        }
+    }
+
+    /// Returns the format of `{{time}}`.
+    pub fn time_format(&self) -> &str {
+        match self.time_format.trim() {
+            "" => DEFAULT_TIME_FORMAT,
+            format => format,
+        }
+    }
+
+    /// Returns every template of the vault, ordered by path. Empty if the config has no
+    /// templates folder or the folder does not exist.
+    pub fn templates(&self, vault: &Vault) -> Vec<Note> {
+        let folder = self.folder.trim().trim_matches('/');
+        if folder.is_empty() {
+            return vec![];
+        }
+
+        let Ok(VaultEntry::Directory { entries, .. }) =
+            VaultEntry::try_from(vault.path.join(folder).as_path())
+        else {
+            return vec![];
+        };
+
+        let mut templates: Vec<Note> = entries
+            .into_iter()
+            .filter(|entry| !entry.name().starts_with('.'))
+            .flat_map(VaultEntry::into_notes)
+            .collect();
+
+        templates.sort_by(|a, b| a.path().cmp(b.path()));
+        templates
+    }
+
+    /// Reads the template and expands its variables with the formats of the config.
+    ///
+    /// Returns an [`Error`] if the template cannot be read.
+    pub fn render(&self, template: &Note, title: &str, now: PrimitiveDateTime) -> Result<String> {
+        let template = fs::read_to_string(template.path())?;
+        Ok(expand(
+            &template,
+            title,
+            now,
+            self.date_format(),
+            self.time_format(),
+        ))
+    }
+}
+
+/// Expands the variables of a template: `{{title}}`, `{{date}}` and `{{time}}` in the given
+/// formats, and `{{date:FORMAT}}` or `{{time:FORMAT}}` with a [`date_format`] of their own.
+/// Unknown variables are left as is.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_core::obsidian::templates;
+/// use time::macros::datetime;
+///
+/// let expanded = templates::expand(
+///     "# {{title}}\n{{date}} {{time}}, {{date:dddd}} {{unknown}}",
+///     "Standup",
+///     datetime!(2024-03-01 09:05),
+///     "DD.MM.YYYY",
+///     "HH:mm",
+/// );
+/// assert_eq!(expanded, "# Standup\n01.03.2024 09:05, Friday {{unknown}}");
+/// ```
+pub fn expand(
+    template: &str,
+    title: &str,
+    now: PrimitiveDateTime,
+    date_format: &str,
+    time_format: &str,
+) -> String {
+    let mut expanded = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}").map(|end| start + end) else {
+            break;
+        };
+
+        expanded.push_str(&rest[..start]);
+        let variable = &rest[start + 2..end];
+        let (name, format) = match variable.split_once(':') {
+            Some((name, format)) => (name.trim(), Some(format.trim())),
+            None => (variable.trim(), None),
+        };
+
+        match (name, format) {
+            ("title", None) => expanded.push_str(title),
+            ("date", format) => {
+                expanded.push_str(&date_format::format(now, format.unwrap_or(date_format)))
+            }
+            ("time", format) => {
+                expanded.push_str(&date_format::format(now, format.unwrap_or(time_format)))
+            }
+            _ => expanded.push_str(&rest[start..end + 2]),
+        }
+
+        rest = &rest[end + 2..];
+    }
+
+    expanded.push_str(rest);
+    expanded
+}
+
+/// Creates a new note with the provided name from the template, with its variables expanded and
+/// `{{title}}` set to the name of the created note.
+///
+/// The note is created with [`obsidian::vault::create_note_with_content`], so an existing note
+/// with the given name gets a numbered suffix appended.
+///
+/// Returns an [`Error`] if the template cannot be read or the note cannot be created.
+///
+/// # Examples
+///
+/// ```
+/// # use std::fs;
+/// # use tempfile::tempdir;
+/// use basalt_core::obsidian::{templates::{self, TemplatesConfig}, Error, Vault};
+/// use time::macros::datetime;
+///
+/// # let tmp_dir = tempdir()?;
+/// let vault = Vault { path: tmp_dir.path().to_path_buf(), ..Default::default() };
+/// # fs::create_dir(vault.path.join("Templates"))?;
+/// fs::write(vault.path.join("Templates/Meeting.md"), "# {{title}}\n\n{{date}}")?;
+/// fs::write(vault.path.join("Untitled.md"), "")?;
+///
+/// let config = TemplatesConfig { folder: "Templates".into(), ..Default::default() };
+/// let template = &config.templates(&vault)[0];
+///
+/// let now = datetime!(2024-03-01 09:30);
+/// let note = templates::create_note(&vault.path, "Untitled", template, &config, now)?;
+/// assert_eq!(note.name(), "Untitled 1");
+/// assert_eq!(fs::read_to_string(note.path())?, "# Untitled 1\n\n2024-03-01");
+/// # Ok::<(), Error>(())
+/// ```
+pub fn create_note<T: AsRef<Path>>(
+    path: T,
+    name: &str,
+    template: &Note,
+    config: &TemplatesConfig,
+    now: PrimitiveDateTime,
+) -> Result<Note> {
+    let (title, _) = obsidian::vault::find_available_path_name(&path, name, Some("md"))?;
+    let content = config.render(template, &title, now)?;
+
+    obsidian::vault::create_note_with_content(path, name, &content)
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+    use time::macros::datetime;
+
+    use super::*;
+
+    #[test]
+    fn test_expand() {
+        let now = datetime!(2024-03-01 09:05);
+
+        assert_eq!(
+            expand(
+                "{{title}}: {{ date }} {{time}}, {{date:dddd}} {{time:h a}} {{unknown}} {{",
+                "Today",
+                now,
+                DEFAULT_DATE_FORMAT,
+                DEFAULT_TIME_FORMAT,
+            ),
+            "Today: 2024-03-01 09:05, Friday 9 am {{unknown}} {{"
+        );
+    }
+
+    #[test]
+    fn test_templates_are_listed_by_path() {
+        let tmp_dir = tempdir().unwrap();
+        let vault = Vault {
+            path: tmp_dir.path().to_path_buf(),
+            ..Default::default()
+        };
+
+        assert_eq!(TemplatesConfig::default().templates(&vault), []);
+
+        for name in [
+            "Templates/Weekly.md",
+            "Templates/Work/Meeting.md",
+            "Note.md",
+        ] {
+            let path = vault.path.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+
+        let config = TemplatesConfig {
+            folder: "/Templates/".into(),
+            ..Default::default()
+        };
+        let names: Vec<String> = config
+            .templates(&vault)
+            .iter()
+            .map(|template| template.name().to_string())
+            .collect();
+        assert_eq!(names, ["Weekly", "Meeting"]);
+    }
+}
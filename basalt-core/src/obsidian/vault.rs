@@ -2,7 +2,7 @@
 use std::{
     fs, io,
     ops::ControlFlow,
-    path::{self, Path, PathBuf},
+    path::{self, Component, Path, PathBuf},
     result,
 };
 
@@ -37,15 +37,29 @@ fn basename(path: &Path, extension: Option<&str>) -> result::Result<String, Erro
     .ok_or_else(|| Error::InvalidPathName(path.to_path_buf()))
 }
 
-/// Creates link replacement patterns for updating links when renaming a note or directory.
+/// Creates the patterns that open a wiki-link pointing to the given name.
 ///
-/// Returns a vector of (old_pattern, new_pattern) tuples for:
+/// Returns patterns for:
 /// - Simple wikilinks: `[[note]]`, `[[note|`, `[[note#`
+pub(crate) fn wiki_link_patterns(name: &str) -> [String; 3] {
+    [
+        format!("[[{name}]]"),
+        format!("[[{name}|"),
+        format!("[[{name}#"),
+    ]
+}
+
+/// Creates link replacement patterns for updating links when renaming a note or directory.
+///
+/// Returns a vector of (old_pattern, new_pattern) tuples for the patterns given by
+/// [`wiki_link_patterns`].
 fn wiki_link_replacements(old_name: &str, new_name: &str) -> [(String, String); 3] {
+    let [old_link, old_alias, old_heading] = wiki_link_patterns(old_name);
+    let [new_link, new_alias, new_heading] = wiki_link_patterns(new_name);
     [
-        (format!("[[{old_name}]]"), format!("[[{new_name}]]")),
-        (format!("[[{old_name}|"), format!("[[{new_name}|")),
-        (format!("[[{old_name}#"), format!("[[{new_name}#")),
+        (old_link, new_link),
+        (old_alias, new_alias),
+        (old_heading, new_heading),
     ]
 }
 
@@ -121,19 +135,10 @@ pub fn update_wiki_links(
         }
     }
 
-    fn entry_to_note(entry: VaultEntry) -> Vec<Note> {
-        match entry {
-            VaultEntry::File(note) => vec![note],
-            VaultEntry::Directory { entries, .. } => {
-                entries.into_iter().flat_map(entry_to_note).collect()
-            }
-        }
-    }
-
     vault
         .entries()
         .into_iter()
-        .flat_map(entry_to_note)
+        .flat_map(VaultEntry::into_notes)
         .try_for_each(replace_wiki_link(&replacements))?;
 
     Ok(())
@@ -319,6 +324,383 @@ pub fn move_dir_to(from: Directory, to: Directory) -> result::Result<Directory,
     Directory::try_from((from.name(), new_path))
 }
 
+/// Copies the note to the given directory.
+///
+/// If a note with the same name already exists in the directory, a numbered suffix will be
+/// appended (e.g., "Note 1", "Note 2", etc.) to find an available name.
+///
+/// # Examples
+///
+/// ```
+/// # use std::fs;
+/// # use tempfile::tempdir;
+/// # use basalt_core::obsidian::{self, Vault, Note, Error};
+/// #
+/// # let tmp_dir = tempdir()?;
+/// # let tmp_path = tmp_dir.path();
+/// #
+/// let vault = Vault { path: tmp_path.to_path_buf(), ..Default::default() };
+/// let note = obsidian::vault::create_note(&vault.path, "/notes/Arbitrary Name")?;
+/// let dir = obsidian::vault::create_dir(&vault.path, "/archive")?;
+///
+/// let copy = obsidian::vault::copy_note(note.clone(), dir.clone())?;
+/// assert_eq!(copy.name(), "Arbitrary Name");
+/// assert_eq!(copy.path(), tmp_path.join("archive/Arbitrary Name.md"));
+/// assert_eq!(fs::exists(note.path())?, true);
+///
+/// let copy = obsidian::vault::copy_note(note, dir)?;
+/// assert_eq!(copy.name(), "Arbitrary Name 1");
+/// assert_eq!(copy.path(), tmp_path.join("archive/Arbitrary Name 1.md"));
+/// # Ok::<(), Error>(())
+/// ```
+pub fn copy_note(note: Note, directory: Directory) -> result::Result<Note, Error> {
+    let extension = note.path().extension().and_then(|ext| ext.to_str());
+    let (name, new_path) = find_available_path_name(directory.path(), note.name(), extension)?;
+
+    fs::copy(note.path(), &new_path)?;
+
+    Note::try_from((name, new_path))
+}
+
+/// Copies the directory and everything in it to the given directory.
+///
+/// If a directory with the same name already exists in the target directory, a numbered suffix
+/// will be appended (e.g., "Dir 1", "Dir 2", etc.) to find an available name. A directory cannot
+/// be copied into itself.
+///
+/// # Examples
+///
+/// ```
+/// # use std::fs;
+/// # use tempfile::tempdir;
+/// # use basalt_core::obsidian::{self, Vault, Note, Error};
+/// use basalt_core::obsidian::directory::Directory;
+/// #
+/// # let tmp_dir = tempdir()?;
+/// # let tmp_path = tmp_dir.path();
+/// #
+/// let vault = Vault { path: tmp_path.to_path_buf(), ..Default::default() };
+/// let dir_a = obsidian::vault::create_dir(&vault.path, "/notes")?;
+/// let dir_b = obsidian::vault::create_dir(&vault.path, "/archive")?;
+/// obsidian::vault::create_note(dir_a.path(), "/nested/Arbitrary Name")?;
+///
+/// let dir = obsidian::vault::copy_dir(dir_a.clone(), dir_b)?;
+/// assert_eq!(dir.name(), "notes");
+/// assert_eq!(dir.path(), tmp_path.join("archive/notes"));
+/// assert_eq!(fs::exists(dir.path().join("nested/Arbitrary Name.md"))?, true);
+/// assert_eq!(fs::exists(dir_a.path().join("nested/Arbitrary Name.md"))?, true);
+///
+/// let root = Directory::new("vault", &vault.path);
+/// let dir = obsidian::vault::copy_dir(dir_a.clone(), root)?;
+/// assert_eq!(dir.name(), "notes 1");
+///
+/// assert!(obsidian::vault::copy_dir(dir_a.clone(), dir_a).is_err());
+/// # Ok::<(), Error>(())
+/// ```
+pub fn copy_dir(from: Directory, to: Directory) -> result::Result<Directory, Error> {
+    if to.path().starts_with(from.path()) {
+        return Err(Error::Io(std::io::ErrorKind::InvalidInput.into()));
+    }
+
+    let name = basename(from.path(), None)?;
+    let (name, new_path) = find_available_path_name(to.path(), &name, None)?;
+
+    copy_dir_all(from.path(), &new_path)?;
+
+    Directory::try_from((name, new_path))
+}
+
+/// Recursively copies the contents of the `from` directory into the `to` directory.
+fn copy_dir_all(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+
+    fs::read_dir(from)?.try_for_each(|entry| {
+        let entry = entry?;
+        let path = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir_all(&entry.path(), &path)
+        } else {
+            fs::copy(entry.path(), path).map(|_| ())
+        }
+    })
+}
+
+/// Name of the folder, relative to the vault root, that Obsidian moves deleted files into.
+pub const TRASH_DIR: &str = ".trash";
+
+/// What happens to deleted notes and directories. Mirrors the "Deleted files" option of Obsidian,
+/// stored as `trashOption` in the `.obsidian/app.json` of the vault.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeleteMode {
+    /// Moves the deleted files into the trash of the operating system, the default of Obsidian.
+    ///
+    /// Supported on Linux and the BSDs (the freedesktop.org home trash) and on macOS. Elsewhere,
+    /// or when the vault is on another file system than the trash, the files are moved into the
+    /// [`TRASH_DIR`] of the vault instead.
+    #[default]
+    System,
+    /// Moves the deleted files into the [`TRASH_DIR`] of the vault. Name clashes in the trash get
+    /// a numbered suffix.
+    Local,
+    /// Removes the deleted files from the file system. Obsidian calls this option `none`.
+    #[serde(alias = "none")]
+    Permanent,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct AppConfig {
+    #[serde(rename = "trashOption", default)]
+    trash_option: DeleteMode,
+}
+
+/// Reads the delete mode of the vault from the `trashOption` of `.obsidian/app.json`. A vault
+/// without the file or the option uses the default of Obsidian, [`DeleteMode::System`].
+///
+/// Returns an [`Error`] if the file cannot be read or parsed.
+///
+/// # Examples
+///
+/// ```
+/// # use std::fs;
+/// # use tempfile::tempdir;
+/// use basalt_core::obsidian::{self, DeleteMode, Error, Vault};
+///
+/// # let tmp_dir = tempdir()?;
+/// let vault = Vault { path: tmp_dir.path().to_path_buf(), ..Default::default() };
+/// assert_eq!(obsidian::vault::load_delete_mode(&vault)?, DeleteMode::System);
+///
+/// fs::create_dir(vault.path.join(".obsidian"))?;
+/// fs::write(vault.path.join(".obsidian/app.json"), r#"{ "trashOption": "local" }"#)?;
+/// assert_eq!(obsidian::vault::load_delete_mode(&vault)?, DeleteMode::Local);
+///
+/// fs::write(vault.path.join(".obsidian/app.json"), r#"{ "trashOption": "none" }"#)?;
+/// assert_eq!(obsidian::vault::load_delete_mode(&vault)?, DeleteMode::Permanent);
+/// # Ok::<(), Error>(())
+/// ```
+pub fn load_delete_mode(vault: &Vault) -> result::Result<DeleteMode, Error> {
+    let path = vault.path.join(".obsidian").join("app.json");
+
+    if !path.try_exists()? {
+        return Ok(DeleteMode::default());
+    }
+
+    let contents = fs::read_to_string(path)?;
+    let config: AppConfig = serde_json::from_str(&contents)?;
+    Ok(config.trash_option)
+}
+
+/// Moves the file or directory behind the path into the vault's [`TRASH_DIR`].
+fn move_to_trash(vault: &Vault, path: &Path, extension: Option<&str>) -> result::Result<(), Error> {
+    let trash_path = vault.path.join(TRASH_DIR);
+    fs::create_dir_all(&trash_path)?;
+
+    let name = basename(path, extension)?;
+    let (_, new_path) = find_available_path_name(&trash_path, &name, extension)?;
+    fs::rename(path, new_path)?;
+
+    Ok(())
+}
+
+/// Percent-encodes the path for the `Path` key of a `.trashinfo` file.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn percent_encode(path: &Path) -> String {
+    use std::os::unix::ffi::OsStrExt;
+
+    path.as_os_str()
+        .as_bytes()
+        .iter()
+        .map(|&byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                (byte as char).to_string()
+            }
+            byte => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+/// Moves the file or directory into the freedesktop.org trash at `trash_dir`: the file goes into
+/// `files/` and a `.trashinfo` file recording where it came from into `info/`, so file managers
+/// can restore it.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn move_to_freedesktop_trash(
+    trash_dir: &Path,
+    path: &Path,
+    extension: Option<&str>,
+) -> result::Result<(), Error> {
+    use std::io::Write;
+
+    let files = trash_dir.join("files");
+    let info = trash_dir.join("info");
+    fs::create_dir_all(&files)?;
+    fs::create_dir_all(&info)?;
+
+    let name = basename(path, extension)?;
+    let (_, new_path) = find_available_path_name(&files, &name, extension)?;
+    let file_name = new_path
+        .file_name()
+        .ok_or_else(|| Error::InvalidPathName(new_path.clone()))?;
+    let info_path = info.join(format!("{}.trashinfo", file_name.to_string_lossy()));
+
+    // The spec asks for the local time; basalt-core has no access to the local offset, so the
+    // deletion date is written in UTC.
+    let now = time::OffsetDateTime::now_utc();
+    let deletion_date = format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        now.year(),
+        now.month() as u8,
+        now.day(),
+        now.hour(),
+        now.minute(),
+        now.second()
+    );
+
+    // Creating the info file first claims the name, as the spec requires.
+    let mut info_file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&info_path)?;
+    write!(
+        info_file,
+        "[Trash Info]\nPath={}\nDeletionDate={deletion_date}\n",
+        percent_encode(&path::absolute(path)?)
+    )?;
+
+    fs::rename(path, new_path).inspect_err(|_| {
+        let _ = fs::remove_file(&info_path);
+    })?;
+
+    Ok(())
+}
+
+/// Moves the file or directory into the trash of the operating system.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn move_to_system_trash(path: &Path, extension: Option<&str>) -> result::Result<(), Error> {
+    let data_dir =
+        dirs::data_local_dir().ok_or_else(|| Error::PathNotFound("$XDG_DATA_HOME".to_string()))?;
+    move_to_freedesktop_trash(&data_dir.join("Trash"), path, extension)
+}
+
+/// Moves the file or directory into the trash of the operating system.
+#[cfg(target_os = "macos")]
+fn move_to_system_trash(path: &Path, extension: Option<&str>) -> result::Result<(), Error> {
+    let trash_path = dirs::home_dir()
+        .ok_or_else(|| Error::PathNotFound("~".to_string()))?
+        .join(".Trash");
+    let name = basename(path, extension)?;
+    let (_, new_path) = find_available_path_name(&trash_path, &name, extension)?;
+    fs::rename(path, new_path)?;
+    Ok(())
+}
+
+/// Moves the file or directory into the trash of the operating system.
+#[cfg(not(unix))]
+fn move_to_system_trash(_path: &Path, _extension: Option<&str>) -> result::Result<(), Error> {
+    Err(Error::Io(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "no system trash on this platform",
+    )))
+}
+
+/// Deletes the file or directory behind the path the way the mode asks for, and returns the mode
+/// that was used: [`DeleteMode::System`] falls back to [`DeleteMode::Local`] when the system
+/// trash cannot take the files.
+fn delete_path(
+    vault: &Vault,
+    path: &Path,
+    extension: Option<&str>,
+    mode: DeleteMode,
+    remove: impl FnOnce(&Path) -> io::Result<()>,
+) -> result::Result<DeleteMode, Error> {
+    match mode {
+        DeleteMode::System => match move_to_system_trash(path, extension) {
+            Ok(()) => Ok(DeleteMode::System),
+            Err(_) => move_to_trash(vault, path, extension).map(|_| DeleteMode::Local),
+        },
+        DeleteMode::Local => move_to_trash(vault, path, extension).map(|_| DeleteMode::Local),
+        DeleteMode::Permanent => Ok(remove(path).map(|_| DeleteMode::Permanent)?),
+    }
+}
+
+/// Deletes the note the way the [`DeleteMode`] asks for: into the trash of the operating system,
+/// into the vault's [`TRASH_DIR`], or from the file system. Returns the mode that was used, see
+/// [`DeleteMode::System`].
+///
+/// # Examples
+///
+/// ```
+/// # use std::fs;
+/// # use tempfile::tempdir;
+/// # use basalt_core::obsidian::{self, Vault, Note, Error, DeleteMode, TRASH_DIR};
+/// #
+/// # let tmp_dir = tempdir()?;
+/// # let tmp_path = tmp_dir.path();
+/// #
+/// let vault = Vault { path: tmp_path.to_path_buf(), ..Default::default() };
+/// let note = obsidian::vault::create_note(&vault.path, "/notes/Arbitrary Name")?;
+/// obsidian::vault::delete_note(&vault, note.clone(), DeleteMode::Local)?;
+///
+/// assert_eq!(fs::exists(note.path())?, false);
+/// assert_eq!(fs::exists(tmp_path.join(TRASH_DIR).join("Arbitrary Name.md"))?, true);
+///
+/// let note = obsidian::vault::create_note(&vault.path, "Arbitrary Name")?;
+/// obsidian::vault::delete_note(&vault, note.clone(), DeleteMode::Local)?;
+/// assert_eq!(fs::exists(tmp_path.join(TRASH_DIR).join("Arbitrary Name 1.md"))?, true);
+///
+/// let note = obsidian::vault::create_note(&vault.path, "Arbitrary Name")?;
+/// let mode = obsidian::vault::delete_note(&vault, note.clone(), DeleteMode::Permanent)?;
+/// assert_eq!(mode, DeleteMode::Permanent);
+/// assert_eq!(fs::exists(note.path())?, false);
+/// assert_eq!(fs::exists(tmp_path.join(TRASH_DIR).join("Arbitrary Name 2.md"))?, false);
+/// # Ok::<(), Error>(())
+/// ```
+pub fn delete_note(
+    vault: &Vault,
+    note: Note,
+    mode: DeleteMode,
+) -> result::Result<DeleteMode, Error> {
+    let extension = note.path().extension().and_then(|ext| ext.to_str());
+    delete_path(vault, note.path(), extension, mode, |path| {
+        fs::remove_file(path)
+    })
+}
+
+/// Deletes the directory and everything in it the way the [`DeleteMode`] asks for, like
+/// [`delete_note`].
+///
+/// # Examples
+///
+/// ```
+/// # use std::fs;
+/// # use tempfile::tempdir;
+/// # use basalt_core::obsidian::{self, Vault, Note, Error, DeleteMode, TRASH_DIR};
+/// #
+/// # let tmp_dir = tempdir()?;
+/// # let tmp_path = tmp_dir.path();
+/// #
+/// let vault = Vault { path: tmp_path.to_path_buf(), ..Default::default() };
+/// let dir = obsidian::vault::create_dir(&vault.path, "/notes")?;
+/// obsidian::vault::create_note(dir.path(), "Arbitrary Name")?;
+/// obsidian::vault::delete_dir(&vault, dir.clone(), DeleteMode::Local)?;
+///
+/// assert_eq!(fs::exists(dir.path())?, false);
+/// assert_eq!(fs::exists(tmp_path.join(TRASH_DIR).join("notes/Arbitrary Name.md"))?, true);
+///
+/// let dir = obsidian::vault::create_dir(&vault.path, "/notes")?;
+/// obsidian::vault::delete_dir(&vault, dir.clone(), DeleteMode::Permanent)?;
+/// assert_eq!(fs::exists(dir.path())?, false);
+/// # Ok::<(), Error>(())
+/// ```
+pub fn delete_dir(
+    vault: &Vault,
+    directory: Directory,
+    mode: DeleteMode,
+) -> result::Result<DeleteMode, Error> {
+    delete_path(vault, directory.path(), None, mode, |path| {
+        fs::remove_dir_all(path)
+    })
+}
+
 /// Creates a new empty directory with the provided name.
 ///
 /// If a directory with the given name already exists, a numbered suffix will be appended
@@ -328,6 +710,7 @@ pub fn move_dir_to(from: Directory, to: Directory) -> result::Result<Directory,
 ///
 /// Returns an error if:
 /// - I/O operations fail (directory creation, path checks)
+/// - The name leads outside the vault ([`Error::PathOutsideVault`])
 /// - No available name is found after 999 attempts ([`Error::MaxAttemptsExceeded`])
 ///
 /// # Examples
@@ -347,7 +730,8 @@ pub fn move_dir_to(from: Directory, to: Directory) -> result::Result<Directory,
 /// # Ok::<(), Error>(())
 /// ```
 pub fn create_dir<T: AsRef<Path>>(path: T, name: &str) -> result::Result<Directory, Error> {
-    let (name, path) = find_available_path_name(path, name, None)?;
+    let name = vault_relative_name(name)?;
+    let (name, path) = find_available_path_name(path, &name, None)?;
     fs::create_dir_all(&path)?;
     Directory::try_from((name, path))
 }
@@ -402,6 +786,7 @@ pub fn create_untitled_dir<T: AsRef<Path>>(path: T) -> result::Result<Directory,
 ///
 /// Returns an error if:
 /// - I/O operations fail (directory creation, file writing, or path checks)
+/// - The name leads outside the vault ([`Error::PathOutsideVault`])
 /// - No available name is found after 999 attempts ([`Error::MaxAttemptsExceeded`])
 ///
 /// # Examples
@@ -415,14 +800,58 @@ pub fn create_untitled_dir<T: AsRef<Path>>(path: T) -> result::Result<Directory,
 /// # let tmp_path = tmp_dir.path();
 /// #
 /// let vault = Vault { path: tmp_path.to_path_buf(), ..Default::default() };
-/// let note = obsidian::vault::create_note(vault.path, "/notes/Arbitrary Name")?;
+/// let note = obsidian::vault::create_note(&vault.path, "/notes/Arbitrary Name")?;
 /// assert_eq!(note.name(), "Arbitrary Name");
 /// assert_eq!(note.path(), tmp_path.join("notes/Arbitrary Name.md"));
 /// assert_eq!(fs::exists(note.path())?, true);
+///
+/// let outside = obsidian::vault::create_note(&vault.path, "notes/../../Outside");
+/// assert!(matches!(outside, Err(Error::PathOutsideVault(_))));
+/// assert_eq!(fs::exists(tmp_path.join("../Outside.md"))?, false);
 /// # Ok::<(), Error>(())
 /// ```
 pub fn create_note<T: AsRef<Path>>(path: T, name: &str) -> result::Result<Note, Error> {
-    let name = name.trim_start_matches(path::MAIN_SEPARATOR);
+    create_note_with_content(path, name, "")
+}
+
+/// Creates a new note with the provided name and initial content.
+///
+/// Names are resolved like in [`create_note`], so an existing note with the given name gets a
+/// numbered suffix appended instead of being overwritten.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - I/O operations fail (directory creation, file writing, or path checks)
+/// - The name leads outside the vault ([`Error::PathOutsideVault`])
+/// - No available name is found after 999 attempts ([`Error::MaxAttemptsExceeded`])
+///
+/// # Examples
+///
+/// ```
+/// # use std::fs;
+/// # use tempfile::tempdir;
+/// # use basalt_core::obsidian::{self, Vault, Note, Error};
+/// #
+/// # let tmp_dir = tempdir()?;
+/// # let tmp_path = tmp_dir.path();
+/// #
+/// let vault = Vault { path: tmp_path.to_path_buf(), ..Default::default() };
+/// let note = obsidian::vault::create_note_with_content(&vault.path, "Meeting", "# Agenda")?;
+/// assert_eq!(note.name(), "Meeting");
+/// assert_eq!(fs::read_to_string(note.path())?, "# Agenda");
+///
+/// let note = obsidian::vault::create_note_with_content(&vault.path, "Meeting", "")?;
+/// assert_eq!(note.name(), "Meeting 1");
+/// # Ok::<(), Error>(())
+/// ```
+pub fn create_note_with_content<T: AsRef<Path>>(
+    path: T,
+    name: &str,
+    content: &str,
+) -> result::Result<Note, Error> {
+    let name = vault_relative_name(name)?;
+    let name = name.as_str();
     let path = path.as_ref();
 
     let base_path = path.join(name).with_extension("md");
@@ -434,7 +863,7 @@ pub fn create_note<T: AsRef<Path>>(path: T, name: &str) -> result::Result<Note,
 
     let (name, path) = find_available_path_name(path, name, Some("md"))?;
 
-    fs::write(&path, "")?;
+    fs::write(&path, content)?;
 
     Note::try_from((name, path))
 }
@@ -476,6 +905,50 @@ pub fn create_untitled_note<T: AsRef<Path>>(path: T) -> result::Result<Note, Err
     create_note(path, "Untitled")
 }
 
+/// Resolves a path relative to the vault root, dropping `.` components and resolving `..`
+/// components against the preceding ones.
+///
+/// Returns [`None`] if the path is absolute, has a prefix such as a Windows drive, names the
+/// vault root itself, or leads outside the vault through `..` components.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::{Path, PathBuf};
+/// use basalt_core::obsidian::vault::relative_path;
+///
+/// assert_eq!(relative_path("notes/./drafts/../Note.md"), Some(PathBuf::from("notes/Note.md")));
+/// assert_eq!(relative_path("notes/../../Note.md"), None);
+/// assert_eq!(relative_path("/etc/passwd"), None);
+/// assert_eq!(relative_path("notes/.."), None);
+/// ```
+pub fn relative_path<T: AsRef<Path>>(path: T) -> Option<PathBuf> {
+    let mut relative = PathBuf::new();
+
+    for component in path.as_ref().components() {
+        match component {
+            Component::Normal(name) => relative.push(name),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !relative.pop() {
+                    return None;
+                }
+            }
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+
+    (!relative.as_os_str().is_empty()).then_some(relative)
+}
+
+/// Normalizes a note or directory name given relative to the vault root, where a leading
+/// separator also refers to the vault root.
+fn vault_relative_name(name: &str) -> result::Result<String, Error> {
+    relative_path(name.trim_start_matches(path::MAIN_SEPARATOR))
+        .map(|path| path.to_string_lossy().into_owned())
+        .ok_or_else(|| Error::PathOutsideVault(PathBuf::from(name)))
+}
+
 /// Find available path name by incrementing number suffix at the end.
 ///
 /// Increments until we find a 'free' name e.g. if "Untitled 1" exists we will
@@ -639,3 +1112,45 @@ impl<'de> Deserialize<'de> for Vault {
         deserialized.try_into().map_err(serde::de::Error::custom)
     }
 }
+
+#[cfg(all(test, unix, not(target_os = "macos")))]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn test_move_to_freedesktop_trash() {
+        let tmp_dir = tempdir().unwrap();
+        let trash_dir = tmp_dir.path().join("Trash");
+        let vault_path = tmp_dir.path().join("My Vault");
+        fs::create_dir(&vault_path).unwrap();
+
+        for _ in 0..2 {
+            let path = vault_path.join("Note #1.md");
+            fs::write(&path, "").unwrap();
+            move_to_freedesktop_trash(&trash_dir, &path, Some("md")).unwrap();
+            assert!(!fs::exists(&path).unwrap());
+        }
+
+        assert!(fs::exists(trash_dir.join("files/Note #1.md")).unwrap());
+        assert!(fs::exists(trash_dir.join("files/Note #1 1.md")).unwrap());
+
+        let info = fs::read_to_string(trash_dir.join("info/Note #1 1.md.trashinfo")).unwrap();
+        let mut lines = info.lines();
+        assert_eq!(lines.next(), Some("[Trash Info]"));
+        assert_eq!(
+            lines.next(),
+            Some(
+                format!(
+                    "Path={}/My%20Vault/Note%20%231.md",
+                    tmp_dir.path().display()
+                )
+                .as_str()
+            )
+        );
+        assert!(lines
+            .next()
+            .is_some_and(|line| line.starts_with("DeletionDate=")));
+    }
+}
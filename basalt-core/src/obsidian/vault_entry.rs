@@ -24,6 +24,16 @@ impl VaultEntry {
             Self::File(note) => note.name(),
         }
     }
+
+    /// Flattens the entry into every note it contains, walking directories depth-first.
+    pub fn into_notes(self) -> Vec<Note> {
+        match self {
+            Self::File(note) => vec![note],
+            Self::Directory { entries, .. } => {
+                entries.into_iter().flat_map(Self::into_notes).collect()
+            }
+        }
+    }
 }
 
 impl TryFrom<&Path> for VaultEntry {
@@ -64,6 +74,16 @@ pub trait FindNote {
     fn find_note<'a>(&'a self, path: &Path) -> Option<&'a Note>;
 }
 
+/// Finds the first note with the given name, walking directories depth-first. Names are compared
+/// case-insensitively, the same way Obsidian resolves wiki-links.
+pub(crate) fn find_note_by_name<'a>(entries: &'a [VaultEntry], name: &str) -> Option<&'a Note> {
+    entries.iter().find_map(|entry| match entry {
+        VaultEntry::File(note) if note.name().to_lowercase() == name.to_lowercase() => Some(note),
+        VaultEntry::Directory { entries, .. } => find_note_by_name(entries, name),
+        _ => None,
+    })
+}
+
 impl FindNote for Vec<VaultEntry> {
     fn find_note<'a>(&'a self, path: &Path) -> Option<&'a Note> {
         self.iter().find_map(|entry| entry.find_note(path))
@@ -0,0 +1,140 @@
+//! This module provides parsing and resolving of Obsidian wiki-links, e.g.
+//! `[[target#heading|alias]]`.
+use std::ops::Range;
+
+use crate::obsidian::{vault_entry::find_note_by_name, FindNote, Note, Vault};
+
+/// A single wiki-link found in note content.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WikiLink {
+    /// The linked note name or vault-relative path, without the `.md` extension. Empty when the
+    /// link points to a heading in the same note, e.g. `[[#heading]]`.
+    pub target: String,
+
+    /// The linked heading, if any. For nested headings (`[[note#a#b]]`) only the last heading is
+    /// kept.
+    pub heading: Option<String>,
+
+    /// The displayed alias, if any.
+    pub alias: Option<String>,
+
+    /// Byte range of the whole link, from `[[` to `]]`, within the content.
+    pub range: Range<usize>,
+}
+
+impl WikiLink {
+    /// Returns the wiki-link that contains the given byte offset, if any. Links never span
+    /// multiple lines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::obsidian::wiki_link::WikiLink;
+    ///
+    /// let content = "See [[Recipes#Pancakes|pancakes]] for more.";
+    ///
+    /// let link = WikiLink::at(content, 10).unwrap();
+    /// assert_eq!(link.target, "Recipes");
+    /// assert_eq!(link.heading.as_deref(), Some("Pancakes"));
+    /// assert_eq!(link.alias.as_deref(), Some("pancakes"));
+    /// assert_eq!(link.range, 4..33);
+    ///
+    /// assert_eq!(WikiLink::at(content, 2), None);
+    /// ```
+    pub fn at(content: &str, offset: usize) -> Option<WikiLink> {
+        let offset = offset.min(content.len());
+        let line_start = content[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = content[offset..]
+            .find('\n')
+            .map_or(content.len(), |i| offset + i);
+        let line = &content[line_start..line_end];
+
+        let mut search_from = 0;
+        while let Some(start) = line[search_from..].find("[[").map(|i| search_from + i) {
+            let end = start + 2 + line[start + 2..].find("]]")? + 2;
+            if (line_start + start..line_start + end).contains(&offset) {
+                return WikiLink::parse(
+                    &line[start + 2..end - 2],
+                    line_start + start..line_start + end,
+                );
+            }
+            search_from = end;
+        }
+
+        None
+    }
+
+    /// Parses the inner text of a link, between `[[` and `]]`.
+    fn parse(inner: &str, range: Range<usize>) -> Option<WikiLink> {
+        let (link, alias) = match inner.split_once('|') {
+            Some((link, alias)) => (link, Some(alias.trim().to_string())),
+            None => (inner, None),
+        };
+
+        let (target, heading) = match link.split_once('#') {
+            Some((target, headings)) => (
+                target,
+                headings
+                    .rsplit('#')
+                    .map(str::trim)
+                    .find(|heading| !heading.is_empty())
+                    .map(str::to_string),
+            ),
+            None => (link, None),
+        };
+
+        let target = target.trim().trim_end_matches(".md").to_string();
+        if target.is_empty() && heading.is_none() {
+            return None;
+        }
+
+        Some(WikiLink {
+            target,
+            heading,
+            alias,
+            range,
+        })
+    }
+
+    /// Resolves the link target to a note in the vault.
+    ///
+    /// A target is first looked up as a vault-relative path, e.g. `[[notes/Recipes]]`, and then
+    /// by note name anywhere in the vault. Names are compared case-insensitively. Returns [`None`]
+    /// for links to the same note (`[[#heading]]`) and for targets that do not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tempfile::tempdir;
+    /// # use basalt_core::obsidian::{self, Vault, Error};
+    /// use basalt_core::obsidian::wiki_link::WikiLink;
+    ///
+    /// # let tmp_dir = tempdir()?;
+    /// let vault = Vault { path: tmp_dir.path().to_path_buf(), ..Default::default() };
+    /// let recipes = obsidian::vault::create_note(&vault.path, "/notes/Recipes")?;
+    ///
+    /// let link = WikiLink::at("[[recipes#Pancakes]]", 0).unwrap();
+    /// assert_eq!(link.resolve(&vault), Some(recipes.clone()));
+    ///
+    /// let link = WikiLink::at("[[notes/Recipes]]", 0).unwrap();
+    /// assert_eq!(link.resolve(&vault), Some(recipes));
+    ///
+    /// let link = WikiLink::at("[[Groceries]]", 0).unwrap();
+    /// assert_eq!(link.resolve(&vault), None);
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn resolve(&self, vault: &Vault) -> Option<Note> {
+        if self.target.is_empty() {
+            return None;
+        }
+
+        let entries = vault.entries();
+        let path = vault.path.join(format!("{}.md", self.target));
+        let name = self.target.rsplit('/').next().unwrap_or(&self.target);
+
+        entries
+            .find_note(&path)
+            .or_else(|| find_note_by_name(&entries, name))
+            .cloned()
+    }
+}
@@ -49,10 +49,17 @@ use ratatui::{
     },
 };
 
-use basalt_core::markdown::{self, HeadingLevel, ItemKind};
+use basalt_core::markdown::{
+    self,
+    highlight::{highlight, TokenKind},
+    math, HeadingLevel, ItemKind, Style,
+};
 
 use super::state::MarkdownViewState;
 
+/// Width of a rendered horizontal rule.
+const RULE_WIDTH: usize = 40;
+
 /// A widget for rendering markdown text using [`MarkdownViewState`].
 ///
 /// # Example
@@ -103,43 +110,35 @@ impl MarkdownView {
         Line::from([prefix].into_iter().chain(content).collect::<Vec<_>>()).bold()
     }
 
-    fn item<'a>(kind: Option<ItemKind>, content: Vec<Span<'a>>, prefix: Span<'a>) -> Line<'a> {
+    fn item<'a>(kind: ItemKind, content: Vec<Span<'a>>, prefix: Span<'a>) -> Line<'a> {
         match kind {
-            Some(kind) => match kind {
-                ItemKind::Unchecked => Line::from(
-                    [prefix, "󰄱 ".black()]
-                        .into_iter()
-                        .chain(content)
-                        .collect::<Vec<_>>(),
-                ),
-                ItemKind::Checked => Line::from(
-                    [prefix, "󰄲 ".magenta()]
-                        .into_iter()
-                        .chain(content)
-                        .collect::<Vec<_>>(),
-                ),
-                ItemKind::HardChecked => Line::from(
-                    [prefix, "󰄲 ".magenta()]
-                        .into_iter()
-                        .chain(content)
-                        .collect::<Vec<_>>(),
-                )
-                .black()
-                .add_modifier(Modifier::CROSSED_OUT),
-                ItemKind::Ordered(num) => Line::from(
-                    [prefix, num.to_string().black(), ". ".into()]
-                        .into_iter()
-                        .chain(content)
-                        .collect::<Vec<_>>(),
-                ),
-                ItemKind::Unordered => Line::from(
-                    [prefix, "- ".black()]
-                        .into_iter()
-                        .chain(content)
-                        .collect::<Vec<_>>(),
-                ),
-            },
-            None => Line::from(
+            ItemKind::Unchecked => Line::from(
+                [prefix, "󰄱 ".black()]
+                    .into_iter()
+                    .chain(content)
+                    .collect::<Vec<_>>(),
+            ),
+            ItemKind::Checked => Line::from(
+                [prefix, "󰄲 ".magenta()]
+                    .into_iter()
+                    .chain(content)
+                    .collect::<Vec<_>>(),
+            ),
+            ItemKind::HardChecked => Line::from(
+                [prefix, "󰄲 ".magenta()]
+                    .into_iter()
+                    .chain(content)
+                    .collect::<Vec<_>>(),
+            )
+            .black()
+            .add_modifier(Modifier::CROSSED_OUT),
+            ItemKind::Ordered(num) => Line::from(
+                [prefix, num.to_string().black(), ". ".into()]
+                    .into_iter()
+                    .chain(content)
+                    .collect::<Vec<_>>(),
+            ),
+            ItemKind::Unordered => Line::from(
                 [prefix, "- ".black()]
                     .into_iter()
                     .chain(content)
@@ -148,48 +147,227 @@ impl MarkdownView {
         }
     }
 
+    fn styled_span<'a>(span: Span<'a>, style: &Style) -> Span<'a> {
+        match style {
+            Style::Code => span.red().bg(Color::Rgb(10, 10, 10)),
+            Style::Emphasis => span.italic(),
+            Style::Strong => span.bold(),
+            Style::Strikethrough => span.crossed_out(),
+            Style::Highlight => span.black().on_yellow(),
+            Style::Link(_) => span.blue().underlined(),
+            Style::WikiLink(_) => span.cyan().underlined(),
+            Style::Image(_) | Style::Embed(_) => span.magenta(),
+            Style::InlineMath | Style::DisplayMath => span.yellow(),
+            Style::FootnoteReference => span.cyan(),
+            Style::Superscript | Style::Subscript | Style::HardBreak => span,
+        }
+    }
+
     fn text_to_spans<'a>(text: markdown::Text) -> Vec<Span<'a>> {
         text.into_iter()
-            .map(|text| Span::from(text.content))
+            .map(|text| {
+                let content = match text.content.as_str() {
+                    "\n" => String::from(" "),
+                    _ if text.has_style(&Style::FootnoteReference) => format!("[{}]", text.content),
+                    _ if text.has_style(&Style::InlineMath)
+                        || text.has_style(&Style::DisplayMath) =>
+                    {
+                        math::to_unicode(&text.content)
+                    }
+                    _ => text.content,
+                };
+                text.styles
+                    .iter()
+                    .fold(Span::from(content), MarkdownView::styled_span)
+            })
             .collect()
     }
 
-    fn code_block<'a>(text: markdown::Text) -> Vec<Line<'a>> {
-        text.into_iter()
-            .flat_map(|text| {
-                text.content
-                    .clone()
-                    .split("\n")
-                    .map(String::from)
-                    .collect::<Vec<String>>()
+    fn table<'a>(head: Vec<markdown::Text>, rows: Vec<Vec<markdown::Text>>) -> Vec<Line<'a>> {
+        let row = |cells: Vec<markdown::Text>| {
+            let spans = cells
+                .into_iter()
+                .enumerate()
+                .flat_map(|(index, cell)| {
+                    let separator = (index > 0).then(|| " │ ".dark_gray());
+                    separator
+                        .into_iter()
+                        .chain(MarkdownView::text_to_spans(cell))
+                })
+                .collect::<Vec<_>>();
+            Line::from(spans)
+        };
+
+        let mut lines = vec![row(head).bold()];
+        lines.extend(rows.into_iter().map(row));
+        lines.push(Line::default());
+        lines
+    }
+
+    fn token_span<'a>(content: &str, kind: TokenKind) -> Span<'a> {
+        let span = Span::from(content.to_string());
+        match kind {
+            TokenKind::Plain => span,
+            TokenKind::Comment => span.dark_gray(),
+            TokenKind::Keyword => span.magenta(),
+            TokenKind::String => span.green(),
+            TokenKind::Constant => span.yellow(),
+            TokenKind::Function => span.blue(),
+            TokenKind::Type => span.cyan(),
+        }
+    }
+
+    fn code_block<'a>(lang: Option<String>, text: markdown::Text) -> Vec<Line<'a>> {
+        let code = text
+            .into_iter()
+            .map(|text| text.content)
+            .collect::<String>();
+        let highlighted = lang
+            .and_then(|lang| highlight(&code, &lang))
+            .unwrap_or_default();
+
+        code.split("\n")
+            .enumerate()
+            .map(|(index, line)| match highlighted.get(index) {
+                Some(tokens) => Line::from(
+                    tokens
+                        .iter()
+                        .map(|token| {
+                            MarkdownView::token_span(&line[token.range.clone()], token.kind)
+                        })
+                        .collect::<Vec<_>>(),
+                ),
+                None => Line::from(line.to_string()).red(),
             })
-            .map(|text| Line::from(text).red().bg(Color::Rgb(10, 10, 10)))
+            .map(|line| line.bg(Color::Rgb(10, 10, 10)))
             .collect()
     }
 
     fn render_markdown<'a>(node: markdown::Node, prefix: Span<'a>) -> Vec<Line<'a>> {
         match node.markdown_node {
             markdown::MarkdownNode::Paragraph { text } => {
-                let mut spans = MarkdownView::text_to_spans(text);
-                spans.insert(0, prefix.clone());
-                vec![spans.into(), Line::from(prefix)]
+                let mut lines = vec![];
+                let mut spans = vec![prefix.clone()];
+                for node in text {
+                    if node.has_style(&Style::HardBreak) {
+                        lines.push(Line::from(std::mem::replace(
+                            &mut spans,
+                            vec![prefix.clone()],
+                        )));
+                    } else {
+                        spans.extend(MarkdownView::text_to_spans(node.into()));
+                    }
+                }
+                lines.extend([spans.into(), Line::from(prefix)]);
+                lines
             }
             markdown::MarkdownNode::Heading { level, text } => [
                 MarkdownView::heading(level, MarkdownView::text_to_spans(text)),
                 Line::default(),
             ]
             .to_vec(),
-            markdown::MarkdownNode::Item { kind, text } => [
-                MarkdownView::item(kind, MarkdownView::text_to_spans(text), prefix),
-                Line::default(),
-            ]
-            .to_vec(),
+            markdown::MarkdownNode::List { nodes, .. } => {
+                let mut lines = nodes
+                    .into_iter()
+                    .flat_map(|child| MarkdownView::render_markdown(child, prefix.clone()))
+                    .collect::<Vec<Line<'a>>>();
+
+                lines.push(Line::default());
+
+                lines
+            }
+            markdown::MarkdownNode::Item { kind, nodes } => {
+                let mut nodes = nodes.into_iter().peekable();
+                let text = match nodes.peek().map(|node| &node.markdown_node) {
+                    Some(markdown::MarkdownNode::Paragraph { .. }) => match nodes.next() {
+                        Some(markdown::Node {
+                            markdown_node: markdown::MarkdownNode::Paragraph { text },
+                            ..
+                        }) => MarkdownView::text_to_spans(text),
+                        _ => vec![],
+                    },
+                    _ => vec![],
+                };
+
+                let nested_prefix = Span::from(format!("{}  ", prefix.content));
+                let mut lines = vec![MarkdownView::item(kind, text, prefix)];
+                lines.extend(
+                    nodes
+                        .flat_map(|child| {
+                            MarkdownView::render_markdown(child, nested_prefix.clone())
+                        })
+                        .filter(|line| line.width() > nested_prefix.width()),
+                );
+                lines
+            }
+            markdown::MarkdownNode::Table { head, rows, .. } => MarkdownView::table(head, rows),
+            markdown::MarkdownNode::Rule => {
+                vec![
+                    Line::from("─".repeat(RULE_WIDTH)).dark_gray(),
+                    Line::default(),
+                ]
+            }
+            markdown::MarkdownNode::FootnoteDefinition { label, nodes } => {
+                let mut lines = nodes
+                    .into_iter()
+                    .flat_map(|child| MarkdownView::render_markdown(child, Span::default()))
+                    .collect::<Vec<Line<'a>>>();
+
+                if let Some(first) = lines.first_mut() {
+                    first.spans.insert(0, format!("[{label}]: ").cyan());
+                }
+
+                lines
+            }
+            markdown::MarkdownNode::HtmlBlock { text } => {
+                let mut lines = text
+                    .into_iter()
+                    .map(|text| text.content)
+                    .collect::<String>()
+                    .lines()
+                    .map(|line| Line::from(line.to_string()).dark_gray())
+                    .collect::<Vec<Line<'a>>>();
+
+                lines.push(Line::default());
+
+                lines
+            }
+            markdown::MarkdownNode::DefinitionList { nodes } => nodes
+                .into_iter()
+                .flat_map(|child| MarkdownView::render_markdown(child, prefix.clone()))
+                .collect(),
+            markdown::MarkdownNode::DefinitionTitle { text } => {
+                let mut spans = MarkdownView::text_to_spans(text);
+                spans.insert(0, prefix);
+                vec![Line::from(spans).bold()]
+            }
+            markdown::MarkdownNode::Definition { nodes } => nodes
+                .into_iter()
+                .flat_map(|child| MarkdownView::render_markdown(child, Span::from(": ").black()))
+                .collect(),
             // TODO: Add lang support and syntax highlighting
-            markdown::MarkdownNode::CodeBlock { text, .. } => {
-                let mut lines = MarkdownView::code_block(text);
+            markdown::MarkdownNode::CodeBlock { lang, text } => {
+                let mut lines = MarkdownView::code_block(lang, text);
                 lines.insert(0, Line::default());
                 lines
             }
+            markdown::MarkdownNode::Frontmatter { frontmatter } => {
+                let mut lines = frontmatter
+                    .properties
+                    .into_iter()
+                    .map(|(key, value)| {
+                        Line::from(vec![
+                            prefix.clone(),
+                            Span::from(format!("{key}: ")).dark_gray(),
+                            Span::from(value.to_string()),
+                        ])
+                    })
+                    .collect::<Vec<Line<'a>>>();
+
+                lines.push(Line::default());
+
+                lines
+            }
             // TODO: Support callout block quote types
             markdown::MarkdownNode::BlockQuote { nodes, .. } => {
                 let mut lines = nodes
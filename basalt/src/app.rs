@@ -1,4 +1,7 @@
-use basalt_core::obsidian::{self, create_untitled_dir, create_untitled_note, Note, Vault};
+use basalt_core::obsidian::{
+    self, create_untitled_dir, create_untitled_note, daily_notes, directory::Directory, templates,
+    wiki_link::WikiLink, DeleteMode, Note, Vault,
+};
 use ratatui::{
     buffer::Buffer,
     crossterm::{
@@ -9,8 +12,9 @@ use ratatui::{
     layout::{Constraint, Flex, Layout, Position, Rect, Size},
     style::Style,
     widgets::{Block, StatefulWidget, Widget},
-    DefaultTerminal,
+    DefaultTerminal, Frame,
 };
+use time::{OffsetDateTime, PrimitiveDateTime, UtcOffset};
 use tracing::{debug, error, info, warn};
 
 use std::{
@@ -23,7 +27,10 @@ use std::{
 };
 
 use crate::{
+    backlinks::{self, Backlinks, BacklinksState},
     command,
+    command_line::{self, CommandLine, CommandLineState},
+    command_palette::{self, CommandPalette, CommandPaletteState},
     config::{self, Config, Keystroke, Theme},
     debug_log::{self, DebugLogModal, DebugLogModalState, LogLevel},
     explorer::{self, Explorer, ExplorerState, Item, Visibility},
@@ -36,10 +43,15 @@ use crate::{
         state::{EditMode, Mode, NoteEditorState, View},
     },
     outline::{self, Outline, OutlineState},
+    quick_switcher_modal::{self, QuickSwitcherModal, QuickSwitcherModalState},
+    search_modal::{self, SearchModal, SearchModalState},
+    shell,
     splash_modal::{self, SplashModal, SplashModalState},
     statusbar::{StatusBar, StatusBarState},
     stylized_text::{self, FontStyle},
     tabs::{Tab, Tabs},
+    tags::{self, Tags, TagsState},
+    template_modal::{self, TemplateAction, TemplateModal, TemplateModalState},
     text_counts::{CharCount, WordCount},
     theme_selector_modal::{self, ThemeSelectorModal, ThemeSelectorModalState},
     toast::{self, Toast, TOAST_WIDTH},
@@ -71,12 +83,20 @@ pub struct AppState<'a> {
     screen_size: Size,
     is_running: bool,
     pending_keys: Vec<Keystroke>,
+    /// Target of an unresolved wiki-link the user was offered to create. Following the same link
+    /// again creates the note.
+    pending_link_target: Option<String>,
+    /// Offset of the local time zone, read on startup while the process still has a single
+    /// thread. `None` if it could not be determined, in which case UTC is used.
+    utc_offset: Option<UtcOffset>,
 
     active_pane: ActivePane,
     theme: Theme,
     explorer: ExplorerState,
     tabs: Tabs<'a>,
     outline: OutlineState,
+    backlinks: BacklinksState,
+    tags: TagsState,
     toasts: Vec<Toast>,
 
     input_modal: InputModalState,
@@ -85,6 +105,14 @@ pub struct AppState<'a> {
     vault_selector_modal: VaultSelectorModalState<'a>,
     debug_log_modal: DebugLogModalState,
     theme_selector_modal: ThemeSelectorModalState,
+    search_modal: SearchModalState,
+    quick_switcher_modal: QuickSwitcherModalState,
+    template_modal: TemplateModalState,
+    command_palette: CommandPaletteState,
+    command_line: CommandLineState,
+
+    #[cfg(feature = "images")]
+    graphics: crate::image::Graphics,
 }
 
 impl<'a> AppState<'a> {
@@ -103,6 +131,26 @@ impl<'a> AppState<'a> {
             return ActivePane::HelpModal;
         }
 
+        if self.command_palette.is_visible() {
+            return ActivePane::CommandPalette;
+        }
+
+        if self.command_line.visible {
+            return ActivePane::CommandLine;
+        }
+
+        if self.template_modal.is_visible() {
+            return ActivePane::TemplateModal;
+        }
+
+        if self.quick_switcher_modal.is_visible() {
+            return ActivePane::QuickSwitcherModal;
+        }
+
+        if self.search_modal.is_visible() {
+            return ActivePane::SearchModal;
+        }
+
         if self.theme_selector_modal.visible {
             return ActivePane::ThemeSelectorModal;
         }
@@ -131,6 +179,8 @@ pub enum Message<'a> {
     Quit,
     Exec(String),
     Spawn(String),
+    Filter(String),
+    Read(String),
     CopyToClipboard(String),
     Resize(Size),
     SetActivePane(ActivePane),
@@ -141,12 +191,18 @@ pub enum Message<'a> {
     RescanVault,
     CreateUntitledNote,
     CreateUntitledFolder,
+    DeleteNote(Note),
+    DeleteDir(Directory),
+    Paste(explorer::Clipboard),
     OpenVault(&'a Vault),
     SelectNote(SelectedNote),
+    FollowLink(WikiLink),
     UpdateSelectedNoteContent((String, Option<Vec<ast::Node>>)),
     TabNext,
     TabPrevious,
     CloseTab,
+    OpenDailyNote(DailyNote),
+    ApplyTemplate(Note, TemplateAction),
 
     Batch(Vec<Message<'a>>),
     Toast(toast::Message),
@@ -155,14 +211,29 @@ pub enum Message<'a> {
     Explorer(explorer::Message),
     NoteEditor(note_editor::Message),
     Outline(outline::Message),
+    Backlinks(backlinks::Message),
+    Tags(tags::Message),
     HelpModal(help_modal::Message),
     VaultSelectorModal(vault_selector_modal::Message),
     DebugLog(debug_log::Message),
     ThemeSelectorModal(theme_selector_modal::Message),
+    SearchModal(search_modal::Message),
+    QuickSwitcherModal(quick_switcher_modal::Message),
+    TemplateModal(template_modal::Message),
+    CommandPalette(command_palette::Message),
+    CommandLine(command_line::Message),
     PreviewTheme(Theme),
     SaveTheme(String),
 }
 
+/// The daily note to open, relative to today or to the daily note in the active tab.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DailyNote {
+    Today,
+    Previous,
+    Next,
+}
+
 #[derive(Debug, Default, Clone, Copy, PartialEq)]
 pub enum ActivePane {
     #[default]
@@ -170,11 +241,18 @@ pub enum ActivePane {
     Explorer,
     NoteEditor,
     Outline,
+    Backlinks,
+    Tags,
     Input,
     HelpModal,
     VaultSelectorModal,
     DebugLogModal,
     ThemeSelectorModal,
+    SearchModal,
+    QuickSwitcherModal,
+    TemplateModal,
+    CommandPalette,
+    CommandLine,
 }
 
 impl From<ActivePane> for &str {
@@ -184,11 +262,18 @@ impl From<ActivePane> for &str {
             ActivePane::Explorer => "Explorer",
             ActivePane::NoteEditor => "Note Editor",
             ActivePane::Outline => "Outline",
+            ActivePane::Backlinks => "Backlinks",
+            ActivePane::Tags => "Tags",
             ActivePane::Input => "Input",
             ActivePane::HelpModal => "Help",
             ActivePane::VaultSelectorModal => "Vault Selector",
             ActivePane::DebugLogModal => "Debug Log",
             ActivePane::ThemeSelectorModal => "Theme Selector",
+            ActivePane::SearchModal => "Search",
+            ActivePane::QuickSwitcherModal => "Quick Switcher",
+            ActivePane::TemplateModal => "Templates",
+            ActivePane::CommandPalette => "Command Palette",
+            ActivePane::CommandLine => "Command Line",
         }
     }
 }
@@ -256,6 +341,8 @@ fn apply_theme(state: &mut AppState, theme: Theme) {
     state.theme = theme;
     state.explorer.set_theme(&theme);
     state.outline.set_theme(&theme);
+    state.backlinks.set_theme(&theme);
+    state.tags.set_theme(&theme);
     state.tabs.set_theme(&theme);
 }
 
@@ -267,15 +354,35 @@ fn active_config_section<'a>(
         ActivePane::Splash => &config.splash,
         ActivePane::Explorer => &config.explorer,
         ActivePane::Outline => &config.outline,
+        ActivePane::Backlinks => &config.backlinks,
+        ActivePane::Tags => &config.tags,
         ActivePane::HelpModal => &config.help_modal,
         ActivePane::VaultSelectorModal => &config.vault_selector_modal,
         ActivePane::ThemeSelectorModal => &config.theme_selector_modal,
+        ActivePane::SearchModal => &config.search_modal,
+        ActivePane::QuickSwitcherModal => &config.quick_switcher_modal,
+        ActivePane::TemplateModal => &config.template_modal,
+        ActivePane::CommandPalette => &config.command_palette,
+        // The command line takes every key while open, so it never looks up a binding.
+        ActivePane::CommandLine => &config.note_editor,
         ActivePane::Input => &config.input_modal,
         ActivePane::NoteEditor => &config.note_editor,
         ActivePane::DebugLogModal => &config.debug_log_modal,
     }
 }
 
+/// Returns the images of the active note to write with a graphics protocol. The images are hidden
+/// while a modal or a toast is shown, since they would be drawn on top of it.
+#[cfg(feature = "images")]
+fn visible_images(state: &AppState) -> Vec<crate::image::Visible> {
+    let covered = state.active_component() != state.active_pane || !state.toasts.is_empty();
+
+    match state.tabs.active_editor() {
+        Some(editor) if !covered => editor.virtual_document.embeds().visible().to_vec(),
+        _ => vec![],
+    }
+}
+
 fn focused_cursor(state: &AppState) -> Option<Position> {
     match state.active_component() {
         ActivePane::NoteEditor => state
@@ -283,6 +390,11 @@ fn focused_cursor(state: &AppState) -> Option<Position> {
             .active_editor()
             .and_then(|editor| editor.terminal_cursor),
         ActivePane::Input => state.input_modal.terminal_cursor,
+        ActivePane::SearchModal => state.search_modal.terminal_cursor(),
+        ActivePane::QuickSwitcherModal => state.quick_switcher_modal.terminal_cursor(),
+        ActivePane::TemplateModal => state.template_modal.terminal_cursor(),
+        ActivePane::CommandPalette => state.command_palette.terminal_cursor(),
+        ActivePane::CommandLine => state.command_line.terminal_cursor,
         _ => None,
     }
 }
@@ -294,6 +406,11 @@ fn cursor_style(state: &AppState) -> SetCursorStyle {
             Some(Mode::Insert | Mode::Edit)
         ),
         ActivePane::Input => state.input_modal.is_editing(),
+        ActivePane::SearchModal => state.search_modal.is_editing(),
+        ActivePane::QuickSwitcherModal => state.quick_switcher_modal.is_editing(),
+        ActivePane::TemplateModal => state.template_modal.is_editing(),
+        ActivePane::CommandPalette => state.command_palette.is_editing(),
+        ActivePane::CommandLine => true,
         _ => false,
     };
     if inserting {
@@ -321,6 +438,404 @@ fn rebuild_outline(state: &mut AppState, config: &Config) {
     state.outline.set_theme(&state.theme);
 }
 
+fn active_vault_note(state: &AppState) -> Option<Note> {
+    state
+        .tabs
+        .active_note()
+        .map(|note| Note::new_unchecked(note.name(), note.path()))
+}
+
+/// Looks up the backlinks of the active note again, e.g. after switching to another note.
+fn refresh_backlinks(state: &mut AppState) {
+    let note = active_vault_note(state);
+    backlinks::update(
+        &backlinks::Message::Refresh,
+        &state.vault,
+        note.as_ref(),
+        &mut state.backlinks,
+    );
+}
+
+/// Returns the directory that new and pasted explorer items go into: the selected directory, the
+/// directory of the selected note, or the vault root when nothing is selected.
+fn explorer_target_dir<'s>(state: &'s AppState) -> &'s Path {
+    match state.explorer.current_item() {
+        Some(Item::Directory { path, .. }) => path,
+        Some(Item::File { note, .. }) => note.path().parent().unwrap_or(&state.vault.path),
+        _ => &state.vault.path,
+    }
+}
+
+/// Moves or copies the clipboard item into the target directory of the explorer.
+fn paste<'a>(state: &mut AppState, clipboard: explorer::Clipboard) -> Message<'a> {
+    let target = explorer_target_dir(state).to_path_buf();
+    let directory = Directory::new(&basename(&target), &target);
+    let item = clipboard.item();
+    let old_path = item.path().to_path_buf();
+
+    let result = match (&clipboard, item) {
+        // Cutting and pasting into the same directory leaves the item where it is.
+        (explorer::Clipboard::Cut(_), _) if old_path.parent() == Some(target.as_path()) => {
+            state.explorer.clipboard = None;
+            return Message::SetActivePane(ActivePane::Explorer);
+        }
+        (explorer::Clipboard::Cut(_), Item::File { note, .. }) => {
+            obsidian::vault::move_note_to(note.clone(), directory).map(|note| note.path().into())
+        }
+        (explorer::Clipboard::Cut(_), Item::Directory { name, path, .. }) => {
+            obsidian::vault::move_dir_to(Directory::new(name, path), directory)
+                .map(|dir| dir.path().into())
+        }
+        (explorer::Clipboard::Copy(_), Item::File { note, .. }) => {
+            obsidian::vault::copy_note(note.clone(), directory).map(|note| note.path().into())
+        }
+        (explorer::Clipboard::Copy(_), Item::Directory { name, path, .. }) => {
+            obsidian::vault::copy_dir(Directory::new(name, path), directory)
+                .map(|dir| dir.path().into())
+        }
+    };
+
+    let new_path: PathBuf = match result {
+        Ok(new_path) => new_path,
+        Err(error) => {
+            error!(?error, from = %old_path.display(), to = %target.display(), "failed to paste");
+            return Message::Toast(toast::Message::Create(toast::Toast::error(
+                "Failed to paste",
+                Duration::from_secs(2),
+            )));
+        }
+    };
+
+    let (rename, toast) = match clipboard {
+        explorer::Clipboard::Cut(item) => {
+            info!(from = %old_path.display(), to = %new_path.display(), "moved");
+            state.explorer.clipboard = None;
+            state.tabs.relocate(&old_path, &new_path);
+            let rename = (!item.is_dir()).then(|| (old_path, new_path.clone()));
+            (rename, "Moved")
+        }
+        explorer::Clipboard::Copy(_) => {
+            info!(from = %old_path.display(), to = %new_path.display(), "copied");
+            (None, "Copied")
+        }
+    };
+
+    Message::Batch(vec![
+        Message::RefreshVault {
+            rename,
+            select: Some(new_path),
+        },
+        Message::Toast(toast::Message::Create(toast::Toast::success(
+            toast,
+            Duration::from_secs(2),
+        ))),
+    ])
+}
+
+fn basename(path: &Path) -> String {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// The configured delete mode, or else the one of the vault, like Obsidian uses it.
+fn delete_mode(vault: &Vault, config: &Config) -> DeleteMode {
+    config.delete_mode.unwrap_or_else(|| {
+        obsidian::vault::load_delete_mode(vault).unwrap_or_else(|error| {
+            warn!(?error, "failed to read the delete mode of the vault");
+            DeleteMode::default()
+        })
+    })
+}
+
+/// Closes the tabs of a deleted note or directory and refreshes the explorer.
+fn finish_delete<'a>(
+    state: &mut AppState,
+    config: &Config,
+    path: &Path,
+    result: obsidian::Result<DeleteMode>,
+) -> Message<'a> {
+    let mode = match result {
+        Ok(mode) => mode,
+        Err(error) => {
+            error!(?error, path = %path.display(), "failed to delete");
+            return Message::Toast(toast::Message::Create(toast::Toast::error(
+                "Failed to delete",
+                Duration::from_secs(2),
+            )));
+        }
+    };
+
+    info!(path = %path.display(), ?mode, "deleted");
+    state.tabs.close_path(path);
+    focus_active_editor(state);
+    rebuild_outline(state, config);
+    refresh_backlinks(state);
+
+    let toast = match mode {
+        DeleteMode::System => "Moved to trash",
+        DeleteMode::Local => "Moved to the vault's .trash folder",
+        DeleteMode::Permanent => "Deleted",
+    };
+    Message::Batch(vec![
+        Message::RefreshVault {
+            rename: None,
+            select: None,
+        },
+        Message::Toast(toast::Message::Create(toast::Toast::success(
+            toast,
+            Duration::from_secs(2),
+        ))),
+    ])
+}
+
+/// Returns the current local date and time.
+fn local_now(state: &AppState) -> PrimitiveDateTime {
+    let now = OffsetDateTime::now_utc().to_offset(state.utc_offset.unwrap_or(UtcOffset::UTC));
+    PrimitiveDateTime::new(now.date(), now.time())
+}
+
+/// Returns the context of the `exec:` and `spawn:` commands: the vault, and the note and the cursor
+/// of the active tab.
+fn command_context(state: &AppState) -> command::CommandContext {
+    let (note_name, note_path) = state
+        .tabs
+        .active_note()
+        .map(|note| {
+            (
+                note.name().to_string(),
+                note.path().to_string_lossy().to_string(),
+            )
+        })
+        .unwrap_or_default();
+    let editor = state.tabs.active_editor();
+
+    command::CommandContext {
+        vault_name: state.vault.name.clone(),
+        vault_path: state.vault.path.to_string_lossy().to_string(),
+        note_name,
+        note_path,
+        selection: editor
+            .and_then(NoteEditorState::selected_text)
+            .unwrap_or_default(),
+        line: editor.map(NoteEditorState::cursor_line),
+        heading: editor
+            .and_then(NoteEditorState::heading_at_cursor)
+            .unwrap_or_default(),
+    }
+}
+
+/// Where the output of a command run on the text of the active note goes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum FilterOutput {
+    /// Replaces the text the command read, for `filter:` commands.
+    Replace,
+    /// Is inserted at the cursor, keeping the text the command read, for `read:` commands.
+    Insert,
+}
+
+/// Runs the `filter:` or `read:` command on the selection of the active note, or on the whole
+/// note when nothing is selected, and replaces the text with the output of the command or inserts
+/// the output at the cursor.
+fn filter_note<'a>(
+    state: &AppState,
+    config: &Config,
+    command: &str,
+    output: FilterOutput,
+) -> Message<'a> {
+    let toast = |toast| Message::Toast(toast::Message::Create(toast));
+
+    let Some(editor) = state
+        .tabs
+        .active_editor()
+        .filter(|editor| editor.editor_enabled())
+    else {
+        return toast(toast::Toast::info(
+            "No editable note to filter",
+            Duration::from_secs(2),
+        ));
+    };
+
+    let (range, input) = editor.filter_input();
+    let range = match output {
+        FilterOutput::Replace => range,
+        FilterOutput::Insert => editor.insert_range(),
+    };
+    let context = command_context(state);
+
+    match command::filter_command(command, &input, &context, command_shell(config)) {
+        Ok(text) => Message::Batch(vec![
+            Message::SetActivePane(ActivePane::NoteEditor),
+            Message::NoteEditor(note_editor::Message::ReplaceText(range, text)),
+        ]),
+        Err(error) => {
+            error!(?error, command, "filter command failed");
+            toast(toast::Toast::error(
+                &format!("Filter failed: {error}"),
+                Duration::from_secs(3),
+            ))
+        }
+    }
+}
+
+/// Returns the shell the `exec:`, `spawn:` and `filter:` commands run through when
+/// `command_shell` is enabled.
+fn command_shell(config: &Config) -> Option<&'static str> {
+    config.command_shell.then_some(shell::POSIX_SHELL)
+}
+
+/// Opens today's daily note, creating it if needed, or steps to the closest existing daily note
+/// before or after the one in the active tab.
+fn open_daily_note<'a>(state: &AppState, day: DailyNote) -> Message<'a> {
+    let toast = |toast: toast::Toast| Message::Toast(toast::Message::Create(toast));
+
+    let config = match daily_notes::load(&state.vault) {
+        Ok(config) => config,
+        Err(error) => {
+            error!(?error, "failed to read daily notes config");
+            return toast(toast::Toast::error(
+                "Failed to read the daily notes config",
+                Duration::from_secs(2),
+            ));
+        }
+    };
+
+    let now = local_now(state);
+
+    let note = match day {
+        DailyNote::Today => match daily_notes::open_today(&state.vault, &config, now) {
+            Ok(note) => note,
+            Err(error) => {
+                error!(?error, "failed to open daily note");
+                return toast(toast::Toast::error(
+                    "Failed to open today's daily note",
+                    Duration::from_secs(2),
+                ));
+            }
+        },
+        DailyNote::Previous | DailyNote::Next => {
+            let date = state
+                .tabs
+                .active_note()
+                .and_then(|note| config.date_of(&state.vault, note.path()))
+                .unwrap_or(now.date());
+            let (note, direction) = match day {
+                DailyNote::Previous => (
+                    daily_notes::previous(&state.vault, &config, date),
+                    "previous",
+                ),
+                _ => (daily_notes::next(&state.vault, &config, date), "next"),
+            };
+            match note {
+                Some(note) => note,
+                None => {
+                    return toast(toast::Toast::info(
+                        &format!("No {direction} daily note"),
+                        Duration::from_secs(2),
+                    ))
+                }
+            }
+        }
+    };
+
+    info!(path = %note.path().display(), "opened daily note");
+    let mut messages = vec![
+        Message::SelectNote(note.clone().into()),
+        Message::SetActivePane(ActivePane::NoteEditor),
+    ];
+    if day == DailyNote::Today {
+        // Today's note may have just been created.
+        messages.insert(
+            0,
+            Message::RefreshVault {
+                rename: None,
+                select: Some(note.path().to_path_buf()),
+            },
+        );
+    }
+    Message::Batch(messages)
+}
+
+/// Creates an untitled note from the template in the explorer's target folder, or inserts the
+/// template at the cursor of the active tab.
+fn apply_template<'a>(state: &AppState, template: &Note, action: TemplateAction) -> Message<'a> {
+    let toast = |toast: toast::Toast| Message::Toast(toast::Message::Create(toast));
+
+    let config = match templates::load(&state.vault) {
+        Ok(config) => config,
+        Err(error) => {
+            error!(?error, "failed to read templates config");
+            return toast(toast::Toast::error(
+                "Failed to read the templates config",
+                Duration::from_secs(2),
+            ));
+        }
+    };
+    let now = local_now(state);
+
+    match action {
+        TemplateAction::NewNote => {
+            let target_dir = explorer_target_dir(state);
+            match templates::create_note(target_dir, "Untitled", template, &config, now) {
+                Ok(note) => {
+                    info!(path = %note.path().display(), "created note from template");
+                    Message::Batch(vec![
+                        Message::Explorer(explorer::Message::Open),
+                        Message::RefreshVault {
+                            rename: None,
+                            select: Some(note.path().to_path_buf()),
+                        },
+                        toast(toast::Toast::success(
+                            "Note created from template",
+                            Duration::from_secs(2),
+                        )),
+                        Message::SelectNote(note.into()),
+                    ])
+                }
+                Err(error) => {
+                    error!(?error, "failed to create note from template");
+                    toast(toast::Toast::error(
+                        "Failed to create a note from the template",
+                        Duration::from_secs(2),
+                    ))
+                }
+            }
+        }
+        TemplateAction::Insert => {
+            let editable = state
+                .tabs
+                .active_editor()
+                .is_some_and(NoteEditorState::editor_enabled);
+            let Some(note) = state.tabs.active_note().filter(|_| editable) else {
+                return toast(toast::Toast::info(
+                    "No editable note to insert the template into",
+                    Duration::from_secs(2),
+                ));
+            };
+
+            match config.render(template, note.name(), now) {
+                Ok(text) => Message::Batch(vec![
+                    Message::SetActivePane(ActivePane::NoteEditor),
+                    Message::NoteEditor(note_editor::Message::InsertText(text)),
+                ]),
+                Err(error) => {
+                    error!(?error, "failed to read template");
+                    toast(toast::Toast::error(
+                        "Failed to read the template",
+                        Duration::from_secs(2),
+                    ))
+                }
+            }
+        }
+    }
+}
+
+/// Rebuilds the tag index, e.g. after notes were changed, moved or deleted.
+fn refresh_tags(state: &mut AppState) {
+    tags::update(&tags::Message::Refresh, &state.vault, &mut state.tags);
+}
+
 fn focus_active_editor(state: &mut AppState) {
     let focused = state.active_pane == ActivePane::NoteEditor;
     if let Some(editor) = state.tabs.active_editor_mut() {
@@ -434,6 +949,7 @@ impl<'a> App<'a> {
         };
 
         let mut state = AppState {
+            utc_offset: UtcOffset::current_local_offset().ok(),
             vault,
             explorer,
             active_pane,
@@ -447,6 +963,8 @@ impl<'a> App<'a> {
                 symbols: config.symbols.clone(),
                 ..Default::default()
             },
+            backlinks: BacklinksState::new(&config.symbols),
+            tags: TagsState::new(&config.symbols),
             debug_log_modal: DebugLogModalState {
                 visible: debug,
                 min_level: log_level,
@@ -517,14 +1035,22 @@ impl<'a> App<'a> {
     fn draw(&self, state: &mut AppState<'a>) -> Result<()> {
         let mut terminal = self.terminal.borrow_mut();
 
-        terminal.draw(|frame| {
-            let area = frame.area();
-            self.render(area, frame.buffer_mut(), state);
+        terminal.draw(|frame| self.draw_frame(frame, state))?;
+
+        #[cfg(feature = "images")]
+        {
+            let protocol = crate::image::protocol();
+            let images = visible_images(state);
 
-            if let Some(position) = focused_cursor(state) {
-                frame.set_cursor_position(position);
+            if state.graphics.needs_clear(protocol, &images) {
+                terminal.clear()?;
+                terminal.draw(|frame| self.draw_frame(frame, state))?;
             }
-        })?;
+
+            state
+                .graphics
+                .draw(protocol, terminal.backend_mut(), &images)?;
+        }
 
         if focused_cursor(state).is_some() {
             execute!(terminal.backend_mut(), cursor_style(state))?;
@@ -533,6 +1059,15 @@ impl<'a> App<'a> {
         Ok(())
     }
 
+    fn draw_frame(&self, frame: &mut Frame, state: &mut AppState<'a>) {
+        let area = frame.area();
+        self.render(area, frame.buffer_mut(), state);
+
+        if let Some(position) = focused_cursor(state) {
+            frame.set_cursor_position(position);
+        }
+    }
+
     fn handle_event(
         config: &'a Config,
         state: &mut AppState<'_>,
@@ -580,6 +1115,27 @@ impl<'a> App<'a> {
                 state.pending_keys.clear();
                 input::handle_editing_event(key_event).map(Message::Input)
             }
+            ActivePane::SearchModal if state.search_modal.is_editing() => {
+                state.pending_keys.clear();
+                search_modal::handle_editing_event(key_event).map(Message::SearchModal)
+            }
+            ActivePane::QuickSwitcherModal if state.quick_switcher_modal.is_editing() => {
+                state.pending_keys.clear();
+                quick_switcher_modal::handle_editing_event(key_event)
+                    .map(Message::QuickSwitcherModal)
+            }
+            ActivePane::TemplateModal if state.template_modal.is_editing() => {
+                state.pending_keys.clear();
+                template_modal::handle_editing_event(key_event).map(Message::TemplateModal)
+            }
+            ActivePane::CommandPalette if state.command_palette.is_editing() => {
+                state.pending_keys.clear();
+                command_palette::handle_editing_event(key_event).map(Message::CommandPalette)
+            }
+            ActivePane::CommandLine => {
+                state.pending_keys.clear();
+                command_line::handle_editing_event(key_event).map(Message::CommandLine)
+            }
             active => App::handle_pending_keys(
                 Keystroke::from(key_event),
                 config,
@@ -599,15 +1155,19 @@ impl<'a> App<'a> {
         let section = active_config_section(config, active);
 
         let global_message = config.global.sequence_to_message(pending_keys);
-        if global_message.is_some() {
-            pending_keys.clear();
-            return global_message;
-        }
-
         let section_message = section.sequence_to_message(pending_keys);
-        if section_message.is_some() {
+
+        // Global bindings are evaluated first, apart from in the note editor, whose vim bindings
+        // use keys that are global elsewhere, e.g. `?` to search backward instead of the help.
+        let message = if active == ActivePane::NoteEditor {
+            section_message.or(global_message)
+        } else {
+            global_message.or(section_message)
+        };
+
+        if message.is_some() {
             pending_keys.clear();
-            return section_message;
+            return message;
         }
 
         let is_sequence_prefix = config.global.is_sequence_prefix(pending_keys)
@@ -641,7 +1201,12 @@ impl<'a> App<'a> {
                 }
             }
             Message::Quit => state.is_running = false,
-            Message::Resize(size) => state.screen_size = size,
+            Message::Resize(size) => {
+                state.screen_size = size;
+                // Resizing clears the screen, taking the written images with it.
+                #[cfg(feature = "images")]
+                state.graphics.reset();
+            }
             Message::RefreshVault { rename, select } => {
                 if let Some((old, new)) = &rename {
                     // FIXME: Handle error propagation when wiki link update fails
@@ -656,6 +1221,8 @@ impl<'a> App<'a> {
                     state.tabs.rename(old, new, name);
                 }
                 state.explorer.with_entries(state.vault.entries(), select);
+                state.tabs.refresh_embeds();
+                refresh_tags(state);
                 debug!(?rename, "refreshed vault");
 
                 // Reload the note editor for the currently selected note
@@ -685,50 +1252,35 @@ impl<'a> App<'a> {
             }
             Message::RescanVault => {
                 state.explorer.refresh_entries(state.vault.entries());
+                refresh_tags(state);
                 debug!("rescanned vault after watcher change");
             }
-            Message::CreateUntitledNote => {
-                let path = match state.explorer.current_item() {
-                    Some(Item::Directory { path, .. }) => path,
-                    Some(Item::File { note, .. }) => {
-                        note.path().parent().unwrap_or(&state.vault.path)
-                    }
-                    _ => &state.vault.path,
-                };
-                match create_untitled_note(path) {
-                    Ok(note) => {
-                        info!(path = %note.path().display(), "created note");
-                        return Some(Message::Batch(vec![
-                            Message::Explorer(explorer::Message::Open),
-                            Message::RefreshVault {
-                                rename: None,
-                                select: Some(note.path().to_path_buf()),
-                            },
-                            Message::Toast(toast::Message::Create(toast::Toast::success(
-                                "Note created",
-                                Duration::from_secs(2),
-                            ))),
-                            Message::SelectNote(note.into()),
-                        ]));
-                    }
-                    Err(error) => {
-                        error!(?error, "failed to create note");
-                        return Some(Message::Toast(toast::Message::Create(toast::Toast::error(
-                            "Failed to create a new note",
+            Message::CreateUntitledNote => match create_untitled_note(explorer_target_dir(state)) {
+                Ok(note) => {
+                    info!(path = %note.path().display(), "created note");
+                    return Some(Message::Batch(vec![
+                        Message::Explorer(explorer::Message::Open),
+                        Message::RefreshVault {
+                            rename: None,
+                            select: Some(note.path().to_path_buf()),
+                        },
+                        Message::Toast(toast::Message::Create(toast::Toast::success(
+                            "Note created",
                             Duration::from_secs(2),
-                        ))));
-                    }
+                        ))),
+                        Message::SelectNote(note.into()),
+                    ]));
                 }
-            }
+                Err(error) => {
+                    error!(?error, "failed to create note");
+                    return Some(Message::Toast(toast::Message::Create(toast::Toast::error(
+                        "Failed to create a new note",
+                        Duration::from_secs(2),
+                    ))));
+                }
+            },
             Message::CreateUntitledFolder => {
-                let path = match state.explorer.current_item() {
-                    Some(Item::Directory { path, .. }) => path,
-                    Some(Item::File { note, .. }) => {
-                        note.path().parent().unwrap_or(&state.vault.path)
-                    }
-                    _ => &state.vault.path,
-                };
-                match create_untitled_dir(path) {
+                match create_untitled_dir(explorer_target_dir(state)) {
                     Ok(note) => {
                         info!(path = %note.path().display(), "created folder");
                         return Some(Message::Batch(vec![
@@ -752,6 +1304,19 @@ impl<'a> App<'a> {
                     }
                 }
             }
+            Message::Paste(clipboard) => return Some(paste(state, clipboard)),
+            Message::DeleteNote(note) => {
+                let path = note.path().to_path_buf();
+                let mode = delete_mode(&state.vault, config);
+                let result = obsidian::vault::delete_note(&state.vault, note, mode);
+                return Some(finish_delete(state, config, &path, result));
+            }
+            Message::DeleteDir(directory) => {
+                let path = directory.path().to_path_buf();
+                let mode = delete_mode(&state.vault, config);
+                let result = obsidian::vault::delete_dir(&state.vault, directory, mode);
+                return Some(finish_delete(state, config, &path, result));
+            }
             Message::SetActivePane(active_pane) => match active_pane {
                 ActivePane::Explorer => {
                     state.active_pane = active_pane;
@@ -773,6 +1338,14 @@ impl<'a> App<'a> {
                     // TODO: use event/message
                     state.outline.set_active(true);
                 }
+                ActivePane::Backlinks => {
+                    state.active_pane = active_pane;
+                    state.backlinks.set_active(true);
+                }
+                ActivePane::Tags => {
+                    state.active_pane = active_pane;
+                    state.tags.set_active(true);
+                }
                 ActivePane::Input => {
                     state.active_pane = active_pane;
                 }
@@ -784,6 +1357,8 @@ impl<'a> App<'a> {
                 state.explorer = ExplorerState::new(&vault.name, vault.entries(), &config.symbols);
                 state.tabs = Tabs::default();
                 rebuild_outline(state, config);
+                refresh_backlinks(state);
+                refresh_tags(state);
                 apply_theme(state, state.theme);
                 return Some(Message::SetActivePane(ActivePane::Explorer));
             }
@@ -802,9 +1377,13 @@ impl<'a> App<'a> {
                         &config.symbols,
                     );
                     editor.set_vim_mode(config.vim_mode);
+                    if config.live_preview {
+                        editor.set_edit_mode(EditMode::LivePreview);
+                    }
                     editor.set_editor_enabled(config.experimental_editor);
+                    editor.set_vault(&state.vault);
                     if config.experimental_editor && config.vim_mode {
-                        editor.set_view(View::Edit(EditMode::Source));
+                        editor.set_view(View::Edit(editor.edit_mode()));
                     } else {
                         editor.set_view(View::Read);
                     }
@@ -815,6 +1394,7 @@ impl<'a> App<'a> {
                 }
 
                 rebuild_outline(state, config);
+                refresh_backlinks(state);
 
                 apply_theme(state, state.theme);
 
@@ -822,6 +1402,75 @@ impl<'a> App<'a> {
                     return Some(Message::Explorer(explorer::Message::HidePane));
                 }
             }
+            Message::FollowLink(link) => {
+                let jump_to_heading = link
+                    .heading
+                    .clone()
+                    .map(|heading| Message::Outline(outline::Message::JumpToHeading(heading)));
+
+                // A link to a heading in the same note, e.g. `[[#heading]]`.
+                if link.target.is_empty() {
+                    return jump_to_heading;
+                }
+
+                let note = match link.resolve(&state.vault) {
+                    Some(note) => {
+                        state.pending_link_target = None;
+                        note
+                    }
+                    None if state.pending_link_target.as_ref() == Some(&link.target) => {
+                        state.pending_link_target = None;
+                        match obsidian::vault::create_note(&state.vault.path, &link.target) {
+                            Ok(note) => {
+                                info!(path = %note.path().display(), "created linked note");
+                                return Some(Message::Batch(vec![
+                                    Message::RefreshVault {
+                                        rename: None,
+                                        select: Some(note.path().to_path_buf()),
+                                    },
+                                    Message::Toast(toast::Message::Create(toast::Toast::success(
+                                        "Note created",
+                                        Duration::from_secs(2),
+                                    ))),
+                                    Message::SelectNote(note.into()),
+                                    Message::SetActivePane(ActivePane::NoteEditor),
+                                ]));
+                            }
+                            Err(error) => {
+                                error!(?error, "failed to create linked note");
+                                return Some(Message::Toast(toast::Message::Create(
+                                    toast::Toast::error(
+                                        "Failed to create a new note",
+                                        Duration::from_secs(2),
+                                    ),
+                                )));
+                            }
+                        }
+                    }
+                    None => {
+                        let message = format!(
+                            "\"{}\" does not exist. Follow the link again to create it",
+                            link.target
+                        );
+                        state.pending_link_target = Some(link.target);
+                        return Some(Message::Toast(toast::Message::Create(toast::Toast::warn(
+                            &message,
+                            Duration::from_secs(4),
+                        ))));
+                    }
+                };
+
+                return Some(Message::Batch(
+                    [
+                        Some(Message::SelectNote(note.into())),
+                        jump_to_heading,
+                        Some(Message::SetActivePane(ActivePane::NoteEditor)),
+                    ]
+                    .into_iter()
+                    .flatten()
+                    .collect(),
+                ));
+            }
             Message::UpdateSelectedNoteContent((updated_content, nodes)) => {
                 if let Some(selected_note) = state.tabs.active_note_mut() {
                     selected_note.content = updated_content;
@@ -833,43 +1482,48 @@ impl<'a> App<'a> {
                 focus_active_editor(state);
                 sync_explorer_to_active_tab(state);
                 rebuild_outline(state, config);
+                refresh_backlinks(state);
             }
             Message::TabPrevious => {
                 state.tabs.prev();
                 focus_active_editor(state);
                 sync_explorer_to_active_tab(state);
                 rebuild_outline(state, config);
+                refresh_backlinks(state);
             }
             Message::CloseTab => {
                 state.tabs.close_active();
                 focus_active_editor(state);
                 sync_explorer_to_active_tab(state);
                 rebuild_outline(state, config);
+                refresh_backlinks(state);
+            }
+            Message::OpenDailyNote(day) => return Some(open_daily_note(state, day)),
+            Message::ApplyTemplate(template, action) => {
+                return Some(apply_template(state, &template, action))
             }
             Message::Exec(command) => {
-                let (note_name, note_path) = state
-                    .tabs
-                    .active_note()
-                    .map(|note| (note.name(), note.path().to_string_lossy()))
-                    .unwrap_or_default();
-
                 return command::sync_command(
                     terminal,
                     command,
-                    &state.vault.name,
-                    note_name,
-                    &note_path,
+                    &command_context(state),
+                    command_shell(config),
                 );
             }
 
             Message::Spawn(command) => {
-                let (note_name, note_path) = state
-                    .tabs
-                    .active_note()
-                    .map(|note| (note.name(), note.path().to_string_lossy()))
-                    .unwrap_or_default();
+                return command::spawn_command(
+                    command,
+                    &command_context(state),
+                    command_shell(config),
+                );
+            }
 
-                return command::spawn_command(command, &state.vault.name, note_name, &note_path);
+            Message::Filter(command) => {
+                return Some(filter_note(state, config, &command, FilterOutput::Replace))
+            }
+            Message::Read(command) => {
+                return Some(filter_note(state, config, &command, FilterOutput::Insert))
             }
 
             Message::CopyToClipboard(text) => {
@@ -893,6 +1547,30 @@ impl<'a> App<'a> {
                     state.theme,
                 );
             }
+            Message::SearchModal(message) => {
+                return search_modal::update(message, &state.vault, &mut state.search_modal);
+            }
+            Message::QuickSwitcherModal(message) => {
+                return quick_switcher_modal::update(
+                    message,
+                    &state.vault,
+                    &state.tabs.recent_paths(),
+                    &mut state.quick_switcher_modal,
+                );
+            }
+            Message::TemplateModal(message) => {
+                return template_modal::update(message, &state.vault, &mut state.template_modal);
+            }
+            Message::CommandPalette(message) => {
+                return command_palette::update(message, config, &mut state.command_palette);
+            }
+            Message::CommandLine(message) => {
+                let modified = state
+                    .tabs
+                    .active_editor()
+                    .is_some_and(NoteEditorState::modified);
+                return command_line::update(message, &mut state.command_line, modified);
+            }
             Message::PreviewTheme(theme) => apply_theme(state, theme),
             Message::SaveTheme(name) => {
                 let toast = match config::save_theme(&name) {
@@ -912,9 +1590,39 @@ impl<'a> App<'a> {
             Message::Explorer(message) => {
                 return explorer::update(&message, state.screen_size, &mut state.explorer);
             }
+            // The backlinks pane sits after the outline while it is open.
+            Message::Outline(outline::Message::SwitchPaneNext) if state.backlinks.is_open() => {
+                state.outline.set_active(false);
+                return Some(Message::SetActivePane(ActivePane::Backlinks));
+            }
+            // The tags pane sits after the outline and the backlinks while it is open.
+            Message::Outline(outline::Message::SwitchPaneNext) if state.tags.is_open() => {
+                state.outline.set_active(false);
+                return Some(Message::SetActivePane(ActivePane::Tags));
+            }
             Message::Outline(message) => {
                 return outline::update(&message, &mut state.outline);
             }
+            Message::Backlinks(backlinks::Message::SwitchPaneNext) if state.tags.is_open() => {
+                state.backlinks.set_active(false);
+                return Some(Message::SetActivePane(ActivePane::Tags));
+            }
+            Message::Tags(tags::Message::SwitchPanePrevious) if state.backlinks.is_open() => {
+                state.tags.set_active(false);
+                return Some(Message::SetActivePane(ActivePane::Backlinks));
+            }
+            Message::Tags(message) => {
+                return tags::update(&message, &state.vault, &mut state.tags);
+            }
+            Message::Backlinks(message) => {
+                let note = active_vault_note(state);
+                return backlinks::update(
+                    &message,
+                    &state.vault,
+                    note.as_ref(),
+                    &mut state.backlinks,
+                );
+            }
             Message::NoteEditor(message) => {
                 let size = state.screen_size;
                 if let Some(editor) = state.tabs.active_editor_mut() {
@@ -979,7 +1687,7 @@ impl<'a> App<'a> {
             Visibility::FullWidth => (Constraint::Fill(1), Constraint::Length(0)),
         };
 
-        let [explorer_pane, note, outline] = Layout::horizontal([
+        let [explorer_pane, note, outline, backlinks, tags] = Layout::horizontal([
             left,
             right,
             if state.outline.is_open() {
@@ -987,6 +1695,16 @@ impl<'a> App<'a> {
             } else {
                 Constraint::Length(4)
             },
+            if state.backlinks.is_open() {
+                Constraint::Length(35)
+            } else {
+                Constraint::Length(0)
+            },
+            if state.tags.is_open() {
+                Constraint::Length(35)
+            } else {
+                Constraint::Length(0)
+            },
         ])
         .areas(content);
 
@@ -1003,6 +1721,8 @@ impl<'a> App<'a> {
             }
         }
         Outline.render(outline, buf, &mut state.outline);
+        Backlinks.render(backlinks, buf, &mut state.backlinks);
+        Tags.render(tags, buf, &mut state.tags);
         let border_modal = self.config.symbols.border_modal.into();
         Input::new(border_modal, theme).render(explorer_pane, buf, &mut state.input_modal);
 
@@ -1030,6 +1750,7 @@ impl<'a> App<'a> {
 
         let status_bar = StatusBar::new(&theme);
         status_bar.render(statusbar, buf, &mut status_bar_state);
+        CommandLine::new(&theme).render(statusbar, buf, &mut state.command_line);
 
         self.render_modals(area, buf, state);
         self.render_toasts(area, buf, state);
@@ -1072,6 +1793,30 @@ impl<'a> App<'a> {
             );
         }
 
+        if state.search_modal.is_visible() {
+            let border_modal = self.config.symbols.border_modal.into();
+            SearchModal::new(border_modal, theme).render(area, buf, &mut state.search_modal);
+        }
+
+        if state.quick_switcher_modal.is_visible() {
+            let border_modal = self.config.symbols.border_modal.into();
+            QuickSwitcherModal::new(border_modal, theme).render(
+                area,
+                buf,
+                &mut state.quick_switcher_modal,
+            );
+        }
+
+        if state.template_modal.is_visible() {
+            let border_modal = self.config.symbols.border_modal.into();
+            TemplateModal::new(border_modal, theme).render(area, buf, &mut state.template_modal);
+        }
+
+        if state.command_palette.is_visible() {
+            let border_modal = self.config.symbols.border_modal.into();
+            CommandPalette::new(border_modal, theme).render(area, buf, &mut state.command_palette);
+        }
+
         if state.help_modal.visible {
             let border_modal = self.config.symbols.border_modal.into();
             HelpModal::new(border_modal, theme).render(area, buf, &mut state.help_modal);
@@ -1112,3 +1857,30 @@ impl<'a> StatefulWidget for &App<'a> {
         self.render_main(area, buf, state);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Leader;
+
+    #[test]
+    fn test_note_editor_key_bindings_win_over_global() {
+        let config = config::bundled(true, &Leader::default()).unwrap();
+        let mut pending_keys = Vec::new();
+        let mut press = |c, active| {
+            let key = Keystroke::from(KeyEvent::from(KeyCode::Char(c)));
+            App::handle_pending_keys(key, &config, active, &mut pending_keys)
+        };
+
+        assert_eq!(
+            press('?', ActivePane::NoteEditor),
+            Some(Message::CommandLine(command_line::Message::Open(
+                command_line::Prompt::Search(note_editor::Direction::Backward)
+            )))
+        );
+        assert_eq!(
+            press('?', ActivePane::Explorer),
+            Some(Message::HelpModal(help_modal::Message::Toggle))
+        );
+    }
+}
@@ -0,0 +1,137 @@
+use basalt_core::obsidian::{
+    backlinks::{self, Backlink},
+    Note, Vault,
+};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{ListItem, StatefulWidget},
+};
+
+pub use crate::list_pane::Message;
+use crate::{
+    app::{ActivePane, Message as AppMessage, SelectedNote},
+    config::{Symbols, Theme},
+    list_pane::{self, ListPane, ListPaneState},
+    note_editor,
+};
+
+/// Drives the backlinks pane. The links pointing to the open note are looked up again whenever
+/// the pane opens or the note changes, and selecting one opens the linking note at the link.
+pub fn update<'a>(
+    message: &Message,
+    vault: &Vault,
+    note: Option<&Note>,
+    state: &mut BacklinksState,
+) -> Option<AppMessage<'a>> {
+    if *message != Message::Select {
+        return list_pane::update(message, state, |state| {
+            state.set_items(
+                note.map(|note| backlinks::backlinks(vault, note))
+                    .unwrap_or_default(),
+            );
+            state.select_first();
+        });
+    }
+
+    let backlink = state.selected()?.clone();
+    state.set_active(false);
+    Some(AppMessage::Batch(vec![
+        AppMessage::SelectNote(SelectedNote::from(&backlink.note)),
+        AppMessage::NoteEditor(note_editor::Message::JumpToOffset(backlink.range.start)),
+        AppMessage::SetActivePane(ActivePane::NoteEditor),
+    ]))
+}
+
+/// The links from other notes to the open note.
+pub type BacklinksState = ListPaneState<Backlink>;
+
+#[derive(Default)]
+pub struct Backlinks;
+
+impl Backlinks {
+    /// Shows the linking note and line, followed by the line itself.
+    fn backlink_item<'a>(backlink: &'a Backlink, _: &Symbols, theme: &Theme) -> ListItem<'a> {
+        ListItem::new(vec![
+            Line::from(vec![
+                Span::raw(backlink.note.name()).fg(theme.text).bold(),
+                Span::raw(format!(":{}", backlink.line + 1)).fg(theme.muted),
+            ]),
+            Line::from(Span::raw(backlink.text.as_str()).fg(theme.muted)),
+        ])
+    }
+}
+
+impl StatefulWidget for Backlinks {
+    type State = BacklinksState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        ListPane::new("Backlinks", "No backlinks", Self::backlink_item).render(area, buf, state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use ratatui::widgets::ListState;
+
+    use super::*;
+
+    fn backlink(name: &str, start: usize) -> Backlink {
+        Backlink {
+            note: Note::new_unchecked(name, Path::new(&format!("{name}.md"))),
+            range: start..start + 10,
+            line: 0,
+            text: String::from("[[Target]]"),
+        }
+    }
+
+    #[test]
+    fn test_toggle_looks_up_backlinks_only_when_open() {
+        let vault = Vault::default();
+        let note = Note::new_unchecked("Target", Path::new("Target.md"));
+        let mut state = BacklinksState {
+            items: vec![backlink("Stale", 0)],
+            ..Default::default()
+        };
+
+        update(&Message::Refresh, &vault, Some(&note), &mut state);
+        assert_eq!(state.items().len(), 1);
+
+        update(&Message::Toggle, &vault, Some(&note), &mut state);
+        assert!(state.is_open());
+        assert!(state.items().is_empty());
+    }
+
+    #[test]
+    fn test_select_jumps_to_link() {
+        let mut state = BacklinksState {
+            items: vec![backlink("First", 4), backlink("Second", 12)],
+            open: true,
+            active: true,
+            list_state: ListState::default().with_selected(Some(0)),
+            ..Default::default()
+        };
+
+        update(&Message::Down, &Vault::default(), None, &mut state);
+        update(&Message::Down, &Vault::default(), None, &mut state);
+        assert_eq!(
+            state.selected().map(|link| link.note.name()),
+            Some("Second")
+        );
+
+        let Some(AppMessage::Batch(messages)) =
+            update(&Message::Select, &Vault::default(), None, &mut state)
+        else {
+            panic!("expected a batch of messages");
+        };
+        assert_eq!(
+            messages[1],
+            AppMessage::NoteEditor(note_editor::Message::JumpToOffset(12))
+        );
+        assert!(!state.active);
+    }
+}
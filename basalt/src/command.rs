@@ -6,27 +6,25 @@ use ratatui::{
     DefaultTerminal,
 };
 use serde::{Deserialize, Deserializer};
-use std::{io::stdout, process};
+use std::{
+    io::{self, stdout, Write},
+    process::{self, Stdio},
+    thread,
+};
 use tracing::error;
 
 use crate::{
-    app::{Message, ScrollAmount},
-    debug_log, explorer, help_modal, input, note_editor,
+    app::{DailyNote, Message, ScrollAmount},
+    backlinks, command_line, command_palette, debug_log, explorer, help_modal, input, note_editor,
     note_editor::state::Operator,
     note_editor::Direction,
-    outline, splash_modal, theme_selector_modal, vault_selector_modal,
+    outline, query_modal, quick_switcher_modal, search_modal, shell, splash_modal, tags,
+    template_modal::{self, TemplateAction},
+    theme_selector_modal, vault_selector_modal,
 };
 
-trait ReplaceVar {
-    fn replace_var(&self, variable: &str, content: &str) -> Self;
-}
-
-impl ReplaceVar for String {
-    fn replace_var(&self, variable: &str, content: &str) -> Self {
-        self.replace(variable, content)
-    }
-}
-
+// The command palette commands are named after the component, like every other command.
+#[allow(clippy::enum_variant_names)]
 #[derive(Clone, Debug, PartialEq)]
 pub(crate) enum Command {
     Quit,
@@ -43,6 +41,10 @@ pub(crate) enum Command {
     ExplorerNewUntitledNote,
     ExplorerNewUntitledFolder,
     ExplorerToggleInputRename,
+    ExplorerToggleInputDelete,
+    ExplorerCut,
+    ExplorerCopy,
+    ExplorerPaste,
     ExplorerToggleOutline,
     ExplorerSwitchPaneNext,
     ExplorerSwitchPanePrevious,
@@ -62,6 +64,20 @@ pub(crate) enum Command {
     OutlineSwitchPaneNext,
     OutlineSwitchPanePrevious,
 
+    BacklinksUp,
+    BacklinksDown,
+    BacklinksSelect,
+    BacklinksToggle,
+    BacklinksSwitchPaneNext,
+    BacklinksSwitchPanePrevious,
+
+    TagsUp,
+    TagsDown,
+    TagsSelect,
+    TagsToggle,
+    TagsSwitchPaneNext,
+    TagsSwitchPanePrevious,
+
     HelpModalScrollUpOne,
     HelpModalScrollDownOne,
     HelpModalScrollUpHalfPage,
@@ -77,6 +93,12 @@ pub(crate) enum Command {
     NoteEditorSwitchPanePrevious,
     NoteEditorToggleExplorer,
     NoteEditorToggleOutline,
+    NoteEditorFollowLink,
+    NoteEditorOpenEmbed,
+    NoteEditorFootnoteDefinition,
+    NoteEditorFootnoteReference,
+    NoteEditorToggleProperties,
+    NoteEditorToggleCalloutFold,
     NoteEditorCursorUp,
     NoteEditorCursorDown,
     NoteEditorScrollToTop,
@@ -89,6 +111,10 @@ pub(crate) enum Command {
     TabPrevious,
     TabClose,
 
+    DailyNoteToday,
+    DailyNotePrevious,
+    DailyNoteNext,
+
     NoteEditorExperimentalCursorWordForward,
     NoteEditorExperimentalCursorWordBackward,
     NoteEditorExperimentalToggleView,
@@ -96,6 +122,8 @@ pub(crate) enum Command {
     NoteEditorExperimentalSetReadView,
     NoteEditorExperimentalSave,
     NoteEditorExperimentalExit,
+    NoteEditorExperimentalToggleLivePreview,
+    NoteEditorCommandLine,
     NoteEditorExperimentalCursorLeft,
     NoteEditorExperimentalCursorRight,
     NoteEditorInsertMode,
@@ -121,6 +149,12 @@ pub(crate) enum Command {
     NoteEditorTillBackward,
     NoteEditorRepeatFind,
     NoteEditorRepeatFindReverse,
+    NoteEditorSearchForward,
+    NoteEditorSearchBackward,
+    NoteEditorSearchNext,
+    NoteEditorSearchPrevious,
+    NoteEditorSearchWordForward,
+    NoteEditorSearchWordBackward,
     NoteEditorDelete,
     NoteEditorChange,
     NoteEditorYankOperator,
@@ -154,6 +188,35 @@ pub(crate) enum Command {
     ThemeSelectorModalOpen,
     ThemeSelectorModalToggle,
 
+    SearchModalUp,
+    SearchModalDown,
+    SearchModalClose,
+    SearchModalOpen,
+    SearchModalToggle,
+    SearchModalEditMode,
+
+    QuickSwitcherModalUp,
+    QuickSwitcherModalDown,
+    QuickSwitcherModalClose,
+    QuickSwitcherModalOpen,
+    QuickSwitcherModalToggle,
+    QuickSwitcherModalEditMode,
+
+    TemplateModalUp,
+    TemplateModalDown,
+    TemplateModalClose,
+    TemplateModalApply,
+    TemplateModalNewNote,
+    TemplateModalInsert,
+    TemplateModalEditMode,
+
+    CommandPaletteUp,
+    CommandPaletteDown,
+    CommandPaletteClose,
+    CommandPaletteRun,
+    CommandPaletteToggle,
+    CommandPaletteEditMode,
+
     InputModalWordForward,
     InputModalWordBackward,
     InputModalLeft,
@@ -164,151 +227,392 @@ pub(crate) enum Command {
 
     Exec(String),
     Spawn(String),
+    Filter(String),
+    Read(String),
 }
 
+/// Every built-in command by the name it is bound with in the config.
+pub(crate) const COMMANDS: &[(&str, Command)] = &[
+    ("quit", Command::Quit),
+    ("splash_up", Command::SplashUp),
+    ("splash_down", Command::SplashDown),
+    ("splash_open", Command::SplashOpen),
+    ("explorer_up", Command::ExplorerUp),
+    ("explorer_down", Command::ExplorerDown),
+    ("explorer_open", Command::ExplorerOpen),
+    ("explorer_sort", Command::ExplorerSort),
+    ("explorer_toggle", Command::ExplorerToggle),
+    (
+        "explorer_new_untitled_note",
+        Command::ExplorerNewUntitledNote,
+    ),
+    (
+        "explorer_new_untitled_folder",
+        Command::ExplorerNewUntitledFolder,
+    ),
+    ("explorer_toggle_outline", Command::ExplorerToggleOutline),
+    (
+        "explorer_toggle_input_rename",
+        Command::ExplorerToggleInputRename,
+    ),
+    (
+        "explorer_toggle_input_delete",
+        Command::ExplorerToggleInputDelete,
+    ),
+    ("explorer_cut", Command::ExplorerCut),
+    ("explorer_copy", Command::ExplorerCopy),
+    ("explorer_paste", Command::ExplorerPaste),
+    ("explorer_switch_pane_next", Command::ExplorerSwitchPaneNext),
+    ("explorer_hide_pane", Command::ExplorerHidePane),
+    ("explorer_expand_pane", Command::ExplorerExpandPane),
+    (
+        "explorer_switch_pane_previous",
+        Command::ExplorerSwitchPanePrevious,
+    ),
+    ("explorer_scroll_up_one", Command::ExplorerScrollUpOne),
+    ("explorer_scroll_down_one", Command::ExplorerScrollDownOne),
+    (
+        "explorer_scroll_up_half_page",
+        Command::ExplorerScrollUpHalfPage,
+    ),
+    (
+        "explorer_scroll_down_half_page",
+        Command::ExplorerScrollDownHalfPage,
+    ),
+    ("input_modal_word_forward", Command::InputModalWordForward),
+    ("input_modal_word_backward", Command::InputModalWordBackward),
+    ("input_modal_left", Command::InputModalLeft),
+    ("input_modal_right", Command::InputModalRight),
+    ("input_modal_cancel", Command::InputModalCancel),
+    ("input_modal_accept", Command::InputModalAccept),
+    ("input_modal_edit_mode", Command::InputModalEditMode),
+    ("outline_up", Command::OutlineUp),
+    ("outline_down", Command::OutlineDown),
+    ("outline_select", Command::OutlineSelect),
+    ("outline_expand", Command::OutlineExpand),
+    ("outline_toggle", Command::OutlineToggle),
+    ("outline_toggle_explorer", Command::OutlineToggleExplorer),
+    ("outline_switch_pane_next", Command::OutlineSwitchPaneNext),
+    (
+        "outline_switch_pane_previous",
+        Command::OutlineSwitchPanePrevious,
+    ),
+    ("backlinks_up", Command::BacklinksUp),
+    ("backlinks_down", Command::BacklinksDown),
+    ("backlinks_select", Command::BacklinksSelect),
+    ("backlinks_toggle", Command::BacklinksToggle),
+    (
+        "backlinks_switch_pane_next",
+        Command::BacklinksSwitchPaneNext,
+    ),
+    (
+        "backlinks_switch_pane_previous",
+        Command::BacklinksSwitchPanePrevious,
+    ),
+    ("tags_up", Command::TagsUp),
+    ("tags_down", Command::TagsDown),
+    ("tags_select", Command::TagsSelect),
+    ("tags_toggle", Command::TagsToggle),
+    ("tags_switch_pane_next", Command::TagsSwitchPaneNext),
+    ("tags_switch_pane_previous", Command::TagsSwitchPanePrevious),
+    ("help_modal_scroll_up_one", Command::HelpModalScrollUpOne),
+    (
+        "help_modal_scroll_down_one",
+        Command::HelpModalScrollDownOne,
+    ),
+    (
+        "help_modal_scroll_up_half_page",
+        Command::HelpModalScrollUpHalfPage,
+    ),
+    (
+        "help_modal_scroll_down_half_page",
+        Command::HelpModalScrollDownHalfPage,
+    ),
+    ("help_modal_toggle", Command::HelpModalToggle),
+    ("help_modal_close", Command::HelpModalClose),
+    ("note_editor_scroll_up_one", Command::NoteEditorScrollUpOne),
+    (
+        "note_editor_scroll_down_one",
+        Command::NoteEditorScrollDownOne,
+    ),
+    (
+        "note_editor_scroll_up_half_page",
+        Command::NoteEditorScrollUpHalfPage,
+    ),
+    (
+        "note_editor_scroll_down_half_page",
+        Command::NoteEditorScrollDownHalfPage,
+    ),
+    (
+        "note_editor_switch_pane_next",
+        Command::NoteEditorSwitchPaneNext,
+    ),
+    (
+        "note_editor_switch_pane_previous",
+        Command::NoteEditorSwitchPanePrevious,
+    ),
+    (
+        "note_editor_toggle_explorer",
+        Command::NoteEditorToggleExplorer,
+    ),
+    (
+        "note_editor_toggle_outline",
+        Command::NoteEditorToggleOutline,
+    ),
+    ("note_editor_follow_link", Command::NoteEditorFollowLink),
+    ("note_editor_open_embed", Command::NoteEditorOpenEmbed),
+    (
+        "note_editor_footnote_definition",
+        Command::NoteEditorFootnoteDefinition,
+    ),
+    (
+        "note_editor_footnote_reference",
+        Command::NoteEditorFootnoteReference,
+    ),
+    (
+        "note_editor_toggle_properties",
+        Command::NoteEditorToggleProperties,
+    ),
+    (
+        "note_editor_toggle_callout_fold",
+        Command::NoteEditorToggleCalloutFold,
+    ),
+    ("note_editor_cursor_up", Command::NoteEditorCursorUp),
+    ("note_editor_cursor_down", Command::NoteEditorCursorDown),
+    ("note_editor_scroll_to_top", Command::NoteEditorScrollToTop),
+    (
+        "note_editor_scroll_to_bottom",
+        Command::NoteEditorScrollToBottom,
+    ),
+    ("explorer_scroll_to_top", Command::ExplorerScrollToTop),
+    ("explorer_scroll_to_bottom", Command::ExplorerScrollToBottom),
+    ("tab_next", Command::TabNext),
+    ("tab_previous", Command::TabPrevious),
+    ("tab_close", Command::TabClose),
+    ("daily_note_today", Command::DailyNoteToday),
+    ("daily_note_previous", Command::DailyNotePrevious),
+    ("daily_note_next", Command::DailyNoteNext),
+    (
+        "note_editor_experimental_cursor_word_forward",
+        Command::NoteEditorExperimentalCursorWordForward,
+    ),
+    (
+        "note_editor_experimental_cursor_word_backward",
+        Command::NoteEditorExperimentalCursorWordBackward,
+    ),
+    (
+        "note_editor_experimental_set_edit_view",
+        Command::NoteEditorExperimentalSetEditView,
+    ),
+    (
+        "note_editor_experimental_toggle_view",
+        Command::NoteEditorExperimentalToggleView,
+    ),
+    (
+        "note_editor_experimental_set_read_view",
+        Command::NoteEditorExperimentalSetReadView,
+    ),
+    (
+        "note_editor_experimental_save",
+        Command::NoteEditorExperimentalSave,
+    ),
+    (
+        "note_editor_experimental_exit",
+        Command::NoteEditorExperimentalExit,
+    ),
+    (
+        "note_editor_experimental_toggle_live_preview",
+        Command::NoteEditorExperimentalToggleLivePreview,
+    ),
+    ("note_editor_command_line", Command::NoteEditorCommandLine),
+    (
+        "note_editor_experimental_cursor_left",
+        Command::NoteEditorExperimentalCursorLeft,
+    ),
+    (
+        "note_editor_experimental_cursor_right",
+        Command::NoteEditorExperimentalCursorRight,
+    ),
+    ("note_editor_insert_mode", Command::NoteEditorInsertMode),
+    ("note_editor_append", Command::NoteEditorAppend),
+    ("note_editor_replace_char", Command::NoteEditorReplaceChar),
+    ("note_editor_visual_mode", Command::NoteEditorVisualMode),
+    (
+        "note_editor_visual_line_mode",
+        Command::NoteEditorVisualLineMode,
+    ),
+    ("note_editor_delete", Command::NoteEditorDelete),
+    ("note_editor_change", Command::NoteEditorChange),
+    ("note_editor_yank", Command::NoteEditorYankOperator),
+    (
+        "note_editor_delete_under_cursor",
+        Command::NoteEditorDeleteUnderCursor,
+    ),
+    (
+        "note_editor_delete_to_line_end",
+        Command::NoteEditorDeleteToLineEnd,
+    ),
+    (
+        "note_editor_change_to_line_end",
+        Command::NoteEditorChangeToLineEnd,
+    ),
+    (
+        "note_editor_substitute_char",
+        Command::NoteEditorSubstituteChar,
+    ),
+    ("note_editor_paste_after", Command::NoteEditorPasteAfter),
+    ("note_editor_paste_before", Command::NoteEditorPasteBefore),
+    ("note_editor_undo", Command::NoteEditorUndo),
+    ("note_editor_redo", Command::NoteEditorRedo),
+    (
+        "note_editor_cursor_line_start",
+        Command::NoteEditorCursorLineStart,
+    ),
+    (
+        "note_editor_cursor_line_end",
+        Command::NoteEditorCursorLineEnd,
+    ),
+    (
+        "note_editor_cursor_first_non_blank",
+        Command::NoteEditorCursorFirstNonblank,
+    ),
+    (
+        "note_editor_cursor_word_end",
+        Command::NoteEditorCursorWordEnd,
+    ),
+    (
+        "note_editor_cursor_word_forward_big",
+        Command::NoteEditorCursorWordForwardBig,
+    ),
+    (
+        "note_editor_cursor_word_backward_big",
+        Command::NoteEditorCursorWordBackwardBig,
+    ),
+    (
+        "note_editor_cursor_word_end_big",
+        Command::NoteEditorCursorWordEndBig,
+    ),
+    (
+        "note_editor_paragraph_forward",
+        Command::NoteEditorParagraphForward,
+    ),
+    (
+        "note_editor_paragraph_backward",
+        Command::NoteEditorParagraphBackward,
+    ),
+    ("note_editor_matching_pair", Command::NoteEditorMatchingPair),
+    (
+        "note_editor_cursor_doc_start",
+        Command::NoteEditorCursorDocStart,
+    ),
+    (
+        "note_editor_cursor_doc_end",
+        Command::NoteEditorCursorDocEnd,
+    ),
+    ("note_editor_find_forward", Command::NoteEditorFindForward),
+    ("note_editor_find_backward", Command::NoteEditorFindBackward),
+    ("note_editor_till_forward", Command::NoteEditorTillForward),
+    ("note_editor_till_backward", Command::NoteEditorTillBackward),
+    ("note_editor_repeat_find", Command::NoteEditorRepeatFind),
+    (
+        "note_editor_repeat_find_reverse",
+        Command::NoteEditorRepeatFindReverse,
+    ),
+    (
+        "note_editor_search_forward",
+        Command::NoteEditorSearchForward,
+    ),
+    (
+        "note_editor_search_backward",
+        Command::NoteEditorSearchBackward,
+    ),
+    ("note_editor_search_next", Command::NoteEditorSearchNext),
+    (
+        "note_editor_search_previous",
+        Command::NoteEditorSearchPrevious,
+    ),
+    (
+        "note_editor_search_word_forward",
+        Command::NoteEditorSearchWordForward,
+    ),
+    (
+        "note_editor_search_word_backward",
+        Command::NoteEditorSearchWordBackward,
+    ),
+    ("vault_selector_modal_up", Command::VaultSelectorModalUp),
+    ("vault_selector_modal_down", Command::VaultSelectorModalDown),
+    (
+        "vault_selector_modal_close",
+        Command::VaultSelectorModalClose,
+    ),
+    ("vault_selector_modal_open", Command::VaultSelectorModalOpen),
+    (
+        "vault_selector_modal_toggle",
+        Command::VaultSelectorModalToggle,
+    ),
+    ("debug_log_toggle", Command::DebugLogToggle),
+    ("debug_log_close", Command::DebugLogClose),
+    ("debug_log_clear", Command::DebugLogClear),
+    ("debug_log_cycle_level", Command::DebugLogCycleLevel),
+    ("debug_log_scroll_up_one", Command::DebugLogScrollUpOne),
+    ("debug_log_scroll_down_one", Command::DebugLogScrollDownOne),
+    (
+        "debug_log_scroll_up_half_page",
+        Command::DebugLogScrollUpHalfPage,
+    ),
+    (
+        "debug_log_scroll_down_half_page",
+        Command::DebugLogScrollDownHalfPage,
+    ),
+    ("theme_selector_modal_up", Command::ThemeSelectorModalUp),
+    ("theme_selector_modal_down", Command::ThemeSelectorModalDown),
+    (
+        "theme_selector_modal_close",
+        Command::ThemeSelectorModalClose,
+    ),
+    ("theme_selector_modal_open", Command::ThemeSelectorModalOpen),
+    (
+        "theme_selector_modal_toggle",
+        Command::ThemeSelectorModalToggle,
+    ),
+    ("search_modal_up", Command::SearchModalUp),
+    ("search_modal_down", Command::SearchModalDown),
+    ("search_modal_close", Command::SearchModalClose),
+    ("search_modal_open", Command::SearchModalOpen),
+    ("search_modal_toggle", Command::SearchModalToggle),
+    ("search_modal_edit_mode", Command::SearchModalEditMode),
+    ("quick_switcher_modal_up", Command::QuickSwitcherModalUp),
+    ("quick_switcher_modal_down", Command::QuickSwitcherModalDown),
+    (
+        "quick_switcher_modal_close",
+        Command::QuickSwitcherModalClose,
+    ),
+    ("quick_switcher_modal_open", Command::QuickSwitcherModalOpen),
+    (
+        "quick_switcher_modal_toggle",
+        Command::QuickSwitcherModalToggle,
+    ),
+    (
+        "quick_switcher_modal_edit_mode",
+        Command::QuickSwitcherModalEditMode,
+    ),
+    ("template_modal_up", Command::TemplateModalUp),
+    ("template_modal_down", Command::TemplateModalDown),
+    ("template_modal_close", Command::TemplateModalClose),
+    ("template_modal_apply", Command::TemplateModalApply),
+    ("template_modal_new_note", Command::TemplateModalNewNote),
+    ("template_modal_insert", Command::TemplateModalInsert),
+    ("template_modal_edit_mode", Command::TemplateModalEditMode),
+    ("command_palette_up", Command::CommandPaletteUp),
+    ("command_palette_down", Command::CommandPaletteDown),
+    ("command_palette_close", Command::CommandPaletteClose),
+    ("command_palette_run", Command::CommandPaletteRun),
+    ("command_palette_toggle", Command::CommandPaletteToggle),
+    ("command_palette_edit_mode", Command::CommandPaletteEditMode),
+];
+
 fn str_to_command(s: &str) -> Option<Command> {
     match s {
-        "quit" => Some(Command::Quit),
-
-        "splash_up" => Some(Command::SplashUp),
-        "splash_down" => Some(Command::SplashDown),
-        "splash_open" => Some(Command::SplashOpen),
-
-        "explorer_up" => Some(Command::ExplorerUp),
-        "explorer_down" => Some(Command::ExplorerDown),
-        "explorer_open" => Some(Command::ExplorerOpen),
-        "explorer_sort" => Some(Command::ExplorerSort),
-        "explorer_toggle" => Some(Command::ExplorerToggle),
-        "explorer_new_untitled_note" => Some(Command::ExplorerNewUntitledNote),
-        "explorer_new_untitled_folder" => Some(Command::ExplorerNewUntitledFolder),
-        "explorer_toggle_outline" => Some(Command::ExplorerToggleOutline),
-        "explorer_toggle_input_rename" => Some(Command::ExplorerToggleInputRename),
-        "explorer_switch_pane_next" => Some(Command::ExplorerSwitchPaneNext),
-        "explorer_hide_pane" => Some(Command::ExplorerHidePane),
-        "explorer_expand_pane" => Some(Command::ExplorerExpandPane),
-        "explorer_switch_pane_previous" => Some(Command::ExplorerSwitchPanePrevious),
-        "explorer_scroll_up_one" => Some(Command::ExplorerScrollUpOne),
-        "explorer_scroll_down_one" => Some(Command::ExplorerScrollDownOne),
-        "explorer_scroll_up_half_page" => Some(Command::ExplorerScrollUpHalfPage),
-        "explorer_scroll_down_half_page" => Some(Command::ExplorerScrollDownHalfPage),
-
-        "input_modal_word_forward" => Some(Command::InputModalWordForward),
-        "input_modal_word_backward" => Some(Command::InputModalWordBackward),
-        "input_modal_left" => Some(Command::InputModalLeft),
-        "input_modal_right" => Some(Command::InputModalRight),
-        "input_modal_cancel" => Some(Command::InputModalCancel),
-        "input_modal_accept" => Some(Command::InputModalAccept),
-        "input_modal_edit_mode" => Some(Command::InputModalEditMode),
-
-        "outline_up" => Some(Command::OutlineUp),
-        "outline_down" => Some(Command::OutlineDown),
-        "outline_select" => Some(Command::OutlineSelect),
-        "outline_expand" => Some(Command::OutlineExpand),
-        "outline_toggle" => Some(Command::OutlineToggle),
-        "outline_toggle_explorer" => Some(Command::OutlineToggleExplorer),
-        "outline_switch_pane_next" => Some(Command::OutlineSwitchPaneNext),
-        "outline_switch_pane_previous" => Some(Command::OutlineSwitchPanePrevious),
-
-        "help_modal_scroll_up_one" => Some(Command::HelpModalScrollUpOne),
-        "help_modal_scroll_down_one" => Some(Command::HelpModalScrollDownOne),
-        "help_modal_scroll_up_half_page" => Some(Command::HelpModalScrollUpHalfPage),
-        "help_modal_scroll_down_half_page" => Some(Command::HelpModalScrollDownHalfPage),
-        "help_modal_toggle" => Some(Command::HelpModalToggle),
-        "help_modal_close" => Some(Command::HelpModalClose),
-
-        "note_editor_scroll_up_one" => Some(Command::NoteEditorScrollUpOne),
-        "note_editor_scroll_down_one" => Some(Command::NoteEditorScrollDownOne),
-        "note_editor_scroll_up_half_page" => Some(Command::NoteEditorScrollUpHalfPage),
-        "note_editor_scroll_down_half_page" => Some(Command::NoteEditorScrollDownHalfPage),
-        "note_editor_switch_pane_next" => Some(Command::NoteEditorSwitchPaneNext),
-        "note_editor_switch_pane_previous" => Some(Command::NoteEditorSwitchPanePrevious),
-        "note_editor_toggle_explorer" => Some(Command::NoteEditorToggleExplorer),
-        "note_editor_toggle_outline" => Some(Command::NoteEditorToggleOutline),
-        "note_editor_cursor_up" => Some(Command::NoteEditorCursorUp),
-        "note_editor_cursor_down" => Some(Command::NoteEditorCursorDown),
-        "note_editor_scroll_to_top" => Some(Command::NoteEditorScrollToTop),
-        "note_editor_scroll_to_bottom" => Some(Command::NoteEditorScrollToBottom),
-
-        "explorer_scroll_to_top" => Some(Command::ExplorerScrollToTop),
-        "explorer_scroll_to_bottom" => Some(Command::ExplorerScrollToBottom),
-
-        "tab_next" => Some(Command::TabNext),
-        "tab_previous" => Some(Command::TabPrevious),
-        "tab_close" => Some(Command::TabClose),
-
-        "note_editor_experimental_cursor_word_forward" => {
-            Some(Command::NoteEditorExperimentalCursorWordForward)
-        }
-        "note_editor_experimental_cursor_word_backward" => {
-            Some(Command::NoteEditorExperimentalCursorWordBackward)
-        }
-        "note_editor_experimental_set_edit_view" => {
-            Some(Command::NoteEditorExperimentalSetEditView)
-        }
-        "note_editor_experimental_toggle_view" => Some(Command::NoteEditorExperimentalToggleView),
-        "note_editor_experimental_set_read_view" => {
-            Some(Command::NoteEditorExperimentalSetReadView)
-        }
-        "note_editor_experimental_save" => Some(Command::NoteEditorExperimentalSave),
-        "note_editor_experimental_exit" => Some(Command::NoteEditorExperimentalExit),
-        "note_editor_experimental_cursor_left" => Some(Command::NoteEditorExperimentalCursorLeft),
-        "note_editor_experimental_cursor_right" => Some(Command::NoteEditorExperimentalCursorRight),
-        "note_editor_insert_mode" => Some(Command::NoteEditorInsertMode),
-        "note_editor_append" => Some(Command::NoteEditorAppend),
-        "note_editor_replace_char" => Some(Command::NoteEditorReplaceChar),
-        "note_editor_visual_mode" => Some(Command::NoteEditorVisualMode),
-        "note_editor_visual_line_mode" => Some(Command::NoteEditorVisualLineMode),
-        "note_editor_delete" => Some(Command::NoteEditorDelete),
-        "note_editor_change" => Some(Command::NoteEditorChange),
-        "note_editor_yank" => Some(Command::NoteEditorYankOperator),
-        "note_editor_delete_under_cursor" => Some(Command::NoteEditorDeleteUnderCursor),
-        "note_editor_delete_to_line_end" => Some(Command::NoteEditorDeleteToLineEnd),
-        "note_editor_change_to_line_end" => Some(Command::NoteEditorChangeToLineEnd),
-        "note_editor_substitute_char" => Some(Command::NoteEditorSubstituteChar),
-        "note_editor_paste_after" => Some(Command::NoteEditorPasteAfter),
-        "note_editor_paste_before" => Some(Command::NoteEditorPasteBefore),
-        "note_editor_undo" => Some(Command::NoteEditorUndo),
-        "note_editor_redo" => Some(Command::NoteEditorRedo),
-        "note_editor_cursor_line_start" => Some(Command::NoteEditorCursorLineStart),
-        "note_editor_cursor_line_end" => Some(Command::NoteEditorCursorLineEnd),
-        "note_editor_cursor_first_non_blank" => Some(Command::NoteEditorCursorFirstNonblank),
-        "note_editor_cursor_word_end" => Some(Command::NoteEditorCursorWordEnd),
-        "note_editor_cursor_word_forward_big" => Some(Command::NoteEditorCursorWordForwardBig),
-        "note_editor_cursor_word_backward_big" => Some(Command::NoteEditorCursorWordBackwardBig),
-        "note_editor_cursor_word_end_big" => Some(Command::NoteEditorCursorWordEndBig),
-        "note_editor_paragraph_forward" => Some(Command::NoteEditorParagraphForward),
-        "note_editor_paragraph_backward" => Some(Command::NoteEditorParagraphBackward),
-        "note_editor_matching_pair" => Some(Command::NoteEditorMatchingPair),
-        "note_editor_cursor_doc_start" => Some(Command::NoteEditorCursorDocStart),
-        "note_editor_cursor_doc_end" => Some(Command::NoteEditorCursorDocEnd),
-        "note_editor_find_forward" => Some(Command::NoteEditorFindForward),
-        "note_editor_find_backward" => Some(Command::NoteEditorFindBackward),
-        "note_editor_till_forward" => Some(Command::NoteEditorTillForward),
-        "note_editor_till_backward" => Some(Command::NoteEditorTillBackward),
-        "note_editor_repeat_find" => Some(Command::NoteEditorRepeatFind),
-        "note_editor_repeat_find_reverse" => Some(Command::NoteEditorRepeatFindReverse),
-
-        "vault_selector_modal_up" => Some(Command::VaultSelectorModalUp),
-        "vault_selector_modal_down" => Some(Command::VaultSelectorModalDown),
-        "vault_selector_modal_close" => Some(Command::VaultSelectorModalClose),
-        "vault_selector_modal_open" => Some(Command::VaultSelectorModalOpen),
-        "vault_selector_modal_toggle" => Some(Command::VaultSelectorModalToggle),
-
-        "debug_log_toggle" => Some(Command::DebugLogToggle),
-        "debug_log_close" => Some(Command::DebugLogClose),
-        "debug_log_clear" => Some(Command::DebugLogClear),
-        "debug_log_cycle_level" => Some(Command::DebugLogCycleLevel),
-        "debug_log_scroll_up_one" => Some(Command::DebugLogScrollUpOne),
-        "debug_log_scroll_down_one" => Some(Command::DebugLogScrollDownOne),
-        "debug_log_scroll_up_half_page" => Some(Command::DebugLogScrollUpHalfPage),
-        "debug_log_scroll_down_half_page" => Some(Command::DebugLogScrollDownHalfPage),
-
-        "theme_selector_modal_up" => Some(Command::ThemeSelectorModalUp),
-        "theme_selector_modal_down" => Some(Command::ThemeSelectorModalDown),
-        "theme_selector_modal_close" => Some(Command::ThemeSelectorModalClose),
-        "theme_selector_modal_open" => Some(Command::ThemeSelectorModalOpen),
-        "theme_selector_modal_toggle" => Some(Command::ThemeSelectorModalToggle),
-
         // TODO: Remove deprecations in the next major version
         // Deprecated
         "note_editor_experimental_set_edit_mode" => {
@@ -320,7 +624,10 @@ fn str_to_command(s: &str) -> Option<Command> {
         }
         // Deprecated
         "note_editor_experimental_exit_mode" => Some(Command::NoteEditorExperimentalExit),
-        _ => None,
+        _ => COMMANDS
+            .iter()
+            .find(|(name, _)| *name == s)
+            .map(|(_, command)| command.clone()),
     }
 }
 
@@ -337,6 +644,12 @@ impl<'de> Deserialize<'de> for Command {
             .or(s
                 .strip_prefix("spawn:")
                 .map(|command| Command::Spawn(command.to_string())))
+            .or(s
+                .strip_prefix("filter:")
+                .map(|command| Command::Filter(command.to_string())))
+            .or(s
+                .strip_prefix("read:")
+                .map(|command| Command::Read(command.to_string())))
         {
             return Ok(command);
         }
@@ -365,6 +678,12 @@ impl From<Command> for Message<'_> {
             Command::ExplorerToggleInputRename => {
                 Message::Explorer(explorer::Message::ToggleInputRename)
             }
+            Command::ExplorerToggleInputDelete => {
+                Message::Explorer(explorer::Message::ToggleInputDelete)
+            }
+            Command::ExplorerCut => Message::Explorer(explorer::Message::Cut),
+            Command::ExplorerCopy => Message::Explorer(explorer::Message::Copy),
+            Command::ExplorerPaste => Message::Explorer(explorer::Message::Paste),
             Command::ExplorerHidePane => Message::Explorer(explorer::Message::HidePane),
             Command::ExplorerExpandPane => Message::Explorer(explorer::Message::ExpandPane),
             Command::ExplorerSwitchPaneNext => Message::Explorer(explorer::Message::SwitchPaneNext),
@@ -405,6 +724,24 @@ impl From<Command> for Message<'_> {
                 Message::Outline(outline::Message::SwitchPanePrevious)
             }
 
+            Command::BacklinksUp => Message::Backlinks(backlinks::Message::Up),
+            Command::BacklinksDown => Message::Backlinks(backlinks::Message::Down),
+            Command::BacklinksSelect => Message::Backlinks(backlinks::Message::Select),
+            Command::BacklinksToggle => Message::Backlinks(backlinks::Message::Toggle),
+            Command::BacklinksSwitchPaneNext => {
+                Message::Backlinks(backlinks::Message::SwitchPaneNext)
+            }
+            Command::BacklinksSwitchPanePrevious => {
+                Message::Backlinks(backlinks::Message::SwitchPanePrevious)
+            }
+
+            Command::TagsUp => Message::Tags(tags::Message::Up),
+            Command::TagsDown => Message::Tags(tags::Message::Down),
+            Command::TagsSelect => Message::Tags(tags::Message::Select),
+            Command::TagsToggle => Message::Tags(tags::Message::Toggle),
+            Command::TagsSwitchPaneNext => Message::Tags(tags::Message::SwitchPaneNext),
+            Command::TagsSwitchPanePrevious => Message::Tags(tags::Message::SwitchPanePrevious),
+
             Command::HelpModalScrollUpOne => {
                 Message::HelpModal(help_modal::Message::ScrollUp(ScrollAmount::One))
             }
@@ -452,12 +789,29 @@ impl From<Command> for Message<'_> {
             Command::TabNext => Message::TabNext,
             Command::TabPrevious => Message::TabPrevious,
             Command::TabClose => Message::CloseTab,
+            Command::DailyNoteToday => Message::OpenDailyNote(DailyNote::Today),
+            Command::DailyNotePrevious => Message::OpenDailyNote(DailyNote::Previous),
+            Command::DailyNoteNext => Message::OpenDailyNote(DailyNote::Next),
             Command::NoteEditorToggleExplorer => {
                 Message::NoteEditor(note_editor::Message::ToggleExplorer)
             }
             Command::NoteEditorToggleOutline => {
                 Message::NoteEditor(note_editor::Message::ToggleOutline)
             }
+            Command::NoteEditorFollowLink => Message::NoteEditor(note_editor::Message::FollowLink),
+            Command::NoteEditorOpenEmbed => Message::NoteEditor(note_editor::Message::OpenEmbed),
+            Command::NoteEditorFootnoteDefinition => {
+                Message::NoteEditor(note_editor::Message::JumpToFootnoteDefinition)
+            }
+            Command::NoteEditorFootnoteReference => {
+                Message::NoteEditor(note_editor::Message::JumpToFootnoteReference)
+            }
+            Command::NoteEditorToggleProperties => {
+                Message::NoteEditor(note_editor::Message::ToggleProperties)
+            }
+            Command::NoteEditorToggleCalloutFold => {
+                Message::NoteEditor(note_editor::Message::ToggleCalloutFold)
+            }
 
             // Experimental
             Command::NoteEditorExperimentalToggleView => {
@@ -471,6 +825,12 @@ impl From<Command> for Message<'_> {
             }
             Command::NoteEditorExperimentalSave => Message::NoteEditor(note_editor::Message::Save),
             Command::NoteEditorExperimentalExit => Message::NoteEditor(note_editor::Message::Exit),
+            Command::NoteEditorExperimentalToggleLivePreview => {
+                Message::NoteEditor(note_editor::Message::ToggleLivePreview)
+            }
+            Command::NoteEditorCommandLine => {
+                Message::CommandLine(command_line::Message::Open(command_line::Prompt::Command))
+            }
             Command::NoteEditorExperimentalCursorWordForward => {
                 Message::NoteEditor(note_editor::Message::CursorWordForward)
             }
@@ -581,6 +941,24 @@ impl From<Command> for Message<'_> {
             Command::NoteEditorRepeatFindReverse => {
                 Message::NoteEditor(note_editor::Message::RepeatFind { reverse: true })
             }
+            Command::NoteEditorSearchForward => Message::CommandLine(command_line::Message::Open(
+                command_line::Prompt::Search(Direction::Forward),
+            )),
+            Command::NoteEditorSearchBackward => Message::CommandLine(command_line::Message::Open(
+                command_line::Prompt::Search(Direction::Backward),
+            )),
+            Command::NoteEditorSearchNext => {
+                Message::NoteEditor(note_editor::Message::SearchNext { reverse: false })
+            }
+            Command::NoteEditorSearchPrevious => {
+                Message::NoteEditor(note_editor::Message::SearchNext { reverse: true })
+            }
+            Command::NoteEditorSearchWordForward => {
+                Message::NoteEditor(note_editor::Message::SearchWord(Direction::Forward))
+            }
+            Command::NoteEditorSearchWordBackward => {
+                Message::NoteEditor(note_editor::Message::SearchWord(Direction::Backward))
+            }
 
             Command::VaultSelectorModalClose => {
                 Message::VaultSelectorModal(vault_selector_modal::Message::Close)
@@ -630,39 +1008,166 @@ impl From<Command> for Message<'_> {
                 Message::ThemeSelectorModal(theme_selector_modal::Message::Select)
             }
 
+            Command::SearchModalUp => Message::SearchModal(search_modal::Message::Up),
+            Command::SearchModalDown => Message::SearchModal(search_modal::Message::Down),
+            Command::SearchModalClose => Message::SearchModal(search_modal::Message::Close),
+            Command::SearchModalOpen => Message::SearchModal(search_modal::Message::Select),
+            Command::SearchModalToggle => Message::SearchModal(search_modal::Message::Toggle),
+            Command::SearchModalEditMode => Message::SearchModal(search_modal::Message::EditMode),
+
+            Command::QuickSwitcherModalUp => {
+                Message::QuickSwitcherModal(quick_switcher_modal::Message::Up)
+            }
+            Command::QuickSwitcherModalDown => {
+                Message::QuickSwitcherModal(quick_switcher_modal::Message::Down)
+            }
+            Command::QuickSwitcherModalClose => {
+                Message::QuickSwitcherModal(quick_switcher_modal::Message::Close)
+            }
+            Command::QuickSwitcherModalOpen => {
+                Message::QuickSwitcherModal(quick_switcher_modal::Message::Select)
+            }
+            Command::QuickSwitcherModalToggle => {
+                Message::QuickSwitcherModal(quick_switcher_modal::Message::Toggle)
+            }
+            Command::QuickSwitcherModalEditMode => {
+                Message::QuickSwitcherModal(quick_switcher_modal::Message::EditMode)
+            }
+
+            Command::TemplateModalUp => {
+                Message::TemplateModal(template_modal::Message::Modal(query_modal::Message::Up))
+            }
+            Command::TemplateModalDown => {
+                Message::TemplateModal(template_modal::Message::Modal(query_modal::Message::Down))
+            }
+            Command::TemplateModalClose => {
+                Message::TemplateModal(template_modal::Message::Modal(query_modal::Message::Close))
+            }
+            Command::TemplateModalApply => {
+                Message::TemplateModal(template_modal::Message::Modal(query_modal::Message::Select))
+            }
+            Command::TemplateModalNewNote => {
+                Message::TemplateModal(template_modal::Message::Open(TemplateAction::NewNote))
+            }
+            Command::TemplateModalInsert => {
+                Message::TemplateModal(template_modal::Message::Open(TemplateAction::Insert))
+            }
+            Command::TemplateModalEditMode => Message::TemplateModal(
+                template_modal::Message::Modal(query_modal::Message::EditMode),
+            ),
+
+            Command::CommandPaletteUp => Message::CommandPalette(command_palette::Message::Up),
+            Command::CommandPaletteDown => Message::CommandPalette(command_palette::Message::Down),
+            Command::CommandPaletteClose => {
+                Message::CommandPalette(command_palette::Message::Close)
+            }
+            Command::CommandPaletteRun => Message::CommandPalette(command_palette::Message::Select),
+            Command::CommandPaletteToggle => {
+                Message::CommandPalette(command_palette::Message::Toggle)
+            }
+            Command::CommandPaletteEditMode => {
+                Message::CommandPalette(command_palette::Message::EditMode)
+            }
+
             Command::Exec(command) => Message::Exec(command),
             Command::Spawn(command) => Message::Spawn(command),
+            Command::Filter(command) => Message::Filter(command),
+            Command::Read(command) => Message::Read(command),
         }
     }
 }
 
-pub fn run_command<'a>(
-    command: String,
-    vault_name: &str,
-    note_name: &str,
-    note_path: &str,
-    mut callback: impl FnMut(&str, &[&str]) -> Option<Message<'a>>,
-) -> Option<Message<'a>> {
-    let expanded = command
-        .replace_var("%vault", vault_name)
-        // Order matters, otherwise all mentions of %note_path would be replaced with %note value
-        .replace_var("%note_path", note_path)
-        .replace_var("%note", note_name);
-
-    let args = expanded.split_whitespace().collect::<Vec<_>>();
+/// The context the `%` variables of the `exec:` and `spawn:` commands are replaced with.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CommandContext {
+    pub vault_name: String,
+    pub vault_path: String,
+    pub note_name: String,
+    pub note_path: String,
+    /// The selected text of the note editor.
+    pub selection: String,
+    /// The line of the note editor cursor, counted from one.
+    pub line: Option<usize>,
+    /// The heading of the section the note editor cursor is in.
+    pub heading: String,
+}
 
-    match args.as_slice() {
-        [command, args @ ..] => callback(command, args),
-        [] => None,
+impl CommandContext {
+    fn variables(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("vault", self.vault_name.clone()),
+            ("vault_path", self.vault_path.clone()),
+            ("note", self.note_name.clone()),
+            ("note_path", self.note_path.clone()),
+            ("selection", self.selection.clone()),
+            (
+                "line",
+                self.line.map(|line| line.to_string()).unwrap_or_default(),
+            ),
+            ("heading", self.heading.clone()),
+        ]
     }
 }
 
+/// Returns the program and the arguments the command runs with.
+///
+/// Without a shell the command is split into arguments like a shell does, and the variables are
+/// replaced within each argument, so a value with spaces stays one argument. With a shell the
+/// command is run as `<shell> -c <command> basalt <values>...`, so pipes, redirections and the
+/// rest of the shell syntax work. The variables become references to the positional parameters
+/// holding their values, see [`shell::expand_parameters`], so a value is never read as shell
+/// syntax. Returns `None` for an empty command or a command with a quote left open.
+pub fn command_args(
+    command: &str,
+    context: &CommandContext,
+    shell: Option<&str>,
+) -> Option<Vec<String>> {
+    let variables = context.variables();
+    let variables = variables
+        .iter()
+        .map(|(name, value)| (*name, value.as_str()))
+        .collect::<Vec<_>>();
+
+    let args = match shell {
+        Some(shell) => {
+            let names: Vec<&str> = variables.iter().map(|(name, _)| *name).collect();
+            let script = shell::expand_parameters(command, &names);
+            [shell, "-c", &script, "basalt"]
+                .into_iter()
+                .chain(variables.iter().map(|(_, value)| *value))
+                .map(String::from)
+                .collect()
+        }
+        None => shell::split(command)?
+            .iter()
+            .map(|arg| shell::expand(arg, &variables, str::to_string))
+            .collect(),
+    };
+
+    (!command.trim().is_empty()).then_some(args)
+}
+
+fn process(
+    command: &str,
+    context: &CommandContext,
+    shell: Option<&str>,
+) -> Option<process::Command> {
+    let Some(args) = command_args(command, context, shell) else {
+        error!(command, "invalid command");
+        return None;
+    };
+
+    let (program, args) = args.split_first()?;
+    let mut process = process::Command::new(program);
+    process.args(args);
+    Some(process)
+}
+
 pub fn sync_command<'a>(
     terminal: &mut DefaultTerminal,
     command: String,
-    vault_name: &str,
-    note_name: &str,
-    note_path: &str,
+    context: &CommandContext,
+    shell: Option<&str>,
 ) -> Option<Message<'a>> {
     fn enter_alternate_screen(terminal: &mut DefaultTerminal) -> Result<(), std::io::Error> {
         disable_raw_mode()?;
@@ -672,39 +1177,176 @@ pub fn sync_command<'a>(
         terminal.clear()
     }
 
-    run_command(
-        command,
-        vault_name,
-        note_name,
-        note_path,
-        |command, args| {
-            if let Err(error) = process::Command::new(command).arg(args.join(" ")).status() {
-                error!(?error, command, "exec command failed");
-                return None;
-            }
-            enter_alternate_screen(terminal)
-                .map(|_| Message::Explorer(explorer::Message::Select))
-                .ok()
-        },
-    )
+    if let Err(error) = process(&command, context, shell)?.status() {
+        error!(?error, command, "exec command failed");
+        return None;
+    }
+
+    enter_alternate_screen(terminal)
+        .map(|_| Message::Explorer(explorer::Message::Select))
+        .ok()
 }
 
 pub fn spawn_command<'a>(
     command: String,
-    vault_name: &str,
-    note_name: &str,
-    note_path: &str,
+    context: &CommandContext,
+    shell: Option<&str>,
 ) -> Option<Message<'a>> {
-    run_command(
-        command,
-        vault_name,
-        note_name,
-        note_path,
-        |command, args| {
-            if let Err(error) = process::Command::new(command).arg(args.join(" ")).spawn() {
-                error!(?error, command, "spawn command failed");
-            }
-            None
-        },
-    )
+    if let Err(error) = process(&command, context, shell)?.spawn() {
+        error!(?error, command, "spawn command failed");
+    }
+    None
+}
+
+/// Runs the command with the input on its standard input and returns its standard output, like
+/// vim's `!` filter. Fails if the command cannot be run or exits with an error, with the first line
+/// of its standard error as the message.
+pub fn filter_command(
+    command: &str,
+    input: &str,
+    context: &CommandContext,
+    shell: Option<&str>,
+) -> io::Result<String> {
+    let mut child = process(command, context, shell)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid command"))?
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // The input is written from another thread so a command writing its output before reading
+    // all of its input cannot block on a full pipe.
+    let mut stdin = child.stdin.take();
+    let output = thread::scope(|scope| {
+        scope.spawn(|| {
+            if let Some(stdin) = stdin.as_mut() {
+                // A command that does not read its input closes the pipe early, which is fine.
+                let _ = stdin.write_all(input.as_bytes());
+            }
+            drop(stdin);
+        });
+        child.wait_with_output()
+    })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let message = stderr
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| output.status.to_string());
+        return Err(io::Error::other(message));
+    }
+
+    String::from_utf8(output.stdout)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> CommandContext {
+        CommandContext {
+            vault_name: "Notes".into(),
+            vault_path: "/home/Notes".into(),
+            note_name: "My Note".into(),
+            note_path: "/home/Notes/My Note.md".into(),
+            selection: "it's selected".into(),
+            line: Some(12),
+            heading: "Ideas".into(),
+        }
+    }
+
+    #[test]
+    fn test_command_args() {
+        let args = |command: &str, shell: Option<&str>| command_args(command, &context(), shell);
+
+        assert_eq!(
+            args("nvim +%line %note_path", None).unwrap(),
+            ["nvim", "+12", "/home/Notes/My Note.md"]
+        );
+        assert_eq!(
+            args("open 'obsidian://open?vault=%vault&file=%note'", None).unwrap(),
+            ["open", "obsidian://open?vault=Notes&file=My Note"]
+        );
+        assert_eq!(
+            args("echo %selection | pbcopy", None).unwrap(),
+            ["echo", "it's selected", "|", "pbcopy"]
+        );
+        assert_eq!(
+            args("echo %selection | pbcopy", Some(shell::POSIX_SHELL)).unwrap(),
+            [
+                "sh",
+                "-c",
+                r#"echo "${5}" | pbcopy"#,
+                "basalt",
+                "Notes",
+                "/home/Notes",
+                "My Note",
+                "/home/Notes/My Note.md",
+                "it's selected",
+                "12",
+                "Ideas"
+            ]
+        );
+        assert_eq!(
+            args("grep -n %heading %vault_path/*.md > out", Some("sh")).unwrap()[2],
+            r#"grep -n "${7}" "${2}"/*.md > out"#
+        );
+        assert_eq!(args("echo 'open", None), None);
+        assert_eq!(args("  ", None), None);
+        assert_eq!(args("", Some("sh")), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_filter_command() {
+        let filter = |command: &str, input: &str| {
+            filter_command(command, input, &context(), Some("sh"))
+                .map_err(|error| error.to_string())
+        };
+
+        assert_eq!(filter("sort", "b\na\nc\n"), Ok("a\nb\nc\n".into()));
+        assert_eq!(
+            filter("tr a-z A-Z | sed s/MY/%heading/", "my note\n"),
+            Ok("Ideas NOTE\n".into())
+        );
+        assert_eq!(
+            filter("echo failed >&2; exit 3", "input"),
+            Err("failed".into())
+        );
+        assert_eq!(
+            filter_command("cat 'open", "", &context(), None).map_err(|error| error.kind()),
+            Err(io::ErrorKind::InvalidInput)
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_shell_variables_are_not_shell_syntax() {
+        let context = CommandContext {
+            note_name: "a 'b' \"c\"".into(),
+            selection: "$(echo injected) `echo injected`".into(),
+            ..Default::default()
+        };
+        let run = |command: &str| {
+            let args = command_args(command, &context, Some("sh")).unwrap();
+            let output = process::Command::new(&args[0])
+                .args(&args[1..])
+                .output()
+                .unwrap();
+            String::from_utf8(output.stdout).unwrap()
+        };
+
+        let selection = "$(echo injected) `echo injected`\n";
+        assert_eq!(run("echo %selection"), selection);
+        assert_eq!(run("echo \"%selection\""), selection);
+        assert_eq!(run("echo '%selection'"), selection);
+        assert_eq!(
+            run("echo \"%note: %selection\" '(%note)'"),
+            "a 'b' \"c\": $(echo injected) `echo injected` (a 'b' \"c\")\n"
+        );
+    }
 }
@@ -0,0 +1,465 @@
+//! The vim `:` command line of the note editor. It opens over the status bar and runs the typed
+//! ex command on the active note when accepted. As `/` and `?` it reads a search pattern instead,
+//! previewing the search in the note while typing.
+use std::time::Duration;
+
+use basalt_core::obsidian::wiki_link::WikiLink;
+use ratatui::{
+    buffer::Buffer,
+    crossterm::event::{KeyCode, KeyEvent},
+    layout::{Position, Rect},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, StatefulWidget, Widget},
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    app::Message as AppMessage,
+    config::Theme,
+    note_editor::{
+        self,
+        substitute::{SubstituteError, Substitution},
+        Direction,
+    },
+    toast,
+};
+
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+pub enum ExError {
+    #[error("Not an editor command: {0}")]
+    Unknown(String),
+    #[error("Argument required: {0}")]
+    MissingArgument(String),
+    #[error("Trailing characters: {0}")]
+    TrailingCharacters(String),
+    #[error(transparent)]
+    Substitute(#[from] SubstituteError),
+}
+
+/// A command typed into the command line.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExCommand {
+    /// `:w`, saves the note.
+    Write,
+    /// `:q`, closes the note. `:q!` closes it even with unsaved changes.
+    Quit { force: bool },
+    /// `:wq` and `:x`, save and close the note. The note stays open when the write fails.
+    WriteQuit,
+    /// `:e <note>`, opens the note like a wiki-link to it would.
+    Edit(String),
+    /// `:<line>`, jumps to the line.
+    Line(usize),
+    /// `:s` and `:%s`, substitute in the cursor's line or in the whole note.
+    Substitute(Substitution),
+    /// `:noh`, stops highlighting the matches of the last search.
+    NoHighlight,
+}
+
+impl ExCommand {
+    /// Parses the command typed after the `:`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_tui::command_line::ExCommand;
+    ///
+    /// assert_eq!(ExCommand::parse("q!"), Ok(ExCommand::Quit { force: true }));
+    /// assert_eq!(ExCommand::parse("e Recipes"), Ok(ExCommand::Edit("Recipes".into())));
+    /// assert_eq!(ExCommand::parse("42"), Ok(ExCommand::Line(42)));
+    /// assert!(ExCommand::parse("%s/foo/bar/g").is_ok());
+    /// ```
+    pub fn parse(input: &str) -> Result<Self, ExError> {
+        let input = input.trim();
+
+        if let Ok(line) = input.parse() {
+            return Ok(ExCommand::Line(line));
+        }
+
+        let (whole_note, command) = match input.strip_prefix('%') {
+            Some(command) => (true, command),
+            None => (false, input),
+        };
+
+        let name_len = command
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(command.len());
+        let (name, rest) = command.split_at(name_len);
+        let (force, rest) = match rest.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, rest),
+        };
+        let argument = rest.trim();
+
+        let no_argument = |command: ExCommand| {
+            if argument.is_empty() {
+                Ok(command)
+            } else {
+                Err(ExError::TrailingCharacters(argument.to_string()))
+            }
+        };
+
+        match name {
+            "s" | "substitute" => Ok(ExCommand::Substitute(Substitution::parse(
+                rest, whole_note,
+            )?)),
+            _ if whole_note => Err(ExError::Unknown(input.to_string())),
+            "w" | "write" => no_argument(ExCommand::Write),
+            "q" | "quit" => no_argument(ExCommand::Quit { force }),
+            "wq" | "x" | "xit" => no_argument(ExCommand::WriteQuit),
+            "noh" | "nohlsearch" => no_argument(ExCommand::NoHighlight),
+            "e" | "edit" if argument.is_empty() => Err(ExError::MissingArgument(name.to_string())),
+            "e" | "edit" => Ok(ExCommand::Edit(argument.to_string())),
+            _ => Err(ExError::Unknown(input.to_string())),
+        }
+    }
+
+    /// The message that runs the command on the active note. `modified` tells whether the note has
+    /// unsaved changes, which `:q` refuses to drop.
+    fn into_message<'a>(self, modified: bool) -> AppMessage<'a> {
+        match self {
+            ExCommand::Write => AppMessage::NoteEditor(note_editor::Message::Save),
+            ExCommand::Quit { force: false } if modified => {
+                error("No write since last change (add ! to override)")
+            }
+            ExCommand::Quit { .. } => AppMessage::CloseTab,
+            ExCommand::WriteQuit => AppMessage::NoteEditor(note_editor::Message::SaveAndClose),
+            ExCommand::Edit(note) => match WikiLink::at(&format!("[[{note}]]"), 2) {
+                Some(link) => AppMessage::FollowLink(link),
+                None => error(&format!("Invalid note name: {note}")),
+            },
+            ExCommand::Line(line) => AppMessage::NoteEditor(note_editor::Message::JumpToLine(line)),
+            ExCommand::Substitute(substitution) => {
+                AppMessage::NoteEditor(note_editor::Message::Substitute(substitution))
+            }
+            ExCommand::NoHighlight => {
+                AppMessage::NoteEditor(note_editor::Message::ClearSearchHighlight)
+            }
+        }
+    }
+}
+
+fn error<'a>(message: &str) -> AppMessage<'a> {
+    AppMessage::Toast(toast::Message::Create(toast::Toast::error(
+        message,
+        Duration::from_secs(2),
+    )))
+}
+
+/// What the command line reads: an ex command after `:`, or a search pattern after `/` or `?`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Prompt {
+    #[default]
+    Command,
+    Search(Direction),
+}
+
+impl Prompt {
+    fn symbol(self) -> char {
+        match self {
+            Prompt::Command => ':',
+            Prompt::Search(Direction::Forward) => '/',
+            Prompt::Search(Direction::Backward) => '?',
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    Open(Prompt),
+    Cancel,
+    Run,
+    Delete,
+    KeyEvent(KeyEvent),
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CommandLineState {
+    pub visible: bool,
+    prompt: Prompt,
+    input: String,
+    /// Byte offset of the cursor in the input.
+    cursor: usize,
+    pub(crate) terminal_cursor: Option<Position>,
+}
+
+impl CommandLineState {
+    fn open(&mut self, prompt: Prompt) {
+        self.visible = true;
+        self.prompt = prompt;
+        self.input.clear();
+        self.cursor = 0;
+    }
+
+    fn close(&mut self) -> String {
+        self.visible = false;
+        self.cursor = 0;
+        std::mem::take(&mut self.input)
+    }
+
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    fn insert_char(&mut self, c: char) {
+        self.input.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+    }
+
+    fn delete_char(&mut self) {
+        if let Some(c) = self.input[..self.cursor].chars().next_back() {
+            self.cursor -= c.len_utf8();
+            self.input.remove(self.cursor);
+        }
+    }
+
+    fn cursor_left(&mut self) {
+        if let Some(c) = self.input[..self.cursor].chars().next_back() {
+            self.cursor -= c.len_utf8();
+        }
+    }
+
+    fn cursor_right(&mut self) {
+        if let Some(c) = self.input[self.cursor..].chars().next() {
+            self.cursor += c.len_utf8();
+        }
+    }
+}
+
+/// The message that ends the search previewed in the note when the command line closes without
+/// running it.
+fn cancel<'a>(prompt: Prompt) -> Option<AppMessage<'a>> {
+    match prompt {
+        Prompt::Search(_) => Some(AppMessage::NoteEditor(note_editor::Message::SearchCancel)),
+        Prompt::Command => None,
+    }
+}
+
+/// Drives the command line. `Run` closes it and runs the typed command, or the search, on the
+/// active note; `modified` tells whether the note has unsaved changes.
+pub fn update<'a>(
+    message: Message,
+    state: &mut CommandLineState,
+    modified: bool,
+) -> Option<AppMessage<'a>> {
+    let prompt = state.prompt;
+
+    match message {
+        Message::Open(prompt) => state.open(prompt),
+        Message::Cancel => {
+            state.close();
+            return cancel(prompt);
+        }
+        // Deleting past the start closes the command line, like in vim.
+        Message::Delete if state.input.is_empty() => {
+            state.close();
+            return cancel(prompt);
+        }
+        Message::Delete => state.delete_char(),
+        Message::Run => {
+            let input = state.close();
+            return match prompt {
+                Prompt::Search(direction) => {
+                    Some(AppMessage::NoteEditor(note_editor::Message::Search {
+                        pattern: input,
+                        direction,
+                    }))
+                }
+                Prompt::Command if input.trim().is_empty() => None,
+                Prompt::Command => Some(match ExCommand::parse(&input) {
+                    Ok(command) => command.into_message(modified),
+                    Err(err) => error(&err.to_string()),
+                }),
+            };
+        }
+        Message::KeyEvent(key) => match key.code {
+            KeyCode::Char(c) => state.insert_char(c),
+            KeyCode::Left => state.cursor_left(),
+            KeyCode::Right => state.cursor_right(),
+            KeyCode::Home => state.cursor = 0,
+            KeyCode::End => state.cursor = state.input.len(),
+            _ => {}
+        },
+    }
+
+    // Every change to a search pattern is previewed in the note.
+    match prompt {
+        Prompt::Search(direction) => Some(AppMessage::NoteEditor(
+            note_editor::Message::SearchPreview {
+                pattern: state.input.clone(),
+                direction,
+            },
+        )),
+        Prompt::Command => None,
+    }
+}
+
+/// Maps key events while typing a command. Every printable key goes to the input.
+pub fn handle_editing_event(key: KeyEvent) -> Option<Message> {
+    match key.code {
+        KeyCode::Esc => Some(Message::Cancel),
+        KeyCode::Enter => Some(Message::Run),
+        KeyCode::Backspace => Some(Message::Delete),
+        _ => Some(Message::KeyEvent(key)),
+    }
+}
+
+/// Draws the command line over the status bar.
+pub struct CommandLine<'a> {
+    theme: &'a Theme,
+}
+
+impl<'a> CommandLine<'a> {
+    pub fn new(theme: &'a Theme) -> Self {
+        Self { theme }
+    }
+}
+
+impl StatefulWidget for CommandLine<'_> {
+    type State = CommandLineState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        state.terminal_cursor = None;
+        if !state.visible {
+            return;
+        }
+
+        let bar = self.theme.status_bar;
+        Block::new()
+            .style(Style::new().bg(bar.background))
+            .render(area, buf);
+        Line::from(format!("{}{}", state.prompt.symbol(), state.input))
+            .fg(bar.foreground)
+            .render(area, buf);
+
+        let column = 1 + state.input[..state.cursor].width() as u16;
+        state.terminal_cursor = Some(Position::new(
+            (area.x + column).min(area.right().saturating_sub(1)),
+            area.y,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let tests = [
+            ("w", Ok(ExCommand::Write)),
+            ("write", Ok(ExCommand::Write)),
+            (" q ", Ok(ExCommand::Quit { force: false })),
+            ("quit!", Ok(ExCommand::Quit { force: true })),
+            ("wq", Ok(ExCommand::WriteQuit)),
+            ("x", Ok(ExCommand::WriteQuit)),
+            ("noh", Ok(ExCommand::NoHighlight)),
+            ("e  My Note ", Ok(ExCommand::Edit("My Note".into()))),
+            (
+                "edit Recipes#Pancakes",
+                Ok(ExCommand::Edit("Recipes#Pancakes".into())),
+            ),
+            ("12", Ok(ExCommand::Line(12))),
+            ("e", Err(ExError::MissingArgument("e".into()))),
+            (
+                "w notes.md",
+                Err(ExError::TrailingCharacters("notes.md".into())),
+            ),
+            ("%w", Err(ExError::Unknown("%w".into()))),
+            ("sort", Err(ExError::Unknown("sort".into()))),
+            (
+                "s",
+                Err(ExError::Substitute(SubstituteError::MissingPattern)),
+            ),
+        ];
+
+        tests
+            .into_iter()
+            .for_each(|(input, expected)| assert_eq!(ExCommand::parse(input), expected, "{input}"));
+
+        assert!(matches!(
+            ExCommand::parse("%s/a/b/g"),
+            Ok(ExCommand::Substitute(substitution)) if substitution.whole_note
+        ));
+        assert!(matches!(
+            ExCommand::parse("s#a#b#"),
+            Ok(ExCommand::Substitute(substitution)) if !substitution.whole_note
+        ));
+    }
+
+    #[test]
+    fn test_quit_keeps_unsaved_changes() {
+        assert!(matches!(
+            ExCommand::Quit { force: false }.into_message(true),
+            AppMessage::Toast(_)
+        ));
+        assert_eq!(
+            ExCommand::Quit { force: true }.into_message(true),
+            AppMessage::CloseTab
+        );
+        assert_eq!(
+            ExCommand::Quit { force: false }.into_message(false),
+            AppMessage::CloseTab
+        );
+    }
+
+    #[test]
+    fn test_typing_and_running() {
+        let mut state = CommandLineState::default();
+        let key = |c| Message::KeyEvent(KeyEvent::from(KeyCode::Char(c)));
+
+        update(Message::Open(Prompt::Command), &mut state, false);
+        "wx".chars().for_each(|c| {
+            update(key(c), &mut state, false);
+        });
+        update(Message::Delete, &mut state, false);
+        assert_eq!(state.input(), "w");
+
+        let message = update(Message::Run, &mut state, false);
+        assert_eq!(
+            message,
+            Some(AppMessage::NoteEditor(note_editor::Message::Save))
+        );
+        assert!(!state.visible);
+
+        update(Message::Open(Prompt::Command), &mut state, false);
+        update(Message::Delete, &mut state, false);
+        assert!(!state.visible, "deleting past the start closes it");
+    }
+
+    #[test]
+    fn test_search_previews_while_typing() {
+        let mut state = CommandLineState::default();
+        let direction = Direction::Backward;
+        let preview = |pattern: &str| {
+            Some(AppMessage::NoteEditor(
+                note_editor::Message::SearchPreview {
+                    pattern: pattern.into(),
+                    direction,
+                },
+            ))
+        };
+
+        update(Message::Open(Prompt::Search(direction)), &mut state, false);
+        let message = update(
+            Message::KeyEvent(KeyEvent::from(KeyCode::Char('a'))),
+            &mut state,
+            false,
+        );
+        assert_eq!(message, preview("a"));
+        assert_eq!(
+            update(Message::Run, &mut state, false),
+            Some(AppMessage::NoteEditor(note_editor::Message::Search {
+                pattern: "a".into(),
+                direction,
+            }))
+        );
+
+        update(Message::Open(Prompt::Search(direction)), &mut state, false);
+        assert_eq!(
+            update(Message::Cancel, &mut state, false),
+            Some(AppMessage::NoteEditor(note_editor::Message::SearchCancel)),
+            "cancelling moves the cursor back"
+        );
+    }
+}
@@ -0,0 +1,379 @@
+use std::cmp::Reverse;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{Position, Rect},
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{BorderType, ListItem, StatefulWidget},
+};
+
+pub use crate::query_modal::{handle_editing_event, Message};
+use crate::{
+    app::Message as AppMessage,
+    command::{Command, COMMANDS},
+    config::{Config, Theme},
+    fuzzy,
+    query_modal::{self, highlighted, QueryModal, QueryModalState},
+};
+
+/// Command name prefixes and the section they are shown under in the palette. Longer prefixes
+/// sharing a start with a shorter one come first.
+const SECTIONS: &[(&str, &str)] = &[
+    ("note_editor", "Note editor"),
+    ("explorer", "Explorer"),
+    ("outline", "Outline"),
+    ("backlinks", "Backlinks"),
+    ("tags", "Tags"),
+    ("tab", "Tab"),
+    ("daily_note", "Daily note"),
+    ("splash", "Splash"),
+    ("input_modal", "Input"),
+    ("help_modal", "Help"),
+    ("vault_selector_modal", "Vault selector"),
+    ("debug_log", "Debug log"),
+    ("theme_selector_modal", "Theme selector"),
+    ("search_modal", "Search"),
+    ("quick_switcher_modal", "Quick switcher"),
+    ("template_modal", "Templates"),
+];
+
+/// Drives the command palette. The commands and their key bindings are collected from the config
+/// when the palette opens; `Select` closes the palette and runs the highlighted command.
+pub fn update<'a>(
+    message: Message,
+    config: &Config,
+    state: &mut CommandPaletteState,
+) -> Option<AppMessage<'a>> {
+    match message {
+        Message::Toggle if state.modal.visible => state.modal.hide(),
+        Message::Toggle => state.open(config),
+        Message::Select | Message::Submit => {
+            let command = state.selected_command()?.command.clone();
+            state.modal.hide();
+            return Some(command.into());
+        }
+        message => {
+            let commands = &state.commands;
+            query_modal::update(&message, &mut state.modal, |modal| {
+                modal.set_results(rank(commands, &modal.query))
+            });
+        }
+    }
+
+    None
+}
+
+/// A command listed in the palette.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PaletteCommand {
+    pub(crate) command: Command,
+    /// Human-readable name, e.g. `Explorer: Toggle outline`.
+    pub label: String,
+    /// Keys bound to the command in any section of the config.
+    pub keys: Vec<String>,
+}
+
+/// Turns a command name into a human-readable label, e.g. `explorer_toggle_outline` into
+/// `Explorer: Toggle outline`.
+fn label(name: &str) -> String {
+    let (section, action) = SECTIONS
+        .iter()
+        .find_map(|(prefix, section)| {
+            let action = name.strip_prefix(prefix)?.strip_prefix('_')?;
+            Some((Some(*section), action))
+        })
+        .unwrap_or((None, name));
+
+    let action = action.replace('_', " ");
+    let mut chars = action.chars();
+    let action = chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default();
+
+    match section {
+        Some(section) => format!("{section}: {action}"),
+        None => action,
+    }
+}
+
+/// Lists every built-in command, followed by the `exec:`, `spawn:`, `filter:` and `read:` commands bound in
+/// the config. The palette's own commands are left out, as running them from the palette would
+/// only reopen it.
+pub fn commands(config: &Config) -> Vec<PaletteCommand> {
+    let sections = config.sections();
+    let keys = |command: &Command| {
+        let message = AppMessage::from(command.clone());
+        sections
+            .iter()
+            .flat_map(|section| section.keys_for(&message))
+            .fold(Vec::new(), |mut keys: Vec<String>, key| {
+                if !keys.iter().any(|k| k == key) {
+                    keys.push(key.to_string());
+                }
+                keys
+            })
+    };
+
+    let built_in = COMMANDS
+        .iter()
+        .filter(|(name, _)| !name.starts_with("command_palette_"))
+        .map(|(name, command)| (label(name), command.clone()));
+
+    let mut user_defined: Vec<(String, Command)> = Vec::new();
+    for message in sections
+        .iter()
+        .flat_map(|section| section.key_bindings.values())
+    {
+        let (label, command) = match message {
+            AppMessage::Exec(command) => (
+                format!("Exec: {}", command.trim()),
+                Command::Exec(command.clone()),
+            ),
+            AppMessage::Spawn(command) => (
+                format!("Spawn: {}", command.trim()),
+                Command::Spawn(command.clone()),
+            ),
+            AppMessage::Filter(command) => (
+                format!("Filter: {}", command.trim()),
+                Command::Filter(command.clone()),
+            ),
+            AppMessage::Read(command) => (
+                format!("Read: {}", command.trim()),
+                Command::Read(command.clone()),
+            ),
+            _ => continue,
+        };
+        if !user_defined
+            .iter()
+            .any(|(_, existing)| *existing == command)
+        {
+            user_defined.push((label, command));
+        }
+    }
+
+    built_in
+        .chain(user_defined)
+        .map(|(label, command)| PaletteCommand {
+            keys: keys(&command),
+            label,
+            command,
+        })
+        .collect()
+}
+
+/// A command matching the query.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommandMatch {
+    pub command: PaletteCommand,
+    pub score: i64,
+    /// Byte offsets of the matched characters within the label.
+    pub indices: Vec<usize>,
+}
+
+/// Ranks the commands by their fuzzy score against the query on the label. Ties keep the order
+/// of the commands.
+pub fn rank(commands: &[PaletteCommand], query: &str) -> Vec<CommandMatch> {
+    let mut matches: Vec<CommandMatch> = commands
+        .iter()
+        .filter_map(|command| {
+            let fuzzy_match = fuzzy::fuzzy_match(query, &command.label)?;
+            Some(CommandMatch {
+                command: command.clone(),
+                score: fuzzy_match.score,
+                indices: fuzzy_match.indices,
+            })
+        })
+        .collect();
+
+    matches.sort_by_key(|command_match| Reverse(command_match.score));
+    matches
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CommandPaletteState {
+    pub(crate) modal: QueryModalState<CommandMatch>,
+    commands: Vec<PaletteCommand>,
+}
+
+impl CommandPaletteState {
+    pub fn is_visible(&self) -> bool {
+        self.modal.is_visible()
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.modal.is_editing()
+    }
+
+    pub fn terminal_cursor(&self) -> Option<Position> {
+        self.modal.terminal_cursor()
+    }
+
+    pub fn query(&self) -> &str {
+        self.modal.query()
+    }
+
+    pub fn commands(&self) -> &[PaletteCommand] {
+        &self.commands
+    }
+
+    pub fn matches(&self) -> &[CommandMatch] {
+        self.modal.results()
+    }
+
+    pub fn selected_command(&self) -> Option<&PaletteCommand> {
+        self.modal
+            .selected()
+            .map(|command_match| &command_match.command)
+    }
+
+    fn open(&mut self, config: &Config) {
+        self.commands = commands(config);
+        self.modal.query.clear();
+        self.modal.open();
+        self.rank();
+    }
+
+    /// Ranks the commands against the current query and selects the best match.
+    fn rank(&mut self) {
+        self.modal
+            .set_results(rank(&self.commands, &self.modal.query));
+    }
+}
+
+pub struct CommandPalette {
+    pub border_type: BorderType,
+    pub theme: Theme,
+}
+
+impl CommandPalette {
+    pub fn new(border_type: BorderType, theme: Theme) -> Self {
+        Self { border_type, theme }
+    }
+
+    /// Renders the label with the matched characters in the accent colour, and the bound keys
+    /// aligned to the right edge of the given width.
+    fn match_item<'a>(
+        command_match: &'a CommandMatch,
+        theme: &Theme,
+        width: usize,
+    ) -> ListItem<'a> {
+        let command = &command_match.command;
+        let mut spans = highlighted(&command.label, &command_match.indices, theme);
+
+        let keys = command.keys.join(", ");
+        let used = command.label.chars().count() + keys.chars().count();
+        spans.push(Span::raw(" ".repeat(width.saturating_sub(used).max(2))));
+        spans.push(Span::raw(keys).fg(theme.muted));
+
+        ListItem::new(Line::from(spans))
+    }
+}
+
+impl StatefulWidget for CommandPalette {
+    type State = CommandPaletteState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let results_title = match state.matches().len() {
+            1 => String::from("1 command"),
+            count => format!("{count} commands"),
+        };
+
+        QueryModal::new(
+            "Command palette",
+            results_title,
+            "No matching commands",
+            Self::match_item,
+            self.border_type,
+            self.theme,
+        )
+        .render(area, buf, &mut state.modal);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use ratatui::crossterm::event::{KeyCode, KeyEvent};
+
+    use crate::{config::ConfigSection, explorer};
+
+    use super::*;
+
+    fn config() -> Config<'static> {
+        Config {
+            global: ConfigSection::from(BTreeMap::from([
+                (
+                    "Spaceo".to_string(),
+                    AppMessage::Exec("open %note_path".into()),
+                ),
+                ("q".to_string(), AppMessage::Quit),
+            ])),
+            explorer: ConfigSection::from(BTreeMap::from([
+                ("k".to_string(), AppMessage::Explorer(explorer::Message::Up)),
+                (
+                    "up".to_string(),
+                    AppMessage::Explorer(explorer::Message::Up),
+                ),
+            ])),
+            ..Default::default()
+        }
+    }
+
+    fn labels(state: &CommandPaletteState) -> Vec<&str> {
+        state
+            .matches()
+            .iter()
+            .map(|command_match| command_match.command.label.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_label() {
+        assert_eq!(label("quit"), "Quit");
+        assert_eq!(label("tab_next"), "Tab: Next");
+        assert_eq!(label("tags_toggle"), "Tags: Toggle");
+        assert_eq!(
+            label("note_editor_toggle_callout_fold"),
+            "Note editor: Toggle callout fold"
+        );
+    }
+
+    #[test]
+    fn test_commands_list_bindings_and_user_commands() {
+        let commands = commands(&config());
+        let command = |label: &str| commands.iter().find(|command| command.label == label);
+
+        assert_eq!(command("Explorer: Up").unwrap().keys, ["k", "up"]);
+        assert_eq!(command("Quit").unwrap().keys, ["q"]);
+        assert!(command("Explorer: Sort").unwrap().keys.is_empty());
+        assert_eq!(
+            command("Exec: open %note_path").unwrap().command,
+            Command::Exec("open %note_path".into())
+        );
+        assert!(!commands
+            .iter()
+            .any(|command| command.label.starts_with("Command palette")));
+    }
+
+    #[test]
+    fn test_typing_filters_and_select_runs_command() {
+        let config = config();
+        let mut state = CommandPaletteState::default();
+        update(Message::Toggle, &config, &mut state);
+        assert!(state.is_visible() && state.is_editing());
+        assert_eq!(state.matches().len(), state.commands().len());
+
+        "exp sort".chars().for_each(|c| {
+            let key = KeyEvent::from(KeyCode::Char(c));
+            update(Message::KeyEvent(key), &config, &mut state);
+        });
+        assert_eq!(labels(&state).first(), Some(&"Explorer: Sort"));
+
+        let message = update(Message::Submit, &config, &mut state);
+        assert_eq!(message, Some(AppMessage::Explorer(explorer::Message::Sort)));
+        assert!(!state.is_visible());
+    }
+}
@@ -1,4 +1,4 @@
-mod env;
+pub(crate) mod env;
 mod key_binding;
 pub mod symbol;
 pub mod theme;
@@ -6,13 +6,14 @@ pub mod theme;
 use core::fmt;
 use std::{collections::BTreeMap, fs::read_to_string};
 
+use basalt_core::obsidian::DeleteMode;
 use etcetera::{choose_base_strategy, home_dir, BaseStrategy};
-use key_binding::{KeyBinding, KeySpec, Leader};
+use key_binding::{KeyBinding, KeySpec};
 use serde::Deserialize;
 
 use crate::{app::Message, command::Command};
 
-pub(crate) use key_binding::{Key, Keystroke};
+pub(crate) use key_binding::{Key, Keystroke, Leader};
 pub(crate) use symbol::Symbols;
 pub(crate) use theme::Theme;
 
@@ -53,6 +54,14 @@ impl ConfigSection<'_> {
         });
     }
 
+    /// Returns the keys bound to the given message.
+    pub fn keys_for<'b>(&'b self, message: &'b Message) -> impl Iterator<Item = &'b str> {
+        self.key_bindings
+            .iter()
+            .filter(move |(_, bound)| *bound == message)
+            .map(|(key, _)| key.as_str())
+    }
+
     /// Replaces this section's key_bindings entirely with those from another config.
     pub(crate) fn replace_key_bindings(&mut self, config: Self) {
         if !config.key_bindings.is_empty() {
@@ -88,18 +97,28 @@ impl fmt::Display for ConfigSection<'_> {
 pub struct Config<'a> {
     pub experimental_editor: bool,
     pub vim_mode: bool,
+    pub live_preview: bool,
+    pub command_shell: bool,
+    /// Overrides the `trashOption` of the vault's `.obsidian/app.json` when set.
+    pub delete_mode: Option<DeleteMode>,
     pub symbols: Symbols,
     pub theme: Theme,
     pub global: ConfigSection<'a>,
     pub splash: ConfigSection<'a>,
     pub explorer: ConfigSection<'a>,
     pub outline: ConfigSection<'a>,
+    pub backlinks: ConfigSection<'a>,
+    pub tags: ConfigSection<'a>,
     pub input_modal: ConfigSection<'a>,
     pub help_modal: ConfigSection<'a>,
     pub note_editor: ConfigSection<'a>,
     pub vault_selector_modal: ConfigSection<'a>,
     pub debug_log_modal: ConfigSection<'a>,
     pub theme_selector_modal: ConfigSection<'a>,
+    pub search_modal: ConfigSection<'a>,
+    pub quick_switcher_modal: ConfigSection<'a>,
+    pub template_modal: ConfigSection<'a>,
+    pub command_palette: ConfigSection<'a>,
 }
 
 impl Default for Config<'_> {
@@ -131,26 +150,57 @@ impl ConfigSection<'_> {
     }
 }
 
-impl Config<'_> {
+impl<'a> Config<'a> {
     fn from_toml(value: TomlConfig, leader: &Leader) -> Self {
         Self {
             symbols: value.symbols.into(),
             theme: theme::theme_by_name(value.theme.as_deref().unwrap_or("default")),
             experimental_editor: value.experimental_editor,
             vim_mode: value.vim_mode,
+            live_preview: value.live_preview,
+            command_shell: value.command_shell,
+            delete_mode: value.delete_mode,
             global: ConfigSection::from_toml(value.global, leader),
             splash: ConfigSection::from_toml(value.splash, leader),
             explorer: ConfigSection::from_toml(value.explorer, leader),
             outline: ConfigSection::from_toml(value.outline, leader),
+            backlinks: ConfigSection::from_toml(value.backlinks, leader),
+            tags: ConfigSection::from_toml(value.tags, leader),
             input_modal: ConfigSection::from_toml(value.input_modal, leader),
             help_modal: ConfigSection::from_toml(value.help_modal, leader),
             note_editor: ConfigSection::from_toml(value.note_editor, leader),
             vault_selector_modal: ConfigSection::from_toml(value.vault_selector_modal, leader),
             debug_log_modal: ConfigSection::from_toml(value.debug_log_modal, leader),
             theme_selector_modal: ConfigSection::from_toml(value.theme_selector_modal, leader),
+            search_modal: ConfigSection::from_toml(value.search_modal, leader),
+            quick_switcher_modal: ConfigSection::from_toml(value.quick_switcher_modal, leader),
+            template_modal: ConfigSection::from_toml(value.template_modal, leader),
+            command_palette: ConfigSection::from_toml(value.command_palette, leader),
         }
     }
 
+    /// Returns every key binding section, the global section first.
+    pub(crate) fn sections(&self) -> Vec<&ConfigSection<'a>> {
+        vec![
+            &self.global,
+            &self.splash,
+            &self.explorer,
+            &self.outline,
+            &self.backlinks,
+            &self.tags,
+            &self.input_modal,
+            &self.help_modal,
+            &self.note_editor,
+            &self.vault_selector_modal,
+            &self.debug_log_modal,
+            &self.theme_selector_modal,
+            &self.search_modal,
+            &self.quick_switcher_modal,
+            &self.template_modal,
+            &self.command_palette,
+        ]
+    }
+
     /// Takes self and another config and merges the `key_bindings` together overwriting the
     /// existing entries with the value from another config.
     pub(crate) fn merge(&mut self, config: Self) -> Self {
@@ -158,10 +208,15 @@ impl Config<'_> {
         self.theme = config.theme;
         self.experimental_editor = config.experimental_editor;
         self.vim_mode = config.vim_mode;
+        self.live_preview = config.live_preview;
+        self.command_shell = config.command_shell;
+        self.delete_mode = config.delete_mode;
         self.global.merge_key_bindings(config.global);
         self.explorer.merge_key_bindings(config.explorer);
         self.splash.merge_key_bindings(config.splash);
         self.outline.merge_key_bindings(config.outline);
+        self.backlinks.merge_key_bindings(config.backlinks);
+        self.tags.merge_key_bindings(config.tags);
         self.input_modal.merge_key_bindings(config.input_modal);
         self.note_editor.merge_key_bindings(config.note_editor);
         self.help_modal.merge_key_bindings(config.help_modal);
@@ -171,6 +226,13 @@ impl Config<'_> {
             .merge_key_bindings(config.debug_log_modal);
         self.theme_selector_modal
             .merge_key_bindings(config.theme_selector_modal);
+        self.search_modal.merge_key_bindings(config.search_modal);
+        self.quick_switcher_modal
+            .merge_key_bindings(config.quick_switcher_modal);
+        self.template_modal
+            .merge_key_bindings(config.template_modal);
+        self.command_palette
+            .merge_key_bindings(config.command_palette);
         self.clone()
     }
 
@@ -181,6 +243,8 @@ impl Config<'_> {
         self.explorer.replace_key_bindings(config.explorer);
         self.splash.replace_key_bindings(config.splash);
         self.outline.replace_key_bindings(config.outline);
+        self.backlinks.replace_key_bindings(config.backlinks);
+        self.tags.replace_key_bindings(config.tags);
         self.input_modal.replace_key_bindings(config.input_modal);
         self.note_editor.replace_key_bindings(config.note_editor);
         self.help_modal.replace_key_bindings(config.help_modal);
@@ -190,6 +254,13 @@ impl Config<'_> {
             .replace_key_bindings(config.debug_log_modal);
         self.theme_selector_modal
             .replace_key_bindings(config.theme_selector_modal);
+        self.search_modal.replace_key_bindings(config.search_modal);
+        self.quick_switcher_modal
+            .replace_key_bindings(config.quick_switcher_modal);
+        self.template_modal
+            .replace_key_bindings(config.template_modal);
+        self.command_palette
+            .replace_key_bindings(config.command_palette);
         self.clone()
     }
 }
@@ -268,6 +339,12 @@ struct TomlConfig {
     #[serde(default)]
     vim_mode: bool,
     #[serde(default)]
+    live_preview: bool,
+    #[serde(default)]
+    command_shell: bool,
+    #[serde(default)]
+    delete_mode: Option<DeleteMode>,
+    #[serde(default)]
     leader: Leader,
     #[serde(default)]
     global: TomlConfigSection,
@@ -278,6 +355,10 @@ struct TomlConfig {
     #[serde(default)]
     outline: TomlConfigSection,
     #[serde(default)]
+    backlinks: TomlConfigSection,
+    #[serde(default)]
+    tags: TomlConfigSection,
+    #[serde(default)]
     input_modal: TomlConfigSection,
     #[serde(default)]
     help_modal: TomlConfigSection,
@@ -289,6 +370,14 @@ struct TomlConfig {
     debug_log_modal: TomlConfigSection,
     #[serde(default)]
     theme_selector_modal: TomlConfigSection,
+    #[serde(default)]
+    search_modal: TomlConfigSection,
+    #[serde(default)]
+    quick_switcher_modal: TomlConfigSection,
+    #[serde(default)]
+    template_modal: TomlConfigSection,
+    #[serde(default)]
+    command_palette: TomlConfigSection,
 }
 
 /// Finds and reads the user configuration file in order of priority.
@@ -368,6 +457,26 @@ const BASE_CONFIGURATION_STR: &str =
 
 const VIM_CONFIGURATION_STR: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/vim.toml"));
 
+/// The bundled configuration: the base config.toml, with the vim.toml key bindings replacing
+/// its own in vim mode.
+pub(crate) fn bundled<'a>(vim_mode: bool, leader: &Leader) -> Result<Config<'a>, ConfigError> {
+    // TODO: Use compile time toml parsing instead to check the build error during compile time
+    // Requires a custom proc-macro workspace crate
+    let mut config = Config::from_toml(
+        toml::from_str::<TomlConfig>(BASE_CONFIGURATION_STR)?,
+        leader,
+    );
+
+    if vim_mode {
+        let vim_config = toml::from_str::<TomlConfig>(VIM_CONFIGURATION_STR)
+            .map_err(ConfigError::from)
+            .map(|vim| Config::from_toml(vim, leader))?;
+        config.replace(vim_config);
+    }
+
+    Ok(config)
+}
+
 /// Loads and merges configuration from multiple sources in priority order.
 ///
 /// The configuration is built by layering sources with increasing precedence:
@@ -393,24 +502,13 @@ pub fn load<'a>() -> Result<(Config<'a>, Vec<String>), ConfigError> {
         .map(|user| user.leader.clone())
         .unwrap_or_default();
 
-    // TODO: Use compile time toml parsing instead to check the build error during compile time
-    // Requires a custom proc-macro workspace crate
-    let mut config = Config::from_toml(
-        toml::from_str::<TomlConfig>(BASE_CONFIGURATION_STR)?,
-        &leader,
-    );
+    let vim_mode = user_config.as_ref().is_some_and(|user| user.vim_mode);
+    let mut config = bundled(vim_mode, &leader)?;
 
     if config.symbols.preset == symbol::Preset::Auto {
         config.symbols.preset = symbol::detect_preset(env::SystemEnv)
     }
 
-    if user_config.as_ref().is_some_and(|user| user.vim_mode) {
-        let vim_config = toml::from_str::<TomlConfig>(VIM_CONFIGURATION_STR)
-            .map_err(ConfigError::from)
-            .map(|vim| Config::from_toml(vim, &leader))?;
-        config.replace(vim_config);
-    }
-
     if let Some(user) = user_config {
         config.merge(Config::from_toml(user, &leader));
     }
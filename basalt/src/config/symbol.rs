@@ -76,7 +76,10 @@ pub struct Symbols {
     pub callout_bug: String,
     pub callout_example: String,
     pub callout_quote: String,
+    pub embed_image: String,
+    pub embed_note: String,
     pub horizontal_rule: String,
+    pub thematic_break: String,
     pub folder_expanded_collapsed: String,
     pub folder_collapsed_collapsed: String,
     pub heading_collapsed_dot: String,
@@ -144,7 +147,10 @@ impl From<TomlSymbols> for Symbols {
             callout_bug,
             callout_example,
             callout_quote,
+            embed_image,
+            embed_note,
             horizontal_rule,
+            thematic_break,
             folder_expanded_collapsed,
             folder_collapsed_collapsed,
             heading_collapsed_dot,
@@ -217,7 +223,10 @@ impl Default for Symbols {
             callout_bug: "[b]".into(),
             callout_example: "[e]".into(),
             callout_quote: "\"".into(),
+            embed_image: "[img]".into(),
+            embed_note: "[>]".into(),
             horizontal_rule: "═".into(),
+            thematic_break: "-".into(),
             folder_expanded_collapsed: "+".into(),
             folder_collapsed_collapsed: "-".into(),
             heading_collapsed_dot: ".".into(),
@@ -284,7 +293,10 @@ impl Symbols {
             callout_bug: "⊙".into(),
             callout_example: "≡".into(),
             callout_quote: "❞".into(),
+            embed_image: "▣".into(),
+            embed_note: "↪".into(),
             horizontal_rule: "═".into(),
+            thematic_break: "─".into(),
             folder_expanded_collapsed: "▪".into(),
             folder_collapsed_collapsed: "▫".into(),
             heading_collapsed_dot: "·".into(),
@@ -345,7 +357,10 @@ impl Symbols {
             callout_bug: "\u{f188}".into(),
             callout_example: "\u{f0ca}".into(),
             callout_quote: "\u{f10d}".into(),
+            embed_image: "\u{f03e}".into(),
+            embed_note: "\u{f0c1}".into(),
             horizontal_rule: "═".into(),
+            thematic_break: "─".into(),
             folder_expanded_collapsed: "\u{f07c}".into(),
             folder_collapsed_collapsed: "\u{f07b}".into(),
             heading_collapsed_dot: "·".into(),
@@ -417,7 +432,10 @@ pub struct TomlSymbols {
     callout_bug: Option<String>,
     callout_example: Option<String>,
     callout_quote: Option<String>,
+    embed_image: Option<String>,
+    embed_note: Option<String>,
     horizontal_rule: Option<String>,
+    thematic_break: Option<String>,
     folder_expanded_collapsed: Option<String>,
     folder_collapsed_collapsed: Option<String>,
     heading_collapsed_dot: Option<String>,
@@ -1,5 +1,6 @@
 use std::{collections::HashMap, fs::read_to_string, str::FromStr};
 
+use basalt_core::markdown::highlight::TokenKind;
 use etcetera::{choose_base_strategy, BaseStrategy};
 use ratatui::{style::Color, widgets, widgets::Borders};
 use serde::Deserialize;
@@ -32,6 +33,16 @@ pub struct Theme {
     pub heading_6: Color,
     /// Background for fenced code blocks.
     pub code_bg: Color,
+    /// Syntax highlighting token colours for fenced code blocks.
+    pub code_comment: Color,
+    pub code_keyword: Color,
+    pub code_string: Color,
+    pub code_constant: Color,
+    pub code_function: Color,
+    pub code_type: Color,
+    /// Editor search matches: background and text.
+    pub search_match_bg: Color,
+    pub search_match_fg: Color,
     /// Block-quote bar and text.
     pub blockquote: Color,
     /// List bullets and ordered-list numbers.
@@ -187,6 +198,14 @@ impl Default for Theme {
             heading_5: Color::Reset,
             heading_6: Color::Reset,
             code_bg: Color::Black,
+            code_comment: Color::DarkGray,
+            code_keyword: Color::Magenta,
+            code_string: Color::Green,
+            code_constant: Color::Yellow,
+            code_function: Color::Blue,
+            code_type: Color::Cyan,
+            search_match_bg: Color::Yellow,
+            search_match_fg: Color::Black,
             blockquote: Color::Magenta,
             list_marker: Color::DarkGray,
             task: Color::Magenta,
@@ -221,6 +240,19 @@ impl Theme {
             _ => self.text,
         }
     }
+
+    /// Code block colour for a highlighted token; plain tokens use [`text`](Theme::text).
+    pub fn code_token(&self, kind: TokenKind) -> Color {
+        match kind {
+            TokenKind::Plain => self.text,
+            TokenKind::Comment => self.code_comment,
+            TokenKind::Keyword => self.code_keyword,
+            TokenKind::String => self.code_string,
+            TokenKind::Constant => self.code_constant,
+            TokenKind::Function => self.code_function,
+            TokenKind::Type => self.code_type,
+        }
+    }
 }
 
 /// A theme as written in a TOML file: a `[palette]` of named colours, a value
@@ -248,6 +280,14 @@ struct TomlTheme {
     heading_5: Option<String>,
     heading_6: Option<String>,
     code_bg: Option<String>,
+    code_comment: Option<String>,
+    code_keyword: Option<String>,
+    code_string: Option<String>,
+    code_constant: Option<String>,
+    code_function: Option<String>,
+    code_type: Option<String>,
+    search_match_bg: Option<String>,
+    search_match_fg: Option<String>,
     blockquote: Option<String>,
     list_marker: Option<String>,
     task: Option<String>,
@@ -338,6 +378,14 @@ impl From<TomlTheme> for Theme {
             heading_5: color(value.heading_5, default.heading_5),
             heading_6: color(value.heading_6, default.heading_6),
             code_bg: color(value.code_bg, default.code_bg),
+            code_comment: color(value.code_comment, default.code_comment),
+            code_keyword: color(value.code_keyword, default.code_keyword),
+            code_string: color(value.code_string, default.code_string),
+            code_constant: color(value.code_constant, default.code_constant),
+            code_function: color(value.code_function, default.code_function),
+            code_type: color(value.code_type, default.code_type),
+            search_match_bg: color(value.search_match_bg, default.search_match_bg),
+            search_match_fg: color(value.search_match_fg, default.search_match_fg),
             blockquote: color(value.blockquote, default.blockquote),
             list_marker: color(value.list_marker, default.list_marker),
             task: color(value.task, default.task),
@@ -630,4 +678,26 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn builtins_resolve_code_token_colours() {
+        for (name, theme) in load_themes()
+            .into_iter()
+            .filter(|(name, _)| name != "default")
+        {
+            for kind in [
+                TokenKind::Comment,
+                TokenKind::Keyword,
+                TokenKind::String,
+                TokenKind::Constant,
+                TokenKind::Function,
+                TokenKind::Type,
+            ] {
+                assert!(
+                    matches!(theme.code_token(kind), Color::Rgb(..)),
+                    "{name} has no palette colour for {kind:?}"
+                );
+            }
+        }
+    }
 }
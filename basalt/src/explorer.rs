@@ -6,11 +6,16 @@ pub use item::Item;
 use ratatui::layout::Position;
 use ratatui::layout::Size;
 use ratatui::widgets::Borders;
+pub use state::Clipboard;
 pub use state::ExplorerState;
 pub use state::Sort;
 pub use state::Visibility;
 
-use std::{marker::PhantomData, path::PathBuf};
+use std::{
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 use ratatui::{
     buffer::Buffer,
@@ -27,6 +32,7 @@ use crate::config::{Symbols, Theme};
 use crate::input;
 use crate::input::InputModalConfig;
 use crate::outline;
+use crate::toast;
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Message {
@@ -38,6 +44,10 @@ pub enum Message {
     Toggle,
     ToggleOutline,
     ToggleInputRename,
+    ToggleInputDelete,
+    Cut,
+    Copy,
+    Paste,
     HidePane,
     ExpandPane,
     SwitchPaneNext,
@@ -48,6 +58,27 @@ pub enum Message {
     ScrollToBottom,
 }
 
+/// Opens the input modal right below the given item.
+fn open_input<'a>(
+    state: &ExplorerState,
+    item: &Item,
+    label: &str,
+    input: &str,
+    callback: input::Callback,
+) -> AppMessage<'a> {
+    let selected_index = state.list_state.selected().unwrap_or(0);
+    AppMessage::Input(input::Message::Open(InputModalConfig {
+        // Offset of 2 is used to move the area two rows down so that the original row is visible.
+        position: Position::from((
+            2 + (item.depth() * 2) as u16,
+            (selected_index + 2).saturating_sub(state.list_state.offset()) as u16,
+        )),
+        label: label.to_string(),
+        initial_input: input.to_string(),
+        callback,
+    }))
+}
+
 pub fn update<'a>(
     message: &Message,
     screen_size: Size,
@@ -85,7 +116,6 @@ pub fn update<'a>(
         }
         Message::ToggleInputRename => {
             if let Some(current_item) = state.current_item() {
-                let selected_index = state.list_state.selected().unwrap_or(0);
                 let (label, input, callback) = match current_item {
                     Item::File { note, .. } => {
                         let input = note.name();
@@ -97,18 +127,46 @@ pub fn update<'a>(
                         input::Callback::RenameDir(Directory::new(name, path)),
                     ),
                 };
-                return Some(AppMessage::Input(input::Message::Open(InputModalConfig {
-                    // Offset of 2 is used to move the area two rows down so that the original row is visible.
-                    position: Position::from((
-                        2 + (current_item.depth() * 2) as u16,
-                        (selected_index + 2).saturating_sub(state.list_state.offset()) as u16,
-                    )),
-                    label: label.to_string(),
-                    initial_input: input.to_string(),
-                    callback,
-                })));
+                return Some(open_input(state, current_item, label, input, callback));
+            }
+        }
+        Message::ToggleInputDelete => {
+            if let Some(current_item) = state.current_item() {
+                let (label, name, callback) = match current_item {
+                    Item::File { note, .. } => (
+                        "Delete",
+                        note.name(),
+                        input::Callback::DeleteNote(note.clone()),
+                    ),
+                    Item::Directory { name, path, .. } => (
+                        "Delete Directory",
+                        name.as_str(),
+                        input::Callback::DeleteDir(Directory::new(name, path)),
+                    ),
+                };
+                let question = format!("Delete \"{name}\"?");
+                return Some(open_input(state, current_item, label, &question, callback));
             }
         }
+        Message::Cut => {
+            let item = state.current_item()?.clone();
+            let toast = format!("Cut \"{}\"", item.name());
+            state.clipboard = Some(Clipboard::Cut(item));
+            return Some(AppMessage::Toast(toast::Message::Create(
+                toast::Toast::info(&toast, Duration::from_secs(2)),
+            )));
+        }
+        Message::Copy => {
+            let item = state.current_item()?.clone();
+            let toast = format!("Copied \"{}\"", item.name());
+            state.clipboard = Some(Clipboard::Copy(item));
+            return Some(AppMessage::Toast(toast::Message::Create(
+                toast::Toast::info(&toast, Duration::from_secs(2)),
+            )));
+        }
+        Message::Paste => {
+            return state.clipboard.clone().map(AppMessage::Paste);
+        }
         Message::Open => {
             if state.open().is_some_and(|v| v) {
                 let note = state.selected_note.as_ref()?;
@@ -142,9 +200,16 @@ impl Explorer<'_> {
         symbols: &'a Symbols,
         theme: &'a Theme,
         selected_path: Option<PathBuf>,
+        clipboard_path: Option<&'a Path>,
         is_open: bool,
     ) -> impl Fn(&'a (Item, usize)) -> ListItem<'a> {
         move |(item, depth)| {
+            // Items waiting in the clipboard are shown in italics.
+            let style = if clipboard_path == Some(item.path()) {
+                Style::new().italic()
+            } else {
+                Style::new()
+            };
             let indentation = if *depth > 0 {
                 Span::raw(format!("{} ", symbols.tree_indent).repeat(*depth)).black()
             } else {
@@ -174,6 +239,7 @@ impl Explorer<'_> {
                         (false, true) => [symbols.selected.clone().into()].to_vec(),
                         (false, false) => [symbols.unselected.clone().fg(theme.muted)].to_vec(),
                     }))
+                    .style(style)
                 }
                 Item::Directory { expanded, name, .. } => {
                     ListItem::new(Line::from(match (is_open, expanded) {
@@ -196,6 +262,7 @@ impl Explorer<'_> {
                             [symbols.folder_collapsed_collapsed.clone().fg(theme.muted)].to_vec()
                         }
                     }))
+                    .style(style)
                 }
             }
         }
@@ -246,6 +313,10 @@ impl<'a> StatefulWidget for Explorer<'a> {
                 &state.symbols,
                 &state.theme,
                 state.selected_path(),
+                state
+                    .clipboard
+                    .as_ref()
+                    .map(|clipboard| clipboard.item().path()),
                 state.is_open(),
             ))
             .collect();
@@ -330,6 +401,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cut_and_copy_mark_item_for_paste() {
+        let note = Note::new_unchecked("Note", Path::new("Note.md"));
+        let mut state = ExplorerState::new(
+            "Test",
+            vec![VaultEntry::File(note.clone())],
+            &Symbols::unicode(),
+        );
+
+        assert_eq!(update(&Message::Paste, Size::default(), &mut state), None);
+
+        update(&Message::Cut, Size::default(), &mut state);
+        let Some(Clipboard::Cut(item)) = state.clipboard.clone() else {
+            panic!("expected the note to be cut");
+        };
+        assert_eq!(item.path(), note.path());
+
+        update(&Message::Copy, Size::default(), &mut state);
+        assert_eq!(
+            update(&Message::Paste, Size::default(), &mut state),
+            Some(AppMessage::Paste(Clipboard::Copy(item)))
+        );
+    }
+
     #[test]
     fn test_render_entries() {
         let tests = [
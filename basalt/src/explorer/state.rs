@@ -10,6 +10,21 @@ use crate::config::{Symbols, Theme};
 
 use super::Item;
 
+/// An explorer item marked with cut or copy, waiting to be pasted into another directory.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Clipboard {
+    Cut(Item),
+    Copy(Item),
+}
+
+impl Clipboard {
+    pub fn item(&self) -> &Item {
+        match self {
+            Self::Cut(item) | Self::Copy(item) => item,
+        }
+    }
+}
+
 #[derive(Debug, Default, Copy, Clone, PartialEq)]
 pub enum Sort {
     #[default]
@@ -37,6 +52,7 @@ pub struct ExplorerState {
     pub(crate) active: bool,
     pub(crate) sort: Sort,
     pub(crate) list_state: ListState,
+    pub(crate) clipboard: Option<Clipboard>,
 
     pub(crate) symbols: Symbols,
     pub(crate) theme: Theme,
@@ -0,0 +1,120 @@
+/// Score of every matched character.
+const MATCH_SCORE: i64 = 16;
+
+/// Bonus for a character matched right after the previous matched character.
+const CONSECUTIVE_BONUS: i64 = 24;
+
+/// Bonus for a character matched at the start of a word, e.g. after `/`, `-` or a space.
+const WORD_START_BONUS: i64 = 20;
+
+/// Highest penalty for the characters skipped between two matched characters.
+const MAX_GAP_PENALTY: i64 = 12;
+
+/// A fuzzy match of a query against a text.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FuzzyMatch {
+    /// Relevance of the match. Higher is better.
+    pub score: i64,
+    /// Byte offsets of the matched characters within the text.
+    pub indices: Vec<usize>,
+}
+
+fn is_word_start(previous: Option<char>, current: char) -> bool {
+    match previous {
+        None => true,
+        Some(previous) => {
+            matches!(previous, '/' | '\\' | ' ' | '-' | '_' | '.')
+                || (previous.is_lowercase() && current.is_uppercase())
+        }
+    }
+}
+
+fn eq_ignore_case(a: char, b: char) -> bool {
+    a == b || a.to_lowercase().eq(b.to_lowercase())
+}
+
+/// Matches the query greedily from the character at `start`, the index of a character of the
+/// text.
+fn match_from(query: &[char], text: &str, start: usize) -> Option<FuzzyMatch> {
+    let mut query = query.iter().peekable();
+    let mut result = FuzzyMatch::default();
+
+    let mut previous = None;
+    let mut last_match: Option<usize> = None;
+    for (index, (offset, c)) in text.char_indices().enumerate() {
+        let Some(&&wanted) = query.peek() else {
+            break;
+        };
+
+        if index >= start && eq_ignore_case(c, wanted) {
+            query.next();
+            result.score += MATCH_SCORE;
+            if is_word_start(previous, c) {
+                result.score += WORD_START_BONUS;
+            }
+            result.score += match last_match {
+                Some(last) if last + 1 == index => CONSECUTIVE_BONUS,
+                Some(last) => -((index - last - 1) as i64).min(MAX_GAP_PENALTY),
+                None => -(index as i64).min(MAX_GAP_PENALTY),
+            };
+            result.indices.push(offset);
+            last_match = Some(index);
+        }
+
+        previous = Some(c);
+    }
+
+    query.peek().is_none().then_some(result)
+}
+
+/// Matches the query against the text as a case-insensitive subsequence: every character of the
+/// query has to appear in the text in the same order, but not necessarily next to each other.
+/// Whitespace in the query is ignored.
+///
+/// Matches with consecutive characters and characters at the start of words score higher, and
+/// skipped characters lower the score. Every occurrence of the first query character is tried as
+/// the start of the match, so `meet` matches the second word of `Team meeting`. An empty query
+/// matches everything with a score of `0`.
+pub fn fuzzy_match(query: &str, text: &str) -> Option<FuzzyMatch> {
+    let query: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).collect();
+    let Some(&first) = query.first() else {
+        return Some(FuzzyMatch::default());
+    };
+
+    text.chars()
+        .enumerate()
+        .filter(|(_, c)| eq_ignore_case(*c, first))
+        .map_while(|(start, _)| match_from(&query, text, start))
+        .max_by(|a, b| {
+            a.score
+                .cmp(&b.score)
+                .then_with(|| b.indices.cmp(&a.indices))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_match() {
+        assert_eq!(fuzzy_match("", "anything").map(|m| m.score), Some(0));
+        assert_eq!(fuzzy_match("xyz", "Daily note"), None);
+        assert_eq!(
+            fuzzy_match("dn", "Daily note").map(|m| m.indices),
+            Some(vec![0, 6])
+        );
+        assert_eq!(
+            fuzzy_match("daily NOTE", "Daily note").map(|m| m.indices),
+            Some(vec![0, 1, 2, 3, 4, 6, 7, 8, 9])
+        );
+
+        let score = |query, text| fuzzy_match(query, text).unwrap().score;
+        assert!(score("note", "Notes") > score("note", "Another one"));
+        assert!(score("pn", "projects/notes") > score("pn", "happen"));
+        assert_eq!(
+            fuzzy_match("meet", "Team meeting").map(|m| m.indices),
+            Some(vec![5, 6, 7, 8])
+        );
+    }
+}
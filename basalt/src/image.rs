@@ -0,0 +1,523 @@
+//! Inline images of the note editor, enabled with the `images` cargo feature.
+//!
+//! An image embed on a line of its own (`![[image.png]]` or `![alt](image.png)`) is resolved
+//! against the vault and laid out as rows of blank cells below its caption. Terminals that speak a
+//! graphics [`Protocol`] get the image written on top of those cells after each frame, see
+//! [`Graphics`]; any other terminal gets the image drawn into the cells with half blocks.
+pub mod encode;
+pub mod protocol;
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use ::image::{imageops::FilterType, RgbaImage};
+use basalt_core::obsidian::vault::relative_path;
+use ratatui::{
+    buffer::Buffer,
+    crossterm::{cursor::MoveTo, queue, terminal},
+    layout::Rect,
+};
+use tracing::warn;
+
+use crate::config::env::SystemEnv;
+
+pub use protocol::Protocol;
+
+/// Most rows an image takes in the note editor.
+pub const MAX_ROWS: u16 = 20;
+
+/// Size of a terminal cell in pixels when the terminal does not report it.
+const DEFAULT_CELL_SIZE: (u16, u16) = (8, 16);
+
+/// Most encoded images kept around, so scrolling back and forth does not encode them again.
+const MAX_CACHED_SEQUENCES: usize = 32;
+
+/// Most decoded images kept around, scaled to the size they are shown at.
+const MAX_CACHED_IMAGES: usize = 16;
+
+/// Returns the image protocol of the terminal, detected once from the environment.
+pub fn protocol() -> Protocol {
+    static PROTOCOL: OnceLock<Protocol> = OnceLock::new();
+    *PROTOCOL.get_or_init(|| protocol::detect(SystemEnv))
+}
+
+/// Returns the size of a terminal cell in image pixels. Half blocks fit two pixels in a cell; the
+/// graphics protocols use the cell size reported by the terminal.
+pub fn cell_size(protocol: Protocol) -> (u16, u16) {
+    static CELL_SIZE: OnceLock<(u16, u16)> = OnceLock::new();
+
+    if !protocol.is_graphics() {
+        return (1, 2);
+    }
+
+    *CELL_SIZE.get_or_init(|| {
+        terminal::window_size()
+            .ok()
+            .filter(|size| size.columns > 0 && size.rows > 0)
+            .map(|size| (size.width / size.columns, size.height / size.rows))
+            .filter(|(width, height)| *width > 0 && *height > 0)
+            .unwrap_or(DEFAULT_CELL_SIZE)
+    })
+}
+
+/// Returns the size in cells, as `(cols, rows)`, of an image of the given pixel dimensions. The
+/// image keeps its own size when it fits, and is scaled down to `max_cols` columns and
+/// [`MAX_ROWS`] rows, keeping its aspect ratio.
+pub fn fit(dimensions: (u32, u32), cell_size: (u16, u16), max_cols: u16) -> (u16, u16) {
+    let (width, height) = (dimensions.0.max(1) as f64, dimensions.1.max(1) as f64);
+    let (cell_width, cell_height) = (cell_size.0.max(1) as f64, cell_size.1.max(1) as f64);
+    let rows_for = |cols: f64| (cols * cell_width * height / width / cell_height).ceil();
+
+    let mut cols = (width / cell_width).ceil().min(max_cols as f64).max(1.0);
+    let mut rows = rows_for(cols);
+
+    if rows > MAX_ROWS as f64 {
+        rows = MAX_ROWS as f64;
+        cols = (rows * cell_height * width / height / cell_width)
+            .floor()
+            .max(1.0);
+    }
+
+    (cols as u16, rows.max(1.0) as u16)
+}
+
+/// Resolves an embed target to an image file the way Obsidian does: relative to the note, then
+/// relative to the vault root, and last by file name anywhere in the vault. Remote images and
+/// targets leading outside the vault are not resolved.
+pub fn resolve(vault_path: &Path, note_dir: &Path, target: &str) -> Option<PathBuf> {
+    let target = percent_decode(target.split(['#', '?']).next().unwrap_or(target));
+    if target.contains("://") {
+        return None;
+    }
+
+    let relative = Path::new(target.trim_start_matches('/'));
+    let from_note = note_dir
+        .strip_prefix(vault_path)
+        .ok()
+        .and_then(|note_dir| relative_path(note_dir.join(relative)));
+
+    [from_note, relative_path(relative)]
+        .into_iter()
+        .flatten()
+        .map(|relative| vault_path.join(relative))
+        .find(|path| path.is_file())
+        .or_else(|| find_by_name(vault_path, relative.file_name()?.to_str()?))
+}
+
+/// Finds the shallowest file with the given name in the directory, skipping hidden directories.
+fn find_by_name(dir: &Path, name: &str) -> Option<PathBuf> {
+    let mut dirs = VecDeque::from([dir.to_path_buf()]);
+
+    while let Some(dir) = dirs.pop_front() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut entries: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+        entries.sort();
+
+        for path in entries {
+            let file_name = path.file_name().and_then(|name| name.to_str());
+            if path.is_dir() && !file_name.is_some_and(|name| name.starts_with('.')) {
+                dirs.push_back(path);
+            } else if path.is_file() && file_name == Some(name) {
+                return Some(path);
+            }
+        }
+    }
+
+    None
+}
+
+/// Decodes `%XX` escapes of a markdown link destination.
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let escape = (bytes[i] == b'%')
+            .then(|| value.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match escape {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Decodes the image file.
+fn load(path: &Path) -> Option<RgbaImage> {
+    ::image::open(path)
+        .inspect_err(|error| warn!(?error, ?path, "failed to decode image"))
+        .ok()
+        .map(|image| image.into_rgba8())
+}
+
+/// Decodes the image file and scales it to the given size in cells.
+fn load_scaled(path: &Path, cols: u16, rows: u16, cell_size: (u16, u16)) -> Option<RgbaImage> {
+    let image = load(path)?;
+    Some(::image::imageops::resize(
+        &image,
+        cols as u32 * cell_size.0 as u32,
+        rows as u32 * cell_size.1 as u32,
+        FilterType::Triangle,
+    ))
+}
+
+/// Path and pixel dimensions of a resolved image embed.
+type ResolvedImage = (PathBuf, (u32, u32));
+
+/// An image laid out in the note editor.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Placement {
+    pub path: PathBuf,
+    /// Index of the first document line reserved for the image.
+    pub line: usize,
+    pub cols: u16,
+    pub rows: u16,
+}
+
+/// The on-screen part of a [`Placement`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Visible {
+    pub path: PathBuf,
+    pub cols: u16,
+    pub rows: u16,
+    /// The visible cells relative to the top left cell of the image.
+    pub crop: Rect,
+    /// The visible cells on the screen.
+    pub area: Rect,
+}
+
+/// The image embeds of a note: where they resolve to, where they are laid out and which of them
+/// are on the screen.
+#[derive(Clone, Debug, Default)]
+pub struct Embeds {
+    vault_path: PathBuf,
+    note_dir: PathBuf,
+    /// Resolved path and pixel dimensions by embed target; `None` if the target is not a
+    /// readable image.
+    images: HashMap<String, Option<ResolvedImage>>,
+    /// Half-block pixels by path and size in cells.
+    pixels: HashMap<(PathBuf, u16, u16), Option<RgbaImage>>,
+    placements: Vec<Placement>,
+    visible: Vec<Visible>,
+}
+
+impl Embeds {
+    /// Sets the vault and the note the embeds resolve against. Returns `true` if they changed.
+    pub fn set_note_path(&mut self, vault_path: &Path, note_path: &Path) -> bool {
+        let note_dir = note_path.parent().unwrap_or(vault_path);
+        if self.vault_path == vault_path && self.note_dir == note_dir {
+            return false;
+        }
+
+        *self = Self {
+            vault_path: vault_path.to_path_buf(),
+            note_dir: note_dir.to_path_buf(),
+            ..Default::default()
+        };
+        true
+    }
+
+    pub fn placements(&self) -> &[Placement] {
+        &self.placements
+    }
+
+    /// Returns the images written with a graphics protocol on the last render.
+    pub fn visible(&self) -> &[Visible] {
+        &self.visible
+    }
+
+    pub fn clear_placements(&mut self) {
+        self.placements.clear();
+    }
+
+    /// Lays out the image of the embed target starting at the given document line. Returns the
+    /// rows taken by the image, or `None` if the target is not an image in the vault.
+    pub fn place(&mut self, target: &str, line: usize, max_cols: u16) -> Option<u16> {
+        if self.vault_path.as_os_str().is_empty() || max_cols == 0 {
+            return None;
+        }
+
+        let (vault_path, note_dir) = (&self.vault_path, &self.note_dir);
+        let (path, dimensions) = self
+            .images
+            .entry(target.to_string())
+            .or_insert_with(|| {
+                let path = resolve(vault_path, note_dir, target)?;
+                let dimensions = ::image::image_dimensions(&path)
+                    .inspect_err(|error| warn!(?error, ?path, "failed to read image"))
+                    .ok()?;
+                Some((path, dimensions))
+            })
+            .clone()?;
+
+        let (cols, rows) = fit(dimensions, cell_size(protocol()), max_cols);
+        self.placements.push(Placement {
+            path,
+            line,
+            cols,
+            rows,
+        });
+
+        Some(rows)
+    }
+
+    /// Draws the images that are on the screen. `top` is the document line at the top of `area`,
+    /// negative while lines above the document are shown, and `left` the horizontal scroll.
+    pub fn render(
+        &mut self,
+        area: Rect,
+        buf: &mut Buffer,
+        top: isize,
+        left: u16,
+        protocol: Protocol,
+    ) {
+        self.visible.clear();
+
+        for placement in &self.placements {
+            let y = placement.line as isize - top;
+            let x = -(left as isize);
+            let image = Rect::new(0, 0, placement.cols, placement.rows);
+
+            // The visible cells relative to the image: its overlap with the area.
+            let crop_x = (-x).max(0) as u16;
+            let crop_y = (-y).max(0) as u16;
+            let crop = Rect::new(
+                crop_x,
+                crop_y,
+                (area.width as isize - x.max(0)).max(0) as u16,
+                (area.height as isize - y.max(0)).max(0) as u16,
+            )
+            .intersection(image);
+
+            if crop.is_empty() {
+                continue;
+            }
+
+            let screen = Rect::new(
+                area.x + (x + crop_x as isize) as u16,
+                area.y + (y + crop_y as isize) as u16,
+                crop.width,
+                crop.height,
+            );
+
+            if protocol.is_graphics() {
+                self.visible.push(Visible {
+                    path: placement.path.clone(),
+                    cols: placement.cols,
+                    rows: placement.rows,
+                    crop,
+                    area: screen,
+                });
+                continue;
+            }
+
+            if self.pixels.len() > MAX_CACHED_IMAGES {
+                self.pixels.clear();
+            }
+
+            let pixels = self
+                .pixels
+                .entry((placement.path.clone(), placement.cols, placement.rows))
+                .or_insert_with(|| {
+                    load_scaled(
+                        &placement.path,
+                        placement.cols,
+                        placement.rows,
+                        cell_size(protocol),
+                    )
+                });
+
+            if let Some(pixels) = pixels {
+                encode::halfblocks(pixels, crop, screen, buf);
+            }
+        }
+    }
+}
+
+/// The images written to the terminal with a graphics protocol. Written images are not part of
+/// the terminal buffer, so they are written again only when they move, and removed before that.
+#[derive(Clone, Debug, Default)]
+pub struct Graphics {
+    shown: Vec<Visible>,
+    /// Decoded images by path and size in cells, scaled to that size.
+    scaled: HashMap<(PathBuf, u16, u16), Option<RgbaImage>>,
+    sequences: HashMap<Visible, String>,
+}
+
+impl Graphics {
+    /// Returns `true` if the shown images must be wiped by redrawing the whole screen before the
+    /// given images are written.
+    pub fn needs_clear(&self, protocol: Protocol, visible: &[Visible]) -> bool {
+        protocol.needs_clear() && !self.shown.is_empty() && self.shown != visible
+    }
+
+    /// Forgets the shown images, after the screen was cleared.
+    pub fn reset(&mut self) {
+        self.shown.clear();
+    }
+
+    /// Writes the given images if they differ from the shown ones.
+    pub fn draw(
+        &mut self,
+        protocol: Protocol,
+        writer: &mut impl Write,
+        visible: &[Visible],
+    ) -> io::Result<()> {
+        if !protocol.is_graphics() || self.shown == visible {
+            return Ok(());
+        }
+
+        if protocol == Protocol::Kitty && !self.shown.is_empty() {
+            write!(writer, "{}", encode::KITTY_DELETE_ALL)?;
+        }
+
+        if self.sequences.len() > MAX_CACHED_SEQUENCES {
+            self.sequences.clear();
+        }
+        if self.scaled.len() > MAX_CACHED_IMAGES {
+            self.scaled.clear();
+        }
+
+        // Saving and restoring the cursor keeps it where the frame left it.
+        write!(writer, "\x1b7")?;
+        for image in visible {
+            if !self.sequences.contains_key(image) {
+                let Some(sequence) = self.encode(protocol, image) else {
+                    continue;
+                };
+                self.sequences.insert(image.clone(), sequence);
+            }
+
+            queue!(writer, MoveTo(image.area.x, image.area.y))?;
+            write!(writer, "{}", self.sequences[image])?;
+        }
+        write!(writer, "\x1b8")?;
+        writer.flush()?;
+
+        self.shown = visible.to_vec();
+        Ok(())
+    }
+
+    fn encode(&mut self, protocol: Protocol, image: &Visible) -> Option<String> {
+        let scaled = self
+            .scaled
+            .entry((image.path.clone(), image.cols, image.rows))
+            .or_insert_with(|| {
+                load_scaled(&image.path, image.cols, image.rows, cell_size(protocol))
+            })
+            .as_ref()?;
+
+        let (cell_width, cell_height) = cell_size(protocol);
+        let (cell_width, cell_height) = (cell_width as u32, cell_height as u32);
+        let cropped = ::image::imageops::crop_imm(
+            scaled,
+            image.crop.x as u32 * cell_width,
+            image.crop.y as u32 * cell_height,
+            image.crop.width as u32 * cell_width,
+            image.crop.height as u32 * cell_height,
+        )
+        .to_image();
+
+        match protocol {
+            Protocol::Kitty => encode::kitty(&cropped, image.area.width, image.area.height),
+            Protocol::Iterm2 => encode::iterm2(&cropped, image.area.width, image.area.height),
+            Protocol::Sixel => Some(encode::sixel(&cropped)),
+            Protocol::Halfblocks => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit() {
+        // Small images keep their size.
+        assert_eq!(fit((80, 32), (8, 16), 100), (10, 2));
+        // Wide images are scaled down to the width.
+        assert_eq!(fit((1600, 400), (8, 16), 50), (50, 7));
+        // Tall images are scaled down to the most rows.
+        assert_eq!(fit((400, 1600), (8, 16), 50), (10, MAX_ROWS));
+        // Half blocks fit two pixels in a cell.
+        assert_eq!(fit((40, 20), (1, 2), 100), (40, 10));
+    }
+
+    #[test]
+    fn test_resolve_stays_in_the_vault() {
+        let vault = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+
+        assert_eq!(
+            resolve(&vault, &vault.join("image"), "../image.rs"),
+            Some(vault.join("image.rs"))
+        );
+        assert_eq!(
+            resolve(&vault, &vault.join("image"), "/image.rs"),
+            Some(vault.join("image.rs"))
+        );
+        assert_eq!(resolve(&vault, &vault, "../Cargo.toml"), None);
+        assert_eq!(
+            resolve(&vault, &vault.join("image"), "../../Cargo.toml"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("a%20cat.png"), "a cat.png");
+        assert_eq!(percent_decode("100%.png"), "100%.png");
+        assert_eq!(percent_decode("%E2%9C%93.png"), "✓.png");
+    }
+
+    #[test]
+    fn test_render_crops_to_the_visible_area() {
+        let mut embeds = Embeds {
+            placements: vec![Placement {
+                path: PathBuf::from("image.png"),
+                line: 4,
+                cols: 10,
+                rows: 6,
+            }],
+            ..Default::default()
+        };
+        let area = Rect::new(2, 1, 8, 8);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 20, 20));
+
+        embeds.render(area, &mut buf, 6, 0, Protocol::Kitty);
+        assert_eq!(
+            embeds.visible(),
+            [Visible {
+                path: PathBuf::from("image.png"),
+                cols: 10,
+                rows: 6,
+                crop: Rect::new(0, 2, 8, 4),
+                area: Rect::new(2, 1, 8, 4),
+            }]
+        );
+
+        embeds.render(area, &mut buf, -2, 3, Protocol::Kitty);
+        assert_eq!(embeds.visible()[0].crop, Rect::new(3, 0, 7, 2));
+        assert_eq!(embeds.visible()[0].area, Rect::new(2, 7, 7, 2));
+
+        embeds.render(area, &mut buf, 10, 0, Protocol::Kitty);
+        assert_eq!(embeds.visible(), []);
+    }
+}
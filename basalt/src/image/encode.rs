@@ -0,0 +1,298 @@
+//! Encoders of images for each [`Protocol`](super::Protocol). Every encoder takes the image
+//! already scaled and cropped to the cells it covers.
+use std::{collections::BTreeSet, fmt::Write, io::Cursor};
+
+use ::image::{ImageFormat, RgbaImage};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ratatui::{buffer::Buffer, layout::Rect, style::Color};
+
+/// Deletes every image placed on the screen with the kitty graphics protocol.
+pub const KITTY_DELETE_ALL: &str = "\x1b_Ga=d,d=A,q=2\x1b\\";
+
+/// Size of a base64 chunk of a kitty graphics command.
+const KITTY_CHUNK_SIZE: usize = 4096;
+
+/// Pixels with less opacity are left out of sixel and half-block images.
+const ALPHA_THRESHOLD: u8 = 128;
+
+fn png(image: &RgbaImage) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+        .ok()?;
+    Some(bytes)
+}
+
+/// Encodes the image as kitty graphics commands that transmit a PNG and place it over `cols` by
+/// `rows` cells at the cursor, without moving the cursor.
+pub fn kitty(image: &RgbaImage, cols: u16, rows: u16) -> Option<String> {
+    let data = STANDARD.encode(png(image)?);
+    let chunks: Vec<&[u8]> = data.as_bytes().chunks(KITTY_CHUNK_SIZE).collect();
+    let mut sequence = String::with_capacity(data.len() + chunks.len() * 16);
+
+    for (index, chunk) in chunks.iter().enumerate() {
+        let more = (index + 1 < chunks.len()) as u8;
+        let chunk = std::str::from_utf8(chunk).ok()?;
+
+        if index == 0 {
+            let _ = write!(
+                sequence,
+                "\x1b_Gf=100,a=T,q=2,c={cols},r={rows},C=1,m={more};{chunk}\x1b\\"
+            );
+        } else {
+            let _ = write!(sequence, "\x1b_Gm={more};{chunk}\x1b\\");
+        }
+    }
+
+    Some(sequence)
+}
+
+/// Encodes the image as an iTerm2 inline image of `cols` by `rows` cells.
+pub fn iterm2(image: &RgbaImage, cols: u16, rows: u16) -> Option<String> {
+    let png = png(image)?;
+    Some(format!(
+        "\x1b]1337;File=inline=1;size={};width={cols};height={rows};preserveAspectRatio=0;doNotMoveCursor=1:{}\x07",
+        png.len(),
+        STANDARD.encode(&png)
+    ))
+}
+
+/// Index of the colour in the 6×6×6 colour cube of the sixel palette.
+fn sixel_color(pixel: [u8; 4]) -> Option<u8> {
+    if pixel[3] < ALPHA_THRESHOLD {
+        return None;
+    }
+
+    let level = |value: u8| ((value as u16 * 5 + 127) / 255) as u8;
+    Some(level(pixel[0]) * 36 + level(pixel[1]) * 6 + level(pixel[2]))
+}
+
+/// Appends a run of sixel characters, using a repeat for runs longer than three.
+fn push_run(sequence: &mut String, sixel: char, count: usize) {
+    match count {
+        0 => {}
+        1..=3 => (0..count).for_each(|_| sequence.push(sixel)),
+        _ => {
+            let _ = write!(sequence, "!{count}{sixel}");
+        }
+    }
+}
+
+/// Encodes the image as DEC sixel graphics with the colours reduced to a 6×6×6 colour cube.
+/// Transparent pixels are left out.
+pub fn sixel(image: &RgbaImage) -> String {
+    let (width, height) = image.dimensions();
+    let colors: Vec<Option<u8>> = image.pixels().map(|pixel| sixel_color(pixel.0)).collect();
+    let color_at = |x: u32, y: u32| colors[(y * width + x) as usize];
+
+    let mut sequence = format!("\x1bP0;1;0q\"1;1;{width};{height}");
+
+    let palette: BTreeSet<u8> = colors.iter().flatten().copied().collect();
+    for &color in &palette {
+        let percent = |level: u8| level as u16 * 100 / 5;
+        let _ = write!(
+            sequence,
+            "#{color};2;{};{};{}",
+            percent(color / 36),
+            percent(color / 6 % 6),
+            percent(color % 6)
+        );
+    }
+
+    for band in (0..height).step_by(6) {
+        let band_height = (height - band).min(6);
+        let band_colors: BTreeSet<u8> = (band..band + band_height)
+            .flat_map(|y| (0..width).filter_map(move |x| color_at(x, y)))
+            .collect();
+
+        for (index, &color) in band_colors.iter().enumerate() {
+            if index > 0 {
+                sequence.push('$');
+            }
+            let _ = write!(sequence, "#{color}");
+
+            let mut run = (None, 0);
+            for x in 0..width {
+                let bits = (0..band_height)
+                    .filter(|row| color_at(x, band + row) == Some(color))
+                    .fold(0u8, |bits, row| bits | 1 << row);
+                let sixel = (63 + bits) as char;
+
+                match run {
+                    (Some(current), count) if current == sixel => run = (Some(sixel), count + 1),
+                    (current, count) => {
+                        if let Some(current) = current {
+                            push_run(&mut sequence, current, count);
+                        }
+                        run = (Some(sixel), 1);
+                    }
+                }
+            }
+
+            // A trailing run of empty sixels draws nothing.
+            if let (Some(sixel), count) = run {
+                if sixel != '?' {
+                    push_run(&mut sequence, sixel, count);
+                }
+            }
+        }
+
+        sequence.push('-');
+    }
+
+    sequence.push_str("\x1b\\");
+    sequence
+}
+
+/// Draws the `crop` cells of the image into the `area` of the buffer with half blocks: the upper
+/// half block takes the colour of the upper pixel and the background the colour of the lower
+/// one. The image holds two pixels per cell.
+pub fn halfblocks(image: &RgbaImage, crop: Rect, area: Rect, buf: &mut Buffer) {
+    let pixel = |x: u32, y: u32| {
+        image
+            .get_pixel_checked(x, y)
+            .filter(|pixel| pixel[3] >= ALPHA_THRESHOLD)
+            .map(|pixel| Color::Rgb(pixel[0], pixel[1], pixel[2]))
+    };
+
+    for row in 0..crop.height.min(area.height) {
+        for col in 0..crop.width.min(area.width) {
+            let x = (crop.x + col) as u32;
+            let y = (crop.y + row) as u32 * 2;
+
+            let Some(cell) = buf.cell_mut((area.x + col, area.y + row)) else {
+                continue;
+            };
+
+            match (pixel(x, y), pixel(x, y + 1)) {
+                (Some(upper), Some(lower)) => cell.set_char('▀').set_fg(upper).set_bg(lower),
+                (Some(upper), None) => cell.set_char('▀').set_fg(upper),
+                (None, Some(lower)) => cell.set_char('▄').set_fg(lower),
+                (None, None) => cell,
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::image::{ImageReader, Rgba};
+
+    use super::*;
+
+    const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+    const BLUE: Rgba<u8> = Rgba([0, 0, 255, 255]);
+    const CLEAR: Rgba<u8> = Rgba([0, 0, 0, 0]);
+
+    /// A sample image: the upper half red and the lower half blue.
+    fn sample(width: u32, height: u32) -> RgbaImage {
+        RgbaImage::from_fn(
+            width,
+            height,
+            |_, y| if y < height / 2 { RED } else { BLUE },
+        )
+    }
+
+    fn decode(data: &str) -> RgbaImage {
+        let bytes = STANDARD.decode(data).unwrap();
+        ImageReader::new(Cursor::new(bytes))
+            .with_guessed_format()
+            .unwrap()
+            .decode()
+            .unwrap()
+            .into_rgba8()
+    }
+
+    #[test]
+    fn test_kitty() {
+        let image = sample(4, 4);
+        let sequence = kitty(&image, 2, 1).unwrap();
+
+        let data = sequence
+            .strip_prefix("\x1b_Gf=100,a=T,q=2,c=2,r=1,C=1,m=0;")
+            .and_then(|rest| rest.strip_suffix("\x1b\\"))
+            .unwrap();
+        assert_eq!(decode(data), image);
+    }
+
+    #[test]
+    fn test_kitty_sends_large_images_in_chunks() {
+        let image = RgbaImage::from_fn(128, 128, |x, y| {
+            Rgba([(x * 7 + y) as u8, (y * 13) as u8, (x ^ y) as u8, 255])
+        });
+        let sequence = kitty(&image, 16, 8).unwrap();
+
+        let chunks: Vec<&str> = sequence
+            .split_terminator("\x1b\\")
+            .map(|command| command.split_once(';').unwrap().1)
+            .collect();
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|chunk| chunk.len() <= KITTY_CHUNK_SIZE));
+        assert_eq!(sequence.matches("m=1;").count(), chunks.len() - 1);
+        assert!(sequence.ends_with(&format!("\x1b_Gm=0;{}\x1b\\", chunks.last().unwrap())));
+        assert_eq!(decode(&chunks.concat()), image);
+    }
+
+    #[test]
+    fn test_iterm2() {
+        let image = sample(4, 4);
+        let sequence = iterm2(&image, 3, 2).unwrap();
+
+        let (header, data) = sequence
+            .strip_suffix('\x07')
+            .and_then(|rest| rest.split_once(':'))
+            .unwrap();
+        let size = STANDARD.decode(data).unwrap().len();
+        assert_eq!(
+            header,
+            format!(
+                "\x1b]1337;File=inline=1;size={size};width=3;height=2;preserveAspectRatio=0;doNotMoveCursor=1"
+            )
+        );
+        assert_eq!(decode(data), image);
+    }
+
+    #[test]
+    fn test_sixel() {
+        assert_eq!(
+            sixel(&sample(2, 6)),
+            "\x1bP0;1;0q\"1;1;2;6#5;2;0;0;100#180;2;100;0;0#5ww$#180FF-\x1b\\"
+        );
+
+        // Runs longer than three repeat, and transparent pixels are left out.
+        let image = RgbaImage::from_fn(6, 7, |x, y| if x < 5 && y < 6 { RED } else { CLEAR });
+        assert_eq!(
+            sixel(&image),
+            "\x1bP0;1;0q\"1;1;6;7#180;2;100;0;0#180!5~--\x1b\\"
+        );
+    }
+
+    #[test]
+    fn test_halfblocks() {
+        let image = RgbaImage::from_fn(2, 4, |x, y| match (x, y) {
+            (1, 3) => CLEAR,
+            (_, 0 | 1) => RED,
+            _ => BLUE,
+        });
+        let mut buf = Buffer::empty(Rect::new(0, 0, 3, 3));
+
+        halfblocks(
+            &image,
+            Rect::new(0, 0, 2, 2),
+            Rect::new(1, 1, 2, 2),
+            &mut buf,
+        );
+
+        let red = Color::Rgb(255, 0, 0);
+        let blue = Color::Rgb(0, 0, 255);
+        let cell = |x, y| {
+            let cell = &buf[(x, y)];
+            (cell.symbol().to_string(), cell.fg, cell.bg)
+        };
+        assert_eq!(cell(1, 1), ("▀".into(), red, red));
+        assert_eq!(cell(2, 1), ("▀".into(), red, red));
+        assert_eq!(cell(1, 2), ("▀".into(), blue, blue));
+        assert_eq!(cell(2, 2), ("▀".into(), blue, Color::Reset));
+        assert_eq!(cell(0, 0), (" ".into(), Color::Reset, Color::Reset));
+    }
+}
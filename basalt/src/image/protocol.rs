@@ -0,0 +1,137 @@
+use crate::config::env::Env;
+
+/// How images are drawn in the terminal.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Protocol {
+    /// The kitty graphics protocol, also spoken by Ghostty.
+    Kitty,
+    /// Inline images of iTerm2, also spoken by WezTerm.
+    Iterm2,
+    /// DEC sixel graphics.
+    Sixel,
+    /// Unicode half blocks with true colour, two pixels per cell. Works in any terminal.
+    #[default]
+    Halfblocks,
+}
+
+impl Protocol {
+    /// Returns `true` if the images are drawn with escape sequences on top of the terminal cells
+    /// rather than into the cells.
+    pub fn is_graphics(&self) -> bool {
+        !matches!(self, Self::Halfblocks)
+    }
+
+    /// Returns `true` if drawn images stay on the screen until the cells beneath them are
+    /// redrawn, so moving or hiding an image needs a full redraw. Kitty can delete its images.
+    pub fn needs_clear(&self) -> bool {
+        matches!(self, Self::Iterm2 | Self::Sixel)
+    }
+}
+
+/// Detects the image protocol of the terminal from the environment. Graphics protocols are not
+/// passed through terminal multiplexers, so tmux and screen always fall back to half blocks.
+pub fn detect(env: impl Env) -> Protocol {
+    let var = |key: &str| env.var(key).unwrap_or_default();
+    let term = var("TERM").to_ascii_lowercase();
+    let term_program = var("TERM_PROGRAM").to_ascii_lowercase();
+
+    let is_multiplexer = !var("TMUX").is_empty()
+        || term.starts_with("screen")
+        || term.starts_with("tmux")
+        || term_program == "tmux";
+
+    let is_kitty = !var("KITTY_WINDOW_ID").is_empty()
+        || term.contains("kitty")
+        || term.contains("ghostty")
+        || term_program == "ghostty";
+
+    let is_iterm2 =
+        matches!(term_program.as_str(), "iterm.app" | "wezterm") || var("LC_TERMINAL") == "iTerm2";
+
+    let is_sixel = term.contains("sixel")
+        || term.starts_with("foot")
+        || term.starts_with("mlterm")
+        || term_program == "contour"
+        || !var("KONSOLE_VERSION").is_empty();
+
+    if is_multiplexer {
+        Protocol::Halfblocks
+    } else if is_kitty {
+        Protocol::Kitty
+    } else if is_iterm2 {
+        Protocol::Iterm2
+    } else if is_sixel {
+        Protocol::Sixel
+    } else {
+        Protocol::Halfblocks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    struct TestEnv(HashMap<&'static str, &'static str>);
+
+    impl Env for TestEnv {
+        fn var(&self, key: &str) -> Option<String> {
+            self.0.get(key).map(|v| v.to_string())
+        }
+    }
+
+    fn env_from(pairs: &[(&'static str, &'static str)]) -> TestEnv {
+        TestEnv(pairs.iter().copied().collect())
+    }
+
+    #[test]
+    fn kitty_and_ghostty_use_kitty() {
+        let env = env_from(&[("TERM", "xterm-kitty"), ("KITTY_WINDOW_ID", "1")]);
+        assert_eq!(detect(env), Protocol::Kitty);
+
+        let env = env_from(&[("TERM", "xterm-ghostty"), ("TERM_PROGRAM", "ghostty")]);
+        assert_eq!(detect(env), Protocol::Kitty);
+    }
+
+    #[test]
+    fn iterm2_and_wezterm_use_iterm2() {
+        let env = env_from(&[("TERM", "xterm-256color"), ("TERM_PROGRAM", "iTerm.app")]);
+        assert_eq!(detect(env), Protocol::Iterm2);
+
+        let env = env_from(&[("TERM", "xterm-256color"), ("TERM_PROGRAM", "WezTerm")]);
+        assert_eq!(detect(env), Protocol::Iterm2);
+    }
+
+    #[test]
+    fn sixel_terminals_use_sixel() {
+        for pairs in [
+            [("TERM", "foot"), ("LANG", "en_US.UTF-8")],
+            [("TERM", "mlterm"), ("LANG", "en_US.UTF-8")],
+            [("TERM", "xterm-256color"), ("KONSOLE_VERSION", "230804")],
+        ] {
+            assert_eq!(detect(env_from(&pairs)), Protocol::Sixel);
+        }
+    }
+
+    #[test]
+    fn multiplexers_fall_back_to_halfblocks() {
+        let env = env_from(&[
+            ("TERM", "tmux-256color"),
+            ("TMUX", "/tmp/tmux-1000/default,1,0"),
+            ("KITTY_WINDOW_ID", "1"),
+        ]);
+        assert_eq!(detect(env), Protocol::Halfblocks);
+
+        let env = env_from(&[("TERM", "screen-256color"), ("TERM_PROGRAM", "WezTerm")]);
+        assert_eq!(detect(env), Protocol::Halfblocks);
+    }
+
+    #[test]
+    fn unknown_terminal_falls_back_to_halfblocks() {
+        let env = env_from(&[("TERM", "xterm-256color")]);
+        assert_eq!(detect(env), Protocol::Halfblocks);
+
+        assert_eq!(detect(env_from(&[])), Protocol::Halfblocks);
+    }
+}
@@ -16,12 +16,23 @@ enum InputMode {
     #[default]
     Normal,
     Editing,
+    /// The input shows a read-only question that is answered by accepting or cancelling.
+    Confirm,
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Callback {
     RenameDir(Directory),
     RenameNote(Note),
+    DeleteDir(Directory),
+    DeleteNote(Note),
+}
+
+impl Callback {
+    /// Whether the callback only needs a confirmation instead of a typed value.
+    fn is_confirmation(&self) -> bool {
+        matches!(self, Callback::DeleteDir(_) | Callback::DeleteNote(_))
+    }
 }
 
 #[derive(Clone, Default, Debug, PartialEq)]
@@ -82,29 +93,42 @@ impl InputModalState {
         self.callback = Some(callback.clone());
     }
 
-    pub fn run_callback(&mut self) -> Option<(std::path::PathBuf, std::path::PathBuf)> {
-        let result = if let Some(callback) = &self.callback {
-            // FIXME: Propagate errors
-            match callback {
-                Callback::RenameNote(note) => {
-                    let original_path = note.path().to_path_buf();
-                    rename_note(note.clone(), &self.input)
-                        .ok()
-                        .map(|n| (original_path, n.path().to_path_buf()))
-                }
-                Callback::RenameDir(directory) => {
-                    let original_path = directory.path().to_path_buf();
-                    rename_dir(directory.clone(), &self.input)
-                        .ok()
-                        .map(|d| (original_path, d.path().to_path_buf()))
-                }
+    pub fn run_callback<'a>(&mut self) -> Option<AppMessage<'a>> {
+        // FIXME: Propagate errors
+        let rename = match self.callback.take()? {
+            Callback::RenameNote(note) => {
+                let original_path = note.path().to_path_buf();
+                rename_note(note, &self.input)
+                    .ok()
+                    .map(|n| (original_path, n.path().to_path_buf()))
             }
-        } else {
-            None
+            Callback::RenameDir(directory) => {
+                let original_path = directory.path().to_path_buf();
+                rename_dir(directory, &self.input)
+                    .ok()
+                    .map(|d| (original_path, d.path().to_path_buf()))
+            }
+            Callback::DeleteNote(note) => return Some(AppMessage::DeleteNote(note)),
+            Callback::DeleteDir(directory) => return Some(AppMessage::DeleteDir(directory)),
         };
 
-        self.callback = None;
-        result
+        let select = rename.as_ref().map(|(_, new)| new.clone());
+        Some(AppMessage::RefreshVault { rename, select })
+    }
+
+    /// Runs the callback and closes the modal. Typed values are only accepted when they differ
+    /// from the original one.
+    fn accept<'a>(&mut self) -> Option<AppMessage<'a>> {
+        if !self.modified && self.input_mode != InputMode::Confirm {
+            self.input_mode = InputMode::Normal;
+            return Some(AppMessage::Input(Message::Cancel));
+        }
+
+        let message = self.run_callback();
+        self.input_mode = InputMode::Normal;
+        self.toggle_visibility();
+        self.modified = false;
+        message
     }
 
     pub fn toggle_visibility(&mut self) {
@@ -221,32 +245,23 @@ pub fn update<'a>(message: Message, state: &mut InputModalState) -> Option<AppMe
         }
         Message::Cancel => match state.input_mode {
             InputMode::Editing => state.input_mode = InputMode::Normal,
-            InputMode::Normal => {
+            InputMode::Normal | InputMode::Confirm => {
+                state.input_mode = InputMode::Normal;
+                state.callback = None;
                 state.toggle_visibility();
                 state.modified = false;
                 return Some(AppMessage::SetActivePane(ActivePane::Explorer));
             }
         },
-        Message::EditMode => {
+        Message::EditMode if state.input_mode != InputMode::Confirm => {
             state.input_mode = InputMode::Editing;
         }
+        Message::Accept => return state.accept(),
         Message::KeyEvent(key) => match key.code {
             KeyCode::Char(c) => {
                 state.insert_char(c);
             }
-            KeyCode::Enter => {
-                if state.modified {
-                    let rename = state.run_callback();
-                    state.input_mode = InputMode::Normal;
-                    state.toggle_visibility();
-                    state.modified = false;
-                    let select = rename.as_ref().map(|(_, new)| new.clone());
-                    return Some(AppMessage::RefreshVault { rename, select });
-                } else {
-                    state.input_mode = InputMode::Normal;
-                    return Some(AppMessage::Input(Message::Cancel));
-                }
-            }
+            KeyCode::Enter => return state.accept(),
             _ => {}
         },
         Message::Open(InputModalConfig {
@@ -260,6 +275,9 @@ pub fn update<'a>(message: Message, state: &mut InputModalState) -> Option<AppMe
             state.set_offset_x(position.x as usize);
             state.set_label(&label);
             state.set_callback(&callback);
+            if callback.is_confirmation() {
+                state.input_mode = InputMode::Confirm;
+            }
             state.toggle_visibility();
             return Some(AppMessage::SetActivePane(ActivePane::Input));
         }
@@ -350,6 +368,7 @@ impl StatefulWidget for Input {
         let mode_color = match state.input_mode {
             InputMode::Editing => self.theme.success,
             InputMode::Normal => self.theme.error,
+            InputMode::Confirm => self.theme.warning,
         };
 
         let mode = format!("{:?}", state.input_mode)
@@ -380,9 +399,12 @@ impl StatefulWidget for Input {
             )
             .render(area, buf);
 
-        let cursor = Rect::new(col.saturating_sub(state.scroll as u16), row, 1, 1)
-            .offset(Offset { x: 2, y: 1 });
-        state.terminal_cursor = Some(Position::new(cursor.x, cursor.y));
+        // A confirmation question is not editable, so it has no cursor.
+        if state.input_mode != InputMode::Confirm {
+            let cursor = Rect::new(col.saturating_sub(state.scroll as u16), row, 1, 1)
+                .offset(Offset { x: 2, y: 1 });
+            state.terminal_cursor = Some(Position::new(cursor.x, cursor.y));
+        }
     }
 }
 
@@ -391,6 +413,7 @@ mod tests {
     use super::*;
     use insta::assert_snapshot;
     use ratatui::{backend::TestBackend, Terminal};
+    use std::path::Path;
 
     #[test]
     fn test_input_states() {
@@ -474,4 +497,35 @@ mod tests {
             assert_snapshot!(name, terminal.backend());
         });
     }
+
+    #[test]
+    fn test_confirm_delete() {
+        let note = Note::new_unchecked("Note", Path::new("Note.md"));
+        let open = || {
+            Message::Open(InputModalConfig {
+                position: Position::default(),
+                label: String::from("Delete"),
+                initial_input: String::from("Delete \"Note\"?"),
+                callback: Callback::DeleteNote(note.clone()),
+            })
+        };
+
+        let mut state = InputModalState::default();
+        update(open(), &mut state);
+        assert_eq!(state.input_mode, InputMode::Confirm);
+
+        update(Message::EditMode, &mut state);
+        assert_eq!(state.input_mode, InputMode::Confirm);
+
+        assert_eq!(
+            update(Message::Accept, &mut state),
+            Some(AppMessage::DeleteNote(note.clone()))
+        );
+
+        update(open(), &mut state);
+        assert_eq!(
+            update(Message::Cancel, &mut state),
+            Some(AppMessage::SetActivePane(ActivePane::Explorer))
+        );
+    }
 }
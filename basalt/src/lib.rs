@@ -1,19 +1,32 @@
 pub mod app;
+pub mod backlinks;
 pub mod cli;
 pub mod clipboard;
 pub mod command;
+pub mod command_line;
+pub mod command_palette;
 pub mod config;
 pub mod debug_log;
 pub mod explorer;
+pub mod fuzzy;
 pub mod header;
 pub mod help_modal;
+#[cfg(feature = "images")]
+pub mod image;
 pub mod input;
+pub mod list_pane;
 pub mod note_editor;
 pub mod outline;
+pub mod query_modal;
+pub mod quick_switcher_modal;
+pub mod search_modal;
+pub mod shell;
 pub mod splash_modal;
 pub mod statusbar;
 pub mod stylized_text;
 pub mod tabs;
+pub mod tags;
+pub mod template_modal;
 pub mod text_counts;
 pub mod theme_selector;
 pub mod theme_selector_modal;
@@ -0,0 +1,265 @@
+//! The list panes that open next to the outline, like the backlinks and the tags. A pane shows a
+//! titled list of rows, opens and closes with a toggle, and takes the focus in turn with the
+//! outline and the explorer. What the rows are, how they are looked up and what selecting one
+//! does is up to the pane.
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Rect},
+    style::{Style, Stylize},
+    widgets::{
+        Block, Borders, List, ListItem, ListState, Padding, Paragraph, StatefulWidget, Widget,
+    },
+};
+
+use crate::{
+    app::{ActivePane, Message as AppMessage},
+    config::{Symbols, Theme},
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    Up,
+    Down,
+    Select,
+    Toggle,
+    Refresh,
+    SwitchPaneNext,
+    SwitchPanePrevious,
+}
+
+/// Handles the messages every list pane shares. `refresh` looks up the rows again, and is only
+/// called while the pane is open. `Select` is left to the pane.
+pub fn update<'a, T>(
+    message: &Message,
+    state: &mut ListPaneState<T>,
+    refresh: impl FnOnce(&mut ListPaneState<T>),
+) -> Option<AppMessage<'a>> {
+    match message {
+        Message::Up => state.previous(),
+        Message::Down => state.next(),
+        Message::Refresh if state.is_open() => refresh(state),
+        Message::Toggle => {
+            state.toggle();
+            if state.is_open() {
+                refresh(state);
+            } else if state.active {
+                state.set_active(false);
+                return Some(AppMessage::SetActivePane(ActivePane::NoteEditor));
+            }
+        }
+        Message::SwitchPaneNext => {
+            state.set_active(false);
+            return Some(AppMessage::SetActivePane(ActivePane::Explorer));
+        }
+        Message::SwitchPanePrevious => {
+            state.set_active(false);
+            return Some(AppMessage::SetActivePane(ActivePane::Outline));
+        }
+        Message::Refresh | Message::Select => {}
+    };
+
+    None
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListPaneState<T> {
+    pub(crate) items: Vec<T>,
+    pub(crate) open: bool,
+    pub(crate) active: bool,
+    pub(crate) list_state: ListState,
+    pub(crate) symbols: Symbols,
+    pub(crate) theme: Theme,
+}
+
+impl<T> Default for ListPaneState<T> {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            open: false,
+            active: false,
+            list_state: ListState::default(),
+            symbols: Symbols::default(),
+            theme: Theme::default(),
+        }
+    }
+}
+
+impl<T> ListPaneState<T> {
+    pub fn new(symbols: &Symbols) -> Self {
+        Self {
+            symbols: symbols.clone(),
+            ..Default::default()
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = *theme;
+    }
+
+    pub fn set_active(&mut self, active: bool) {
+        self.active = active;
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+    }
+
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    pub fn selected(&self) -> Option<&T> {
+        self.list_state
+            .selected()
+            .and_then(|index| self.items.get(index))
+    }
+
+    /// Replaces the rows. The selection stays on the same row index, clamped to the new rows.
+    pub(crate) fn set_items(&mut self, items: Vec<T>) {
+        self.items = items;
+        let last = self.items.len().checked_sub(1);
+        let selected = self.list_state.selected().unwrap_or(0);
+        self.list_state.select(last.map(|last| selected.min(last)));
+    }
+
+    pub(crate) fn select_first(&mut self) {
+        self.list_state
+            .select((!self.items.is_empty()).then_some(0));
+    }
+
+    fn next(&mut self) {
+        let last = self.items.len().saturating_sub(1);
+        let index = self.list_state.selected().map(|i| (i + 1).min(last));
+        self.list_state.select(index);
+    }
+
+    fn previous(&mut self) {
+        self.list_state.select_previous();
+    }
+}
+
+/// Draws a row of a list pane.
+pub type RenderItem<T> = for<'b> fn(&'b T, &Symbols, &Theme) -> ListItem<'b>;
+
+/// Draws a list pane. The pane sits next to the outline and shares its pane styling.
+pub struct ListPane<'a, T> {
+    title: &'a str,
+    empty: &'a str,
+    item: RenderItem<T>,
+}
+
+impl<'a, T> ListPane<'a, T> {
+    /// Creates a pane titled `title` that shows `empty` without rows and draws every row with
+    /// `item`.
+    pub fn new(title: &'a str, empty: &'a str, item: RenderItem<T>) -> Self {
+        Self { title, empty, item }
+    }
+}
+
+impl<T> StatefulWidget for ListPane<'_, T> {
+    type State = ListPaneState<T>;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        if area.is_empty() {
+            return;
+        }
+
+        let active = state.active;
+        let pane = state.theme.outline;
+        let fallback = if active {
+            state.symbols.border_active
+        } else {
+            state.symbols.border_inactive
+        }
+        .into();
+        let border_line = pane.border_line(fallback);
+
+        let mut block = Block::new()
+            .borders(if border_line.is_some() {
+                pane.border_edges.to_borders()
+            } else {
+                Borders::NONE
+            })
+            .style(Style::new().fg(state.theme.text).bg(pane.background))
+            .border_style(Style::new().fg(pane.border(active)))
+            .title(format!(" {} {} ", state.symbols.pane_open, self.title))
+            .title_alignment(Alignment::Right)
+            .padding(Padding::horizontal(1))
+            .title_style(Style::default().italic().bold());
+        if let Some(line) = border_line {
+            block = block.border_type(line);
+        }
+
+        if state.items.is_empty() {
+            Paragraph::new(self.empty)
+                .fg(state.theme.muted)
+                .block(block)
+                .render(area, buf);
+            return;
+        }
+
+        let items: Vec<ListItem> = state
+            .items
+            .iter()
+            .map(|item| (self.item)(item, &state.symbols, &state.theme))
+            .collect();
+
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(Style::default().reversed().fg(state.theme.muted))
+            .highlight_symbol("");
+        StatefulWidget::render(list, area, buf, &mut state.list_state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_closing_active_pane_focuses_note_editor() {
+        let mut state = ListPaneState::<()> {
+            open: true,
+            active: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            update(&Message::Toggle, &mut state, |_| {}),
+            Some(AppMessage::SetActivePane(ActivePane::NoteEditor))
+        );
+        assert!(!state.active);
+    }
+
+    #[test]
+    fn test_refresh_only_while_open() {
+        let mut state = ListPaneState::<u8>::default();
+        let refresh = |state: &mut ListPaneState<u8>| state.set_items(vec![1, 2]);
+
+        update(&Message::Refresh, &mut state, refresh);
+        assert!(state.items().is_empty());
+
+        update(&Message::Toggle, &mut state, refresh);
+        assert_eq!(state.items(), [1, 2]);
+        assert_eq!(state.selected(), Some(&1));
+
+        update(&Message::Down, &mut state, refresh);
+        update(&Message::Down, &mut state, refresh);
+        assert_eq!(
+            state.selected(),
+            Some(&2),
+            "the selection stops at the last row"
+        );
+
+        state.set_items(vec![3]);
+        assert_eq!(
+            state.selected(),
+            Some(&3),
+            "the selection is clamped to the new rows"
+        );
+    }
+}
@@ -1,3 +1,7 @@
+use std::collections::HashMap;
+
+use basalt_core::markdown::{self, Frontmatter};
+
 use crate::note_editor::rich_text::RichText;
 
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
@@ -10,15 +14,15 @@ pub enum HeadingLevel {
     H6,
 }
 
-impl From<pulldown_cmark::HeadingLevel> for HeadingLevel {
-    fn from(value: pulldown_cmark::HeadingLevel) -> Self {
+impl From<markdown::HeadingLevel> for HeadingLevel {
+    fn from(value: markdown::HeadingLevel) -> Self {
         match value {
-            pulldown_cmark::HeadingLevel::H1 => HeadingLevel::H1,
-            pulldown_cmark::HeadingLevel::H2 => HeadingLevel::H2,
-            pulldown_cmark::HeadingLevel::H3 => HeadingLevel::H3,
-            pulldown_cmark::HeadingLevel::H4 => HeadingLevel::H4,
-            pulldown_cmark::HeadingLevel::H5 => HeadingLevel::H5,
-            pulldown_cmark::HeadingLevel::H6 => HeadingLevel::H6,
+            markdown::HeadingLevel::H1 => HeadingLevel::H1,
+            markdown::HeadingLevel::H2 => HeadingLevel::H2,
+            markdown::HeadingLevel::H3 => HeadingLevel::H3,
+            markdown::HeadingLevel::H4 => HeadingLevel::H4,
+            markdown::HeadingLevel::H5 => HeadingLevel::H5,
+            markdown::HeadingLevel::H6 => HeadingLevel::H6,
         }
     }
 }
@@ -42,9 +46,9 @@ pub enum BlockQuoteKind {
     Quote,
 }
 
-impl From<pulldown_cmark::BlockQuoteKind> for BlockQuoteKind {
-    fn from(value: pulldown_cmark::BlockQuoteKind) -> Self {
-        use pulldown_cmark::BlockQuoteKind as Gfm;
+impl From<markdown::BlockQuoteKind> for BlockQuoteKind {
+    fn from(value: markdown::BlockQuoteKind) -> Self {
+        use markdown::BlockQuoteKind as Gfm;
         match value {
             Gfm::Note => BlockQuoteKind::Note,
             Gfm::Tip | Gfm::Important => BlockQuoteKind::Tip,
@@ -76,26 +80,101 @@ impl BlockQuoteKind {
     }
 }
 
+/// Fold state of a foldable callout, taken from Obsidian's fold marker: `[!note]-`
+/// starts folded and `[!note]+` starts expanded.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CalloutFold {
+    Expanded,
+    Folded,
+}
+
+impl CalloutFold {
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::Expanded => Self::Folded,
+            Self::Folded => Self::Expanded,
+        }
+    }
+}
+
 pub struct CalloutMarker {
     pub kind: BlockQuoteKind,
     pub title: Option<String>,
+    pub fold: Option<CalloutFold>,
 }
 
 /// Parses a callout marker (`[!note]`, `[!note]- Title`) from a quote's first
-/// line. Covers Obsidian's fold markers (`-`/`+`, accepted but not yet acted on)
-/// and custom titles, which `pulldown_cmark` does not recognise.
+/// line. Covers Obsidian's fold markers (`-`/`+`) and custom titles, which
+/// the markdown parser does not recognise.
 pub fn parse_callout_marker(line: &str) -> Option<CalloutMarker> {
     let rest = line.trim_start().strip_prefix("[!")?;
     let close = rest.find(']')?;
     let kind = BlockQuoteKind::from_name(&rest[..close]);
     let tail = &rest[close + 1..];
+    let fold = match tail.chars().next() {
+        Some('-') => Some(CalloutFold::Folded),
+        Some('+') => Some(CalloutFold::Expanded),
+        _ => None,
+    };
     let title = tail.strip_prefix(['-', '+']).unwrap_or(tail).trim();
     Some(CalloutMarker {
         kind,
         title: (!title.is_empty()).then(|| title.to_string()),
+        fold,
     })
 }
 
+/// Toggles the fold of the innermost foldable callout containing `offset`. Returns the new fold
+/// state and the source range of the callout, or `None` if no foldable callout contains it.
+pub fn toggle_callout_fold(
+    nodes: &mut [Node],
+    offset: usize,
+) -> Option<(CalloutFold, SourceRange<usize>)> {
+    let node = nodes
+        .iter_mut()
+        .find(|node| node.source_range().contains(&offset))?;
+
+    let nested = node
+        .children_as_mut()
+        .and_then(|children| toggle_callout_fold(children, offset));
+    if nested.is_some() {
+        return nested;
+    }
+
+    match node {
+        Node::BlockQuote {
+            fold: Some(fold),
+            source_range,
+            ..
+        } => {
+            *fold = fold.toggled();
+            Some((*fold, source_range.clone()))
+        }
+        _ => None,
+    }
+}
+
+/// Sets the fold of every foldable callout whose start offset has an entry in `folds`, e.g. to
+/// keep the callouts the user folded or unfolded after the nodes were parsed again.
+pub fn apply_callout_folds(nodes: &mut [Node], folds: &HashMap<usize, CalloutFold>) {
+    for node in nodes {
+        if let Node::BlockQuote {
+            fold: Some(fold),
+            source_range,
+            ..
+        } = node
+        {
+            if let Some(applied) = folds.get(&source_range.start) {
+                *fold = *applied;
+            }
+        }
+
+        if let Some(children) = node.children_as_mut() {
+            apply_callout_folds(children, folds);
+        }
+    }
+}
+
 /// Denotes whether a list is ordered or unordered.
 #[derive(Clone, Debug, PartialEq)]
 pub enum ItemKind {
@@ -130,13 +209,13 @@ pub enum Alignment {
     Right,
 }
 
-impl From<pulldown_cmark::Alignment> for Alignment {
-    fn from(value: pulldown_cmark::Alignment) -> Self {
+impl From<markdown::Alignment> for Alignment {
+    fn from(value: markdown::Alignment) -> Self {
         match value {
-            pulldown_cmark::Alignment::None => Alignment::None,
-            pulldown_cmark::Alignment::Left => Alignment::Left,
-            pulldown_cmark::Alignment::Center => Alignment::Center,
-            pulldown_cmark::Alignment::Right => Alignment::Right,
+            markdown::Alignment::None => Alignment::None,
+            markdown::Alignment::Left => Alignment::Left,
+            markdown::Alignment::Center => Alignment::Center,
+            markdown::Alignment::Right => Alignment::Right,
         }
     }
 }
@@ -165,6 +244,9 @@ pub enum Node {
         /// Custom callout title (Obsidian `> [!note] Title`); `None` falls back
         /// to the kind's own label.
         title: Option<String>,
+        /// Fold state of an Obsidian foldable callout (`> [!note]-` or `> [!note]+`);
+        /// `None` when the quote cannot fold.
+        fold: Option<CalloutFold>,
         nodes: Vec<Node>,
         source_range: SourceRange<usize>,
     },
@@ -190,6 +272,34 @@ pub enum Node {
         rows: Vec<Vec<RichText>>,
         source_range: SourceRange<usize>,
     },
+    /// The YAML frontmatter at the start of the note, shown as a block of note properties.
+    Frontmatter {
+        frontmatter: Frontmatter,
+        source_range: SourceRange<usize>,
+    },
+    /// An image embed on a line of its own, `![[image.png]]` or `![alt](image.png)`. `target` is
+    /// the destination as written in the note; it is resolved against the vault when drawn.
+    Image {
+        target: String,
+        alt: String,
+        source_range: SourceRange<usize>,
+    },
+    /// A note embed on a line of its own, `![[Note]]` or `![[Note#Heading]]`. The embedded note is
+    /// read from the vault when the note is laid out.
+    Embed {
+        target: String,
+        heading: Option<String>,
+        source_range: SourceRange<usize>,
+    },
+    /// A thematic break, `---`, `***` or `___`, drawn as a rule across the note.
+    Rule { source_range: SourceRange<usize> },
+    /// A footnote definition, `[^label]: ...`. References to it are footnote reference segments of
+    /// the text.
+    FootnoteDefinition {
+        label: String,
+        nodes: Vec<Node>,
+        source_range: SourceRange<usize>,
+    },
 }
 
 impl Node {
@@ -202,7 +312,12 @@ impl Node {
             | Self::BlockQuote { source_range, .. }
             | Self::Item { source_range, .. }
             | Self::Task { source_range, .. }
-            | Self::Table { source_range, .. } => source_range,
+            | Self::Table { source_range, .. }
+            | Self::Frontmatter { source_range, .. }
+            | Self::Image { source_range, .. }
+            | Self::Embed { source_range, .. }
+            | Self::Rule { source_range }
+            | Self::FootnoteDefinition { source_range, .. } => source_range,
         }
     }
 
@@ -215,7 +330,12 @@ impl Node {
             | Self::BlockQuote { source_range, .. }
             | Self::Item { source_range, .. }
             | Self::Task { source_range, .. }
-            | Self::Table { source_range, .. } => *source_range = new_range,
+            | Self::Table { source_range, .. }
+            | Self::Frontmatter { source_range, .. }
+            | Self::Image { source_range, .. }
+            | Self::Embed { source_range, .. }
+            | Self::Rule { source_range }
+            | Self::FootnoteDefinition { source_range, .. } => *source_range = new_range,
         }
     }
 
@@ -228,12 +348,24 @@ impl Node {
         }
     }
 
+    pub fn children(&self) -> Option<&[Self]> {
+        match self {
+            Self::List { nodes, .. }
+            | Self::Item { nodes, .. }
+            | Self::Task { nodes, .. }
+            | Self::BlockQuote { nodes, .. }
+            | Self::FootnoteDefinition { nodes, .. } => Some(nodes),
+            _ => None,
+        }
+    }
+
     pub fn children_as_mut(&mut self) -> Option<&mut Vec<Self>> {
         match self {
             Self::List { nodes, .. }
             | Self::Item { nodes, .. }
             | Self::Task { nodes, .. }
-            | Self::BlockQuote { nodes, .. } => Some(nodes),
+            | Self::BlockQuote { nodes, .. }
+            | Self::FootnoteDefinition { nodes, .. } => Some(nodes),
             _ => None,
         }
     }
@@ -277,14 +409,16 @@ pub fn node_to_sexp(node: &Node, indent_level: usize) -> String {
         Node::BlockQuote {
             kind,
             title,
+            fold,
             nodes,
             source_range,
         } => {
             format!(
-                "{:indent$}(blockquote {:?} {:?} @{:?}\n{})",
+                "{:indent$}(blockquote {:?} {:?} {:?} @{:?}\n{})",
                 "",
                 kind,
                 title,
+                fold,
                 source_range,
                 nodes_to_sexp(nodes, indent_level + indent_increment),
                 indent = indent_level
@@ -373,9 +507,87 @@ pub fn node_to_sexp(node: &Node, indent_level: usize) -> String {
                 indent = indent_level,
             )
         }
+        Node::Frontmatter {
+            frontmatter,
+            source_range,
+        } => {
+            let inner_indent = indent_level + indent_increment;
+            let properties = frontmatter
+                .properties
+                .iter()
+                .map(|(key, value)| format!("{:inner_indent$}({key} \"{value}\")", ""))
+                .collect::<Vec<_>>()
+                .join("\n");
+            format!(
+                "{:indent$}(frontmatter @{:?}\n{})",
+                "",
+                source_range,
+                properties,
+                indent = indent_level,
+            )
+        }
+        Node::Image {
+            target,
+            alt,
+            source_range,
+        } => {
+            format!(
+                "{:indent$}(image \"{target}\" \"{alt}\" @{:?})",
+                "",
+                source_range,
+                indent = indent_level,
+            )
+        }
+        Node::Embed {
+            target,
+            heading,
+            source_range,
+        } => {
+            format!(
+                "{:indent$}(embed \"{target}\" {:?} @{:?})",
+                "",
+                heading,
+                source_range,
+                indent = indent_level,
+            )
+        }
+        Node::Rule { source_range } => {
+            format!(
+                "{:indent$}(rule @{:?})",
+                "",
+                source_range,
+                indent = indent_level,
+            )
+        }
+        Node::FootnoteDefinition {
+            label,
+            nodes,
+            source_range,
+        } => {
+            format!(
+                "{:indent$}(footnote \"{label}\" @{:?}\n{})",
+                "",
+                source_range,
+                nodes_to_sexp(nodes, indent_level + indent_increment),
+                indent = indent_level
+            )
+        }
     }
 }
 
+/// File extensions of the image embeds drawn in the note editor.
+pub const IMAGE_EXTENSIONS: [&str; 6] = ["png", "jpg", "jpeg", "gif", "bmp", "webp"];
+
+/// Returns `true` if the embed target points to an image by its extension.
+pub fn is_image_target(target: &str) -> bool {
+    let path = target.split(['#', '?']).next().unwrap_or(target);
+    path.rsplit_once('.').is_some_and(|(_, extension)| {
+        IMAGE_EXTENSIONS
+            .iter()
+            .any(|image| image.eq_ignore_ascii_case(extension))
+    })
+}
+
 pub fn rich_text_to_sexp(rich_text: &RichText, indent_level: usize) -> String {
     rich_text
         .segments()
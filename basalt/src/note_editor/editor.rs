@@ -1,4 +1,4 @@
-use std::{marker::PhantomData, ops::Range};
+use std::marker::PhantomData;
 
 use ratatui::{
     buffer::Buffer,
@@ -10,80 +10,16 @@ use ratatui::{
         StatefulWidget, Widget,
     },
 };
-use unicode_width::UnicodeWidthChar;
 
 use crate::note_editor::{
     cursor::{CursorMode, CursorWidget},
+    render::render_highlight,
     state::NoteEditorState,
-    viewport::Viewport,
-    virtual_document::VirtualLine,
 };
 
 const SELECTION_STYLE: Style = Style::new().reversed();
 const YANK_FLASH_STYLE: Style = Style::new().bg(Color::LightCyan);
 
-fn render_highlight(
-    buf: &mut Buffer,
-    inner_area: Rect,
-    viewport: &Viewport,
-    meta: &[VirtualLine],
-    doc: &[VirtualLine],
-    range: &Range<usize>,
-    style: Style,
-) {
-    let meta_len = meta.len();
-    let viewport_top = viewport.top() as usize;
-    let horizontal_scroll = viewport.left();
-
-    let paint = |buf: &mut Buffer, col: u16, y: u16, width: u16| {
-        if col >= horizontal_scroll {
-            let cell = Rect::new(inner_area.x + col - horizontal_scroll, y, width.max(1), 1)
-                .intersection(inner_area);
-            buf.set_style(cell, style);
-        }
-    };
-
-    meta.iter()
-        .chain(doc.iter())
-        .enumerate()
-        .skip(viewport_top)
-        .take(inner_area.height as usize)
-        .filter(|(idx, _)| *idx >= meta_len)
-        .for_each(|(idx, line)| {
-            let y = inner_area.y + (idx - viewport_top) as u16;
-            let spans = line.virtual_spans();
-            let mut col = 0u16;
-
-            for (i, span) in spans.iter().enumerate() {
-                match span.source_range() {
-                    Some(source_range) => {
-                        span.char_indices().fold(col, |col, (byte_idx, ch)| {
-                            let width = ch.width().unwrap_or(0) as u16;
-                            if range.contains(&(source_range.start + byte_idx)) {
-                                paint(buf, col, y, width);
-                            }
-                            col + width
-                        });
-                    }
-                    // A synthetic span (rendered list marker, prefix, quote glyph)
-                    // stands in for source it does not carry. Highlight it when the
-                    // content it precedes is selected, so a selected line's marker
-                    // is not left blank.
-                    None => {
-                        let precedes_selection = spans[i + 1..]
-                            .iter()
-                            .find_map(|span| span.source_range())
-                            .is_some_and(|next| range.contains(&next.start));
-                        if precedes_selection {
-                            paint(buf, col, y, span.width() as u16);
-                        }
-                    }
-                }
-                col += span.width() as u16;
-            }
-        });
-}
-
 #[derive(Default)]
 pub struct NoteEditor<'a>(pub PhantomData<&'a ()>);
 
@@ -136,6 +72,7 @@ impl<'a> StatefulWidget for NoteEditor<'a> {
 
         state.update_layout();
 
+        let search_matches = state.search_matches().to_vec();
         let meta = state.virtual_document.meta();
         let doc = state.virtual_document.lines();
         let meta_lines_count = meta.len();
@@ -157,6 +94,20 @@ impl<'a> StatefulWidget for NoteEditor<'a> {
             .block(block)
             .render(area, buf);
 
+        if !search_matches.is_empty() {
+            render_highlight(
+                buf,
+                inner_area,
+                state.viewport(),
+                meta,
+                doc,
+                &search_matches,
+                Style::new()
+                    .fg(theme.search_match_fg)
+                    .bg(theme.search_match_bg),
+            );
+        }
+
         if let Some(range) = state.selection_range() {
             render_highlight(
                 buf,
@@ -164,7 +115,7 @@ impl<'a> StatefulWidget for NoteEditor<'a> {
                 state.viewport(),
                 meta,
                 doc,
-                &range,
+                &[range],
                 SELECTION_STYLE,
             );
         }
@@ -176,11 +127,24 @@ impl<'a> StatefulWidget for NoteEditor<'a> {
                 state.viewport(),
                 meta,
                 doc,
-                &range,
+                &[range],
                 YANK_FLASH_STYLE,
             );
         }
 
+        #[cfg(feature = "images")]
+        {
+            let top = state.viewport().top() as isize - meta_lines_count as isize;
+            let left = state.viewport().left();
+            state.virtual_document.embeds_mut().render(
+                inner_area,
+                buf,
+                top,
+                left,
+                crate::image::protocol(),
+            );
+        }
+
         state.terminal_cursor = None;
         if !state.content.is_empty() || state.is_editing() {
             match *state.cursor.mode() {
@@ -225,7 +189,7 @@ mod tests {
     use std::path::Path;
 
     use crate::{
-        config::Symbols,
+        config::{Symbols, Theme},
         note_editor::state::{EditMode, SelectionMode, View},
     };
 
@@ -339,6 +303,15 @@ mod tests {
             }
             ```
             "#},
+            indoc! { r#"---
+            tags: [recipe, breakfast]
+            aliases: Pancakes
+            servings: 4
+            ---
+            ## Properties
+
+            Frontmatter is shown as a block of note properties.
+            "#},
         ];
 
         let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
@@ -824,23 +797,22 @@ mod tests {
                   - [ ] Subtask 1
                 "#},
             ),
-            // TODO: Implement horizontal rule
-            // (
-            //     "horizontal_rule",
-            //     indoc! { r#"## Horizontal rule
-            //     You can use three or more stars `***`, hyphens `---`, or underscore `___` on its own line to add a horizontal bar. You can also separate symbols using spaces.
-            //
-            //     ***
-            //     ****
-            //     * * *
-            //     ---
-            //     ----
-            //     - - -
-            //     ___
-            //     ____
-            //     _ _ _
-            //     "#},
-            // ),
+            (
+                "horizontal_rule",
+                indoc! { r#"## Horizontal rule
+                You can use three or more stars `***`, hyphens `---`, or underscore `___` on its own line to add a horizontal bar. You can also separate symbols using spaces.
+
+                ***
+                ****
+                * * *
+                ---
+                ----
+                - - -
+                ___
+                ____
+                _ _ _
+                "#},
+            ),
             (
                 "tables",
                 indoc! { r#"## Tables
@@ -938,4 +910,53 @@ mod tests {
             "the prettified list marker on a selected line should be highlighted"
         );
     }
+
+    #[test]
+    fn test_search_matches_are_highlighted() {
+        use ratatui::layout::Size;
+        use regex::Regex;
+
+        use crate::note_editor::Direction;
+
+        let mut state = NoteEditorState::new(
+            "- beta alpha\n- alpha\n",
+            "test",
+            Path::new("test.md"),
+            &Symbols::unicode(),
+        );
+        state.set_theme(&Theme {
+            search_match_bg: Color::Rgb(0xd7, 0x99, 0x21),
+            ..Theme::default()
+        });
+        state.set_vim_mode(true);
+        state.set_editor_enabled(true);
+        state.resize_viewport(Size::new(40, 10));
+        state.set_view(View::Edit(EditMode::Source));
+        state.remember_search(Regex::new("alpha").unwrap(), Direction::Forward);
+
+        let mut terminal = Terminal::new(TestBackend::new(40, 10)).unwrap();
+        terminal
+            .draw(|frame| {
+                NoteEditor::default().render(frame.area(), frame.buffer_mut(), &mut state)
+            })
+            .unwrap();
+
+        let highlighted = |symbol: &str| {
+            terminal
+                .backend()
+                .buffer()
+                .content
+                .iter()
+                .filter(|cell| cell.symbol() == symbol && cell.bg == Color::Rgb(0xd7, 0x99, 0x21))
+                .count()
+        };
+
+        assert_eq!(highlighted("p"), 2, "both matches should be highlighted");
+        assert_eq!(highlighted("b"), 0);
+        assert_eq!(
+            highlighted("●"),
+            0,
+            "a match right after a list marker should not claim the marker"
+        );
+    }
 }
@@ -0,0 +1,142 @@
+//! Footnotes, `[^label]` references and their `[^label]: ...` definitions. References are shown as
+//! superscript markers and the definitions are collected at the end of the rendered note.
+use basalt_core::markdown::math::superscript;
+
+use crate::note_editor::ast::Node;
+
+/// Returns the marker a reference or a definition of the footnote is shown with, the superscript
+/// label. The label is kept in brackets instead when it has no superscript or its superscript would
+/// take more bytes than the `[^label]` source, so the rendered text never runs past the source
+/// offsets of its line.
+pub fn marker(label: &str) -> String {
+    superscript(label)
+        .filter(|marker| marker.len() <= label.len() + "[^]".len())
+        .unwrap_or_else(|| format!("[{label}]"))
+}
+
+/// Returns `true` if the `[^label]` starting at `start` begins a definition: it is followed by a
+/// colon and preceded only by indentation or quote markers on its line.
+fn is_definition(content: &str, start: usize, end: usize) -> bool {
+    let line_start = content[..start].rfind('\n').map_or(0, |i| i + 1);
+    content[end..].starts_with(':')
+        && content[line_start..start]
+            .chars()
+            .all(|c| c.is_whitespace() || c == '>')
+}
+
+/// Returns the label of the footnote reference under the offset.
+pub fn reference_at(content: &str, offset: usize) -> Option<&str> {
+    let line_start = content.get(..offset)?.rfind('\n').map_or(0, |i| i + 1);
+    let line_end = content[offset..]
+        .find('\n')
+        .map_or(content.len(), |i| offset + i);
+
+    content[line_start..line_end]
+        .match_indices("[^")
+        .find_map(|(index, _)| {
+            let start = line_start + index;
+            let label_start = start + "[^".len();
+            let label_len = content[label_start..line_end].find(']')?;
+            let label = &content[label_start..label_start + label_len];
+            let end = label_start + label_len + 1;
+
+            let valid = !label.is_empty() && !label.contains(char::is_whitespace);
+            (valid && (start..end).contains(&offset) && !is_definition(content, start, end))
+                .then_some(label)
+        })
+}
+
+/// Returns the offset of the first reference to the footnote.
+pub fn first_reference(content: &str, label: &str) -> Option<usize> {
+    let needle = format!("[^{label}]");
+    content
+        .match_indices(&needle)
+        .map(|(start, _)| start)
+        .find(|&start| !is_definition(content, start, start + needle.len()))
+}
+
+/// Returns the definition of the footnote.
+pub fn definition<'a>(nodes: &'a [Node], label: &str) -> Option<&'a Node> {
+    nodes.iter().find_map(|node| match node {
+        Node::FootnoteDefinition { label: other, .. } if other.eq_ignore_ascii_case(label) => {
+            Some(node)
+        }
+        node => definition(node.children()?, label),
+    })
+}
+
+/// Returns the label of the innermost footnote definition containing the offset.
+pub fn definition_at(nodes: &[Node], offset: usize) -> Option<&str> {
+    let node = nodes
+        .iter()
+        .find(|node| node.source_range().contains(&offset))?;
+
+    let children = node
+        .children()
+        .and_then(|children| definition_at(children, offset));
+
+    match node {
+        Node::FootnoteDefinition { label, .. } => children.or(Some(label)),
+        _ => children,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+    use crate::note_editor::parser;
+
+    const NOTE: &str = indoc! {"
+        Claim[^1] and another[^note].
+
+        > Quoted[^1]
+
+        [^1]: The source.
+        [^note]: A longer note.
+    "};
+
+    #[test]
+    fn test_marker() {
+        assert_eq!(marker("1"), "¹");
+        assert_eq!(marker("10"), "¹⁰");
+        assert_eq!(marker("a"), "ᵃ");
+        assert_eq!(marker("45"), "[45]");
+        assert_eq!(marker("note"), "[note]");
+        assert_eq!(marker("q"), "[q]");
+    }
+
+    #[test]
+    fn test_reference_at() {
+        assert_eq!(reference_at(NOTE, 0), None);
+        assert_eq!(reference_at(NOTE, 5), Some("1"));
+        assert_eq!(reference_at(NOTE, 8), Some("1"));
+        assert_eq!(reference_at(NOTE, 9), None);
+        assert_eq!(reference_at(NOTE, 22), Some("note"));
+        // The label of a definition is not a reference.
+        assert_eq!(reference_at(NOTE, 47), None);
+    }
+
+    #[test]
+    fn test_first_reference() {
+        assert_eq!(first_reference(NOTE, "1"), Some(5));
+        assert_eq!(first_reference(NOTE, "note"), Some(21));
+        assert_eq!(first_reference(NOTE, "missing"), None);
+        assert_eq!(first_reference("[^1]: Unreferenced.", "1"), None);
+    }
+
+    #[test]
+    fn test_definition() {
+        let nodes = parser::from_str(NOTE);
+
+        let range = |node: Option<&Node>| node.map(|node| node.source_range().clone());
+        assert_eq!(range(definition(&nodes, "1")), Some(45..63));
+        assert_eq!(range(definition(&nodes, "note")), Some(63..87));
+        assert_eq!(range(definition(&nodes, "missing")), None);
+
+        assert_eq!(definition_at(&nodes, 50), Some("1"));
+        assert_eq!(definition_at(&nodes, 70), Some("note"));
+        assert_eq!(definition_at(&nodes, 5), None);
+    }
+}
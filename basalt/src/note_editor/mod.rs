@@ -1,13 +1,16 @@
 pub mod ast;
 mod cursor;
 pub mod editor;
+mod footnote;
 mod motion;
 pub mod parser;
 mod render;
 mod rich_text;
 pub mod state;
+pub mod substitute;
 mod text_buffer;
 mod text_wrap;
+pub mod transclusion;
 mod viewport;
 mod virtual_document;
 
@@ -15,23 +18,29 @@ mod virtual_document;
 // for callers (command dispatch) that construct find messages.
 pub use motion::Direction;
 
-use std::time::Duration;
+use std::{ops::Range, time::Duration};
 
 use ratatui::{
     crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
     layout::Size,
 };
+use regex::Regex;
 
 use crate::{
     app::{calc_scroll_amount, ActivePane, Message as AppMessage, ScrollAmount},
     explorer,
-    note_editor::state::{EditMode, FindKind, NoteEditorState, Operator, SelectionMode, View},
+    note_editor::{
+        state::{FindKind, NoteEditorState, Operator, SelectionMode, View},
+        substitute::Substitution,
+    },
     outline, toast,
 };
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Message {
     Save,
+    /// Saves the note and closes its tab, like vim's `:wq`.
+    SaveAndClose,
     SwitchPaneNext,
     SwitchPanePrevious,
     ToggleExplorer,
@@ -52,6 +61,14 @@ pub enum Message {
     ScrollToTop,
     ScrollToBottom,
     JumpToBlock(usize),
+    JumpToOffset(usize),
+    FollowLink,
+    OpenEmbed,
+    JumpToFootnoteDefinition,
+    JumpToFootnoteReference,
+    ToggleProperties,
+    ToggleCalloutFold,
+    ToggleLivePreview,
     Delete,
     InsertMode,
     VisualMode,
@@ -88,6 +105,29 @@ pub enum Message {
     SubstituteChar,
     PasteAfter,
     PasteBefore,
+    /// Inserts the text at the cursor, e.g. an applied template.
+    InsertText(String),
+    /// Replaces the source range with the text, e.g. the output of a `filter:` command.
+    ReplaceText(Range<usize>, String),
+    JumpToLine(usize),
+    Substitute(Substitution),
+    /// Previews the search typed so far on the command line.
+    SearchPreview {
+        pattern: String,
+        direction: motion::Direction,
+    },
+    /// Runs the search typed on the command line. An empty pattern repeats the last search.
+    Search {
+        pattern: String,
+        direction: motion::Direction,
+    },
+    SearchCancel,
+    SearchNext {
+        reverse: bool,
+    },
+    /// Searches for the keyword under the cursor (`*` and `#`).
+    SearchWord(motion::Direction),
+    ClearSearchHighlight,
     Undo,
     Redo,
 }
@@ -126,6 +166,21 @@ fn save<'a>(state: &mut NoteEditorState) -> Option<AppMessage<'a>> {
     }
 }
 
+/// Closes the tab only once the note is saved, so a failed write keeps the unsaved changes open.
+fn save_and_close<'a>(state: &mut NoteEditorState) -> Option<AppMessage<'a>> {
+    let modified = state.modified();
+    match state.save_to_file() {
+        Ok(_) if modified => Some(AppMessage::Batch(vec![
+            AppMessage::UpdateSelectedNoteContent((state.content.to_string(), None)),
+            AppMessage::CloseTab,
+        ])),
+        Ok(_) => Some(AppMessage::CloseTab),
+        Err(_) => Some(AppMessage::Toast(toast::Message::Create(
+            toast::Toast::error("Failed to save file", Duration::from_secs(2)),
+        ))),
+    }
+}
+
 fn shared_message<'a>(state: &mut NoteEditorState, message: Message) -> Option<AppMessage<'a>> {
     match message {
         Message::ToggleExplorer => Some(AppMessage::Explorer(explorer::Message::Toggle)),
@@ -139,6 +194,7 @@ fn shared_message<'a>(state: &mut NoteEditorState, message: Message) -> Option<A
             Some(AppMessage::SetActivePane(ActivePane::Explorer))
         }
         Message::Save => save(state),
+        Message::SaveAndClose => save_and_close(state),
         _ => None,
     }
 }
@@ -276,11 +332,45 @@ fn operate_lines<'a>(
     }
 }
 
+/// Moves to the `count`th match of the search, or applies the pending operator up to it.
+fn search<'a>(
+    state: &mut NoteEditorState,
+    regex: &Regex,
+    direction: motion::Direction,
+    count: usize,
+    from: usize,
+) -> Option<AppMessage<'a>> {
+    let target = (0..count).try_fold(from, |from, _| {
+        motion::search(&state.content, from, regex, direction)
+    });
+    match (target, state.take_operator()) {
+        (Some(target), Some(operator)) => operate(state, operator, target, false, false),
+        (Some(target), None) => motion_to(state, target),
+        (None, _) => search_error(state, &format!("Pattern not found: {}", regex.as_str())),
+    }
+}
+
+fn search_error<'a>(state: &mut NoteEditorState, message: &str) -> Option<AppMessage<'a>> {
+    state.reset_count();
+    state.clear_operator();
+    Some(AppMessage::Toast(toast::Message::Create(
+        toast::Toast::error(message, Duration::from_secs(2)),
+    )))
+}
+
 fn motion_to<'a>(state: &mut NoteEditorState, offset: usize) -> Option<AppMessage<'a>> {
     state.jump_to_offset(offset);
     select_at_cursor(state)
 }
 
+/// A note opened in the same update has not been laid out yet; size it to the screen so a jump
+/// can land. The first render re-lays it out at the cursor's offset.
+fn ensure_layout(state: &mut NoteEditorState, screen_size: Size) {
+    if state.viewport().area().is_empty() {
+        state.resize_viewport(screen_size);
+    }
+}
+
 // FIXME: Add resize message to handle resize related updates like cursor positioning
 pub fn update<'a>(
     message: Message,
@@ -318,7 +408,46 @@ pub fn update<'a>(
             }
             state.cursor_right(count);
         }
-        Message::JumpToBlock(idx) => state.cursor_jump(idx),
+        Message::JumpToBlock(idx) => {
+            ensure_layout(state, screen_size);
+            state.cursor_jump(idx);
+        }
+        Message::JumpToOffset(offset) => {
+            ensure_layout(state, screen_size);
+            state.jump_to_offset(offset);
+            return select_at_cursor(state);
+        }
+        Message::FollowLink => {
+            return state.wiki_link_at_cursor().map(AppMessage::FollowLink);
+        }
+        Message::OpenEmbed => {
+            return state.embed_link_at_cursor().map(AppMessage::FollowLink);
+        }
+        Message::JumpToFootnoteDefinition => {
+            ensure_layout(state, screen_size);
+            state.jump_to_footnote_definition();
+            return select_at_cursor(state);
+        }
+        Message::JumpToFootnoteReference => {
+            ensure_layout(state, screen_size);
+            state.jump_to_footnote_reference();
+            return select_at_cursor(state);
+        }
+        Message::ToggleProperties => {
+            ensure_layout(state, screen_size);
+            state.toggle_properties();
+            return select_at_cursor(state);
+        }
+        Message::ToggleCalloutFold => {
+            ensure_layout(state, screen_size);
+            state.toggle_callout_fold();
+            return select_at_cursor(state);
+        }
+        Message::ToggleLivePreview => {
+            ensure_layout(state, screen_size);
+            state.toggle_live_preview();
+            return select_at_cursor(state);
+        }
         Message::CursorUp => {
             let count = state.take_count().unwrap_or(1);
             if state.pending_operator().is_some() {
@@ -361,6 +490,91 @@ pub fn update<'a>(
             state.cursor_to_end();
             return select_at_cursor(state);
         }
+        Message::InsertText(text) => {
+            ensure_layout(state, screen_size);
+            state.clear_operator();
+            state.insert_text(&text);
+            return Some(content_update(state));
+        }
+        Message::ReplaceText(range, text) => {
+            ensure_layout(state, screen_size);
+            state.clear_operator();
+            state.replace_text(range, &text);
+            return Some(content_update(state));
+        }
+        Message::JumpToLine(line) => {
+            ensure_layout(state, screen_size);
+            state.jump_to_line(line);
+            return select_at_cursor(state);
+        }
+        Message::Substitute(substitution) if state.editor_enabled() => {
+            ensure_layout(state, screen_size);
+            state.clear_operator();
+            return match state.substitute(&substitution) {
+                0 => Some(AppMessage::Toast(toast::Message::Create(
+                    toast::Toast::error(
+                        &format!("Pattern not found: {}", substitution.pattern()),
+                        Duration::from_secs(2),
+                    ),
+                ))),
+                _ => Some(content_update(state)),
+            };
+        }
+        Message::Substitute(_) => {
+            return Some(AppMessage::Toast(toast::Message::Create(
+                toast::Toast::info("No editable note to substitute in", Duration::from_secs(2)),
+            )));
+        }
+        Message::SearchPreview { pattern, direction } => {
+            ensure_layout(state, screen_size);
+            state.preview_search(&pattern, direction);
+            return select_at_cursor(state);
+        }
+        Message::SearchCancel => {
+            state.cancel_search();
+            state.reset_count();
+            state.clear_operator();
+            return select_at_cursor(state);
+        }
+        Message::Search { pattern, direction } => {
+            ensure_layout(state, screen_size);
+            // The search runs from where it started, not from the previewed match.
+            state.cancel_search();
+            let regex = match (pattern.is_empty(), state.last_search()) {
+                (true, Some((regex, _))) => regex,
+                (true, None) => return search_error(state, "No previous search pattern"),
+                (false, _) => match Regex::new(&pattern) {
+                    Ok(regex) => regex,
+                    Err(_) => return search_error(state, &format!("Invalid pattern: {pattern}")),
+                },
+            };
+            state.remember_search(regex.clone(), direction);
+            let count = state.take_count().unwrap_or(1);
+            return search(state, &regex, direction, count, offset(state));
+        }
+        Message::SearchNext { reverse } => {
+            let Some((regex, direction)) = state.last_search() else {
+                return search_error(state, "No previous search pattern");
+            };
+            state.remember_search(regex.clone(), direction);
+            let direction = if reverse { direction.flip() } else { direction };
+            let count = state.take_count().unwrap_or(1);
+            return search(state, &regex, direction, count, offset(state));
+        }
+        Message::SearchWord(direction) => {
+            let keyword = motion::keyword_at(&state.content, offset(state));
+            let Some((keyword, regex)) = keyword.and_then(|keyword| {
+                let pattern = format!(r"\b{}\b", regex::escape(&state.content[keyword.clone()]));
+                Some((keyword, Regex::new(&pattern).ok()?))
+            }) else {
+                return search_error(state, "No keyword under cursor");
+            };
+            state.remember_search(regex.clone(), direction);
+            let count = state.take_count().unwrap_or(1);
+            // From the start of the keyword, both directions skip the keyword itself.
+            return search(state, &regex, direction, count, keyword.start);
+        }
+        Message::ClearSearchHighlight => state.clear_search_highlight(),
         _ => {}
     };
 
@@ -668,10 +882,10 @@ pub fn update<'a>(
         },
         View::Read => match message {
             Message::ToggleView if state.editor_enabled() => {
-                state.set_view(View::Edit(EditMode::Source))
+                state.set_view(View::Edit(state.edit_mode()))
             }
             Message::EditView | Message::InsertMode if state.editor_enabled() => {
-                state.set_view(View::Edit(EditMode::Source));
+                state.set_view(View::Edit(state.edit_mode()));
                 state.set_insert_mode(true);
             }
             Message::ReadView => state.set_view(View::Read),
@@ -699,6 +913,9 @@ pub fn handle_editing_event(key: KeyEvent) -> Option<Message> {
         KeyCode::Char('e') if key.modifiers.contains(KeyModifiers::CONTROL) => {
             Some(Message::ToggleView)
         }
+        KeyCode::Char('l') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            Some(Message::ToggleLivePreview)
+        }
         _ => Some(Message::KeyEvent(key)),
     }
 }
@@ -746,6 +963,85 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_follow_link_under_cursor() {
+        let mut state = vim_edit_state("See [[Target#Heading|alias]] here\n");
+        let size = Size::new(40, 10);
+
+        assert_eq!(update(Message::FollowLink, size, &mut state), None);
+
+        update(Message::JumpToOffset(8), size, &mut state);
+        match update(Message::FollowLink, size, &mut state) {
+            Some(AppMessage::FollowLink(link)) => {
+                assert_eq!(link.target, "Target");
+                assert_eq!(link.heading.as_deref(), Some("Heading"));
+                assert_eq!(link.range, 4..28);
+            }
+            other => panic!("Expected AppMessage::FollowLink(..), got: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_open_embed_under_cursor() {
+        let mut state = vim_edit_state("Intro\n\n![[Recipes#Pancakes]]\n");
+        let size = Size::new(40, 10);
+
+        assert_eq!(update(Message::OpenEmbed, size, &mut state), None);
+
+        update(Message::JumpToOffset(8), size, &mut state);
+        match update(Message::OpenEmbed, size, &mut state) {
+            Some(AppMessage::FollowLink(link)) => {
+                assert_eq!(link.target, "Recipes");
+                assert_eq!(link.heading.as_deref(), Some("Pancakes"));
+                assert_eq!(link.range, 8..28);
+            }
+            other => panic!("Expected AppMessage::FollowLink(..), got: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_jump_between_footnote_reference_and_definition() {
+        let mut state = vim_edit_state("Claim[^1] here.\n\nMore[^1].\n\n[^1]: The source.\n");
+        let size = Size::new(40, 10);
+
+        update(Message::JumpToFootnoteDefinition, size, &mut state);
+        assert_eq!(offset(&state), 0, "no reference under the cursor");
+
+        update(Message::JumpToOffset(6), size, &mut state);
+        update(Message::JumpToFootnoteDefinition, size, &mut state);
+        assert_eq!(offset(&state), 34);
+
+        update(Message::CursorRight, size, &mut state);
+        update(Message::JumpToFootnoteReference, size, &mut state);
+        assert_eq!(offset(&state), 5);
+    }
+
+    #[test]
+    fn test_toggle_properties_hides_property_rows() {
+        let mut state = NoteEditorState::new(
+            "---\ntags: [a, b]\nstatus: draft\n---\n# Heading\n",
+            "test",
+            Path::new("test.md"),
+            &Symbols::unicode(),
+        );
+        let size = Size::new(40, 10);
+        state.resize_viewport(size);
+
+        let expanded = state.virtual_document.lines().len();
+        update(Message::CursorDown, size, &mut state);
+        update(Message::ToggleProperties, size, &mut state);
+
+        assert_eq!(state.virtual_document.lines().len(), expanded - 2);
+        assert_eq!(
+            state.cursor.source_offset(),
+            0,
+            "a cursor on a hidden row moves to the properties header"
+        );
+
+        update(Message::ToggleProperties, size, &mut state);
+        assert_eq!(state.virtual_document.lines().len(), expanded);
+    }
+
     #[test]
     fn test_yank_without_selection_arms_operator() {
         let mut state = vim_edit_state("hello world\n");
@@ -951,6 +1247,236 @@ mod tests {
         assert_eq!(state.content, "one\none\ntwo\n", "p pastes the line below");
     }
 
+    #[test]
+    fn test_insert_text_at_cursor() {
+        let mut state = vim_edit_state("one\ntwo\n");
+        let size = Size::new(40, 10);
+        update(Message::CursorDown, size, &mut state);
+        update(Message::InsertText("## Agenda\n".into()), size, &mut state);
+        assert_eq!(state.content, "one\n## Agenda\ntwo\n");
+        update(Message::Undo, size, &mut state);
+        assert_eq!(state.content, "one\ntwo\n", "u removes the whole insert");
+    }
+
+    #[test]
+    fn test_replace_filtered_text() {
+        let mut state = vim_edit_state("b\na\nc\n");
+        let size = Size::new(40, 10);
+        assert_eq!(state.filter_input(), (0..6, "b\na\nc\n".into()));
+
+        state.toggle_selection(SelectionMode::Line);
+        update(Message::CursorDown, size, &mut state);
+        let (range, input) = state.filter_input();
+        assert_eq!(input, "b\na\n", "the selected lines are filtered");
+
+        update(
+            Message::ReplaceText(range, "a\nb\n".into()),
+            size,
+            &mut state,
+        );
+        assert_eq!(state.content, "a\nb\nc\n");
+        assert_eq!(state.selection(), None);
+        update(Message::Undo, size, &mut state);
+        assert_eq!(state.content, "b\na\nc\n", "u restores the filtered text");
+    }
+
+    #[test]
+    fn test_save_and_close_keeps_tab_when_write_fails() {
+        // The parent folder does not exist, so the write fails without creating anything.
+        let path = Path::new("/nonexistent-basalt-folder/test.md");
+        let mut state = NoteEditorState::new("one\n", "test", path, &Symbols::unicode());
+        let size = Size::new(40, 10);
+        state.resize_viewport(size);
+        state.set_view(View::Edit(EditMode::Source));
+        update(Message::InsertText("zero\n".into()), size, &mut state);
+        assert!(state.modified());
+
+        let message = update(Message::SaveAndClose, size, &mut state);
+        assert!(
+            matches!(message, Some(AppMessage::Toast(_))),
+            "the tab is not closed, got {message:?}"
+        );
+        assert!(state.modified(), "the changes are kept");
+    }
+
+    #[test]
+    fn test_filter_without_selection_reads_whole_note() {
+        let mut state = vim_edit_state("b\na\n");
+        let size = Size::new(40, 10);
+        update(Message::CursorDown, size, &mut state);
+
+        let (range, input) = state.filter_input();
+        assert_eq!(
+            input, "b\na\n",
+            "the whole note is read without a selection"
+        );
+        update(
+            Message::ReplaceText(range, "a\nb\n".into()),
+            size,
+            &mut state,
+        );
+        assert_eq!(state.content, "a\nb\n", "and replaced by filter:");
+        update(Message::Undo, size, &mut state);
+
+        let insert = state.insert_range();
+        assert_eq!(insert, 2..2, "read: inserts at the cursor");
+        update(Message::ReplaceText(insert, "2\n".into()), size, &mut state);
+        assert_eq!(state.content, "b\n2\na\n", "keeping the text it read");
+        update(Message::Undo, size, &mut state);
+        assert_eq!(state.content, "b\na\n", "u removes the inserted output");
+    }
+
+    #[test]
+    fn test_search_and_repeat() {
+        // one two one\ntwo one
+        let mut state = vim_edit_state("one two one\ntwo one\n");
+        let size = Size::new(40, 10);
+        let forward = motion::Direction::Forward;
+        let preview = |pattern: &str| Message::SearchPreview {
+            pattern: pattern.into(),
+            direction: forward,
+        };
+
+        update(preview("t"), size, &mut state);
+        update(preview("tw"), size, &mut state);
+        assert_eq!(state.cursor.source_offset(), 4, "typing previews the match");
+        assert_eq!(state.search_matches(), [4..6, 12..14]);
+        update(Message::SearchCancel, size, &mut state);
+        assert_eq!(state.cursor.source_offset(), 0, "cancelling moves back");
+        assert!(state.search_matches().is_empty());
+
+        update(preview("one"), size, &mut state);
+        update(
+            Message::Search {
+                pattern: "one".into(),
+                direction: forward,
+            },
+            size,
+            &mut state,
+        );
+        assert_eq!(state.cursor.source_offset(), 8, "/one -> second one");
+
+        update(Message::SearchNext { reverse: false }, size, &mut state);
+        assert_eq!(state.cursor.source_offset(), 16, "n -> one on line two");
+        update(Message::SearchNext { reverse: false }, size, &mut state);
+        assert_eq!(state.cursor.source_offset(), 0, "n wraps to the top");
+        update(Message::SearchNext { reverse: true }, size, &mut state);
+        assert_eq!(state.cursor.source_offset(), 16, "N wraps to the bottom");
+
+        update(Message::CountDigit(2), size, &mut state);
+        update(
+            Message::SearchWord(motion::Direction::Backward),
+            size,
+            &mut state,
+        );
+        assert_eq!(state.cursor.source_offset(), 0, "2# -> two matches back");
+        update(Message::SearchWord(forward), size, &mut state);
+        assert_eq!(state.cursor.source_offset(), 8, "* -> next whole word");
+
+        let message = update(
+            Message::Search {
+                pattern: "three".into(),
+                direction: forward,
+            },
+            size,
+            &mut state,
+        );
+        assert!(matches!(message, Some(AppMessage::Toast(_))));
+        assert_eq!(state.cursor.source_offset(), 8, "a failed search stays");
+
+        update(
+            Message::Search {
+                pattern: "one".into(),
+                direction: forward,
+            },
+            size,
+            &mut state,
+        );
+        assert_eq!(state.search_matches(), [0..3, 8..11, 16..19]);
+        update(
+            Message::ReplaceText(20..20, "one\n".into()),
+            size,
+            &mut state,
+        );
+        assert_eq!(
+            state.search_matches(),
+            [0..3, 8..11, 16..19, 20..23],
+            "the matches follow edits"
+        );
+    }
+
+    #[test]
+    fn test_operator_with_search_motion() {
+        let mut state = vim_edit_state("keep delete this foo bar\n");
+        let size = Size::new(40, 10);
+
+        update(Message::CursorWordForward, size, &mut state);
+        update(Message::Operator(Operator::Delete), size, &mut state);
+        update(
+            Message::SearchPreview {
+                pattern: "fo".into(),
+                direction: motion::Direction::Forward,
+            },
+            size,
+            &mut state,
+        );
+        update(
+            Message::Search {
+                pattern: "foo".into(),
+                direction: motion::Direction::Forward,
+            },
+            size,
+            &mut state,
+        );
+        assert_eq!(
+            state.content, "keep foo bar\n",
+            "d/foo deletes up to the match"
+        );
+        assert_eq!(state.pending_operator(), None);
+    }
+
+    #[test]
+    fn test_substitute_and_jump_to_line() {
+        let mut state = vim_edit_state("foo foo\nbar\nfoo\n");
+        state.set_editor_enabled(true);
+        let size = Size::new(40, 10);
+        let substitute = |input, whole_note| {
+            Message::Substitute(Substitution::parse(input, whole_note).unwrap())
+        };
+
+        update(substitute("/foo/baz/", false), size, &mut state);
+        assert_eq!(
+            state.content, "baz foo\nbar\nfoo\n",
+            ":s changes the cursor's line"
+        );
+
+        update(substitute("/o+/0/g", true), size, &mut state);
+        assert_eq!(state.content, "baz f0\nbar\nf0\n");
+        assert_eq!(
+            state.cursor_line(),
+            3,
+            "the cursor lands on the last changed line"
+        );
+
+        update(Message::Undo, size, &mut state);
+        assert_eq!(
+            state.content, "baz foo\nbar\nfoo\n",
+            "u undoes the whole :%s"
+        );
+
+        let message = update(substitute("/qux/baz/", true), size, &mut state);
+        assert!(matches!(message, Some(AppMessage::Toast(_))));
+
+        update(Message::JumpToLine(2), size, &mut state);
+        assert_eq!(state.cursor_line(), 2);
+        update(Message::JumpToLine(99), size, &mut state);
+        assert_eq!(
+            state.cursor_line(),
+            3,
+            "lines past the end land on the last line"
+        );
+    }
+
     #[test]
     fn test_undo_redo() {
         let mut state = vim_edit_state("hello\n");
@@ -5,7 +5,9 @@
 //! boundary, so a returned offset is safe to slice at. Functions that can fail
 //! to find a target return `Option` instead of clamping.
 
-use std::ops::Range;
+use std::{iter, ops::Range};
+
+use regex::Regex;
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 enum Class {
@@ -24,7 +26,7 @@ fn class(c: char, big: bool) -> Class {
     }
 }
 
-/// Direction a find (`f`/`F`/`t`/`T`) scans.
+/// Direction a find (`f`/`F`/`t`/`T`) or a search (`/`/`?`) scans.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Direction {
     Forward,
@@ -217,6 +219,39 @@ pub fn find_char(
     }
 }
 
+/// The start of the next match of `regex` after (or the previous one before) `offset`. Unlike
+/// a find, a search spans lines and wraps around the ends of the content.
+pub fn search(content: &str, offset: usize, regex: &Regex, direction: Direction) -> Option<usize> {
+    let mut starts = regex.find_iter(content).map(|found| found.start());
+    match direction {
+        Direction::Forward => {
+            let first = starts.next()?;
+            iter::once(first)
+                .chain(starts)
+                .find(|&start| start > offset)
+                .or(Some(first))
+        }
+        Direction::Backward => {
+            let (before, last) = starts.fold((None, None), |(before, _), start| {
+                (
+                    if start < offset { Some(start) } else { before },
+                    Some(start),
+                )
+            });
+            before.or(last)
+        }
+    }
+}
+
+/// The keyword under the cursor, or the first one after it on the line, searched by `*` and `#`.
+pub fn keyword_at(content: &str, offset: usize) -> Option<Range<usize>> {
+    let end = line_end_exclusive(content, offset);
+    let (start, _) = chars_from(content, offset)
+        .take_while(|&(i, _)| i < end)
+        .find(|&(_, c)| class(c, false) == Class::Word)?;
+    Some(word_object(content, start, false, false))
+}
+
 fn is_empty_line(content: &str, line_start: usize) -> bool {
     line_start >= content.len() || content[line_start..].starts_with('\n')
 }
@@ -573,6 +608,35 @@ mod tests {
         assert_eq!(find_char(text, 6, 'z', Direction::Backward, false), None);
     }
 
+    #[test]
+    fn search_wraps_around() {
+        let text = "foo bar\nbar foo\n";
+        let regex = Regex::new("foo").unwrap();
+        assert_eq!(search(text, 0, &regex, Direction::Forward), Some(12));
+        assert_eq!(search(text, 12, &regex, Direction::Forward), Some(0)); // wraps to the top
+        assert_eq!(search(text, 12, &regex, Direction::Backward), Some(0));
+        assert_eq!(search(text, 0, &regex, Direction::Backward), Some(12)); // wraps to the bottom
+        assert_eq!(
+            search(text, 0, &Regex::new("baz").unwrap(), Direction::Forward),
+            None
+        );
+    }
+
+    #[test]
+    fn keyword_at_or_after_the_cursor() {
+        let text = "- some_word, next\nline";
+        assert_eq!(
+            keyword_at(text, 5).map(|range| &text[range]),
+            Some("some_word")
+        );
+        assert_eq!(
+            keyword_at(text, 0).map(|range| &text[range]),
+            Some("some_word")
+        );
+        assert_eq!(keyword_at(text, 11).map(|range| &text[range]), Some("next"));
+        assert_eq!(keyword_at("a ...\nb", 2), None); // does not look past the line
+    }
+
     #[test]
     fn paragraph_motions() {
         let text = "a\n\nb\n\nc";
@@ -1,77 +1,32 @@
-use std::ops::{Deref, DerefMut};
-
-use pulldown_cmark::{CodeBlockKind, Event, Options, Tag, TagEnd};
+//! Builds the note editor's [`Node`]s from the markdown AST of [`basalt_core::markdown`].
+//!
+//! The note editor maps the rendered text back onto the source, so the text of a node keeps the
+//! characters of the source where the core AST drops them: `==` around highlights, and `$` around
+//! math whose unicode approximation does not fit in its source.
+use basalt_core::{
+    markdown::{self, MarkdownNode, Options, TextNode},
+    obsidian::wiki_link::WikiLink,
+};
 
 use crate::note_editor::{
     ast::{self, Node, SourceRange, TaskKind},
+    footnote,
     rich_text::{RichText, Style, TextSegment},
 };
 
 pub struct Parser<'a> {
-    events: pulldown_cmark::TextMergeWithOffset<'a, pulldown_cmark::OffsetIter<'a>>,
     source: &'a str,
 }
 
-impl<'a> Deref for Parser<'a> {
-    type Target = pulldown_cmark::TextMergeWithOffset<'a, pulldown_cmark::OffsetIter<'a>>;
-    fn deref(&self) -> &Self::Target {
-        &self.events
-    }
-}
-
-impl DerefMut for Parser<'_> {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.events
-    }
-}
-
-impl<'a> Iterator for Parser<'a> {
-    type Item = (Event<'a>, SourceRange<usize>);
-    fn next(&mut self) -> Option<Self::Item> {
-        self.deref_mut().next()
-    }
-}
-
-#[derive(Clone, Debug, PartialEq, Default)]
-pub struct ParserState {
-    task_kind: Vec<ast::TaskKind>,
-    item_kind: Vec<ast::ItemKind>,
-}
-
 impl<'a> Parser<'a> {
     /// Creates a new [`Parser`] from a Markdown input string.
-    ///
-    /// The parser uses [`pulldown_cmark::Parser::new_ext`] with [`Options::all()`] and
-    /// [`pulldown_cmark::TextMergeWithOffset`] internally.
-    ///
-    /// The offset is required to know where the node appears in the provided source text.
     pub fn new(text: &'a str) -> Self {
-        let mut options = Options::all();
-
-        // Smart punctuation is excluded because it converts ASCII characters (e.g. ", ') to
-        // multi-byte Unicode (“, ‘), making the rendered text longer than the source, causing the
-        // source offset to overlap in some cases and causing unexpected behavior.
-        //
-        // TODO: Holistic approach to support smart punctation. Potentially need to do this on a
-        // different layer of the app to only do the smart punctuation effect visually using
-        // virtual elements or such, but keeping the original source content unchanged.
-        options.remove(Options::ENABLE_SMART_PUNCTUATION);
-
-        let parser = pulldown_cmark::TextMergeWithOffset::new(
-            pulldown_cmark::Parser::new_ext(text, options).into_offset_iter(),
-        );
-
-        Self {
-            events: parser,
-            source: text,
-        }
+        Self { source: text }
     }
 
-    /// A thematic break (`---`, or a table's leftover dash row once the pipes are gone) is kept as
-    /// a plain paragraph so its source stays visible and editable. Without this it parses as a
-    /// [`pulldown_cmark::Event::Rule`], which carries no text and would leave that line uncovered
-    /// by any node — invisible and impossible to fix. Horizontal rules are not rendered specially.
-    fn rule_node(&self, source_range: SourceRange<usize>) -> Node {
+    /// An HTML block is kept as a plain paragraph of its source so it stays visible and editable.
+    /// HTML is not rendered.
+    fn html_node(&self, source_range: SourceRange<usize>) -> Node {
         let text = self
             .source
             .get(source_range.clone())
@@ -83,297 +38,308 @@ impl<'a> Parser<'a> {
         }
     }
 
-    pub fn parse(mut self) -> Vec<Node> {
-        let mut result = Vec::new();
-        let mut state = ParserState::default();
-
-        while let Some((event, source_range)) = self.next() {
-            match event {
-                Event::Start(Tag::Table(alignments)) => {
-                    result.push(self.parse_table(alignments, source_range));
-                }
-                Event::Start(tag) if Self::is_container_tag(&tag) => {
-                    if let Some(node) = self.parse_container(tag, &mut state) {
-                        result.push(node);
-                    }
-                }
-                Event::Rule => result.push(self.rule_node(source_range)),
-                _ => {}
-            }
+    /// Turns a paragraph holding only a `![[Note#Heading]]` embed into a note embed. Embeds of a
+    /// heading in the same note (`![[#Heading]]`) stay paragraphs.
+    fn embed_node(&self, text: RichText, source_range: SourceRange<usize>) -> Node {
+        let source = self.source.get(source_range.clone()).unwrap_or_default();
+        let link = source
+            .find("[[")
+            .and_then(|start| WikiLink::at(source, start + 2))
+            .filter(|link| !link.target.is_empty());
+
+        match link {
+            Some(link) => Node::Embed {
+                target: link.target,
+                heading: link.heading,
+                source_range,
+            },
+            None => Node::Paragraph { text, source_range },
         }
-
-        result
     }
 
-    pub fn parse_container(&mut self, tag: Tag, state: &mut ParserState) -> Option<Node> {
-        let mut nodes = Vec::new();
-        let mut text_segments = Vec::new();
-        let mut inline_styles = Vec::new();
-
-        match tag {
-            Tag::List(Some(start)) => {
-                state.item_kind.push(ast::ItemKind::Ordered(start));
-            }
-            Tag::List(..) => {
-                state.item_kind.push(ast::ItemKind::Unordered);
-            }
-            _ => {}
-        };
+    pub fn parse(self) -> Vec<Node> {
+        let mut options = Options::all();
 
-        while let Some((event, source_range)) = self.next() {
-            match event {
-                Event::Start(Tag::Table(alignments)) => {
-                    nodes.push(self.parse_table(alignments, source_range));
-                }
+        // Smart punctuation is excluded because it converts ASCII characters (e.g. ", ') to
+        // multi-byte Unicode (“, ‘), making the rendered text longer than the source, causing the
+        // source offset to overlap in some cases and causing unexpected behavior.
+        //
+        // TODO: Holistic approach to support smart punctation. Potentially need to do this on a
+        // different layer of the app to only do the smart punctuation effect visually using
+        // virtual elements or such, but keeping the original source content unchanged.
+        options.remove(Options::ENABLE_SMART_PUNCTUATION);
 
-                Event::Start(inner_tag) if Self::is_container_tag(&inner_tag) => {
-                    if let Some(node) = self.parse_container(inner_tag, state) {
-                        nodes.push(node);
-                    }
-                }
+        markdown::Parser::new_ext(self.source, options)
+            .parse()
+            .into_iter()
+            .flat_map(|node| self.nodes(node))
+            .collect()
+    }
 
-                Event::Start(inner_tag) if Self::is_inline_tag(&inner_tag) => {
-                    if let Some(style) = Self::tag_to_style(&inner_tag) {
-                        inline_styles.push(style);
-                    }
+    /// Converts a node of the markdown AST. Definition lists have no node of their own in the note
+    /// editor, so they are replaced by their children.
+    fn nodes(&self, node: markdown::Node) -> Vec<Node> {
+        let source_range = node.source_range;
+
+        let node = match node.markdown_node {
+            MarkdownNode::Heading { level, text } => Node::Heading {
+                level: level.into(),
+                text: rich_text(text),
+                source_range,
+            },
+            MarkdownNode::Paragraph { text } => self.paragraph(text, source_range),
+            MarkdownNode::BlockQuote { kind, nodes } => {
+                let nodes = self.children(nodes);
+                let (marker, nodes) = resolve_callout(kind.map(Into::into), nodes);
+                let (kind, title, fold) = match marker {
+                    Some(marker) => (Some(marker.kind), marker.title, marker.fold),
+                    None => (None, None, None),
+                };
+                Node::BlockQuote {
+                    kind,
+                    title,
+                    fold,
+                    nodes,
+                    source_range,
                 }
+            }
+            MarkdownNode::CodeBlock { lang, text } => Node::CodeBlock {
+                lang,
+                text: rich_text(text),
+                source_range,
+            },
+            MarkdownNode::List { nodes, .. } => Node::List {
+                nodes: self.children(nodes),
+                source_range,
+            },
+            MarkdownNode::Item { kind, nodes } => self.item(kind, nodes, source_range),
+            MarkdownNode::Table {
+                alignments,
+                head,
+                rows,
+            } => Node::Table {
+                alignments: alignments.into_iter().map(Into::into).collect(),
+                head: head.into_iter().map(rich_text).collect(),
+                rows: rows
+                    .into_iter()
+                    .map(|row| row.into_iter().map(rich_text).collect())
+                    .collect(),
+                source_range,
+            },
+            MarkdownNode::Rule => Node::Rule { source_range },
+            MarkdownNode::HtmlBlock { .. } => self.html_node(source_range),
+            MarkdownNode::DefinitionTitle { text } => Node::Paragraph {
+                text: rich_text(text),
+                source_range,
+            },
+            MarkdownNode::FootnoteDefinition { label, nodes } => Node::FootnoteDefinition {
+                label,
+                nodes: self.children(nodes),
+                source_range,
+            },
+            MarkdownNode::DefinitionList { nodes } | MarkdownNode::Definition { nodes } => {
+                return self.children(nodes)
+            }
+            MarkdownNode::Frontmatter { frontmatter } => Node::Frontmatter {
+                frontmatter,
+                source_range,
+            },
+        };
 
-                Event::Rule => nodes.push(self.rule_node(source_range)),
-
-                Event::TaskListMarker(checked) => {
-                    state.task_kind.push(if checked {
-                        TaskKind::Checked
-                    } else {
-                        TaskKind::Unchecked
-                    });
-                }
+        vec![node]
+    }
 
-                Event::Code(text) => {
-                    let text_segment = TextSegment::styled(&text, Style::Code);
-                    text_segments.push(text_segment);
-                }
+    fn children(&self, nodes: Vec<markdown::Node>) -> Vec<Node> {
+        nodes
+            .into_iter()
+            .flat_map(|node| self.nodes(node))
+            .collect()
+    }
 
-                Event::Text(text) => {
-                    let mut text_segment = TextSegment::plain(&text);
-                    inline_styles.iter().for_each(|style| {
-                        text_segment.add_style(style);
-                    });
-                    text_segments.push(text_segment);
+    /// Converts a list item into an item or a task. The text of an item in a tight list, which
+    /// covers the whole item, is kept a paragraph even if it holds only an embed.
+    fn item(
+        &self,
+        kind: markdown::ItemKind,
+        nodes: Vec<markdown::Node>,
+        source_range: SourceRange<usize>,
+    ) -> Node {
+        let nodes = nodes
+            .into_iter()
+            .flat_map(|node| match node.markdown_node {
+                MarkdownNode::Paragraph { text } if node.source_range == source_range => {
+                    vec![Node::Paragraph {
+                        text: rich_text(text),
+                        source_range: node.source_range,
+                    }]
                 }
-
-                Event::SoftBreak => {
-                    let text_segment = TextSegment::empty_line();
-                    text_segments.push(text_segment);
+                _ => self.nodes(node),
+            })
+            .collect();
+
+        let task_kind = match kind {
+            markdown::ItemKind::HardChecked => TaskKind::Checked,
+            markdown::ItemKind::Checked => TaskKind::LooselyChecked,
+            markdown::ItemKind::Unchecked => TaskKind::Unchecked,
+            markdown::ItemKind::Ordered(number) => {
+                return Node::Item {
+                    kind: ast::ItemKind::Ordered(number),
+                    nodes,
+                    source_range,
                 }
-
-                Event::End(tag_end) if Self::tags_match(&tag, &tag_end) => {
-                    let text = if !text_segments.is_empty() {
-                        RichText::from(text_segments)
-                    } else {
-                        RichText::empty()
-                    };
-
-                    return match tag {
-                        Tag::Heading { level, .. } => Some(Node::Heading {
-                            level: level.into(),
-                            text,
-                            source_range,
-                        }),
-                        Tag::Item => {
-                            // This is required since in block quotes list items are considered
-                            // "tight", thus the text is not stored in a paragraph directly.
-                            // TODO: Think if wrapping this into a paragraph is a good idea or not.
-                            // Potentially storing a RichText here is better.
-                            if !text.is_empty() {
-                                nodes.insert(
-                                    0,
-                                    Node::Paragraph {
-                                        text,
-                                        source_range: source_range.clone(),
-                                    },
-                                );
-                            }
-
-                            let item = if let Some(kind) = state.task_kind.pop() {
-                                Some(Node::Task {
-                                    kind,
-                                    nodes,
-                                    source_range,
-                                })
-                            } else {
-                                Some(Node::Item {
-                                    kind: state
-                                        .item_kind
-                                        .last()
-                                        .cloned()
-                                        .unwrap_or(ast::ItemKind::Unordered),
-                                    nodes,
-                                    source_range,
-                                })
-                            };
-
-                            if let Some(ast::ItemKind::Ordered(start)) = state.item_kind.last_mut()
-                            {
-                                *start += 1;
-                            };
-
-                            item
-                        }
-                        Tag::List(..) => {
-                            state.item_kind.pop();
-
-                            Some(Node::List {
-                                nodes,
-                                source_range,
-                            })
-                        }
-                        Tag::CodeBlock(kind) => Some(Node::CodeBlock {
-                            lang: match kind {
-                                CodeBlockKind::Fenced(lang) => Some(lang.to_string()),
-                                _ => None,
-                            },
-                            text,
-                            source_range,
-                        }),
-                        Tag::BlockQuote(kind) => {
-                            let (kind, title, nodes) =
-                                resolve_callout(kind.map(|kind| kind.into()), nodes);
-                            Some(Node::BlockQuote {
-                                kind,
-                                title,
-                                nodes,
-                                source_range,
-                            })
-                        }
-                        Tag::Paragraph => Some(Node::Paragraph { text, source_range }),
-                        _ => None,
-                    };
+            }
+            markdown::ItemKind::Unordered => {
+                return Node::Item {
+                    kind: ast::ItemKind::Unordered,
+                    nodes,
+                    source_range,
                 }
-                _ => {}
             }
-        }
+        };
 
-        None
+        Node::Task {
+            kind: task_kind,
+            nodes,
+            source_range,
+        }
     }
 
-    /// Parses a table from the events between `Start(Table)` and `End(Table)`.
-    ///
-    /// Cells before the first `TableRow` belong to the header; the rest form body rows. Inline
-    /// styles are tracked per cell so emphasis inside a cell is preserved.
-    fn parse_table(
-        &mut self,
-        alignments: Vec<pulldown_cmark::Alignment>,
-        source_range: SourceRange<usize>,
-    ) -> Node {
-        let alignments = alignments.into_iter().map(Into::into).collect();
-        let mut head = Vec::new();
-        let mut rows = Vec::new();
-        let mut row = Vec::new();
-        let mut cell = Vec::new();
-        let mut inline_styles = Vec::new();
-        let mut in_head = false;
-
-        for (event, _) in self.by_ref() {
-            match event {
-                Event::Start(Tag::TableHead) => in_head = true,
-                Event::Start(Tag::TableRow) => in_head = false,
-                Event::Start(Tag::TableCell) => {
-                    cell = Vec::new();
-                    inline_styles.clear();
-                }
-                Event::Start(inner_tag) if Self::is_inline_tag(&inner_tag) => {
-                    if let Some(style) = Self::tag_to_style(&inner_tag) {
-                        inline_styles.push(style);
+    /// Converts a paragraph. A paragraph with a single embed and no other text becomes an image
+    /// or a note embed node.
+    fn paragraph(&self, text: markdown::Text, source_range: SourceRange<usize>) -> Node {
+        // Embeds of the paragraph, as (target, alt text, is wiki-link).
+        let mut embeds: Vec<(&str, String, bool)> = Vec::new();
+        let mut previous_embed = None;
+        let mut has_text = false;
+
+        for node in &text {
+            let embed = node.styles.iter().find_map(|style| match style {
+                markdown::Style::Image(target) => Some((target.as_str(), false)),
+                markdown::Style::Embed(target) => Some((target.as_str(), true)),
+                _ => None,
+            });
+
+            match embed {
+                Some(embed) if previous_embed == Some(embed) => {
+                    if let Some((.., alt, _)) = embeds.last_mut() {
+                        alt.push_str(&node.content);
                     }
                 }
-                Event::End(TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough) => {
-                    inline_styles.pop();
+                Some((target, is_wiki_link)) => {
+                    embeds.push((target, node.content.clone(), is_wiki_link))
                 }
-                Event::Code(text) => cell.push(TextSegment::styled(&text, Style::Code)),
-                Event::Text(text) => {
-                    let mut text_segment = TextSegment::plain(&text);
-                    inline_styles
-                        .iter()
-                        .for_each(|style| text_segment.add_style(style));
-                    cell.push(text_segment);
-                }
-                Event::End(TagEnd::TableCell) => {
-                    let text = RichText::from(std::mem::take(&mut cell));
-                    if in_head {
-                        head.push(text);
-                    } else {
-                        row.push(text);
-                    }
+                None => {
+                    has_text |=
+                        node.has_style(&markdown::Style::Code) || !node.content.trim().is_empty()
                 }
-                Event::End(TagEnd::TableRow) => rows.push(std::mem::take(&mut row)),
-                Event::End(TagEnd::Table) => break,
-                _ => {}
             }
+            previous_embed = embed;
         }
 
-        Node::Table {
-            alignments,
-            head,
-            rows,
-            source_range,
+        match embeds.as_slice() {
+            [(target, alt, _)] if !has_text && ast::is_image_target(target) => Node::Image {
+                target: target.to_string(),
+                alt: alt.clone(),
+                source_range,
+            },
+            [(.., true)] if !has_text => self.embed_node(rich_text(text), source_range),
+            _ => Node::Paragraph {
+                text: rich_text(text),
+                source_range,
+            },
         }
     }
+}
 
-    fn is_container_tag(tag: &Tag) -> bool {
-        matches!(
-            tag,
-            Tag::Paragraph
-                | Tag::Item
-                | Tag::List(..)
-                | Tag::BlockQuote(..)
-                | Tag::CodeBlock(..)
-                | Tag::Heading { .. }
-        )
+/// Converts a text node into a segment, approximating math with unicode. A footnote reference
+/// becomes its marker. The innermost style the note editor knows is kept.
+fn text_segment(node: TextNode) -> Option<TextSegment> {
+    use markdown::Style as MarkdownStyle;
+
+    if node.content == "\n" {
+        return Some(TextSegment::empty_line());
     }
 
-    fn is_inline_tag(tag: &Tag) -> bool {
-        matches!(tag, Tag::Emphasis | Tag::Strong | Tag::Strikethrough)
+    let content = if node.has_style(&MarkdownStyle::InlineMath) {
+        return Some(math_segment(&node.content, "$"));
+    } else if node.has_style(&MarkdownStyle::DisplayMath) {
+        return Some(math_segment(&node.content, "$$"));
+    } else if node.has_style(&MarkdownStyle::FootnoteReference) {
+        return Some(TextSegment::styled(
+            &footnote::marker(&node.content),
+            Style::FootnoteReference,
+        ));
+    } else {
+        node.content
+    };
+
+    if content.is_empty() {
+        return None;
     }
 
-    fn tags_match(start: &Tag, end: &TagEnd) -> bool {
-        fn tag_to_end(tag: &Tag) -> Option<TagEnd> {
-            match tag {
-                Tag::Heading { level, .. } => Some(TagEnd::Heading(*level)),
-                Tag::List(ordered) => Some(TagEnd::List(ordered.is_some())),
-                Tag::Item => Some(TagEnd::Item),
-                Tag::BlockQuote(kind) => Some(TagEnd::BlockQuote(*kind)),
-                Tag::CodeBlock(..) => Some(TagEnd::CodeBlock),
-                Tag::Paragraph => Some(TagEnd::Paragraph),
-                _ => None,
-            }
-        }
+    let style = node.styles.iter().rev().find_map(|style| match style {
+        MarkdownStyle::Code => Some(Style::Code),
+        MarkdownStyle::Emphasis => Some(Style::Emphasis),
+        MarkdownStyle::Strong => Some(Style::Strong),
+        MarkdownStyle::Strikethrough => Some(Style::Strikethrough),
+        _ => None,
+    });
 
-        if let Some(start) = tag_to_end(start) {
-            std::mem::discriminant(&start) == std::mem::discriminant(end)
-        } else {
-            false
-        }
+    Some(TextSegment::new(&content, style))
+}
+
+/// Converts math into its unicode approximation. The math is kept as its `$` delimited source
+/// instead when the approximation would take more bytes than the source, so the rendered text never
+/// runs past the source offsets of its line.
+fn math_segment(latex: &str, delimiter: &str) -> TextSegment {
+    let unicode = markdown::math::to_unicode(latex);
+    if !unicode.is_empty() && unicode.len() <= latex.len() + 2 * delimiter.len() {
+        TextSegment::styled(&unicode, Style::Math)
+    } else {
+        TextSegment::plain(&format!("{delimiter}{latex}{delimiter}"))
     }
+}
+
+/// Converts the text of a node, putting back the `==` delimiters around highlighted text.
+fn rich_text(text: markdown::Text) -> RichText {
+    let mut segments = Vec::new();
+    let mut highlight = false;
 
-    fn tag_to_style(tag: &Tag) -> Option<Style> {
-        match tag {
-            Tag::Emphasis => Some(Style::Emphasis),
-            Tag::Strong => Some(Style::Strong),
-            Tag::Strikethrough => Some(Style::Strikethrough),
-            _ => None,
+    for node in text {
+        let highlighted = node.has_style(&markdown::Style::Highlight);
+        if highlighted != highlight {
+            segments.push(TextSegment::plain("=="));
+            highlight = highlighted;
         }
+        segments.extend(text_segment(node));
+    }
+
+    if highlight {
+        segments.push(TextSegment::plain("=="));
+    }
+
+    if segments.is_empty() {
+        RichText::empty()
+    } else {
+        RichText::from(segments)
     }
 }
 
-/// Resolves a quote's callout kind and title. `pulldown_cmark` handles bare
-/// GitHub alerts (`kind` is `Some`); otherwise we look for an Obsidian-style
-/// marker on the first line and strip that line from the body.
+/// Resolves a quote's callout marker: its kind, title and fold state.
+/// The markdown parser handles bare GitHub alerts (`kind` is `Some`); otherwise
+/// we look for an Obsidian-style marker on the first line and strip that line
+/// from the body.
 fn resolve_callout(
     kind: Option<ast::BlockQuoteKind>,
     mut nodes: Vec<Node>,
-) -> (Option<ast::BlockQuoteKind>, Option<String>, Vec<Node>) {
-    if kind.is_some() {
-        return (kind, None, nodes);
+) -> (Option<ast::CalloutMarker>, Vec<Node>) {
+    if let Some(kind) = kind {
+        let marker = ast::CalloutMarker {
+            kind,
+            title: None,
+            fold: None,
+        };
+        return (Some(marker), nodes);
     }
 
     let stripped = match nodes.first() {
@@ -395,7 +361,7 @@ fn resolve_callout(
     };
 
     let Some((marker, body, source_range)) = stripped else {
-        return (None, None, nodes);
+        return (None, nodes);
     };
 
     if body.is_empty() {
@@ -407,7 +373,7 @@ fn resolve_callout(
         };
     }
 
-    (Some(marker.kind), marker.title, nodes)
+    (Some(marker), nodes)
 }
 
 pub fn from_str(text: &str) -> Vec<Node> {
@@ -546,9 +512,9 @@ mod tests {
                 "#},
             ),
             (
-                // A thematic break (and a table's leftover dash row once the pipes are
-                // deleted) is kept as a plain paragraph so its source stays visible and
-                // editable rather than vanishing.
+                // A thematic break, and a table's leftover dash row once the pipes are
+                // deleted, is a rule node covering its source line so it stays editable
+                // rather than vanishing.
                 "horizontal_rule",
                 indoc! { r#"## Horizontal rule
 
@@ -601,6 +567,66 @@ mod tests {
                 ```
                 "#},
             ),
+            (
+                // Only an image embed on a paragraph of its own becomes an image node; images
+                // within text and embeds of notes stay paragraphs.
+                "images",
+                indoc! { r#"## Images
+
+                ![[diagram.png]]
+
+                ![[assets/photo.JPG|300]]
+
+                ![A cat](../cats/a%20cat.webp "Cat")
+
+                Text with an ![inline](inline.png) image.
+                "#},
+            ),
+            (
+                // Embeds of other notes and their headings become embed nodes; embeds within text
+                // and of headings of the same note stay paragraphs.
+                "embeds",
+                indoc! { r#"## Embeds
+
+                ![[Another note]]
+
+                ![[folder/Recipes#Pancakes|pancakes]]
+
+                See ![[Inline note]] here.
+
+                ![[#Same note]]
+                "#},
+            ),
+            (
+                // Footnote references become their markers. Definition lists have no node of
+                // their own and become their children, highlights and math keep their delimiters.
+                "footnotes_math_and_definitions",
+                indoc! { r#"## Footnotes
+
+                A ==highlighted== claim[^1] with $e^{i\pi} + 1 = 0$ inline.
+
+                [^1]: The source.
+
+                $$\sum_{n=1}^\infty n$$
+
+                Term
+                : Definition of the term.
+                "#},
+            ),
+            (
+                "frontmatter",
+                indoc! { r#"---
+                tags: [recipe, breakfast]
+                servings: 4
+                ---
+                ## Frontmatter
+                Only a metadata block at the very start of the note is frontmatter.
+
+                ---
+                not: frontmatter
+                ---
+                "#},
+            ),
         ];
 
         tests.into_iter().for_each(|(name, text)| {
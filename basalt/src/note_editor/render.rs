@@ -1,19 +1,26 @@
+use std::ops::Range;
+
 use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
     style::{Color, Modifier, Style, Stylize},
     text::Span,
 };
 
+use basalt_core::markdown::{highlight, Frontmatter};
 use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 use crate::{
     config::{Symbols, Theme},
     note_editor::{
         ast::{self, SourceRange},
-        rich_text::RichText,
+        footnote,
+        rich_text::{self, RichText, TextSegment},
         text_wrap::wrap_preserve_trailing,
+        viewport::Viewport,
         virtual_document::{
-            content_span, empty_virtual_line, synthetic_span, virtual_line, VirtualBlock,
-            VirtualLine, VirtualSpan,
+            content_span, empty_virtual_line, is_empty_line, synthetic_span, virtual_line,
+            VirtualBlock, VirtualLine, VirtualSpan,
         },
     },
     stylized_text::stylize,
@@ -39,6 +46,11 @@ const QUOTE_BAR: &str = "┃ ";
 /// Ref: #79.
 const CALLOUT_BAR: &str = "▋ ";
 
+/// Frame drawn around an embedded note.
+const EMBED_TOP: &str = "╭─ ";
+const EMBED_BAR: &str = "│ ";
+const EMBED_BOTTOM: &str = "╰─";
+
 #[derive(Clone, PartialEq, Debug)]
 pub enum RenderStyle {
     Raw,
@@ -145,10 +157,16 @@ fn display_width(text: &str) -> usize {
 /// stays byte-aligned with the source. This mirrors how editing-markdown
 /// previews work (decorate, reveal raw on the cursor line) and keeps nested
 /// lists and structural edits reliable.
+///
+/// In live preview the other lines hide their markdown syntax as well: heading
+/// markers and inline markup (emphasis, code, links) are shown only on the
+/// cursor's line.
+#[allow(clippy::too_many_arguments)]
 pub fn edit_lines<'a>(
     content: &str,
     base: usize,
     cursor_offset: usize,
+    live_preview: bool,
     max_width: usize,
     horizontal_offset: usize,
     symbols: &Symbols,
@@ -185,6 +203,7 @@ pub fn edit_lines<'a>(
                 text,
                 &line_range,
                 line_range.contains(&cursor_offset),
+                live_preview,
                 max_width,
                 fill_width,
                 quote,
@@ -202,6 +221,7 @@ fn edit_line<'a>(
     text: &str,
     line_range: &SourceRange<usize>,
     is_cursor: bool,
+    live_preview: bool,
     max_width: usize,
     fill_width: usize,
     quote: Option<QuoteStyle>,
@@ -219,10 +239,12 @@ fn edit_line<'a>(
     let rest = &text[indent_len..];
 
     // Headings always keep their rendered style (bold, colour, underline) while
-    // editing; the `#` markers stay visible but dimmed so they can still be edited.
+    // editing; the `#` markers stay visible but dimmed so they can still be edited,
+    // unless live preview hides them off the cursor's line.
     if let Some(level) = heading_level(rest) {
+        let preview = live_preview && !is_cursor;
         return heading_lines(
-            text, line_range, indent_len, level, fill_width, symbols, theme,
+            text, line_range, indent_len, level, preview, fill_width, symbols, theme,
         );
     }
 
@@ -236,7 +258,162 @@ fn edit_line<'a>(
         return render_raw_line(text, Span::default(), line_range, max_width, symbols);
     }
 
-    decorate_line(text, line_range, max_width, quote, symbols, theme)
+    decorate_line(
+        text,
+        line_range,
+        live_preview,
+        max_width,
+        quote,
+        symbols,
+        theme,
+    )
+}
+
+/// The spans of a line with its inline markdown syntax (emphasis, code, links) hidden, the way
+/// it reads outside the editor, starting at the source offset `start`. Every segment keeps its
+/// inline style on top of `style`.
+///
+/// The spans stay byte-aligned with the source: a segment found as is in the source becomes a
+/// content span of exactly its bytes, and the hidden syntax between segments becomes a
+/// zero-width content span. Segments shown differently from their source, like math and
+/// footnote markers, are synthetic. A line that does not parse as a single paragraph is kept as
+/// is.
+fn preview_spans<'a>(
+    text: &str,
+    start: usize,
+    style: Style,
+    theme: &Theme,
+) -> Vec<VirtualSpan<'a>> {
+    let segments = match crate::note_editor::parser::from_str(text).as_slice() {
+        [ast::Node::Paragraph { text, .. }] => text.segments().to_vec(),
+        _ => {
+            return vec![content_span!(
+                Span::styled(text.to_string(), style),
+                start..start + text.len()
+            )]
+        }
+    };
+
+    let hidden = |range: SourceRange<usize>| content_span!(Span::styled("", style), range);
+    let mut spans = Vec::new();
+    let mut offset = 0;
+
+    for segment in segments {
+        let span = Span::styled(
+            segment.content.clone(),
+            style.patch(segment_style(&segment, theme)),
+        );
+        let found = match segment.style {
+            Some(rich_text::Style::Math | rich_text::Style::FootnoteReference) => None,
+            _ => text[offset..].find(&segment.content),
+        };
+
+        match found {
+            Some(index) => {
+                let segment_start = offset + index;
+                if index > 0 {
+                    spans.push(hidden((start + offset)..(start + segment_start)));
+                }
+                offset = segment_start + segment.content.len();
+                spans.push(content_span!(
+                    span,
+                    (start + segment_start)..(start + offset)
+                ));
+            }
+            None => spans.push(synthetic_span!(span)),
+        }
+    }
+
+    if offset < text.len() {
+        spans.push(hidden((start + offset)..(start + text.len())));
+    }
+    spans
+}
+
+/// The style of an inline segment in live preview.
+fn segment_style(segment: &TextSegment, theme: &Theme) -> Style {
+    match segment.style {
+        Some(rich_text::Style::Code) => Style::new().bg(theme.code_bg),
+        Some(rich_text::Style::Emphasis | rich_text::Style::Math) => Style::new().italic(),
+        Some(rich_text::Style::Strong) => Style::new().bold(),
+        Some(rich_text::Style::Strikethrough) => Style::new().crossed_out(),
+        Some(rich_text::Style::FootnoteReference) => Style::new().fg(theme.muted),
+        None => Style::new(),
+    }
+}
+
+/// Wraps the spans of a line the way [`text_wrap`] wraps a single span. Spans are split where
+/// the line wraps, a content span together with its source range, and a zero-width span stays
+/// on the line its position falls on.
+fn wrap_spans<'a>(
+    spans: Vec<VirtualSpan<'a>>,
+    prefix: Span<'static>,
+    width: usize,
+    marker: Option<Span<'static>>,
+    symbols: &Symbols,
+) -> Vec<VirtualLine<'a>> {
+    let text: String = spans
+        .iter()
+        .map(|(VirtualSpan::Content(span, _) | VirtualSpan::Synthetic(span))| span.content.as_ref())
+        .collect();
+    let wrap_marker = &symbols.wrap_marker;
+    let wrapped = wrap_preserve_trailing(&text, width, wrap_marker.width() + 1);
+
+    let mut ends: Vec<usize> = wrapped
+        .iter()
+        .scan(0, |end, line| {
+            *end += line.len();
+            Some(*end)
+        })
+        .collect();
+    let last = ends.len() - 1;
+    ends[last] = ends[last].max(text.len());
+    let row_of = |offset: usize| ends.iter().position(|end| offset < *end).unwrap_or(last);
+
+    let mut rows: Vec<Vec<VirtualSpan<'a>>> = vec![Vec::new(); ends.len()];
+    let mut offset = 0;
+    for span in spans {
+        let (VirtualSpan::Content(content, _) | VirtualSpan::Synthetic(content)) = &span;
+        let (content, style) = (content.content.to_string(), content.style);
+        if content.is_empty() {
+            rows[row_of(offset)].push(span);
+            continue;
+        }
+
+        let mut piece_start = 0;
+        while piece_start < content.len() {
+            let row = row_of(offset + piece_start);
+            let piece_end = (ends[row] - offset).min(content.len());
+            let piece = Span::styled(content[piece_start..piece_end].to_string(), style);
+            rows[row].push(match &span {
+                VirtualSpan::Content(_, range) => content_span!(
+                    piece,
+                    (range.start + piece_start)..(range.start + piece_end)
+                ),
+                VirtualSpan::Synthetic(_) => synthetic_span!(piece),
+            });
+            piece_start = piece_end;
+        }
+        offset += content.len();
+    }
+
+    let marker_padding = marker.as_ref().map(|marker| marker.width()).unwrap_or(0);
+    rows.into_iter()
+        .enumerate()
+        .map(|(i, spans)| {
+            let mut line = vec![synthetic_span!(prefix)];
+            match (&marker, i) {
+                (Some(marker), 0) => line.push(synthetic_span!(marker)),
+                (None, 0) => {}
+                _ => line.extend([
+                    synthetic_span!(Span::styled(" ".repeat(marker_padding), prefix.style)),
+                    synthetic_span!(Span::styled(wrap_marker.clone(), Style::new().black())),
+                ]),
+            }
+            line.extend(spans);
+            VirtualLine::new(&line)
+        })
+        .collect()
 }
 
 /// True if the line opens or closes a fenced code block (``` ``` ``` or `~~~`).
@@ -267,12 +444,16 @@ fn code_line<'a>(
 
 /// Renders a heading line for edit mode: the `#` markers are kept (dimmed) so
 /// they remain editable, the title carries its heading style, and H1/H2 get
-/// their underline rule — matching how headings look when not editing.
+/// their underline rule — matching how headings look when not editing. In
+/// preview the markers give way to the rendered heading marker and the title
+/// hides its inline markup.
+#[allow(clippy::too_many_arguments)]
 fn heading_lines<'a>(
     text: &str,
     line_range: &SourceRange<usize>,
     indent_len: usize,
     level: usize,
+    preview: bool,
     fill_width: usize,
     symbols: &Symbols,
     theme: &Theme,
@@ -280,18 +461,38 @@ fn heading_lines<'a>(
     let marker_end = (indent_len + level + 1).min(text.len());
     let title = &text[marker_end..];
     let start = line_range.start;
-
-    let mut lines = vec![virtual_line!([
-        content_span!(text[..indent_len].to_string(), start..start + indent_len),
-        content_span!(
-            text[indent_len..marker_end].to_string().fg(theme.muted),
-            (start + indent_len)..(start + marker_end)
-        ),
-        content_span!(
-            heading_span(title, level, theme),
-            (start + marker_end)..line_range.end
-        )
-    ])];
+    let title_range = (start + marker_end)..line_range.end;
+
+    let mut lines = if preview {
+        let marker = match level {
+            3 => format!("{} ", symbols.h3_marker).fg(theme.heading_3),
+            4 => format!("{} ", symbols.h4_marker).fg(theme.heading_4),
+            5 => format!("{} ", symbols.h5_marker).into(),
+            6 => format!("{} ", symbols.h6_marker).into(),
+            _ => Span::default(),
+        };
+        let mut spans = vec![
+            content_span!(text[..indent_len].to_string(), start..start + indent_len),
+            content_span!(Span::default(), (start + indent_len)..(start + marker_end)),
+            synthetic_span!(marker),
+        ];
+        spans.extend(preview_spans(
+            title,
+            title_range.start,
+            heading_span("", level, theme).style,
+            theme,
+        ));
+        vec![VirtualLine::new(&spans)]
+    } else {
+        vec![virtual_line!([
+            content_span!(text[..indent_len].to_string(), start..start + indent_len),
+            content_span!(
+                text[indent_len..marker_end].to_string().fg(theme.muted),
+                (start + indent_len)..(start + marker_end)
+            ),
+            content_span!(heading_span(title, level, theme), title_range)
+        ])]
+    };
 
     match level {
         1 => lines.push(virtual_line!([synthetic_span!(Span::raw(
@@ -333,10 +534,12 @@ fn raw_quote_line<'a>(
 
 /// Renders one non-cursor source line with its marker replaced by a rendered
 /// icon. The content span starts after the marker, so it stays byte-aligned
-/// with the source and the cursor can target it (revealing the raw line).
+/// with the source and the cursor can target it (revealing the raw line). In
+/// live preview the content also hides its inline markup.
 fn decorate_line<'a>(
     text: &str,
     line_range: &SourceRange<usize>,
+    live_preview: bool,
     max_width: usize,
     quote: Option<QuoteStyle>,
     symbols: &Symbols,
@@ -350,10 +553,16 @@ fn decorate_line<'a>(
     let depth = indent.chars().count() / 2;
     let prefix = Span::raw(indent);
 
-    let render = |marker: Option<Span<'static>>, content_start: usize, content: Span<'a>| {
+    // The content after the marker, with its inline markup hidden in live preview.
+    let render = |marker: Option<Span<'static>>, content_start: usize, style: Style| {
+        let content = &text[content_start..];
         let content_range = (line_range.start + content_start)..line_range.end;
+        if live_preview {
+            let spans = preview_spans(content, content_range.start, style, theme);
+            return wrap_spans(spans, prefix.clone(), max_width, marker, symbols);
+        }
         text_wrap(
-            &content,
+            &Span::styled(content.to_string(), style),
             prefix.clone(),
             &content_range,
             max_width,
@@ -365,20 +574,20 @@ fn decorate_line<'a>(
 
     if let Some((checked, marker_len)) = task_marker(rest) {
         let content_start = indent_len + marker_len;
-        let (icon, content) = if checked {
+        let (icon, style) = if checked {
             (
                 format!("{} ", symbols.task_checked).fg(theme.task),
-                Span::raw(text[content_start..].to_string())
+                Style::new()
                     .fg(theme.muted)
                     .add_modifier(Modifier::CROSSED_OUT),
             )
         } else {
             (
                 format!("{} ", symbols.task_unchecked).fg(theme.muted),
-                Span::raw(text[content_start..].to_string()),
+                Style::new(),
             )
         };
-        return render(Some(icon), content_start, content);
+        return render(Some(icon), content_start, style);
     }
     if let Some(marker_len) = unordered_marker(rest) {
         let bullet = if symbols.list_markers.is_empty() {
@@ -390,7 +599,7 @@ fn decorate_line<'a>(
         return render(
             Some(format!("{bullet} ").fg(theme.list_marker)),
             content_start,
-            Span::raw(text[content_start..].to_string()),
+            Style::new(),
         );
     }
     if let Some(marker_len) = ordered_marker(rest) {
@@ -398,7 +607,7 @@ fn decorate_line<'a>(
         return render(
             Some(rest[..marker_len].to_string().fg(theme.list_marker)),
             content_start,
-            Span::raw(text[content_start..].to_string()),
+            Style::new(),
         );
     }
     if let Some((prefix_len, levels)) = quote_prefix(rest) {
@@ -407,11 +616,11 @@ fn decorate_line<'a>(
         return render(
             Some(Span::raw(quote.bar().repeat(levels)).fg(quote.color)),
             content_start,
-            Span::raw(text[content_start..].to_string()),
+            Style::new(),
         );
     }
 
-    render(None, indent_len, Span::raw(rest.to_string()))
+    render(None, indent_len, Style::new())
 }
 
 /// Number of leading `#` for an ATX heading (`# ` .. `###### `), else `None`.
@@ -556,7 +765,10 @@ pub fn heading<'a>(
             option,
             symbols,
         );
-        wrapped_heading.push(virtual_line!([synthetic_span!(underline)]));
+        wrapped_heading.push(virtual_line!([
+            synthetic_span!(prefix),
+            synthetic_span!(underline)
+        ]));
         wrapped_heading
     };
 
@@ -705,14 +917,205 @@ pub fn paragraph<'a>(
     VirtualBlock::new(&lines, source_range)
 }
 
+/// Renders a thematic break as a rule of the `thematic_break` symbol across the note. The rule is
+/// drawn after an empty span covering the source, which the cursor lands on.
+// FIXME: Use options struct or similar
+#[allow(clippy::too_many_arguments)]
+pub fn rule<'a>(
+    content: &str,
+    prefix: Span<'static>,
+    source_range: &SourceRange<usize>,
+    max_width: usize,
+    horizontal_offset: usize,
+    option: &RenderStyle,
+    symbols: &Symbols,
+    theme: &Theme,
+) -> VirtualBlock<'a> {
+    let lines = match option {
+        RenderStyle::Raw => render_raw(content, source_range, max_width, prefix, symbols),
+        RenderStyle::Visual | RenderStyle::Reader => {
+            let source = content
+                .get(source_range.clone())
+                .unwrap_or_default()
+                .trim_end();
+
+            // Extend the rule by the horizontal scroll so it still spans the viewport when panned.
+            let width = (max_width + horizontal_offset).saturating_sub(prefix.width());
+            let rule = symbols
+                .thematic_break
+                .repeat(width / symbols.thematic_break.width().max(1));
+
+            let mut lines = vec![virtual_line!([
+                synthetic_span!(prefix),
+                content_span!(
+                    Span::default(),
+                    source_range.start..source_range.start + source.len()
+                ),
+                synthetic_span!(Span::styled(rule, Style::new().fg(theme.muted)))
+            ])];
+
+            if prefix.to_string().is_empty() {
+                lines.extend([empty_virtual_line!()]);
+            }
+
+            lines
+        }
+    };
+
+    VirtualBlock::new(&lines, source_range)
+}
+
+/// Returns the caption of an image embed: the alt text, or the target when the alt text is empty or
+/// a size (`![[image.png|300]]`).
+pub fn image_label<'a>(target: &'a str, alt: &'a str) -> &'a str {
+    let is_size = alt.split('x').all(|size| size.parse::<u32>().is_ok());
+    if alt.trim().is_empty() || is_size {
+        target
+    } else {
+        alt
+    }
+}
+
+/// Renders an embed as a caption line: the symbol and the label. The picture of an image is drawn
+/// by the editor below the caption when the `images` feature is enabled, and embedded notes are
+/// shown in a frame, see [`transclusion`].
+// FIXME: Use options struct or similar
+#[allow(clippy::too_many_arguments)]
+pub fn embed<'a>(
+    content: &str,
+    prefix: Span<'static>,
+    symbol: &str,
+    label: &str,
+    source_range: &SourceRange<usize>,
+    max_width: usize,
+    option: &RenderStyle,
+    symbols: &Symbols,
+    theme: &Theme,
+) -> VirtualBlock<'a> {
+    let lines = match option {
+        RenderStyle::Raw => render_raw(content, source_range, max_width, prefix, symbols),
+        RenderStyle::Visual | RenderStyle::Reader => {
+            let source = content
+                .get(source_range.clone())
+                .unwrap_or_default()
+                .trim_end();
+
+            // The caption is a slice of the source so the cursor maps onto it; the whole embed
+            // is shown when the label is not found as is (e.g. percent-encoded targets).
+            let (label, label_start) = match source.find(label) {
+                Some(offset) => (label, source_range.start + offset),
+                None => (source, source_range.start),
+            };
+
+            let mut lines = vec![virtual_line!([
+                synthetic_span!(prefix),
+                synthetic_span!(Span::styled(
+                    format!("{symbol} "),
+                    Style::new().fg(theme.accent)
+                )),
+                content_span!(
+                    Span::styled(label.to_string(), Style::new().fg(theme.muted).italic()),
+                    label_start..label_start + label.len()
+                )
+            ])];
+
+            if prefix.to_string().is_empty() {
+                lines.extend([empty_virtual_line!()]);
+            }
+
+            lines
+        }
+    };
+
+    VirtualBlock::new(&lines, source_range)
+}
+
+/// Returns the caption of a note embed, `Note` or `Note#Heading`, as written in the source.
+pub fn embed_label(target: &str, heading: Option<&str>) -> String {
+    match heading {
+        Some(heading) => format!("{target}#{heading}"),
+        None => target.to_string(),
+    }
+}
+
+/// Renders an embedded note in a frame below the caption of its embed. The embedded blocks are
+/// read-only: they carry no source ranges of the note they are shown in.
+// FIXME: Use options struct or similar
+#[allow(clippy::too_many_arguments)]
+pub fn transclusion<'a>(
+    content: &str,
+    label: &str,
+    source_range: &SourceRange<usize>,
+    embedded_content: &str,
+    nodes: &[ast::Node],
+    max_width: usize,
+    option: &RenderStyle,
+    symbols: &Symbols,
+    theme: &Theme,
+) -> VirtualBlock<'a> {
+    if matches!(option, RenderStyle::Raw) {
+        return VirtualBlock::new(
+            &render_raw(content, source_range, max_width, Span::default(), symbols),
+            source_range,
+        );
+    }
+
+    let frame = Style::new().fg(theme.muted);
+    let body_width = max_width.saturating_sub(EMBED_BAR.width()).max(1);
+
+    let mut lines = embed(
+        content,
+        Span::styled(EMBED_TOP, frame),
+        &symbols.embed_note,
+        label,
+        source_range,
+        max_width,
+        option,
+        symbols,
+        theme,
+    )
+    .lines;
+
+    for (idx, node) in nodes.iter().enumerate() {
+        if idx > 0 {
+            lines.push(virtual_line!([synthetic_span!(Span::styled(
+                EMBED_BAR.trim_end(),
+                frame
+            ))]));
+        }
+
+        let block = render_node(
+            embedded_content,
+            node,
+            body_width,
+            0,
+            Span::styled(EMBED_BAR, frame),
+            option,
+            symbols,
+            theme,
+            0,
+        );
+        let mut block_lines = block.lines;
+        while block_lines.last().is_some_and(is_empty_line) {
+            block_lines.pop();
+        }
+        lines.extend(block_lines.into_iter().map(VirtualLine::into_synthetic));
+    }
+
+    lines.push(virtual_line!([synthetic_span!(Span::styled(
+        format!("{EMBED_BOTTOM}{}", "─".repeat(body_width)),
+        frame
+    ))]));
+
+    VirtualBlock::new(&lines, source_range)
+}
+
 // FIXME: Use options struct or similar
 #[allow(clippy::too_many_arguments)]
 pub fn code_block<'a>(
     content: &str,
     prefix: Span<'static>,
-    // TODO: Add lang support
-    // Ref: https://github.com/erikjuhani/basalt/issues/96
-    _lang: &Option<String>,
+    lang: &Option<String>,
     text: &RichText,
     source_range: &SourceRange<usize>,
     max_width: usize,
@@ -762,25 +1165,61 @@ pub fn code_block<'a>(
                     .bg(code_bg))
             ]);
 
+            let highlighted = lang
+                .as_deref()
+                .and_then(|lang| highlight::highlight(&text, lang));
+
             let mut current_range_start = source_range.start;
 
             let mut lines = vec![padding_line.clone()];
-            lines.extend(text.lines().map(|line| {
+            lines.extend(text.lines().enumerate().map(|(index, line)| {
                 let source_range = line_range(current_range_start, line.len(), true);
                 current_range_start = source_range.end;
 
+                let tokens = highlighted
+                    .as_ref()
+                    .and_then(|lines| lines.get(index))
+                    .filter(|tokens| !tokens.is_empty());
+
+                let code_spans = match tokens {
+                    Some(tokens) => tokens
+                        .iter()
+                        .enumerate()
+                        .map(|(i, token)| {
+                            // The last token also covers the line break, like an unhighlighted
+                            // line does.
+                            let end = if i + 1 == tokens.len() {
+                                source_range.end
+                            } else {
+                                source_range.start + token.range.end
+                            };
+                            content_span!(
+                                line[token.range.clone()]
+                                    .to_string()
+                                    .fg(theme.code_token(token.kind))
+                                    .bg(code_bg),
+                                (source_range.start + token.range.start..end)
+                            )
+                        })
+                        .collect(),
+                    None => vec![content_span!(line.to_string().bg(code_bg), source_range)],
+                };
+
                 virtual_line!([
-                    synthetic_span!(prefix.clone()),
-                    synthetic_span!(Span::styled(" ", Style::new().bg(code_bg))),
-                    content_span!(line.to_string().bg(code_bg), source_range),
-                    synthetic_span!(" "
+                    vec![
+                        synthetic_span!(prefix.clone()),
+                        synthetic_span!(Span::styled(" ", Style::new().bg(code_bg))),
+                    ],
+                    code_spans,
+                    vec![synthetic_span!(" "
                         .repeat(
                             fill_width
                                 .saturating_sub(prefix.width() + line.chars().count())
                                 .saturating_sub(1)
                         )
-                        .bg(code_bg)),
-                ])
+                        .bg(code_bg))],
+                ]
+                .concat())
             }));
             lines.extend([padding_line]);
             lines.extend([empty_virtual_line!()]);
@@ -1007,6 +1446,72 @@ pub fn item<'a>(
     VirtualBlock::new(&lines, source_range)
 }
 
+/// Renders a footnote definition: its marker and the text of the definition, with the rest of the
+/// definition indented under the text.
+// FIXME: Use options struct or similar
+#[allow(clippy::too_many_arguments)]
+pub fn footnote_definition<'a>(
+    content: &str,
+    prefix: Span<'static>,
+    label: &str,
+    nodes: &[ast::Node],
+    source_range: &SourceRange<usize>,
+    max_width: usize,
+    horizontal_offset: usize,
+    option: &RenderStyle,
+    symbols: &Symbols,
+    theme: &Theme,
+) -> VirtualBlock<'a> {
+    let lines = match option {
+        RenderStyle::Raw => render_raw(content, source_range, max_width, prefix, symbols),
+        RenderStyle::Visual | RenderStyle::Reader => {
+            let marker = format!("{} ", footnote::marker(label)).fg(theme.list_marker);
+            let indent = prefix.merge(" ".repeat(marker.width()).into());
+
+            let (mut lines, rest) = match nodes.split_first() {
+                Some((first @ ast::Node::Paragraph { text, .. }, rest)) => (
+                    text_wrap(
+                        &text.to_string().into(),
+                        prefix.clone(),
+                        first.source_range(),
+                        max_width,
+                        Some(marker),
+                        option,
+                        symbols,
+                    ),
+                    rest,
+                ),
+                _ => (
+                    vec![virtual_line!([
+                        synthetic_span!(prefix),
+                        synthetic_span!(marker)
+                    ])],
+                    nodes,
+                ),
+            };
+
+            lines.extend(rest.iter().flat_map(|node| {
+                render_node(
+                    content,
+                    node,
+                    max_width,
+                    horizontal_offset,
+                    indent.clone(),
+                    option,
+                    symbols,
+                    theme,
+                    0,
+                )
+                .lines
+            }));
+
+            lines
+        }
+    };
+
+    VirtualBlock::new(&lines, source_range)
+}
+
 pub fn line_range(start: usize, line_width: usize, newline: bool) -> SourceRange<usize> {
     // NOTE: When the content is replaced by rope the new lines are kept
     // + 1 for newline
@@ -1117,6 +1622,7 @@ pub fn block_quote<'a>(
     prefix: Span<'static>,
     kind: &Option<ast::BlockQuoteKind>,
     title: &Option<String>,
+    fold: &Option<ast::CalloutFold>,
     nodes: &[ast::Node],
     source_range: &SourceRange<usize>,
     max_width: usize,
@@ -1126,6 +1632,7 @@ pub fn block_quote<'a>(
     theme: &Theme,
 ) -> VirtualBlock<'a> {
     let color = callout_color(kind, theme);
+    let folded = kind.is_some() && *fold == Some(ast::CalloutFold::Folded);
     let bar = if kind.is_some() {
         CALLOUT_BAR
     } else {
@@ -1143,16 +1650,32 @@ pub fn block_quote<'a>(
                     let label = title
                         .clone()
                         .unwrap_or_else(|| callout_label(kind).to_string());
-                    virtual_line!([
-                        synthetic_span!(bar_prefix()),
-                        synthetic_span!(Span::styled(
-                            format!("{} {}", callout_symbol(kind, symbols), label),
-                            Style::new().fg(color).add_modifier(Modifier::BOLD),
-                        ))
-                    ])
+                    let fold_symbol = match fold {
+                        Some(ast::CalloutFold::Folded) => format!(" {}", symbols.tree_collapsed),
+                        Some(ast::CalloutFold::Expanded) => format!(" {}", symbols.tree_expanded),
+                        None => String::new(),
+                    };
+                    let header = Span::styled(
+                        format!("{} {}{}", callout_symbol(kind, symbols), label, fold_symbol),
+                        Style::new().fg(color).add_modifier(Modifier::BOLD),
+                    );
+                    if folded {
+                        // A folded callout is only its header, so the header maps to the
+                        // marker line to keep the cursor able to land on the callout.
+                        let source = content.get(source_range.clone()).unwrap_or_default();
+                        let marker_len = source.find('\n').map_or(source.len(), |end| end + 1);
+                        let marker_range = source_range.start..source_range.start + marker_len;
+                        virtual_line!([
+                            synthetic_span!(bar_prefix()),
+                            content_span!(header, marker_range)
+                        ])
+                    } else {
+                        virtual_line!([synthetic_span!(bar_prefix()), synthetic_span!(header)])
+                    }
                 })
                 .collect();
 
+            let nodes = if folded { &[][..] } else { nodes };
             for (i, node) in nodes.iter().enumerate() {
                 lines.extend(
                     render_node(
@@ -1184,6 +1707,111 @@ pub fn block_quote<'a>(
     VirtualBlock::new(&lines, source_range)
 }
 
+/// Widest property key column; longer keys push their value further right.
+const MAX_PROPERTY_KEY_WIDTH: usize = 16;
+
+/// Renders the note frontmatter as a properties block: a header followed by one row per property,
+/// with the values aligned past the keys. A collapsed block only shows the header.
+///
+/// The header maps to the opening `---` line and each row to the source line of its key, so the
+/// cursor moves through the block row by row and editing starts at the property under it.
+// FIXME: Use options struct or similar
+#[allow(clippy::too_many_arguments)]
+pub fn properties<'a>(
+    content: &str,
+    prefix: Span<'static>,
+    frontmatter: &Frontmatter,
+    source_range: &SourceRange<usize>,
+    collapsed: bool,
+    max_width: usize,
+    option: &RenderStyle,
+    symbols: &Symbols,
+    theme: &Theme,
+) -> VirtualBlock<'a> {
+    let lines = match option {
+        RenderStyle::Raw => render_raw(content, source_range, max_width, prefix, symbols),
+        RenderStyle::Visual | RenderStyle::Reader => {
+            let source = content.get(source_range.clone()).unwrap_or_default();
+            let mut line_start = source_range.start;
+            let source_lines: Vec<(&str, SourceRange<usize>)> = source
+                .lines()
+                .map(|line| {
+                    let range = line_range(line_start, line.len(), true);
+                    line_start = range.end;
+                    (line, range)
+                })
+                .collect();
+            let header_range = source_lines
+                .first()
+                .map(|(_, range)| range.clone())
+                .unwrap_or_else(|| source_range.clone());
+
+            let (symbol, count) = if collapsed {
+                let count = frontmatter.properties.len();
+                (&symbols.tree_collapsed, format!(" ({count})"))
+            } else {
+                (&symbols.tree_expanded, String::new())
+            };
+
+            let mut lines = vec![virtual_line!([
+                synthetic_span!(prefix.clone()),
+                synthetic_span!(Span::raw(format!("{symbol} ")).fg(theme.muted)),
+                content_span!(Span::raw("Properties").fg(theme.muted).bold(), header_range),
+                synthetic_span!(Span::raw(count).fg(theme.muted)),
+            ])];
+
+            if !collapsed {
+                let key_width = frontmatter
+                    .properties
+                    .iter()
+                    .map(|(key, _)| key.width())
+                    .max()
+                    .unwrap_or_default()
+                    .min(MAX_PROPERTY_KEY_WIDTH);
+
+                lines.extend(frontmatter.properties.iter().map(|(key, value)| {
+                    let key_range = source_lines
+                        .iter()
+                        .find(|(line, _)| {
+                            line.strip_prefix(key.as_str())
+                                .is_some_and(|rest| rest.trim_start().starts_with(':'))
+                        })
+                        .map(|(_, range)| range.clone())
+                        .unwrap_or_else(|| header_range.clone());
+
+                    let value = match key.as_str() {
+                        "tags" | "tag" => Span::raw(
+                            frontmatter
+                                .tags
+                                .iter()
+                                .map(|tag| format!("#{tag}"))
+                                .collect::<Vec<_>>()
+                                .join(" "),
+                        )
+                        .fg(theme.accent),
+                        _ => Span::raw(value.to_string()).fg(theme.text),
+                    };
+
+                    virtual_line!([
+                        synthetic_span!(prefix.clone()),
+                        synthetic_span!(Span::raw("  ")),
+                        content_span!(Span::raw(key.clone()).fg(theme.muted), key_range),
+                        synthetic_span!(Span::raw(
+                            " ".repeat(key_width.saturating_sub(key.width()) + 2)
+                        )),
+                        synthetic_span!(value),
+                    ])
+                }));
+            }
+
+            lines.push(empty_virtual_line!());
+            lines
+        }
+    };
+
+    VirtualBlock::new(&lines, source_range)
+}
+
 /// Every table column is at least one cell wide so a border is always drawable.
 const MIN_COLUMN_WIDTH: usize = 1;
 
@@ -1226,10 +1854,12 @@ pub fn table<'a>(
 /// Columns and alignments are derived from the live buffer (not the possibly stale AST) so the box
 /// tracks in-flight edits. Until the buffer holds a header and a delimiter row, the whole block is
 /// edited raw line by line.
+#[allow(clippy::too_many_arguments)]
 pub fn edit_table<'a>(
     content: &str,
     base: usize,
     cursor_offset: usize,
+    live_preview: bool,
     max_width: usize,
     horizontal_offset: usize,
     symbols: &Symbols,
@@ -1264,6 +1894,7 @@ pub fn edit_table<'a>(
             content,
             base,
             cursor_offset,
+            live_preview,
             max_width,
             horizontal_offset,
             symbols,
@@ -1737,6 +2368,7 @@ pub fn render_node<'a>(
         BlockQuote {
             kind,
             title,
+            fold,
             nodes,
             source_range,
         } => block_quote(
@@ -1744,6 +2376,7 @@ pub fn render_node<'a>(
             prefix,
             kind,
             title,
+            fold,
             nodes,
             source_range,
             max_width,
@@ -1768,5 +2401,150 @@ pub fn render_node<'a>(
             option,
             symbols,
         ),
+        Frontmatter {
+            frontmatter,
+            source_range,
+        } => properties(
+            content,
+            prefix,
+            frontmatter,
+            source_range,
+            false,
+            max_width,
+            option,
+            symbols,
+            theme,
+        ),
+        Image {
+            target,
+            alt,
+            source_range,
+        } => embed(
+            content,
+            prefix,
+            &symbols.embed_image,
+            image_label(target, alt),
+            source_range,
+            max_width,
+            option,
+            symbols,
+            theme,
+        ),
+        Embed {
+            target,
+            heading,
+            source_range,
+        } => embed(
+            content,
+            prefix,
+            &symbols.embed_note,
+            &embed_label(target, heading.as_deref()),
+            source_range,
+            max_width,
+            option,
+            symbols,
+            theme,
+        ),
+        Rule { source_range } => rule(
+            content,
+            prefix,
+            source_range,
+            max_width,
+            horizontal_offset,
+            option,
+            symbols,
+            theme,
+        ),
+        FootnoteDefinition {
+            label,
+            nodes,
+            source_range,
+        } => footnote_definition(
+            content,
+            prefix,
+            label,
+            nodes,
+            source_range,
+            max_width,
+            horizontal_offset,
+            option,
+            symbols,
+            theme,
+        ),
     }
 }
+
+/// Whether one of the sorted, non-overlapping `ranges` contains the offset.
+fn ranges_contain(ranges: &[Range<usize>], offset: usize) -> bool {
+    let idx = ranges.partition_point(|range| range.end <= offset);
+    ranges.get(idx).is_some_and(|range| range.contains(&offset))
+}
+
+/// Paints `style` over the cells that show the source bytes in `ranges`, like the search
+/// matches, the visual selection or a yanked range.
+pub fn render_highlight(
+    buf: &mut Buffer,
+    inner_area: Rect,
+    viewport: &Viewport,
+    meta: &[VirtualLine],
+    doc: &[VirtualLine],
+    ranges: &[Range<usize>],
+    style: Style,
+) {
+    let meta_len = meta.len();
+    let viewport_top = viewport.top() as usize;
+    let horizontal_scroll = viewport.left();
+
+    let paint = |buf: &mut Buffer, col: u16, y: u16, width: u16| {
+        if col >= horizontal_scroll {
+            let cell = Rect::new(inner_area.x + col - horizontal_scroll, y, width.max(1), 1)
+                .intersection(inner_area);
+            buf.set_style(cell, style);
+        }
+    };
+
+    meta.iter()
+        .chain(doc.iter())
+        .enumerate()
+        .skip(viewport_top)
+        .take(inner_area.height as usize)
+        .filter(|(idx, _)| *idx >= meta_len)
+        .for_each(|(idx, line)| {
+            let y = inner_area.y + (idx - viewport_top) as u16;
+            let spans = line.virtual_spans();
+            let mut col = 0u16;
+
+            for (i, span) in spans.iter().enumerate() {
+                match span.source_range() {
+                    Some(source_range) => {
+                        span.char_indices().fold(col, |col, (byte_idx, ch)| {
+                            let width = ch.width().unwrap_or(0) as u16;
+                            if ranges_contain(ranges, source_range.start + byte_idx) {
+                                paint(buf, col, y, width);
+                            }
+                            col + width
+                        });
+                    }
+                    // A synthetic span (rendered list marker, prefix, quote glyph)
+                    // stands in for source it does not carry. Highlight it when a
+                    // range runs through the content it precedes, so a selected
+                    // line's marker is not left blank, but a match starting right
+                    // after the marker does not claim it.
+                    None => {
+                        let precedes_selection = spans[i + 1..]
+                            .iter()
+                            .find_map(|span| span.source_range())
+                            .is_some_and(|next| {
+                                next.start > 0
+                                    && ranges_contain(ranges, next.start - 1)
+                                    && ranges_contain(ranges, next.start)
+                            });
+                        if precedes_selection {
+                            paint(buf, col, y, span.width() as u16);
+                        }
+                    }
+                }
+                col += span.width() as u16;
+            }
+        });
+}
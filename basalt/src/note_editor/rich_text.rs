@@ -9,6 +9,10 @@ pub enum Style {
     Emphasis,
     Strong,
     Strikethrough,
+    /// A footnote reference, `[^label]`, whose content is the marker it is shown with.
+    FootnoteReference,
+    /// Inline or display math, whose content is the unicode approximation it is shown with.
+    Math,
 }
 
 impl fmt::Display for Style {
@@ -18,6 +22,8 @@ impl fmt::Display for Style {
             Style::Emphasis => write!(f, "Emphasis"),
             Style::Strong => write!(f, "Strong"),
             Style::Strikethrough => write!(f, "Strikethrough"),
+            Style::FootnoteReference => write!(f, "FootnoteReference"),
+            Style::Math => write!(f, "Math"),
         }
     }
 }
@@ -3,27 +3,32 @@ use std::{
     fmt,
     fs::File,
     io::{self, Write},
+    iter,
     ops::Range,
     path::{Path, PathBuf},
     time::{Duration, Instant},
 };
 
+use basalt_core::obsidian::{wiki_link::WikiLink, Vault};
 use ratatui::{
     layout::{Position, Size},
     style::Color,
 };
+use regex::Regex;
 
 use crate::{
     config::{Symbols, Theme},
     note_editor::{
         ast::{self},
         cursor::{self, Cursor},
-        motion::{Direction, TextObjectKind},
+        footnote,
+        motion::{self, Direction, TextObjectKind},
         parser,
         rich_text::RichText,
+        substitute::Substitution,
         text_buffer::TextBuffer,
         viewport::Viewport,
-        virtual_document::VirtualDocument,
+        virtual_document::{hash_str, VirtualDocument},
     },
 };
 
@@ -32,9 +37,8 @@ pub enum EditMode {
     #[default]
     /// Shows the markdown exactly as written
     Source,
-    // TODO:
-    // /// Hides most of the markdown syntax
-    // LivePreview
+    /// Hides most of the markdown syntax, except on the line under the cursor
+    LivePreview,
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
@@ -72,6 +76,29 @@ struct FindMotion {
     kind: FindKind,
 }
 
+/// The last completed search, replayed by `n` and `N`.
+#[derive(Clone, Debug)]
+struct SearchMotion {
+    regex: Regex,
+    direction: Direction,
+}
+
+/// The matches of a search, with the hash of the content and the pattern they were found for.
+#[derive(Clone, Debug)]
+struct SearchMatches {
+    content_hash: u64,
+    pattern: String,
+    ranges: Vec<Range<usize>>,
+}
+
+/// A search being typed on the command line. The cursor previews the match from `origin`, where
+/// it returns if the search is cancelled.
+#[derive(Clone, Debug)]
+struct IncrementalSearch {
+    origin: usize,
+    regex: Option<Regex>,
+}
+
 /// A keypress the editor is armed to consume next: the target of a find, the
 /// object of a text object, or the replacement of `r`. At most one is armed.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -160,6 +187,7 @@ pub struct NoteEditorState<'a> {
     // FIXME: Use Rope instead of String for O(log n) instead of O(n).
     pub content: String,
     pub view: View,
+    edit_mode: EditMode,
     pub cursor: Cursor,
     pub ast_nodes: Vec<ast::Node>,
     pub virtual_document: VirtualDocument<'a>,
@@ -178,6 +206,12 @@ pub struct NoteEditorState<'a> {
     pending_count: Option<usize>,
     pending: Option<Pending>,
     last_find: Option<FindMotion>,
+    last_search: Option<SearchMotion>,
+    incremental_search: Option<IncrementalSearch>,
+    /// Whether the matches of the last search are highlighted, until `:nohlsearch`.
+    highlight_search: bool,
+    /// The matches of the highlighted search, found again when the content or pattern changes.
+    search_matches: Option<SearchMatches>,
     pending_operator: Option<Operator>,
     register: Register,
     undo_stack: Vec<Snapshot>,
@@ -198,6 +232,7 @@ impl<'a> NoteEditorState<'a> {
             text_buffer: None,
             content,
             view: View::Read,
+            edit_mode: EditMode::default(),
             cursor: Cursor::default(),
             viewport: Viewport::default(),
             symbols: symbols.clone(),
@@ -216,6 +251,10 @@ impl<'a> NoteEditorState<'a> {
             pending_count: None,
             pending: None,
             last_find: None,
+            last_search: None,
+            incremental_search: None,
+            highlight_search: false,
+            search_matches: None,
             pending_operator: None,
             register: Register::default(),
             undo_stack: Vec::new(),
@@ -265,6 +304,28 @@ impl<'a> NoteEditorState<'a> {
         self.vim_mode = mode;
     }
 
+    /// The mode the edit view opens in.
+    pub fn edit_mode(&self) -> EditMode {
+        self.edit_mode
+    }
+
+    /// Sets the mode the edit view opens in, switching an open edit view over to it.
+    pub fn set_edit_mode(&mut self, mode: EditMode) {
+        self.edit_mode = mode;
+        if matches!(self.view, View::Edit(..)) {
+            self.view = View::Edit(mode);
+            self.update_layout();
+        }
+    }
+
+    /// Switches editing between showing the markdown as written and the live preview.
+    pub fn toggle_live_preview(&mut self) {
+        self.set_edit_mode(match self.edit_mode {
+            EditMode::Source => EditMode::LivePreview,
+            EditMode::LivePreview => EditMode::Source,
+        });
+    }
+
     pub fn editor_enabled(&self) -> bool {
         self.editor_enabled
     }
@@ -316,8 +377,8 @@ impl<'a> NoteEditorState<'a> {
         if buffer.modified {
             let new_content = buffer.write(&self.content);
             let changed = self.content != new_content;
-            self.content = new_content;
-            self.ast_nodes = parser::from_str(&self.content);
+            let previous = std::mem::replace(&mut self.content, new_content);
+            self.reparse(&previous);
             self.modified = self.modified || changed;
             Some(())
         } else {
@@ -430,6 +491,9 @@ impl<'a> NoteEditorState<'a> {
         let block_idx = self.current_block_idx();
 
         self.view = view;
+        if let View::Edit(mode) = view {
+            self.edit_mode = mode;
+        }
 
         use cursor::Message::*;
 
@@ -559,6 +623,18 @@ impl<'a> NoteEditorState<'a> {
         self.theme
     }
 
+    /// Sets the vault the note belongs to, which its embeds are resolved against.
+    pub fn set_vault(&mut self, vault: &Vault) {
+        self.virtual_document.set_vault(vault, &self.filepath);
+        self.update_layout();
+    }
+
+    /// Reads the embedded notes again, e.g. after they were edited.
+    pub fn refresh_embeds(&mut self) {
+        self.virtual_document.refresh_embeds();
+        self.update_layout();
+    }
+
     /// Swaps the colour theme and re-lays out so the new colours take effect.
     pub fn set_theme(&mut self, theme: &Theme) {
         self.theme = *theme;
@@ -566,6 +642,53 @@ impl<'a> NoteEditorState<'a> {
         self.update_layout();
     }
 
+    /// Collapses or expands the frontmatter properties block. A cursor on a row that gets hidden
+    /// moves to the block header.
+    pub fn toggle_properties(&mut self) {
+        self.virtual_document.toggle_properties();
+
+        let frontmatter = self
+            .ast_nodes
+            .first()
+            .filter(|node| matches!(node, ast::Node::Frontmatter { .. }))
+            .map(|node| node.source_range().clone());
+
+        if let Some(range) = frontmatter {
+            if self.virtual_document.properties_collapsed()
+                && range.contains(&self.cursor.source_offset())
+            {
+                self.cursor.update(
+                    cursor::Message::Jump(range.start),
+                    self.virtual_document.lines(),
+                    &self.text_buffer,
+                );
+            }
+        }
+
+        self.update_layout();
+    }
+
+    /// Folds or unfolds the innermost foldable callout under the cursor. A cursor in the body of
+    /// a callout that gets folded moves to its header.
+    pub fn toggle_callout_fold(&mut self) {
+        let offset = self.cursor.source_offset();
+        let Some((fold, range)) = ast::toggle_callout_fold(&mut self.ast_nodes, offset) else {
+            return;
+        };
+
+        self.virtual_document.set_callout_fold(range.start, fold);
+
+        if fold == ast::CalloutFold::Folded {
+            self.cursor.update(
+                cursor::Message::Jump(range.start),
+                self.virtual_document.lines(),
+                &self.text_buffer,
+            );
+        }
+
+        self.update_layout();
+    }
+
     pub fn modified(&self) -> bool {
         self.modified || self.text_buffer().is_some_and(|buffer| buffer.modified)
     }
@@ -648,6 +771,88 @@ impl<'a> NoteEditorState<'a> {
         self.last_find.map(|find| (find.target, find.kind))
     }
 
+    /// Previews the search typed so far: the cursor moves to the next match from where the search
+    /// started. A pattern that does not compile yet, like a lone `(`, matches nothing.
+    pub fn preview_search(&mut self, pattern: &str, direction: Direction) {
+        let origin = self
+            .incremental_search
+            .as_ref()
+            .map_or(self.cursor.source_offset(), |search| search.origin);
+        let regex = Some(pattern)
+            .filter(|pattern| !pattern.is_empty())
+            .and_then(|pattern| Regex::new(pattern).ok());
+        let target = regex
+            .as_ref()
+            .and_then(|regex| motion::search(&self.content, origin, regex, direction))
+            .unwrap_or(origin);
+        self.incremental_search = Some(IncrementalSearch { origin, regex });
+        self.jump_to_offset(target);
+    }
+
+    /// Ends the search being typed and moves the cursor back to where it started.
+    pub fn cancel_search(&mut self) {
+        if let Some(search) = self.incremental_search.take() {
+            self.jump_to_offset(search.origin);
+        }
+    }
+
+    /// Records a completed search so `n` and `N` can replay it, and highlights its matches.
+    pub fn remember_search(&mut self, regex: Regex, direction: Direction) {
+        self.last_search = Some(SearchMotion { regex, direction });
+        self.highlight_search = true;
+    }
+
+    pub fn last_search(&self) -> Option<(Regex, Direction)> {
+        self.last_search
+            .as_ref()
+            .map(|search| (search.regex.clone(), search.direction))
+    }
+
+    pub fn clear_search_highlight(&mut self) {
+        self.highlight_search = false;
+    }
+
+    /// Source ranges of the matches to highlight: those of the search being typed, or else those
+    /// of the last search. The note is only searched again when its content or the pattern
+    /// changed.
+    pub fn search_matches(&mut self) -> &[Range<usize>] {
+        let regex = match &self.incremental_search {
+            Some(search) => search.regex.clone(),
+            None => self
+                .last_search
+                .as_ref()
+                .filter(|_| self.highlight_search)
+                .map(|search| search.regex.clone()),
+        };
+        let Some(regex) = regex else {
+            return &[];
+        };
+
+        let content = self.live_content();
+        let content_hash = hash_str(&content);
+        let cached = self.search_matches.as_ref().is_some_and(|matches| {
+            matches.content_hash == content_hash && matches.pattern == regex.as_str()
+        });
+
+        if !cached {
+            let ranges = regex
+                .find_iter(&content)
+                .map(|found| found.range())
+                .filter(|range| !range.is_empty())
+                .collect();
+            drop(content);
+            self.search_matches = Some(SearchMatches {
+                content_hash,
+                pattern: regex.as_str().to_string(),
+                ranges,
+            });
+        }
+
+        self.search_matches
+            .as_ref()
+            .map_or(&[], |matches| matches.ranges.as_slice())
+    }
+
     pub fn awaiting_text_object(&self) -> bool {
         matches!(self.pending, Some(Pending::TextObject(_)))
     }
@@ -760,9 +965,18 @@ impl<'a> NoteEditorState<'a> {
         }
     }
 
-    fn restore(&mut self, snapshot: Snapshot) {
-        self.content = snapshot.content;
+    /// Parses the content again after it changed from `previous`, keeping the callouts the user
+    /// folded or unfolded.
+    fn reparse(&mut self, previous: &str) {
+        self.virtual_document
+            .shift_callout_folds(previous, &self.content);
         self.ast_nodes = parser::from_str(&self.content);
+        ast::apply_callout_folds(&mut self.ast_nodes, self.virtual_document.callout_folds());
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        let previous = std::mem::replace(&mut self.content, snapshot.content);
+        self.reparse(&previous);
         self.modified = true;
         self.text_buffer = None;
         self.editing_block = None;
@@ -774,8 +988,9 @@ impl<'a> NoteEditorState<'a> {
     pub fn splice(&mut self, range: Range<usize>, replacement: &str) {
         self.commit_text_buffer();
         self.mark_undo_point();
+        let previous = self.content.clone();
         self.content.replace_range(range.clone(), replacement);
-        self.ast_nodes = parser::from_str(&self.content);
+        self.reparse(&previous);
         self.modified = true;
         self.text_buffer = None;
         self.editing_block = None;
@@ -820,6 +1035,15 @@ impl<'a> NoteEditorState<'a> {
         }
     }
 
+    /// Inserts the text at the cursor as a single undo step, leaving the cursor after it.
+    pub fn insert_text(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        let cursor = self.cursor.source_offset().min(self.content.len());
+        self.splice(cursor..cursor, text);
+    }
+
     /// Source content as currently displayed, accounting for unsaved edits.
     fn live_content(&self) -> Cow<'_, str> {
         self.text_buffer
@@ -855,6 +1079,135 @@ impl<'a> NoteEditorState<'a> {
         Some(range)
     }
 
+    /// Returns the range and the text a `filter:` or `read:` command reads: the selection, or the
+    /// whole note when nothing is selected. Without a selection the whole note is read on purpose,
+    /// like vim's `:%!`, so a formatter can run on a note without selecting it first.
+    pub fn filter_input(&self) -> (Range<usize>, String) {
+        let content = self.live_content();
+        let range = self.selection_range().unwrap_or(0..content.len());
+        let input = content.get(range.clone()).unwrap_or_default().to_string();
+        (range, input)
+    }
+
+    /// Returns the empty range at the cursor, where a `read:` command inserts its output.
+    pub fn insert_range(&self) -> Range<usize> {
+        let cursor = self.cursor.source_offset().min(self.live_content().len());
+        cursor..cursor
+    }
+
+    /// Replaces the range with the text as a single undo step, leaving the cursor at the start of
+    /// the replaced text. A range outside the note is ignored.
+    pub fn replace_text(&mut self, range: Range<usize>, text: &str) {
+        self.commit_text_buffer();
+        if self.content.get(range.clone()).is_none() {
+            return;
+        }
+        self.clear_selection();
+        self.splice(range.clone(), text);
+        self.jump_to_offset(range.start);
+    }
+
+    /// Runs the substitution on the cursor's line, or on the whole note, as a single undo step.
+    /// The cursor lands on the last line that changed. Returns the number of replaced matches.
+    pub fn substitute(&mut self, substitution: &Substitution) -> usize {
+        self.commit_text_buffer();
+        self.clear_selection();
+
+        let range = if substitution.whole_note {
+            0..self.content.len()
+        } else {
+            let cursor = self.cursor.source_offset().min(self.content.len());
+            let start = self.content[..cursor].rfind('\n').map_or(0, |i| i + 1);
+            let end = self.content[cursor..]
+                .find('\n')
+                .map_or(self.content.len(), |i| cursor + i);
+            start..end
+        };
+
+        let Some(substituted) = substitution.apply(&self.content[range.clone()]) else {
+            return 0;
+        };
+        self.splice(range.clone(), &substituted.text);
+        self.jump_to_offset(range.start + substituted.last_line);
+        substituted.count
+    }
+
+    /// Moves the cursor to the start of the source line, counted from one. Lines past the end of
+    /// the note land on the last line.
+    pub fn jump_to_line(&mut self, line: usize) {
+        self.commit_text_buffer();
+        let len = self.content.len();
+        let offset = iter::once(0)
+            .chain(self.content.match_indices('\n').map(|(i, _)| i + 1))
+            .filter(|&start| start == 0 || start < len)
+            .take(line.max(1))
+            .last()
+            .unwrap_or(0);
+        self.jump_to_offset(offset);
+    }
+
+    /// The source line of the cursor, counted from one.
+    pub fn cursor_line(&self) -> usize {
+        let content = self.live_content();
+        let cursor = self.cursor.source_offset().min(content.len());
+        content[..cursor].matches('\n').count() + 1
+    }
+
+    /// The text of the heading of the section the cursor is in, the closest heading above it.
+    pub fn heading_at_cursor(&self) -> Option<String> {
+        let cursor = self.cursor.source_offset();
+        self.ast_nodes
+            .iter()
+            .take_while(|node| node.source_range().start <= cursor)
+            .filter_map(|node| match node {
+                ast::Node::Heading { text, .. } => Some(text.to_string()),
+                _ => None,
+            })
+            .last()
+    }
+
+    /// The wiki-link under the cursor, accounting for unsaved edits.
+    pub fn wiki_link_at_cursor(&self) -> Option<WikiLink> {
+        WikiLink::at(&self.live_content(), self.cursor.source_offset())
+    }
+
+    /// Returns the link of the note embed in the block under the cursor.
+    pub fn embed_link_at_cursor(&self) -> Option<WikiLink> {
+        match self.ast_nodes.get(self.current_block_idx())? {
+            ast::Node::Embed { source_range, .. } => {
+                let content = self.live_content();
+                let start = content.get(source_range.clone())?.find("[[")? + source_range.start;
+                WikiLink::at(&content, start + 2)
+            }
+            _ => None,
+        }
+    }
+
+    /// Moves the cursor to the definition of the footnote referenced under the cursor.
+    pub fn jump_to_footnote_definition(&mut self) {
+        let target = footnote::reference_at(&self.live_content(), self.cursor.source_offset())
+            .and_then(|label| footnote::definition(&self.ast_nodes, label))
+            .map(|node| match node.children() {
+                Some([first, ..]) => first.source_range().start,
+                _ => node.source_range().start,
+            });
+
+        if let Some(offset) = target {
+            self.jump_to_offset(offset);
+        }
+    }
+
+    /// Moves the cursor from the footnote definition under the cursor back to the first reference
+    /// to it.
+    pub fn jump_to_footnote_reference(&mut self) {
+        let target = footnote::definition_at(&self.ast_nodes, self.cursor.source_offset())
+            .and_then(|label| footnote::first_reference(&self.live_content(), label));
+
+        if let Some(offset) = target {
+            self.jump_to_offset(offset);
+        }
+    }
+
     pub fn selected_text(&self) -> Option<String> {
         let range = self.selection_range()?;
         self.live_content().get(range).map(str::to_string)
@@ -1175,7 +1528,8 @@ fn shift_node(node: &mut ast::Node, offset: usize, shift: isize) {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ratatui::layout::Size;
+    use crate::note_editor::virtual_document::VirtualSpan;
+    use ratatui::{layout::Size, style::Modifier};
     use std::path::Path;
 
     fn assert_cursor_visible(state: &NoteEditorState, context: &str) {
@@ -1255,6 +1609,103 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_cursor_line_and_heading() {
+        let mut state = NoteEditorState::new(
+            "Intro\n\n# Recipes\n\n## Pancakes\n\nFlour.\n",
+            "",
+            Path::new("test.md"),
+            &Symbols::unicode(),
+        );
+        state.resize_viewport(Size::new(20, 20));
+
+        assert_eq!(state.cursor_line(), 1);
+        assert_eq!(state.heading_at_cursor(), None);
+
+        state.jump_to_offset(35);
+        assert_eq!(state.cursor_line(), 7);
+        assert_eq!(state.heading_at_cursor(), Some("Pancakes".into()));
+
+        state.jump_to_offset(7);
+        assert_eq!(state.cursor_line(), 3);
+        assert_eq!(state.heading_at_cursor(), Some("Recipes".into()));
+    }
+
+    /// Footnote references render as superscript markers. Read mode collects the definitions
+    /// below a rule at the end of the note, edit mode keeps them in place.
+    #[test]
+    fn test_footnote_definitions_collected_at_end() {
+        let mut state = NoteEditorState::new(
+            "Claim[^1].\n\n[^1]: The source.\n\nAfter.\n",
+            "",
+            Path::new("test.md"),
+            &Symbols::unicode(),
+        );
+        state.resize_viewport(Size::new(20, 14));
+
+        let read: Vec<String> = line_texts(&state)
+            .into_iter()
+            .filter(|line| !line.is_empty())
+            .collect();
+        assert_eq!(
+            read,
+            ["Claim¹.", "After.", &"═".repeat(20), "¹ The source."],
+            "read mode collects definitions at the end"
+        );
+
+        state.set_view(View::Edit(EditMode::Source));
+        state.jump_to_offset(31);
+        let edit: Vec<String> = line_texts(&state)
+            .into_iter()
+            .filter(|line| !line.is_empty())
+            .collect();
+        assert_eq!(
+            edit,
+            ["Claim¹.", "¹ The source.", "After."],
+            "edit mode keeps definitions in place"
+        );
+    }
+
+    /// A hard line break starts a new line, and a thematic break renders as a rule of the
+    /// `thematic_break` symbol.
+    #[test]
+    fn test_hard_breaks_and_rules() {
+        let mut state = NoteEditorState::new(
+            "Roses\\\nViolets  \nSugar\n\n---\n\nEnd.\n",
+            "",
+            Path::new("test.md"),
+            &Symbols::unicode(),
+        );
+        state.resize_viewport(Size::new(20, 10));
+
+        let lines: Vec<String> = line_texts(&state)
+            .into_iter()
+            .filter(|line| !line.is_empty())
+            .collect();
+        assert_eq!(
+            lines,
+            ["Roses", "Violets", "Sugar", &"─".repeat(20), "End."]
+        );
+    }
+
+    /// Inline and display math render as their unicode approximation.
+    #[test]
+    fn test_math_renders_as_unicode() {
+        let mut state = NoteEditorState::new(
+            "Angles $\\alpha^2 + \\beta_1$ and $\\frac{1}{2}$.\n\n$$\\sqrt{x} \\leq \\infty$$\n",
+            "",
+            Path::new("test.md"),
+            &Symbols::unicode(),
+        );
+        state.resize_viewport(Size::new(40, 10));
+
+        let lines: Vec<String> = line_texts(&state)
+            .into_iter()
+            .filter(|line| !line.is_empty())
+            .collect();
+        assert_eq!(lines, ["Angles α² + β₁ and ½.", "√x ≤ ∞"]);
+    }
+
     /// A callout (`> [!NOTE]`) renders an icon + label header above its body in
     /// read mode, accented in the kind's colour. Ref: #79.
     #[test]
@@ -1294,7 +1745,7 @@ mod tests {
     #[test]
     fn test_obsidian_callout_with_title() {
         let mut state = NoteEditorState::new(
-            "> [!note]- A word on moving your vault folder\n> Body text.\n",
+            "> [!note]+ A word on moving your vault folder\n> Body text.\n",
             "test",
             Path::new("test.md"),
             &Symbols::unicode(),
@@ -1317,6 +1768,71 @@ mod tests {
         );
     }
 
+    /// Foldable callouts start folded (`-`) or expanded (`+`) and toggle under the
+    /// cursor; a callout without a fold marker does not fold.
+    #[test]
+    fn test_toggle_callout_fold() {
+        let mut state = NoteEditorState::new(
+            "> [!tip]- Folded\n> Hidden body.\n\n> [!note]\n> Not foldable.\n",
+            "test",
+            Path::new("test.md"),
+            &Symbols::unicode(),
+        );
+        state.resize_viewport(Size::new(40, 12));
+
+        let read = line_texts(&state);
+        assert!(
+            read.iter().any(|line| line.contains("Folded ▸")),
+            "folded callout shows its header, got {read:?}",
+        );
+        assert!(
+            !read.iter().any(|line| line.contains("Hidden body.")),
+            "folded callout hides its body, got {read:?}",
+        );
+
+        state.toggle_callout_fold();
+        let read = line_texts(&state);
+        assert!(
+            read.iter().any(|line| line.contains("Folded ▾"))
+                && read.iter().any(|line| line.contains("Hidden body.")),
+            "expanded callout shows its body, got {read:?}",
+        );
+
+        state.jump_to_offset(40);
+        let lines = state.virtual_document.lines().len();
+        state.toggle_callout_fold();
+        assert_eq!(state.virtual_document.lines().len(), lines);
+    }
+
+    /// A toggled fold outlives edits elsewhere in the note, which parse it again.
+    #[test]
+    fn test_callout_fold_kept_after_edit() {
+        let content = "Head\n\n> [!tip]- Folded\n> Hidden body.\n\nTail\n";
+        let mut state =
+            NoteEditorState::new(content, "test", Path::new("test.md"), &Symbols::unicode());
+        state.resize_viewport(Size::new(40, 12));
+
+        state.jump_to_offset(content.find("[!tip]").unwrap());
+        state.toggle_callout_fold();
+
+        state.splice(content.len()..content.len(), "x");
+        state.splice(0..0, "New ");
+
+        let read = line_texts(&state);
+        assert!(
+            read.iter().any(|line| line.contains("Folded ▾"))
+                && read.iter().any(|line| line.contains("Hidden body.")),
+            "callout stays unfolded after edits, got {read:?}",
+        );
+
+        state.undo();
+        let read = line_texts(&state);
+        assert!(
+            read.iter().any(|line| line.contains("Hidden body.")),
+            "callout stays unfolded after undo, got {read:?}",
+        );
+    }
+
     /// The full Obsidian type set is supported, including aliases (`summary` is
     /// an alias of `abstract`) and types beyond GitHub's five. Ref: #79.
     #[test]
@@ -1454,6 +1970,80 @@ mod tests {
         );
     }
 
+    /// Live preview hides the markdown syntax of every line but the cursor's,
+    /// and toggling it back shows the source again.
+    #[test]
+    fn test_live_preview_hides_syntax_off_the_cursor_line() {
+        let mut state = NoteEditorState::new(
+            "- **bold** item\n- `code` and **bold** [[Note|link]]\n\n### Heading\n",
+            "test",
+            Path::new("test.md"),
+            &Symbols::unicode(),
+        );
+        state.resize_viewport(Size::new(40, 10));
+        state.set_view(View::Edit(EditMode::LivePreview)); // cursor on the first item
+
+        let lines = line_texts(&state);
+        assert!(
+            lines.iter().any(|line| line == "- **bold** item"),
+            "cursor line stays raw, got {lines:?}",
+        );
+        assert!(
+            lines.iter().any(|line| line == "● code and bold link"),
+            "other lines hide their markup, got {lines:?}",
+        );
+
+        // The hidden markup keeps the inline styles, and every content span of the edited list
+        // still shows exactly its source bytes, or nothing for hidden syntax.
+        let list_end = state.content.find("\n\n").unwrap();
+        let spans: Vec<_> = state
+            .virtual_document
+            .lines()
+            .iter()
+            .flat_map(|line| line.virtual_spans().to_vec())
+            .filter(|span| {
+                span.source_range()
+                    .is_some_and(|range| range.end <= list_end)
+            })
+            .collect();
+        let style_of = |text: &str| {
+            spans.iter().find_map(|span| match span {
+                VirtualSpan::Content(span, _) if span.content == text => Some(span.style),
+                _ => None,
+            })
+        };
+        assert_eq!(
+            style_of("code").and_then(|style| style.bg),
+            Some(state.theme.code_bg)
+        );
+        assert!(style_of("bold").is_some_and(|style| style.add_modifier.contains(Modifier::BOLD)));
+        for span in &spans {
+            if let VirtualSpan::Content(span, range) = span {
+                let source = &state.content[range.clone()];
+                assert!(
+                    span.content.is_empty() || span.content == source.trim_end_matches('\n'),
+                    "{:?} shown for {source:?}",
+                    span.content
+                );
+            }
+        }
+
+        state.toggle_live_preview();
+        assert_eq!(state.view, View::Edit(EditMode::Source));
+        let lines = line_texts(&state);
+        assert!(
+            lines
+                .iter()
+                .any(|line| line == "● `code` and **bold** [[Note|link]]"),
+            "source mode shows the markup, got {lines:?}",
+        );
+
+        state.set_view(View::Read);
+        state.toggle_live_preview();
+        assert_eq!(state.view, View::Read);
+        assert_eq!(state.edit_mode(), EditMode::LivePreview);
+    }
+
     /// When the active block's range swallows the blank lines before the next
     /// block (a list does this), those blanks must still render as editable
     /// lines instead of vanishing. Ref: issue #486.
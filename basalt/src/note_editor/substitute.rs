@@ -0,0 +1,270 @@
+//! The vim `:s` substitution: `:s/pattern/replacement/flags` replaces in the cursor's line and
+//! `:%s/pattern/replacement/flags` in the whole note.
+//!
+//! Patterns use the regex crate syntax. Replacements use the vim syntax: `&` and `\0` insert the
+//! whole match, `\1`..`\9` the capture groups and `\r` (or `\n`) a line break.
+use regex::{Regex, RegexBuilder};
+
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+pub enum SubstituteError {
+    #[error("Missing pattern")]
+    MissingPattern,
+    #[error("Invalid delimiter: {0}")]
+    Delimiter(char),
+    #[error("Invalid pattern: {0}")]
+    Pattern(String),
+    #[error("Unknown flag: {0}")]
+    Flag(char),
+}
+
+/// A parsed substitution, ready to be applied to the text of a line or a note.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Substitution {
+    pattern: String,
+    /// The replacement in the regex crate syntax, e.g. `${1}` for the first group.
+    replacement: String,
+    /// Whether the substitution applies to the whole note (`:%s`) instead of the cursor's line.
+    pub whole_note: bool,
+    /// Replaces every match of a line (`g`) instead of the first one.
+    global: bool,
+    ignore_case: bool,
+}
+
+/// The text after a substitution.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Substituted {
+    pub text: String,
+    /// The number of replaced matches.
+    pub count: usize,
+    /// Byte offset of the start of the last line that changed.
+    pub last_line: usize,
+}
+
+impl Substitution {
+    /// Parses what follows the `s` of the command, e.g. `/foo/bar/g`. Any punctuation character
+    /// but `\`, `"` and `|` can stand in for the `/` delimiter, which is escaped as `\/` in the
+    /// pattern and the replacement. The replacement and the flags may be left out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_tui::note_editor::substitute::Substitution;
+    ///
+    /// let substitution = Substitution::parse(r"/(\w+)@example/\1@test/g", false).unwrap();
+    /// let substituted = substitution.apply("ana@example, bo@example").unwrap();
+    ///
+    /// assert_eq!(substituted.text, "ana@test, bo@test");
+    /// assert_eq!(substituted.count, 2);
+    /// ```
+    pub fn parse(input: &str, whole_note: bool) -> Result<Self, SubstituteError> {
+        let mut chars = input.chars();
+        let delimiter = chars.next().ok_or(SubstituteError::MissingPattern)?;
+        if delimiter.is_alphanumeric() || delimiter.is_whitespace() || "\\\"|".contains(delimiter) {
+            return Err(SubstituteError::Delimiter(delimiter));
+        }
+
+        let mut parts = split_unescaped(chars.as_str(), delimiter).into_iter();
+        let pattern = parts.next().unwrap_or_default();
+        let replacement = parts.next().unwrap_or_default();
+        let flags = parts.next().unwrap_or_default();
+
+        if pattern.is_empty() {
+            return Err(SubstituteError::MissingPattern);
+        }
+
+        let mut substitution = Self {
+            pattern,
+            replacement: replacement_template(&replacement),
+            whole_note,
+            global: false,
+            ignore_case: false,
+        };
+
+        for flag in flags.trim_end().chars() {
+            match flag {
+                'g' => substitution.global = true,
+                'i' => substitution.ignore_case = true,
+                'I' => substitution.ignore_case = false,
+                flag => return Err(SubstituteError::Flag(flag)),
+            }
+        }
+
+        substitution.regex()?;
+        Ok(substitution)
+    }
+
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    fn regex(&self) -> Result<Regex, SubstituteError> {
+        RegexBuilder::new(&self.pattern)
+            .case_insensitive(self.ignore_case)
+            .build()
+            .map_err(|_| SubstituteError::Pattern(self.pattern.clone()))
+    }
+
+    /// Applies the substitution to every line of the text. Returns `None` when nothing matched.
+    pub fn apply(&self, text: &str) -> Option<Substituted> {
+        let regex = self.regex().ok()?;
+        let limit = if self.global { 0 } else { 1 };
+
+        let mut result = String::with_capacity(text.len());
+        let mut count = 0;
+        let mut last_line = None;
+
+        for line in text.split_inclusive('\n') {
+            let (body, newline) = match line.strip_suffix('\n') {
+                Some(body) => (body, "\n"),
+                None => (line, ""),
+            };
+
+            let matches = regex.find_iter(body).count();
+            if matches > 0 {
+                count += if self.global { matches } else { 1 };
+                last_line = Some(result.len());
+                result.push_str(&regex.replacen(body, limit, self.replacement.as_str()));
+            } else {
+                result.push_str(body);
+            }
+            result.push_str(newline);
+        }
+
+        last_line.map(|last_line| Substituted {
+            text: result,
+            count,
+            last_line,
+        })
+    }
+}
+
+/// Splits the text at the delimiters not escaped with a backslash. Escaped delimiters lose their
+/// backslash, every other escape is kept for the pattern and the replacement to interpret.
+fn split_unescaped(text: &str, delimiter: char) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut part = String::new();
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(c) if c == delimiter => part.push(c),
+                Some(c) => part.extend(['\\', c]),
+                None => part.push('\\'),
+            },
+            // The flags take the rest, so a stray delimiter there is reported as a flag.
+            c if c == delimiter && parts.len() < 2 => parts.push(std::mem::take(&mut part)),
+            c => part.push(c),
+        }
+    }
+
+    parts.push(part);
+    parts
+}
+
+/// Turns a vim replacement into the regex crate syntax.
+fn replacement_template(replacement: &str) -> String {
+    let mut template = String::with_capacity(replacement.len());
+    let mut chars = replacement.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(digit @ '0'..='9') => template.push_str(&format!("${{{digit}}}")),
+                Some('r' | 'n') => template.push('\n'),
+                Some('t') => template.push('\t'),
+                Some('$') => template.push_str("$$"),
+                Some(c) => template.push(c),
+                None => template.push('\\'),
+            },
+            '&' => template.push_str("${0}"),
+            '$' => template.push_str("$$"),
+            c => template.push(c),
+        }
+    }
+
+    template
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let substitution = Substitution::parse("#a/b#c\\#d#gi", true).unwrap();
+        assert_eq!(substitution.pattern(), "a/b");
+        assert_eq!(substitution.replacement, "c#d");
+        assert!(substitution.whole_note && substitution.global && substitution.ignore_case);
+
+        assert_eq!(
+            Substitution::parse("/foo", false).map(|s| s.replacement),
+            Ok(String::new())
+        );
+        assert_eq!(
+            Substitution::parse("", false),
+            Err(SubstituteError::MissingPattern)
+        );
+        assert_eq!(
+            Substitution::parse("//bar/", false),
+            Err(SubstituteError::MissingPattern)
+        );
+        assert_eq!(
+            Substitution::parse("afooabara", false),
+            Err(SubstituteError::Delimiter('a'))
+        );
+        assert_eq!(
+            Substitution::parse("/foo/bar/x", false),
+            Err(SubstituteError::Flag('x'))
+        );
+        assert_eq!(
+            Substitution::parse("/(foo/bar/", false),
+            Err(SubstituteError::Pattern("(foo".into()))
+        );
+    }
+
+    #[test]
+    fn test_replacement_template() {
+        let tests = [
+            (r"\1-\2", "${1}-${2}"),
+            (r"[&] \&", "[${0}] &"),
+            (r"a\rb\nc", "a\nb\nc"),
+            (r"$5 \$ \\", "$$5 $$ \\"),
+            (r"trailing\", "trailing\\"),
+        ];
+
+        tests.into_iter().for_each(|(replacement, expected)| {
+            assert_eq!(replacement_template(replacement), expected, "{replacement}")
+        });
+    }
+
+    #[test]
+    fn test_apply() {
+        let text = "foo foo\nbar\nFoo foo\n";
+
+        let first = Substitution::parse("/foo/baz/", true).unwrap();
+        assert_eq!(
+            first.apply(text),
+            Some(Substituted {
+                text: "baz foo\nbar\nFoo baz\n".into(),
+                count: 2,
+                last_line: 12,
+            })
+        );
+
+        let global = Substitution::parse("/foo/baz/gi", true).unwrap();
+        assert_eq!(
+            global.apply(text).map(|s| (s.text, s.count)),
+            Some(("baz baz\nbar\nbaz baz\n".into(), 4))
+        );
+
+        let anchored = Substitution::parse("/^/- /", true).unwrap();
+        assert_eq!(
+            anchored.apply("a\nb").map(|s| s.text),
+            Some("- a\n- b".into())
+        );
+
+        let missing = Substitution::parse("/qux/baz/", true).unwrap();
+        assert_eq!(missing.apply(text), None);
+    }
+}
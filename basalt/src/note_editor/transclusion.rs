@@ -0,0 +1,199 @@
+//! Embedded notes, `![[Note]]` and `![[Note#Heading]]`. The embedded note is resolved like a
+//! wiki-link, read from the vault and parsed with the note parser, and shown read-only inside the
+//! note that embeds it.
+use std::{collections::HashMap, fs, iter};
+
+use basalt_core::obsidian::{wiki_link::WikiLink, Note, Vault};
+use tracing::warn;
+
+use crate::note_editor::{ast::Node, parser};
+
+/// The embedded part of a note.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Transclusion {
+    pub note: Note,
+    pub content: String,
+    /// The section under the embedded heading, or every node of the note but its frontmatter.
+    pub nodes: Vec<Node>,
+}
+
+/// Returns the section under the heading: the heading and the nodes up to the next heading of
+/// the same or a higher level. Headings are compared case-insensitively.
+pub fn section(nodes: Vec<Node>, heading: &str) -> Option<Vec<Node>> {
+    let heading = heading.trim();
+    let mut nodes = nodes.into_iter().skip_while(|node| match node {
+        Node::Heading { text, .. } => !text.to_string().trim().eq_ignore_ascii_case(heading),
+        _ => true,
+    });
+
+    let Some(first @ Node::Heading { level, .. }) = nodes.next() else {
+        return None;
+    };
+
+    let rest = nodes
+        .take_while(|node| !matches!(node, Node::Heading { level: next, .. } if *next <= level));
+
+    Some(iter::once(first).chain(rest).collect())
+}
+
+/// Resolves the embeds of a note against the vault. Resolved embeds are kept until
+/// [`Transclusions::clear`], so a note is read once however often the embedding note is laid out.
+#[derive(Clone, Debug, Default)]
+pub struct Transclusions {
+    vault: Option<Vault>,
+    cache: HashMap<(String, Option<String>), Option<Transclusion>>,
+}
+
+impl Transclusions {
+    /// Sets the vault the embeds resolve against. Returns `true` if it changed.
+    pub fn set_vault(&mut self, vault: &Vault) -> bool {
+        if self.vault.as_ref() == Some(vault) {
+            return false;
+        }
+
+        self.vault = Some(vault.clone());
+        self.cache.clear();
+        true
+    }
+
+    /// Forgets the resolved embeds, so they are read again from the vault.
+    pub fn clear(&mut self) {
+        self.cache.clear();
+    }
+
+    /// Returns the embedded part of the target note, or `None` if the note or the heading does
+    /// not exist.
+    pub fn get(&mut self, target: &str, heading: Option<&str>) -> Option<&Transclusion> {
+        let vault = self.vault.as_ref()?;
+
+        self.cache
+            .entry((target.to_string(), heading.map(str::to_string)))
+            .or_insert_with(|| {
+                let link = WikiLink {
+                    target: target.to_string(),
+                    heading: heading.map(str::to_string),
+                    alias: None,
+                    range: 0..0,
+                };
+                let note = link.resolve(vault)?;
+                let content = fs::read_to_string(note.path())
+                    .inspect_err(|error| warn!(?error, "failed to read embedded note"))
+                    .ok()?;
+
+                let nodes = parser::from_str(&content)
+                    .into_iter()
+                    .filter(|node| !matches!(node, Node::Frontmatter { .. }))
+                    .collect();
+                let nodes = match heading {
+                    Some(heading) => section(nodes, heading)?,
+                    None => nodes,
+                };
+
+                Some(Transclusion {
+                    note,
+                    content,
+                    nodes,
+                })
+            })
+            .as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use ratatui::text::Line;
+
+    use super::*;
+    use crate::{
+        config::{Symbols, Theme},
+        note_editor::render::{self, RenderStyle},
+    };
+
+    #[test]
+    fn test_section() {
+        let nodes = parser::from_str(indoc! {"
+            # Recipes
+
+            ## Pancakes
+
+            Flour, eggs and milk.
+
+            ### Toppings
+
+            Syrup.
+
+            ## Waffles
+
+            Batter.
+        "});
+
+        let texts = |nodes: Vec<Node>| -> Vec<String> {
+            nodes
+                .iter()
+                .map(|node| match node {
+                    Node::Heading { text, .. } => format!("# {text}"),
+                    node => node
+                        .rich_text()
+                        .map(ToString::to_string)
+                        .unwrap_or_default(),
+                })
+                .collect()
+        };
+
+        assert_eq!(
+            texts(section(nodes.clone(), "pancakes").unwrap()),
+            [
+                "# Pancakes",
+                "Flour, eggs and milk.",
+                "# Toppings",
+                "Syrup."
+            ]
+        );
+        assert_eq!(
+            texts(section(nodes.clone(), "Waffles").unwrap()),
+            ["# Waffles", "Batter."]
+        );
+        assert_eq!(section(nodes, "Crepes"), None);
+    }
+
+    #[test]
+    fn test_transclusion_frame() {
+        let content = "![[Recipes#Pancakes]]\n";
+        let embedded = indoc! {"
+            ## Pancakes
+
+            Flour, eggs and milk.
+        "};
+        let nodes = parser::from_str(embedded);
+
+        let block = render::transclusion(
+            content,
+            &render::embed_label("Recipes", Some("Pancakes")),
+            &(0..content.len()),
+            embedded,
+            &nodes,
+            24,
+            &RenderStyle::Visual,
+            &Symbols::unicode(),
+            &Theme::default(),
+        );
+
+        let lines: Vec<String> = block
+            .lines
+            .into_iter()
+            .map(|line| Line::from(line).to_string())
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "╭─ ↪ Recipes#Pancakes",
+                "│ Pancakes",
+                "│ ────────────────────",
+                "│",
+                "│ Flour, eggs and milk.",
+                "╰───────────────────────",
+            ]
+        );
+    }
+}
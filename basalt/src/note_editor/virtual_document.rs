@@ -1,10 +1,16 @@
 use std::{
+    collections::HashMap,
     hash::{DefaultHasher, Hash, Hasher},
     iter,
+    path::Path,
     str::{CharIndices, Chars},
 };
 
-use ratatui::text::{Line, Span};
+use basalt_core::obsidian::Vault;
+use ratatui::{
+    style::Style,
+    text::{Line, Span},
+};
 use unicode_width::UnicodeWidthChar;
 
 use std::borrow::Cow;
@@ -14,10 +20,12 @@ use crate::{
     note_editor::{
         ast::{self, SourceRange},
         render::{
-            edit_lines, edit_table, render_node, text_wrap, trailing_empty_lines, RenderStyle,
+            self, edit_lines, edit_table, properties, render_node, text_wrap, trailing_empty_lines,
+            RenderStyle,
         },
-        state::View,
+        state::{EditMode, View},
         text_buffer::TextBuffer,
+        transclusion::Transclusions,
     },
     stylized_text::stylize,
 };
@@ -153,13 +161,27 @@ impl<'a> VirtualLine<'a> {
             .map(|(start, end)| start..end)
     }
 
+    /// Turns every content span into a synthetic one, for lines showing text from outside the
+    /// note.
+    pub fn into_synthetic(self) -> Self {
+        let spans = self
+            .spans
+            .into_iter()
+            .map(|span| match span {
+                VirtualSpan::Content(span, _) => VirtualSpan::Synthetic(span),
+                span => span,
+            })
+            .collect();
+        Self { spans }
+    }
+
     pub fn has_content(&self) -> bool {
         // We short-circuit when we find content span
         self.spans.iter().any(|span| !span.is_synthetic())
     }
 }
 
-fn is_empty_line(line: &VirtualLine<'_>) -> bool {
+pub(crate) fn is_empty_line(line: &VirtualLine<'_>) -> bool {
     line.virtual_spans()
         .iter()
         .all(|span| span.is_synthetic() && span.width() == 0)
@@ -190,7 +212,7 @@ impl<'a> VirtualBlock<'a> {
     }
 }
 
-fn hash_str(value: &str) -> u64 {
+pub(crate) fn hash_str(value: &str) -> u64 {
     let mut hasher = DefaultHasher::new();
     value.hash(&mut hasher);
     hasher.finish()
@@ -203,7 +225,7 @@ fn hash_str(value: &str) -> u64 {
 struct LayoutKey {
     name_hash: u64,
     content_hash: u64,
-    edit: bool,
+    view: View,
     editing_block: Option<usize>,
     cursor_offset: usize,
     ast_len: usize,
@@ -221,7 +243,14 @@ pub struct VirtualDocument<'a> {
     blocks: Vec<VirtualBlock<'a>>,
     lines: Vec<VirtualLine<'a>>,
     line_to_block: Vec<usize>,
+    properties_collapsed: bool,
+    /// Folds the user toggled, keyed by the start offset of their callout. The parsed nodes only
+    /// know the fold a callout's marker starts with, so these are applied again after a reparse.
+    callout_folds: HashMap<usize, ast::CalloutFold>,
     cache_key: Option<LayoutKey>,
+    transclusions: Transclusions,
+    #[cfg(feature = "images")]
+    embeds: crate::image::Embeds,
 }
 
 impl<'a> VirtualDocument<'a> {
@@ -238,6 +267,87 @@ impl<'a> VirtualDocument<'a> {
             self.cache_key = None;
         }
     }
+
+    pub fn properties_collapsed(&self) -> bool {
+        self.properties_collapsed
+    }
+
+    /// Collapses or expands the frontmatter properties block.
+    pub fn toggle_properties(&mut self) {
+        self.properties_collapsed = !self.properties_collapsed;
+        self.cache_key = None;
+    }
+
+    pub fn callout_folds(&self) -> &HashMap<usize, ast::CalloutFold> {
+        &self.callout_folds
+    }
+
+    /// Records the fold of the callout starting at `start`. The nodes change shape without
+    /// changing the content, so the cached layout is dropped too.
+    pub fn set_callout_fold(&mut self, start: usize, fold: ast::CalloutFold) {
+        self.callout_folds.insert(start, fold);
+        self.cache_key = None;
+    }
+
+    /// Moves the recorded callout folds along with an edit that turned `previous` into
+    /// `content`. Folds of callouts starting inside the edited text are dropped.
+    pub fn shift_callout_folds(&mut self, previous: &str, content: &str) {
+        if self.callout_folds.is_empty() {
+            return;
+        }
+
+        let prefix = iter::zip(previous.bytes(), content.bytes())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let suffix = iter::zip(previous.bytes().rev(), content.bytes().rev())
+            .take(previous.len().min(content.len()) - prefix)
+            .take_while(|(a, b)| a == b)
+            .count();
+        let edit_end = previous.len() - suffix;
+
+        self.callout_folds = self
+            .callout_folds
+            .drain()
+            .filter_map(|(start, fold)| match start {
+                start if start < prefix => Some((start, fold)),
+                start if start >= edit_end => {
+                    Some((content.len() - suffix + start - edit_end, fold))
+                }
+                _ => None,
+            })
+            .collect();
+    }
+
+    /// Sets the vault and the path of the note, which embeds are resolved against.
+    pub fn set_vault(&mut self, vault: &Vault, note_path: &Path) {
+        if self.transclusions.set_vault(vault) {
+            self.cache_key = None;
+        }
+
+        #[cfg(feature = "images")]
+        if self.embeds.set_note_path(&vault.path, note_path) {
+            self.cache_key = None;
+        }
+        #[cfg(not(feature = "images"))]
+        let _ = note_path;
+    }
+
+    /// Reads the embedded notes again on the next layout.
+    pub fn refresh_embeds(&mut self) {
+        self.transclusions.clear();
+        self.cache_key = None;
+    }
+
+    #[cfg(feature = "images")]
+    pub fn embeds(&self) -> &crate::image::Embeds {
+        &self.embeds
+    }
+
+    #[cfg(feature = "images")]
+    pub fn embeds_mut(&mut self) -> &mut crate::image::Embeds {
+        &mut self.embeds
+    }
+
     pub fn meta(&self) -> &[VirtualLine<'_>] {
         &self.meta
     }
@@ -273,10 +383,11 @@ impl<'a> VirtualDocument<'a> {
         text_buffer: Option<TextBuffer>,
     ) {
         let edit = matches!(view, View::Edit(..));
+        let live_preview = matches!(view, View::Edit(EditMode::LivePreview));
         let key = LayoutKey {
             name_hash: hash_str(note_name),
             content_hash: hash_str(content),
-            edit,
+            view: *view,
             editing_block: current_block_idx,
             // The cursor only reshapes layout while editing (it reveals its own
             // line raw in the active block); in Read mode it never does.
@@ -327,16 +438,23 @@ impl<'a> VirtualDocument<'a> {
             View::Edit(..) => RenderStyle::Visual,
             View::Read => RenderStyle::Reader,
         };
+        #[cfg(feature = "images")]
+        self.embeds.clear_placements();
+
         let live_content: Cow<'_, str> = text_buffer
             .as_ref()
             .filter(|tb| tb.modified)
             .map(|tb| Cow::Owned(tb.write(content)))
             .unwrap_or(Cow::Borrowed(content));
 
-        let (blocks, lines, line_to_block) = ast_nodes.iter().enumerate().fold(
+        // Footnote definitions are collected at the end of the note when reading, and left in
+        // place when editing.
+        let mut footnotes: Vec<(usize, Vec<VirtualLine<'a>>)> = Vec::new();
+
+        let (blocks, mut lines, mut line_to_block) = ast_nodes.iter().enumerate().fold(
             (vec![], vec![], vec![]),
             |(mut blocks, mut lines, mut line_to_block), (idx, node)| {
-                let is_active = current_block_idx == Some(idx) && matches!(view, View::Edit(..));
+                let is_active = current_block_idx == Some(idx) && edit;
 
                 // The active block reads from the edit buffer, which may not yet
                 // be re-parsed. Its range tracks in-flight edits exactly.
@@ -359,12 +477,14 @@ impl<'a> VirtualDocument<'a> {
                             .map(|b| b.content.as_str())
                             .unwrap_or("");
                         // A table edits as a box with the cursor's row revealed raw;
-                        // every other block edits raw line by line.
+                        // every other block edits raw line by line. Live preview hides
+                        // the markup of every line but the cursor's.
                         let lines = if matches!(node, ast::Node::Table { .. }) {
                             edit_table(
                                 buffer_content,
                                 range.start,
                                 cursor_offset,
+                                live_preview,
                                 width,
                                 horizontal_offset,
                                 &self.symbols,
@@ -375,6 +495,7 @@ impl<'a> VirtualDocument<'a> {
                                 buffer_content,
                                 range.start,
                                 cursor_offset,
+                                live_preview,
                                 width,
                                 horizontal_offset,
                                 &self.symbols,
@@ -383,17 +504,61 @@ impl<'a> VirtualDocument<'a> {
                         };
                         VirtualBlock::new(&lines, range)
                     }
-                    None => render_node(
-                        &live_content,
-                        node,
-                        width,
-                        horizontal_offset,
-                        Span::default(),
-                        &styled,
-                        &self.symbols,
-                        &self.theme,
-                        0,
-                    ),
+                    None => match node {
+                        ast::Node::Frontmatter {
+                            frontmatter,
+                            source_range,
+                        } => properties(
+                            &live_content,
+                            Span::default(),
+                            frontmatter,
+                            source_range,
+                            self.properties_collapsed,
+                            width,
+                            &styled,
+                            &self.symbols,
+                            &self.theme,
+                        ),
+                        ast::Node::Embed {
+                            target,
+                            heading,
+                            source_range,
+                        } => match self.transclusions.get(target, heading.as_deref()) {
+                            Some(transclusion) => render::transclusion(
+                                &live_content,
+                                &render::embed_label(target, heading.as_deref()),
+                                source_range,
+                                &transclusion.content,
+                                &transclusion.nodes,
+                                width,
+                                &styled,
+                                &self.symbols,
+                                &self.theme,
+                            ),
+                            None => render_node(
+                                &live_content,
+                                node,
+                                width,
+                                horizontal_offset,
+                                Span::default(),
+                                &styled,
+                                &self.symbols,
+                                &self.theme,
+                                0,
+                            ),
+                        },
+                        _ => render_node(
+                            &live_content,
+                            node,
+                            width,
+                            horizontal_offset,
+                            Span::default(),
+                            &styled,
+                            &self.symbols,
+                            &self.theme,
+                            0,
+                        ),
+                    },
                 };
 
                 if matches!(styled, RenderStyle::Visual) {
@@ -436,17 +601,52 @@ impl<'a> VirtualDocument<'a> {
                         .extend((0..trailing).map(|_| empty_virtual_line!()));
                 }
 
+                // Reserve blank rows below the caption of an image, which the editor draws
+                // the image on.
+                #[cfg(feature = "images")]
+                if let (None, ast::Node::Image { target, .. }) = (&active_range, node) {
+                    let line = lines.len() + 1;
+                    if let Some(rows) = self.embeds.place(target, line, width as u16) {
+                        block
+                            .lines
+                            .splice(1..1, (0..rows).map(|_| empty_virtual_line!()));
+                    }
+                }
+
                 let block_lines = block.lines.clone();
                 let line_count = block_lines.len();
 
                 blocks.push(block);
-                lines.extend(block_lines);
-                line_to_block.extend(iter::repeat_n(idx, line_count));
+                if matches!(styled, RenderStyle::Reader)
+                    && matches!(node, ast::Node::FootnoteDefinition { .. })
+                {
+                    footnotes.push((idx, block_lines));
+                } else {
+                    lines.extend(block_lines);
+                    line_to_block.extend(iter::repeat_n(idx, line_count));
+                }
 
                 (blocks, lines, line_to_block)
             },
         );
 
+        if let Some((first_idx, _)) = footnotes.first() {
+            let rule = synthetic_span!(Span::styled(
+                self.symbols.horizontal_rule.repeat(width),
+                Style::new().fg(self.theme.muted)
+            ));
+            lines.push(virtual_line!([rule]));
+            line_to_block.push(*first_idx);
+
+            for (idx, block_lines) in footnotes {
+                line_to_block.extend(iter::repeat_n(idx, block_lines.len()));
+                lines.extend(block_lines);
+            }
+
+            lines.push(empty_virtual_line!());
+            line_to_block.push(line_to_block.last().copied().unwrap_or_default());
+        }
+
         self.blocks = blocks;
         self.lines = lines;
         self.line_to_block = line_to_block;
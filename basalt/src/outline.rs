@@ -25,6 +25,7 @@ pub enum Message {
     Down,
     Select,
     SelectAt(usize),
+    JumpToHeading(String),
     SetNodes(Vec<Node>),
     Expand,
     Toggle,
@@ -59,6 +60,13 @@ pub fn update<'a>(message: &Message, state: &mut OutlineState) -> Option<AppMess
                 )));
             }
         }
+        Message::JumpToHeading(heading) => {
+            let block_idx = state.find_heading(heading)?;
+            state.select_at(block_idx);
+            return Some(AppMessage::NoteEditor(note_editor::Message::JumpToBlock(
+                block_idx,
+            )));
+        }
         Message::ToggleExplorer => {
             return Some(AppMessage::Explorer(explorer::Message::Toggle));
         }
@@ -381,4 +389,32 @@ mod tests {
             assert_snapshot!(name, terminal.backend());
         });
     }
+
+    #[test]
+    fn test_jump_to_heading() {
+        let nodes = parser::from_str(indoc! {r#"
+            # Chapter 1
+            Some paragraph content here.
+
+            ## Section 1.1
+            More content.
+        "#});
+        let mut state = OutlineState::new(&nodes, 0, true, &Symbols::unicode());
+
+        assert_eq!(
+            update(
+                &Message::JumpToHeading(String::from("section 1.1")),
+                &mut state
+            ),
+            Some(AppMessage::NoteEditor(note_editor::Message::JumpToBlock(2)))
+        );
+        assert_eq!(
+            state.selected().map(|item| item.content().to_string()),
+            Some(String::from("Section 1.1"))
+        );
+        assert_eq!(
+            update(&Message::JumpToHeading(String::from("Missing")), &mut state),
+            None
+        );
+    }
 }
@@ -21,6 +21,12 @@ impl Item {
             Item::Heading { range, .. } | Item::HeadingEntry { range, .. } => range,
         }
     }
+    pub fn content(&self) -> &str {
+        match self {
+            Item::Heading { content, .. } | Item::HeadingEntry { content, .. } => content,
+        }
+    }
+
     fn contains_index(&self, index: usize) -> bool {
         self.get_range().contains(&index)
     }
@@ -68,3 +74,20 @@ impl FindItem for Vec<Item> {
             .find_map(|(i, item)| item.contains_index(index).then_some((i, item)))
     }
 }
+
+pub trait FindHeading {
+    fn find_heading(&self, heading: &str) -> Option<&Item>;
+}
+
+impl FindHeading for Vec<Item> {
+    /// Finds the first heading with the given text, including headings under collapsed entries.
+    /// Headings are compared case-insensitively, the same way Obsidian resolves heading links.
+    fn find_heading(&self, heading: &str) -> Option<&Item> {
+        let heading = heading.trim().to_lowercase();
+        self.iter().find_map(|item| match item {
+            _ if item.content().trim().to_lowercase() == heading => Some(item),
+            Item::HeadingEntry { children, .. } => children.find_heading(&heading),
+            Item::Heading { .. } => None,
+        })
+    }
+}
@@ -7,7 +7,7 @@ use crate::{
     note_editor::ast::{HeadingLevel, Node},
 };
 
-use super::item::{FindItem, Flatten, Item};
+use super::item::{FindHeading, FindItem, Flatten, Item};
 
 #[derive(Debug, Default, Clone, PartialEq)]
 pub struct OutlineState {
@@ -168,6 +168,13 @@ impl OutlineState {
         }
     }
 
+    /// Returns the block index of the first heading with the given text.
+    pub fn find_heading(&self, heading: &str) -> Option<usize> {
+        self.items
+            .find_heading(heading)
+            .map(|item| item.get_range().start)
+    }
+
     pub fn set_active(&mut self, active: bool) {
         self.active = active;
     }
@@ -0,0 +1,393 @@
+//! The modals that look up results for a typed query, like the vault search and the quick
+//! switcher. A modal shows a query box above a list of results, edits the query in edit mode and
+//! moves through the results in either mode. What the results are, when they are looked up and
+//! what selecting one does is up to the modal.
+use ratatui::{
+    buffer::Buffer,
+    crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+    layout::{Constraint, Flex, Layout, Position, Rect},
+    style::{Style, Stylize},
+    text::Span,
+    widgets::{
+        Block, BorderType, Clear, List, ListItem, ListState, Padding, Paragraph, Scrollbar,
+        ScrollbarOrientation, ScrollbarState, StatefulWidget, Widget,
+    },
+};
+
+use crate::config::Theme;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    Toggle,
+    Up,
+    Down,
+    Select,
+    /// Enter pressed while editing the query.
+    Submit,
+    Close,
+    EditMode,
+    Cancel,
+    Delete,
+    KeyEvent(KeyEvent),
+}
+
+/// Handles the messages every query modal shares. `query_changed` is called after the query was
+/// edited. Opening the modal, `Submit` and `Select` are left to the modal.
+pub fn update<T>(
+    message: &Message,
+    state: &mut QueryModalState<T>,
+    query_changed: impl FnOnce(&mut QueryModalState<T>),
+) {
+    match message {
+        Message::Close => state.hide(),
+        Message::Up => state.previous(),
+        Message::Down => state.next(),
+        Message::EditMode => state.editing = true,
+        Message::Cancel if state.editing => state.editing = false,
+        Message::Cancel => state.hide(),
+        Message::Delete => {
+            state.query.pop();
+            query_changed(state);
+        }
+        // Characters typed with Ctrl or Alt are shortcuts, not part of the query.
+        Message::KeyEvent(key) => match key.code {
+            KeyCode::Char(c) if (key.modifiers - KeyModifiers::SHIFT).is_empty() => {
+                state.query.push(c);
+                query_changed(state);
+            }
+            KeyCode::Up => state.previous(),
+            KeyCode::Down => state.next(),
+            _ => {}
+        },
+        Message::Toggle | Message::Select | Message::Submit => {}
+    }
+}
+
+pub fn handle_editing_event(key: KeyEvent) -> Option<Message> {
+    match key.code {
+        KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => Some(Message::Up),
+        KeyCode::Char('n') if key.modifiers.contains(KeyModifiers::CONTROL) => Some(Message::Down),
+        KeyCode::Esc => Some(Message::Cancel),
+        KeyCode::Backspace => Some(Message::Delete),
+        KeyCode::Enter => Some(Message::Submit),
+        _ => Some(Message::KeyEvent(key)),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryModalState<T> {
+    pub(crate) visible: bool,
+    pub(crate) query: String,
+    pub(crate) editing: bool,
+    pub(crate) results: Vec<T>,
+    pub(crate) list_state: ListState,
+    pub(crate) terminal_cursor: Option<Position>,
+}
+
+impl<T> Default for QueryModalState<T> {
+    fn default() -> Self {
+        Self {
+            visible: false,
+            query: String::new(),
+            editing: false,
+            results: Vec::new(),
+            list_state: ListState::default(),
+            terminal_cursor: None,
+        }
+    }
+}
+
+impl<T> QueryModalState<T> {
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.editing
+    }
+
+    pub fn terminal_cursor(&self) -> Option<Position> {
+        self.terminal_cursor
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn results(&self) -> &[T] {
+        &self.results
+    }
+
+    pub fn selected(&self) -> Option<&T> {
+        self.list_state
+            .selected()
+            .and_then(|index| self.results.get(index))
+    }
+
+    /// Shows the modal with the query in edit mode.
+    pub(crate) fn open(&mut self) {
+        self.visible = true;
+        self.editing = true;
+    }
+
+    pub(crate) fn hide(&mut self) {
+        self.visible = false;
+        self.editing = false;
+    }
+
+    /// Replaces the results and selects the first one.
+    pub(crate) fn set_results(&mut self, results: Vec<T>) {
+        self.results = results;
+        self.list_state
+            .select((!self.results.is_empty()).then_some(0));
+    }
+
+    fn next(&mut self) {
+        let last = self.results.len().saturating_sub(1);
+        let index = self.list_state.selected().map(|i| (i + 1).min(last));
+        self.list_state.select(index);
+    }
+
+    fn previous(&mut self) {
+        self.list_state.select_previous();
+    }
+}
+
+/// Splits the text into spans, with the characters at the given byte offsets in the accent colour.
+pub fn highlighted<'a>(text: &'a str, indices: &[usize], theme: &Theme) -> Vec<Span<'a>> {
+    text.char_indices()
+        .map(|(offset, c)| {
+            let span = Span::raw(&text[offset..offset + c.len_utf8()]);
+            if indices.contains(&offset) {
+                span.fg(theme.accent).bold()
+            } else {
+                span
+            }
+        })
+        .collect()
+}
+
+/// Draws a result of a query modal, given the width the result can take up.
+pub type RenderItem<T> = for<'b> fn(&'b T, &Theme, usize) -> ListItem<'b>;
+
+/// Draws a query modal centered on the screen, with the query box titled `title` above the
+/// results titled `results_title`.
+pub struct QueryModal<'a, T> {
+    title: &'a str,
+    results_title: String,
+    empty: &'a str,
+    item: RenderItem<T>,
+    height: u16,
+    width: u16,
+    border_type: BorderType,
+    theme: Theme,
+}
+
+impl<'a, T> QueryModal<'a, T> {
+    /// Creates a modal that shows `empty` without results and draws every result with `item`.
+    pub fn new(
+        title: &'a str,
+        results_title: String,
+        empty: &'a str,
+        item: RenderItem<T>,
+        border_type: BorderType,
+        theme: Theme,
+    ) -> Self {
+        Self {
+            title,
+            results_title,
+            empty,
+            item,
+            height: 60,
+            width: 80,
+            border_type,
+            theme,
+        }
+    }
+
+    /// Sets the height in percent of the screen and the width in columns.
+    pub fn size(self, height: u16, width: u16) -> Self {
+        Self {
+            height,
+            width,
+            ..self
+        }
+    }
+
+    fn modal_area(&self, area: Rect) -> Rect {
+        let vertical = Layout::vertical([Constraint::Percentage(self.height)]).flex(Flex::Center);
+        let horizontal = Layout::horizontal([Constraint::Length(self.width)]).flex(Flex::Center);
+        let [area] = vertical.areas(area);
+        let [area] = horizontal.areas(area);
+        area
+    }
+}
+
+impl<T> StatefulWidget for QueryModal<'_, T> {
+    type State = QueryModalState<T>;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        state.terminal_cursor = None;
+
+        let area = self.modal_area(area);
+        Widget::render(Clear, area, buf);
+
+        let [query_area, results_area] =
+            Layout::vertical([Constraint::Length(3), Constraint::Fill(1)]).areas(area);
+
+        let mode_color = if state.editing {
+            self.theme.success
+        } else {
+            self.theme.error
+        };
+        let mode = if state.editing { "Editing" } else { "Normal" };
+
+        Paragraph::new(state.query.as_str())
+            .fg(self.theme.text)
+            .block(
+                Block::bordered()
+                    .border_type(self.border_type)
+                    .border_style(Style::default().fg(self.theme.muted))
+                    .bg(self.theme.background)
+                    .title(format!(" {} ", self.title))
+                    .title_style(Style::default().italic().bold())
+                    .title_bottom(vec![
+                        Span::from(" "),
+                        mode.fg(mode_color).bold().italic(),
+                        Span::from(" "),
+                    ])
+                    .padding(Padding::horizontal(1)),
+            )
+            .render(query_area, buf);
+
+        if state.editing {
+            let width = state.query.chars().count() as u16;
+            state.terminal_cursor = Some(Position::new(
+                (query_area.x + 2 + width).min(query_area.right().saturating_sub(2)),
+                query_area.y + 1,
+            ));
+        }
+
+        let block = Block::bordered()
+            .fg(self.theme.muted)
+            .bg(self.theme.background)
+            .title(format!(" {} ", self.results_title))
+            .title_style(Style::default().italic().bold())
+            .border_type(self.border_type);
+
+        if state.results.is_empty() {
+            Paragraph::new(format!(" {}", self.empty))
+                .fg(self.theme.muted)
+                .block(block)
+                .render(results_area, buf);
+            return;
+        }
+
+        // Inner width without the borders, the highlight symbol and a column for the scrollbar.
+        let width = results_area.width.saturating_sub(4) as usize;
+        let items: Vec<ListItem> = state
+            .results
+            .iter()
+            .map(|result| (self.item)(result, &self.theme, width))
+            .collect();
+        let items_count = items.len();
+        let rows: usize = items.iter().map(ListItem::height).sum();
+
+        let list = List::new(items)
+            .block(block)
+            .fg(self.theme.text)
+            .highlight_style(Style::new().reversed().fg(self.theme.muted))
+            .highlight_symbol(" ");
+        StatefulWidget::render(list, results_area, buf, &mut state.list_state);
+
+        let visible_rows = results_area.height.saturating_sub(2) as usize;
+
+        if !results_area.is_empty() && rows > visible_rows {
+            let mut scroll_state =
+                ScrollbarState::new(items_count).position(state.list_state.selected().unwrap_or(0));
+
+            Scrollbar::new(ScrollbarOrientation::VerticalRight).render(
+                results_area,
+                buf,
+                &mut scroll_state,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn type_query(state: &mut QueryModalState<()>, query: &str, modifiers: KeyModifiers) {
+        query.chars().for_each(|c| {
+            update(
+                &Message::KeyEvent(KeyEvent::new(KeyCode::Char(c), modifiers)),
+                state,
+                |_| {},
+            )
+        });
+    }
+
+    #[test]
+    fn test_cancel_leaves_edit_mode_then_hides() {
+        let mut state = QueryModalState::<()>::default();
+        state.open();
+        assert!(state.is_visible());
+        assert!(state.is_editing());
+
+        update(&Message::Cancel, &mut state, |_| {});
+        assert!(state.is_visible());
+        assert!(!state.is_editing());
+
+        update(&Message::Cancel, &mut state, |_| {});
+        assert!(!state.is_visible());
+    }
+
+    #[test]
+    fn test_query_editing() {
+        let mut state = QueryModalState::<()>::default();
+        state.open();
+
+        type_query(&mut state, "milkk", KeyModifiers::NONE);
+        update(&Message::Delete, &mut state, |_| {});
+        assert_eq!(state.query(), "milk");
+
+        type_query(&mut state, "a", KeyModifiers::CONTROL);
+        type_query(&mut state, "b", KeyModifiers::ALT);
+        type_query(&mut state, "S", KeyModifiers::SHIFT);
+        assert_eq!(state.query(), "milkS");
+    }
+
+    #[test]
+    fn test_query_changes_refresh_results() {
+        let mut state = QueryModalState::<usize>::default();
+        let refresh = |state: &mut QueryModalState<usize>| {
+            let len = state.query.len();
+            state.set_results((0..len).collect());
+        };
+
+        "abc".chars().for_each(|c| {
+            update(
+                &Message::KeyEvent(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)),
+                &mut state,
+                refresh,
+            )
+        });
+        assert_eq!(state.results(), [0, 1, 2]);
+        assert_eq!(state.selected(), Some(&0));
+
+        update(&Message::Down, &mut state, refresh);
+        update(&Message::Down, &mut state, refresh);
+        update(&Message::Down, &mut state, refresh);
+        assert_eq!(
+            state.selected(),
+            Some(&2),
+            "the selection stops at the last result"
+        );
+
+        update(&Message::Delete, &mut state, refresh);
+        assert_eq!(state.results(), [0, 1]);
+        assert_eq!(state.selected(), Some(&0));
+    }
+}
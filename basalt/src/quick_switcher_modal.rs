@@ -0,0 +1,315 @@
+use std::path::{Path, PathBuf};
+
+use basalt_core::obsidian::{Note, Vault, VaultEntry};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Position, Rect},
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{BorderType, ListItem, StatefulWidget},
+};
+
+pub use crate::query_modal::{handle_editing_event, Message};
+use crate::{
+    app::{ActivePane, Message as AppMessage, SelectedNote},
+    config::Theme,
+    fuzzy::{self, FuzzyMatch},
+    query_modal::{self, highlighted, QueryModal, QueryModalState},
+};
+
+/// Bonus added to a match on the note name, so that name matches rank above path matches.
+const NAME_BONUS: i64 = 32;
+
+/// Bonus for the most recently focused open tab. Every older tab gets [`RECENT_STEP`] less.
+const RECENT_BONUS: i64 = 48;
+
+const RECENT_STEP: i64 = 8;
+
+/// Drives the quick switcher. The notes of the vault are listed when the modal opens and ranked
+/// again on every change to the query; `Select` opens the highlighted note in a tab.
+pub fn update<'a>(
+    message: Message,
+    vault: &Vault,
+    recent: &[&Path],
+    state: &mut QuickSwitcherModalState,
+) -> Option<AppMessage<'a>> {
+    match message {
+        Message::Toggle if state.modal.visible => state.modal.hide(),
+        Message::Toggle => state.open(vault, recent),
+        Message::Select | Message::Submit => {
+            let note = state.selected_match()?.note.clone();
+            state.modal.hide();
+            return Some(AppMessage::Batch(vec![
+                AppMessage::SelectNote(SelectedNote::from(&note)),
+                AppMessage::SetActivePane(ActivePane::NoteEditor),
+            ]));
+        }
+        message => {
+            let (notes, recent) = (&state.notes, &state.recent);
+            query_modal::update(&message, &mut state.modal, |modal| {
+                modal.set_results(rank(notes, recent, &modal.query))
+            });
+        }
+    }
+
+    None
+}
+
+/// A note of the vault matching the query.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NoteMatch {
+    pub note: Note,
+    /// Path of the note relative to the vault root.
+    pub path: String,
+    pub score: i64,
+    /// Byte offsets of the matched characters within the note name.
+    pub name_indices: Vec<usize>,
+    /// Byte offsets of the matched characters within [`NoteMatch::path`], set when the path
+    /// matched better than the name.
+    pub path_indices: Vec<usize>,
+}
+
+/// Ranks the notes by their fuzzy score against the query, on the note name or the relative path,
+/// whichever matches better. Recently focused tabs are boosted, and with an empty query they are
+/// listed first. Ties are ordered by path.
+pub fn rank(notes: &[(Note, String)], recent: &[PathBuf], query: &str) -> Vec<NoteMatch> {
+    let mut matches: Vec<NoteMatch> = notes
+        .iter()
+        .filter_map(|(note, path)| {
+            let name = fuzzy::fuzzy_match(query, note.name()).map(|name| FuzzyMatch {
+                score: name.score + NAME_BONUS,
+                ..name
+            });
+            let path_match = fuzzy::fuzzy_match(query, path);
+
+            let (score, name_indices, path_indices) = match (name, path_match) {
+                (Some(name), Some(path)) if path.score > name.score => {
+                    (path.score, vec![], path.indices)
+                }
+                (Some(name), _) => (name.score, name.indices, vec![]),
+                (None, Some(path)) => (path.score, vec![], path.indices),
+                (None, None) => return None,
+            };
+
+            let recency = recent
+                .iter()
+                .position(|recent| recent == note.path())
+                .map_or(0, |index| {
+                    (RECENT_BONUS - index as i64 * RECENT_STEP).max(0)
+                });
+
+            Some(NoteMatch {
+                note: note.clone(),
+                path: path.clone(),
+                score: score + recency,
+                name_indices,
+                path_indices,
+            })
+        })
+        .collect();
+
+    matches.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.path.cmp(&b.path)));
+    matches
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct QuickSwitcherModalState {
+    pub(crate) modal: QueryModalState<NoteMatch>,
+    /// Every note of the vault with its path relative to the vault root.
+    notes: Vec<(Note, String)>,
+    /// Paths of the open tabs, most recently focused first.
+    recent: Vec<PathBuf>,
+}
+
+impl QuickSwitcherModalState {
+    pub fn is_visible(&self) -> bool {
+        self.modal.is_visible()
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.modal.is_editing()
+    }
+
+    pub fn terminal_cursor(&self) -> Option<Position> {
+        self.modal.terminal_cursor()
+    }
+
+    pub fn query(&self) -> &str {
+        self.modal.query()
+    }
+
+    pub fn matches(&self) -> &[NoteMatch] {
+        self.modal.results()
+    }
+
+    pub fn selected_match(&self) -> Option<&NoteMatch> {
+        self.modal.selected()
+    }
+
+    /// Lists the notes of the vault and shows every note, the recently focused tabs first.
+    fn open(&mut self, vault: &Vault, recent: &[&Path]) {
+        self.notes = vault
+            .entries()
+            .into_iter()
+            .flat_map(VaultEntry::into_notes)
+            .map(|note| {
+                let path = note
+                    .path()
+                    .strip_prefix(&vault.path)
+                    .unwrap_or(note.path())
+                    .to_string_lossy()
+                    .replace('\\', "/");
+                (note, path)
+            })
+            .collect();
+        self.recent = recent.iter().map(|path| path.to_path_buf()).collect();
+        self.modal.query.clear();
+        self.modal.open();
+        self.rank();
+    }
+
+    /// Ranks the notes against the current query and selects the best match.
+    fn rank(&mut self) {
+        self.modal
+            .set_results(rank(&self.notes, &self.recent, &self.modal.query));
+    }
+}
+
+pub struct QuickSwitcherModal {
+    pub border_type: BorderType,
+    pub theme: Theme,
+}
+
+impl QuickSwitcherModal {
+    pub fn new(border_type: BorderType, theme: Theme) -> Self {
+        Self { border_type, theme }
+    }
+
+    fn match_item<'a>(note_match: &'a NoteMatch, theme: &Theme, _: usize) -> ListItem<'a> {
+        let mut spans = highlighted(note_match.note.name(), &note_match.name_indices, theme);
+        spans
+            .iter_mut()
+            .for_each(|span| *span = span.clone().bold());
+        spans.push(Span::raw("  "));
+        spans.extend(
+            highlighted(&note_match.path, &note_match.path_indices, theme)
+                .into_iter()
+                .map(|span| match span.style.fg {
+                    Some(_) => span,
+                    None => span.fg(theme.muted),
+                }),
+        );
+
+        ListItem::new(Line::from(spans))
+    }
+}
+
+impl StatefulWidget for QuickSwitcherModal {
+    type State = QuickSwitcherModalState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let results_title = match state.matches().len() {
+            1 => String::from("1 note"),
+            count => format!("{count} notes"),
+        };
+
+        QueryModal::new(
+            "Quick switcher",
+            results_title,
+            "No matching notes",
+            Self::match_item,
+            self.border_type,
+            self.theme,
+        )
+        .render(area, buf, &mut state.modal);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use super::*;
+
+    fn notes(paths: &[&str]) -> Vec<(Note, String)> {
+        paths
+            .iter()
+            .map(|path| {
+                let name = path.rsplit('/').next().unwrap().trim_end_matches(".md");
+                let note = Note::new_unchecked(name, Path::new(&format!("/vault/{path}")));
+                (note, path.to_string())
+            })
+            .collect()
+    }
+
+    fn ranked(matches: &[NoteMatch]) -> Vec<&str> {
+        matches
+            .iter()
+            .map(|note_match| note_match.path.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_rank_by_name_and_path() {
+        let notes = notes(&[
+            "Daily/2024-01-01.md",
+            "Projects/Basalt.md",
+            "Projects/Notes on basalt.md",
+            "Inbox.md",
+        ]);
+
+        assert_eq!(
+            ranked(&rank(&notes, &[], "basalt")),
+            ["Projects/Basalt.md", "Projects/Notes on basalt.md"]
+        );
+        assert_eq!(
+            ranked(&rank(&notes, &[], "daily 01")),
+            ["Daily/2024-01-01.md"]
+        );
+        assert_eq!(rank(&notes, &[], "xyz"), []);
+    }
+
+    #[test]
+    fn test_rank_boosts_recent_tabs() {
+        let notes = notes(&["Alpha.md", "Meeting notes.md", "Team meeting.md"]);
+        let recent = [PathBuf::from("/vault/Team meeting.md")];
+
+        assert_eq!(
+            ranked(&rank(&notes, &[], "meeting")),
+            ["Meeting notes.md", "Team meeting.md"]
+        );
+        assert_eq!(
+            ranked(&rank(&notes, &recent, "meeting")),
+            ["Team meeting.md", "Meeting notes.md"]
+        );
+        assert_eq!(
+            ranked(&rank(&notes, &recent, "")),
+            ["Team meeting.md", "Alpha.md", "Meeting notes.md"]
+        );
+    }
+
+    #[test]
+    fn test_typing_filters_and_select_opens_note() {
+        let mut state = QuickSwitcherModalState {
+            notes: notes(&["Alpha.md", "Beta.md"]),
+            ..Default::default()
+        };
+        state.modal.open();
+        let vault = Vault::default();
+
+        update(
+            Message::KeyEvent(KeyEvent::new(KeyCode::Char('b'), KeyModifiers::NONE)),
+            &vault,
+            &[],
+            &mut state,
+        );
+        assert_eq!(ranked(state.matches()), ["Beta.md"]);
+
+        let Some(AppMessage::Batch(messages)) = update(Message::Submit, &vault, &[], &mut state)
+        else {
+            panic!("expected a batch of messages");
+        };
+        assert!(matches!(&messages[0], AppMessage::SelectNote(note) if note.name() == "Beta"));
+        assert!(!state.is_visible());
+    }
+}
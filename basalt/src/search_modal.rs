@@ -0,0 +1,193 @@
+use basalt_core::obsidian::{
+    search::{self, MatchKind, SearchHit},
+    Vault,
+};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Position, Rect},
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{BorderType, ListItem, StatefulWidget},
+};
+
+pub use crate::query_modal::{handle_editing_event, Message};
+use crate::{
+    app::{ActivePane, Message as AppMessage, SelectedNote},
+    config::Theme,
+    note_editor,
+    query_modal::{self, QueryModal, QueryModalState},
+};
+
+/// Drives the vault search. The query is typed in edit mode; `Enter` runs the search against the
+/// vault and moves focus to the results, where `Select` opens the note at the matched offset.
+pub fn update<'a>(
+    message: Message,
+    vault: &Vault,
+    state: &mut SearchModalState,
+) -> Option<AppMessage<'a>> {
+    match message {
+        Message::Toggle if state.modal.visible => state.modal.hide(),
+        Message::Toggle => state.modal.open(),
+        Message::Submit => state.search(vault),
+        Message::Select => {
+            let hit = state.selected_hit()?.clone();
+            state.modal.hide();
+            return Some(AppMessage::Batch(vec![
+                AppMessage::SelectNote(SelectedNote::from(&hit.note)),
+                AppMessage::NoteEditor(note_editor::Message::JumpToOffset(hit.offset)),
+                AppMessage::SetActivePane(ActivePane::NoteEditor),
+            ]));
+        }
+        message => query_modal::update(&message, &mut state.modal, |_| {}),
+    }
+
+    None
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SearchModalState {
+    pub(crate) modal: QueryModalState<SearchHit>,
+    /// Whether the query was searched for, so an empty result means no matches.
+    searched: bool,
+}
+
+impl SearchModalState {
+    pub fn is_visible(&self) -> bool {
+        self.modal.is_visible()
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.modal.is_editing()
+    }
+
+    pub fn terminal_cursor(&self) -> Option<Position> {
+        self.modal.terminal_cursor()
+    }
+
+    pub fn query(&self) -> &str {
+        self.modal.query()
+    }
+
+    pub fn hits(&self) -> &[SearchHit] {
+        self.modal.results()
+    }
+
+    pub fn selected_hit(&self) -> Option<&SearchHit> {
+        self.modal.selected()
+    }
+
+    /// Runs the query against the vault and leaves edit mode so the results can be browsed.
+    fn search(&mut self, vault: &Vault) {
+        self.modal
+            .set_results(search::search(vault, self.modal.query()));
+        self.modal.editing = false;
+        self.searched = true;
+    }
+}
+
+pub struct SearchModal {
+    pub border_type: BorderType,
+    pub theme: Theme,
+}
+
+impl SearchModal {
+    pub fn new(border_type: BorderType, theme: Theme) -> Self {
+        Self { border_type, theme }
+    }
+
+    fn hit_item<'a>(hit: &'a SearchHit, theme: &Theme, _: usize) -> ListItem<'a> {
+        let location = match hit.kind {
+            MatchKind::Name => String::from(" (name)"),
+            MatchKind::Content => format!(":{}", hit.line + 1),
+        };
+
+        let highlight = hit.highlight.clone();
+        let snippet = Line::from(vec![
+            Span::raw("  "),
+            Span::raw(&hit.snippet[..highlight.start]),
+            Span::raw(&hit.snippet[highlight.clone()])
+                .fg(theme.accent)
+                .bold(),
+            Span::raw(&hit.snippet[highlight.end..]),
+        ]);
+
+        ListItem::new(vec![
+            Line::from(vec![
+                Span::raw(hit.note.name()).bold(),
+                Span::raw(location).fg(theme.muted),
+            ]),
+            snippet,
+        ])
+    }
+}
+
+impl StatefulWidget for SearchModal {
+    type State = SearchModalState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let results_title = match state.hits().len() {
+            _ if !state.searched => String::from("Results"),
+            1 => String::from("1 result"),
+            count => format!("{count} results"),
+        };
+        let empty = if state.searched {
+            "No matches"
+        } else {
+            "Press enter to search"
+        };
+
+        QueryModal::new(
+            "Search",
+            results_title,
+            empty,
+            Self::hit_item,
+            self.border_type,
+            self.theme,
+        )
+        .render(area, buf, &mut state.modal);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use super::*;
+
+    fn type_query(state: &mut SearchModalState, vault: &Vault, query: &str) {
+        query.chars().for_each(|c| {
+            update(
+                Message::KeyEvent(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)),
+                vault,
+                state,
+            );
+        });
+    }
+
+    #[test]
+    fn test_toggle_starts_editing() {
+        let mut state = SearchModalState::default();
+        update(Message::Toggle, &Vault::default(), &mut state);
+        assert!(state.is_visible());
+        assert!(state.is_editing());
+
+        update(Message::Toggle, &Vault::default(), &mut state);
+        assert!(!state.is_visible());
+    }
+
+    #[test]
+    fn test_select_without_hits_stays_open() {
+        let vault = Vault::default();
+        let mut state = SearchModalState::default();
+        update(Message::Toggle, &vault, &mut state);
+        type_query(&mut state, &vault, "milk");
+        assert_eq!(state.query(), "milk");
+
+        update(Message::Submit, &vault, &mut state);
+
+        assert!(!state.is_editing());
+        assert!(state.hits().is_empty());
+        assert_eq!(update(Message::Select, &vault, &mut state), None);
+        assert!(state.is_visible());
+    }
+}
@@ -0,0 +1,283 @@
+//! Shell-style parsing of the `exec:` and `spawn:` commands: splitting a command into arguments
+//! the way a POSIX shell does and expanding the `%` variables.
+
+/// The shell commands run through when `command_shell` is enabled. The commands refer to the
+/// values of the `%` variables as POSIX positional parameters, see [`expand_parameters`], so
+/// `$SHELL` is not used: shells like fish or nushell would not read them.
+pub const POSIX_SHELL: &str = "sh";
+
+/// Splits the command into arguments like a POSIX shell does, without expanding anything.
+///
+/// Arguments are separated by unquoted whitespace. Single quotes keep everything up to the
+/// closing quote as is, double quotes keep everything but `\"`, `\\`, `\$` and `` \` `` escapes,
+/// and a backslash outside quotes keeps the next character as is. On Windows a backslash outside
+/// quotes is kept as is instead, so paths like `C:\Users` can be written without quotes. Returns
+/// `None` if a quote is left open or the command ends in a backslash.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_tui::shell::split;
+///
+/// assert_eq!(
+///     split(r#"open 'My Note.md' "a \"b\"""#),
+///     Some(vec!["open".into(), "My Note.md".into(), "a \"b\"".into()])
+/// );
+/// assert_eq!(split("echo 'open"), None);
+/// ```
+pub fn split(command: &str) -> Option<Vec<String>> {
+    let mut args = Vec::new();
+    let mut arg: Option<String> = None;
+    let mut chars = command.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => args.extend(arg.take()),
+            '\'' => {
+                let arg = arg.get_or_insert_with(String::new);
+                loop {
+                    match chars.next()? {
+                        '\'' => break,
+                        c => arg.push(c),
+                    }
+                }
+            }
+            '"' => {
+                let arg = arg.get_or_insert_with(String::new);
+                loop {
+                    match chars.next()? {
+                        '"' => break,
+                        '\\' => match chars.next()? {
+                            c @ ('"' | '\\' | '$' | '`') => arg.push(c),
+                            '\n' => {}
+                            c => arg.extend(['\\', c]),
+                        },
+                        c => arg.push(c),
+                    }
+                }
+            }
+            '\\' if !cfg!(windows) => match chars.next()? {
+                '\n' => {}
+                c => arg.get_or_insert_with(String::new).push(c),
+            },
+            c => arg.get_or_insert_with(String::new).push(c),
+        }
+    }
+
+    args.extend(arg);
+    Some(args)
+}
+
+/// Replaces the `%` variables of the text with their values, passing each value through
+/// `escape`. The longest variable name wins, so `%note_path` is not read as `%note` followed by
+/// `_path`. Unknown variables are kept as is.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_tui::shell::expand;
+///
+/// let variables = [("note", "Ideas"), ("note_path", "/vault/Ideas.md")];
+///
+/// assert_eq!(
+///     expand("%note at %note_path, 100%", &variables, str::to_string),
+///     "Ideas at /vault/Ideas.md, 100%"
+/// );
+/// ```
+pub fn expand(text: &str, variables: &[(&str, &str)], escape: impl Fn(&str) -> String) -> String {
+    let mut expanded = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(index) = rest.find('%') {
+        expanded.push_str(&rest[..index]);
+        rest = &rest[index + 1..];
+
+        let variable = variables
+            .iter()
+            .filter(|(name, _)| rest.starts_with(name))
+            .max_by_key(|(name, _)| name.len());
+
+        match variable {
+            Some((name, value)) => {
+                expanded.push_str(&escape(value));
+                rest = &rest[name.len()..];
+            }
+            None => expanded.push('%'),
+        }
+    }
+
+    expanded.push_str(rest);
+    expanded
+}
+
+/// Replaces the `%` variables of a command for a POSIX shell with references to the positional
+/// parameters, `${1}` for the first name, `${2}` for the second and so on. The values themselves
+/// are passed to the shell as arguments and never become part of the command, so the shell
+/// cannot read them as shell syntax.
+///
+/// Each reference is quoted for the place it stands in, so the value is read as is and as one
+/// word: unquoted as `"${1}"`, inside double quotes as `${1}` and inside single quotes as
+/// `'"${1}"'`. A `%` escaped with a backslash is kept as is. Unknown variables are kept as is.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_tui::shell::expand_parameters;
+///
+/// let names = ["note", "note_path"];
+///
+/// assert_eq!(
+///     expand_parameters(r#"echo %note "at %note_path" '%note'"#, &names),
+///     r#"echo "${1}" "at ${2}" ''"${1}"''"#
+/// );
+/// ```
+pub fn expand_parameters(command: &str, names: &[&str]) -> String {
+    #[derive(PartialEq)]
+    enum Quote {
+        None,
+        Single,
+        Double,
+    }
+
+    let mut expanded = String::with_capacity(command.len());
+    let mut quote = Quote::None;
+    let mut chars = command.char_indices();
+
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' if quote != Quote::Single => {
+                expanded.push(c);
+                expanded.extend(chars.next().map(|(_, c)| c));
+            }
+            '\'' if quote != Quote::Double => {
+                quote = if quote == Quote::Single {
+                    Quote::None
+                } else {
+                    Quote::Single
+                };
+                expanded.push(c);
+            }
+            '"' if quote != Quote::Single => {
+                quote = if quote == Quote::Double {
+                    Quote::None
+                } else {
+                    Quote::Double
+                };
+                expanded.push(c);
+            }
+            '%' => {
+                let rest = &command[index + 1..];
+                let variable = names
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, name)| rest.starts_with(*name))
+                    .max_by_key(|(_, name)| name.len());
+
+                let Some((position, name)) = variable else {
+                    expanded.push(c);
+                    continue;
+                };
+
+                let parameter = format!("${{{}}}", position + 1);
+                match quote {
+                    Quote::None => expanded.push_str(&format!("\"{parameter}\"")),
+                    Quote::Double => expanded.push_str(&parameter),
+                    Quote::Single => expanded.push_str(&format!("'\"{parameter}\"'")),
+                }
+                chars.nth(name.len() - 1);
+            }
+            c => expanded.push(c),
+        }
+    }
+
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split() {
+        let tests = [
+            ("nvim %note_path", Some(vec!["nvim", "%note_path"])),
+            ("  open   a  ", Some(vec!["open", "a"])),
+            ("echo 'a  b' \"c  d\"", Some(vec!["echo", "a  b", "c  d"])),
+            ("echo ''", Some(vec!["echo", ""])),
+            ("echo a'b'\"c\"", Some(vec!["echo", "abc"])),
+            (r#"echo "\$HOME \n""#, Some(vec!["echo", r"$HOME \n"])),
+            ("echo 'a | b' > c", Some(vec!["echo", "a | b", ">", "c"])),
+            ("", Some(vec![])),
+            ("echo \"open", None),
+        ];
+
+        tests.into_iter().for_each(|(command, expected)| {
+            assert_eq!(
+                split(command),
+                expected.map(|args| args.into_iter().map(String::from).collect()),
+                "{command}"
+            )
+        });
+    }
+
+    #[test]
+    fn test_split_backslashes() {
+        let split = |command| split(command).map(|args| args.join("|"));
+
+        if cfg!(windows) {
+            assert_eq!(
+                split(r"explorer C:\Users\me"),
+                Some(r"explorer|C:\Users\me".into())
+            );
+            assert_eq!(split(r"echo a\ b \"), Some(r"echo|a\|b|\".into()));
+        } else {
+            assert_eq!(
+                split(r"explorer C:\Users\me"),
+                Some("explorer|C:Usersme".into())
+            );
+            assert_eq!(split(r"echo a\ b \'"), Some("echo|a b|'".into()));
+            assert_eq!(split("echo \\"), None);
+        }
+    }
+
+    #[test]
+    fn test_expand() {
+        let variables = [
+            ("vault", "Notes"),
+            ("vault_path", "/home/Notes"),
+            ("note", "My Note"),
+            ("note_path", "/home/Notes/My Note.md"),
+        ];
+
+        assert_eq!(
+            expand("%vault_path/%vault", &variables, str::to_string),
+            "/home/Notes/Notes"
+        );
+        assert_eq!(
+            expand("%unknown %%note", &variables, str::to_string),
+            "%unknown %My Note"
+        );
+    }
+
+    #[test]
+    fn test_expand_parameters() {
+        let names = ["vault", "vault_path", "selection"];
+
+        let tests = [
+            ("echo %selection | pbcopy", r#"echo "${3}" | pbcopy"#),
+            (r#"echo "%selection" | pbcopy"#, r#"echo "${3}" | pbcopy"#),
+            ("echo '%selection' | pbcopy", r#"echo ''"${3}"'' | pbcopy"#),
+            (
+                r#"open "obsidian://open?vault=%vault&file=a" '%vault_path/%vault'"#,
+                r#"open "obsidian://open?vault=${1}&file=a" ''"${2}"'/'"${1}"''"#,
+            ),
+            (r#"echo "it's %vault""#, r#"echo "it's ${1}""#),
+            (r#"echo 'a\' %vault"#, r#"echo 'a\' "${1}""#),
+            (r"echo \%vault 100% %unknown", r"echo \%vault 100% %unknown"),
+        ];
+
+        tests.into_iter().for_each(|(command, expected)| {
+            assert_eq!(expand_parameters(command, &names), expected, "{command}")
+        });
+    }
+}
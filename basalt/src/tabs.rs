@@ -1,4 +1,7 @@
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
 use crate::{app::SelectedNote, config::Theme, note_editor::state::NoteEditorState};
 
@@ -12,6 +15,8 @@ pub struct Tab<'a> {
 pub struct Tabs<'a> {
     tabs: Vec<Tab<'a>>,
     active: usize,
+    /// Paths of the open tabs, most recently focused first.
+    recent: Vec<PathBuf>,
 }
 
 impl<'a> Tabs<'a> {
@@ -47,6 +52,29 @@ impl<'a> Tabs<'a> {
         }
     }
 
+    /// Reads the embedded notes of every open tab again.
+    pub fn refresh_embeds(&mut self) {
+        for tab in &mut self.tabs {
+            tab.editor.refresh_embeds();
+        }
+    }
+
+    /// Returns the paths of the open tabs, most recently focused first.
+    pub fn recent_paths(&self) -> Vec<&Path> {
+        self.recent.iter().map(PathBuf::as_path).collect()
+    }
+
+    /// Moves the active tab to the front of the recently focused tabs and forgets closed ones.
+    fn touch(&mut self) {
+        let tabs = &self.tabs;
+        self.recent
+            .retain(|path| tabs.iter().any(|tab| tab.note.path() == path));
+        if let Some(path) = self.active_note().map(|note| note.path().to_path_buf()) {
+            self.recent.retain(|recent| *recent != path);
+            self.recent.insert(0, path);
+        }
+    }
+
     fn index_of(&self, path: &Path) -> Option<usize> {
         self.tabs.iter().position(|tab| tab.note.path() == path)
     }
@@ -55,6 +83,7 @@ impl<'a> Tabs<'a> {
         match self.index_of(path) {
             Some(index) => {
                 self.active = index;
+                self.touch();
                 true
             }
             None => false,
@@ -64,17 +93,20 @@ impl<'a> Tabs<'a> {
     pub fn open(&mut self, tab: Tab<'a>) {
         self.tabs.push(tab);
         self.active = self.tabs.len() - 1;
+        self.touch();
     }
 
     pub fn next(&mut self) {
         if !self.tabs.is_empty() {
             self.active = (self.active + 1) % self.tabs.len();
+            self.touch();
         }
     }
 
     pub fn prev(&mut self) {
         if !self.tabs.is_empty() {
             self.active = (self.active + self.tabs.len() - 1) % self.tabs.len();
+            self.touch();
         }
     }
 
@@ -82,9 +114,22 @@ impl<'a> Tabs<'a> {
         if self.active < self.tabs.len() {
             self.tabs.remove(self.active);
             self.active = self.active.min(self.tabs.len().saturating_sub(1));
+            self.touch();
         }
     }
 
+    /// Closes every tab whose note lives at or below the given path, e.g. after deleting a note
+    /// or a whole directory.
+    pub fn close_path(&mut self, path: &Path) {
+        let active_path = self.active_note().map(|note| note.path().to_path_buf());
+        self.tabs.retain(|tab| !tab.note.path().starts_with(path));
+        self.active = active_path
+            .and_then(|active_path| self.index_of(&active_path))
+            .unwrap_or(self.active)
+            .min(self.tabs.len().saturating_sub(1));
+        self.touch();
+    }
+
     pub fn rename(&mut self, old: &Path, new: &Path, name: &str) {
         if let Some(tab) = self.tabs.iter_mut().find(|tab| tab.note.path() == old) {
             tab.note.set_path(new);
@@ -92,6 +137,31 @@ impl<'a> Tabs<'a> {
             tab.editor.set_filepath(new);
             tab.editor.set_filename(name);
         }
+        if let Some(path) = self.recent.iter_mut().find(|path| *path == old) {
+            *path = new.to_path_buf();
+        }
+    }
+
+    /// Points the tabs at or below `old` to `new` after a note or directory has been moved.
+    pub fn relocate(&mut self, old: &Path, new: &Path) {
+        for tab in &mut self.tabs {
+            if let Ok(rest) = tab.note.path().strip_prefix(old) {
+                let path = match rest.as_os_str().is_empty() {
+                    true => new.to_path_buf(),
+                    false => new.join(rest),
+                };
+                tab.note.set_path(&path);
+                tab.editor.set_filepath(&path);
+            }
+        }
+        for path in &mut self.recent {
+            if let Ok(rest) = path.strip_prefix(old) {
+                *path = match rest.as_os_str().is_empty() {
+                    true => new.to_path_buf(),
+                    false => new.join(rest),
+                };
+            }
+        }
     }
 
     pub(crate) fn titles(&self) -> Vec<(String, bool, bool)> {
@@ -185,6 +255,59 @@ mod tests {
         assert_eq!(tabs.active_note(), None);
     }
 
+    #[test]
+    fn recent_paths_follow_focus() {
+        let mut tabs = Tabs::default();
+        tabs.open(tab("a"));
+        tabs.open(tab("b"));
+        tabs.open(tab("c"));
+        tabs.open_or_focus(Path::new("/vault/a.md"));
+
+        let recent = [
+            Path::new("/vault/a.md"),
+            Path::new("/vault/c.md"),
+            Path::new("/vault/b.md"),
+        ];
+        assert_eq!(tabs.recent_paths(), recent);
+
+        tabs.close_active();
+        assert_eq!(
+            tabs.recent_paths(),
+            [Path::new("/vault/b.md"), Path::new("/vault/c.md")]
+        );
+    }
+
+    #[test]
+    fn close_path_closes_tabs_under_path() {
+        let mut tabs = Tabs::default();
+        tabs.open(tab("a"));
+        tabs.open(tab_in("dir", "b"));
+        tabs.open(tab_in("dir", "c"));
+        tabs.open(tab("d"));
+        tabs.open_or_focus(Path::new("/vault/a.md"));
+
+        tabs.close_path(Path::new("/vault/dir"));
+        assert_eq!(tabs.active_note().map(SelectedNote::name), Some("a"));
+
+        tabs.close_path(Path::new("/vault/a.md"));
+        assert_eq!(tabs.active_note().map(SelectedNote::name), Some("d"));
+        tabs.close_path(Path::new("/vault/d.md"));
+        assert!(tabs.is_empty());
+    }
+
+    #[test]
+    fn relocate_moves_tabs_under_path() {
+        let mut tabs = Tabs::default();
+        tabs.open(tab_in("dir", "a"));
+        tabs.open(tab("b"));
+
+        tabs.relocate(Path::new("/vault/dir"), Path::new("/vault/archive/dir"));
+        tabs.relocate(Path::new("/vault/b.md"), Path::new("/vault/archive/b.md"));
+
+        assert!(tabs.open_or_focus(Path::new("/vault/archive/dir/a.md")));
+        assert!(tabs.open_or_focus(Path::new("/vault/archive/b.md")));
+    }
+
     #[test]
     fn same_name_tabs_are_disambiguated_by_parent() {
         let mut tabs = Tabs::default();
@@ -0,0 +1,285 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use basalt_core::obsidian::{
+    tags::{TagCache, TagIndex},
+    Note, Vault,
+};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{ListItem, StatefulWidget},
+};
+
+pub use crate::list_pane::Message;
+use crate::{
+    app::{ActivePane, Message as AppMessage, SelectedNote},
+    config::{Symbols, Theme},
+    list_pane::{self, ListPane, ListPaneState},
+};
+
+/// Drives the tags pane. Selecting a tag expands or collapses it, selecting a note opens it.
+/// The tag index is rebuilt whenever the pane opens or the vault changes, reading only the notes
+/// that changed since the last build.
+pub fn update<'a>(
+    message: &Message,
+    vault: &Vault,
+    state: &mut TagsState,
+) -> Option<AppMessage<'a>> {
+    if *message != Message::Select {
+        let TagsState {
+            pane,
+            cache,
+            index,
+            expanded,
+        } = state;
+        return list_pane::update(message, pane, |pane| {
+            *index = cache.index(vault);
+            pane.set_items(rows(index, expanded));
+        });
+    }
+
+    match state.pane.selected()? {
+        Row::Tag { path, .. } => {
+            let path = path.clone();
+            state.toggle_tag(&path);
+            None
+        }
+        Row::Note { note, .. } => {
+            let note = SelectedNote::from(note);
+            state.set_active(false);
+            Some(AppMessage::Batch(vec![
+                AppMessage::SelectNote(note),
+                AppMessage::SetActivePane(ActivePane::NoteEditor),
+            ]))
+        }
+    }
+}
+
+/// A row of the tag tree: a tag, or a note using the tag above it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Row {
+    Tag {
+        /// Full tag path, e.g. `project/alpha`.
+        path: String,
+        /// Last segment of the path, e.g. `alpha`.
+        name: String,
+        /// Number of notes using the tag or a tag nested under it.
+        count: usize,
+        expanded: bool,
+        depth: usize,
+    },
+    Note {
+        note: Note,
+        depth: usize,
+    },
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TagsState {
+    pub(crate) pane: ListPaneState<Row>,
+    pub(crate) cache: TagCache,
+    pub(crate) index: TagIndex,
+    /// Lowercased paths of the expanded tags.
+    pub(crate) expanded: BTreeSet<String>,
+}
+
+impl TagsState {
+    pub fn new(symbols: &Symbols) -> Self {
+        Self {
+            pane: ListPaneState::new(symbols),
+            ..Default::default()
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.pane.set_theme(theme);
+    }
+
+    pub fn set_active(&mut self, active: bool) {
+        self.pane.set_active(active);
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.pane.is_open()
+    }
+
+    pub fn rows(&self) -> &[Row] {
+        self.pane.items()
+    }
+
+    fn toggle_tag(&mut self, path: &str) {
+        let key = path.to_lowercase();
+        if !self.expanded.remove(&key) {
+            self.expanded.insert(key);
+        }
+        self.pane.set_items(rows(&self.index, &self.expanded));
+    }
+}
+
+/// Flattens the tag tree into rows. Nested tags come before the notes of an expanded tag.
+fn rows(index: &TagIndex, expanded: &BTreeSet<String>) -> Vec<Row> {
+    // Every tag path and its parents, keyed by the lowercased path so that e.g. `Project` and
+    // `project/alpha` share the same parent. The first seen spelling is shown.
+    let mut paths: BTreeMap<String, String> = BTreeMap::new();
+    for tag in index.tags() {
+        let mut end = 0;
+        for segment in tag.name.split('/') {
+            end += segment.len();
+            let path = &tag.name[..end];
+            paths
+                .entry(path.to_lowercase())
+                .or_insert_with(|| path.to_string());
+            end += 1;
+        }
+    }
+
+    let mut rows = Vec::new();
+    push_rows(index, expanded, &paths, None, 0, &mut rows);
+    rows
+}
+
+fn push_rows(
+    index: &TagIndex,
+    expanded: &BTreeSet<String>,
+    paths: &BTreeMap<String, String>,
+    parent: Option<&str>,
+    depth: usize,
+    rows: &mut Vec<Row>,
+) {
+    let children = paths
+        .iter()
+        .filter(|(key, _)| key.rsplit_once('/').map(|(parent, _)| parent) == parent);
+
+    for (key, path) in children {
+        let is_expanded = expanded.contains(key);
+        rows.push(Row::Tag {
+            path: path.clone(),
+            name: path.rsplit('/').next().unwrap_or(path).to_string(),
+            count: index.notes(path).len(),
+            expanded: is_expanded,
+            depth,
+        });
+
+        if is_expanded {
+            push_rows(index, expanded, paths, Some(key), depth + 1, rows);
+            if let Some(tag) = index.get(path) {
+                rows.extend(tag.notes.iter().map(|note| Row::Note {
+                    note: note.clone(),
+                    depth: depth + 1,
+                }));
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Tags;
+
+impl Tags {
+    fn row_item<'a>(row: &'a Row, symbols: &Symbols, theme: &Theme) -> ListItem<'a> {
+        let indentation = |depth: usize| Span::raw("  ".repeat(depth));
+        match row {
+            Row::Tag {
+                name,
+                count,
+                expanded,
+                depth,
+                ..
+            } => {
+                let symbol = if *expanded {
+                    &symbols.tree_expanded
+                } else {
+                    &symbols.tree_collapsed
+                };
+                ListItem::new(Line::from(vec![
+                    indentation(*depth),
+                    Span::raw(format!("{symbol} ")).fg(theme.muted),
+                    Span::raw(format!("#{name}")).fg(theme.accent),
+                    Span::raw(format!(" {count}")).fg(theme.muted),
+                ]))
+            }
+            Row::Note { note, depth } => ListItem::new(Line::from(vec![
+                indentation(*depth),
+                Span::raw(format!("{} ", symbols.unselected)).fg(theme.muted),
+                Span::raw(note.name()).fg(theme.text),
+            ])),
+        }
+    }
+}
+
+impl StatefulWidget for Tags {
+    type State = TagsState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        ListPane::new("Tags", "No tags", Self::row_item).render(area, buf, &mut state.pane);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use basalt_core::obsidian::tags::Tag;
+
+    use super::*;
+
+    fn tag(name: &str, notes: &[&str]) -> Tag {
+        Tag {
+            name: name.to_string(),
+            notes: notes
+                .iter()
+                .map(|note| Note::new_unchecked(note, Path::new(&format!("{note}.md"))))
+                .collect(),
+        }
+    }
+
+    fn row_names(state: &TagsState) -> Vec<String> {
+        state
+            .rows()
+            .iter()
+            .map(|row| match row {
+                Row::Tag {
+                    name, count, depth, ..
+                } => format!("{}#{name} {count}", "  ".repeat(*depth)),
+                Row::Note { note, depth } => format!("{}{}", "  ".repeat(*depth), note.name()),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_tag_tree_expands_to_notes() {
+        let mut state = TagsState {
+            index: TagIndex::from(vec![
+                tag("project/alpha", &["Alpha"]),
+                tag("rust", &["Alpha"]),
+                tag("Project/beta", &["Beta"]),
+                tag("project", &["Beta"]),
+            ]),
+            pane: ListPaneState {
+                open: true,
+                active: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        state.pane.set_items(rows(&state.index, &state.expanded));
+        assert_eq!(row_names(&state), ["#project 2", "#rust 1"]);
+
+        update(&Message::Select, &Vault::default(), &mut state);
+        assert_eq!(
+            row_names(&state),
+            ["#project 2", "  #alpha 1", "  #beta 1", "  Beta", "#rust 1"]
+        );
+
+        (0..3).for_each(|_| _ = update(&Message::Down, &Vault::default(), &mut state));
+        let Some(AppMessage::Batch(messages)) =
+            update(&Message::Select, &Vault::default(), &mut state)
+        else {
+            panic!("expected a batch of messages");
+        };
+        assert!(matches!(&messages[0], AppMessage::SelectNote(note) if note.name() == "Beta"));
+        assert!(!state.pane.active);
+    }
+}
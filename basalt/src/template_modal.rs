@@ -0,0 +1,293 @@
+use std::{cmp::Reverse, time::Duration};
+
+use basalt_core::obsidian::{templates, Note, Vault};
+use ratatui::{
+    buffer::Buffer,
+    crossterm::event::KeyEvent,
+    layout::{Position, Rect},
+    text::Line,
+    widgets::{BorderType, ListItem, StatefulWidget},
+};
+use tracing::error;
+
+use crate::{
+    app::Message as AppMessage,
+    config::Theme,
+    fuzzy,
+    query_modal::{self, highlighted, QueryModal, QueryModalState},
+    toast,
+};
+
+/// What is done with the picked template.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TemplateAction {
+    /// Creates a new note from the template.
+    #[default]
+    NewNote,
+    /// Inserts the template at the cursor of the open note.
+    Insert,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    Open(TemplateAction),
+    Modal(query_modal::Message),
+}
+
+/// Drives the template picker. The templates are read from the templates folder of the vault when
+/// the modal opens; `Select` closes the modal and applies the highlighted template. Toggling
+/// reopens the picker for the last action.
+pub fn update<'a>(
+    message: Message,
+    vault: &Vault,
+    state: &mut TemplateModalState,
+) -> Option<AppMessage<'a>> {
+    let message = match message {
+        Message::Open(action) => return state.open(vault, action),
+        Message::Modal(message) => message,
+    };
+
+    match message {
+        query_modal::Message::Toggle if state.modal.visible => state.modal.hide(),
+        query_modal::Message::Toggle => return state.open(vault, state.action),
+        query_modal::Message::Select | query_modal::Message::Submit => {
+            let template = state.selected_match()?.template.clone();
+            state.modal.hide();
+            return Some(AppMessage::ApplyTemplate(template, state.action));
+        }
+        message => {
+            let templates = &state.templates;
+            query_modal::update(&message, &mut state.modal, |modal| {
+                modal.set_results(rank(templates, &modal.query))
+            });
+        }
+    }
+
+    None
+}
+
+pub fn handle_editing_event(key: KeyEvent) -> Option<Message> {
+    query_modal::handle_editing_event(key).map(Message::Modal)
+}
+
+/// A template matching the query.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TemplateMatch {
+    pub template: Note,
+    /// Path of the template relative to the templates folder, without the extension.
+    pub name: String,
+    pub score: i64,
+    /// Byte offsets of the matched characters within [`TemplateMatch::name`].
+    pub indices: Vec<usize>,
+}
+
+/// Ranks the templates by their fuzzy score against the query. Ties keep the order of the
+/// templates folder.
+pub fn rank(templates: &[(Note, String)], query: &str) -> Vec<TemplateMatch> {
+    let mut matches: Vec<TemplateMatch> = templates
+        .iter()
+        .filter_map(|(template, name)| {
+            let fuzzy_match = fuzzy::fuzzy_match(query, name)?;
+            Some(TemplateMatch {
+                template: template.clone(),
+                name: name.clone(),
+                score: fuzzy_match.score,
+                indices: fuzzy_match.indices,
+            })
+        })
+        .collect();
+
+    matches.sort_by_key(|template_match| Reverse(template_match.score));
+    matches
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TemplateModalState {
+    pub(crate) modal: QueryModalState<TemplateMatch>,
+    action: TemplateAction,
+    /// Every template of the vault with its name relative to the templates folder.
+    templates: Vec<(Note, String)>,
+}
+
+impl TemplateModalState {
+    pub fn is_visible(&self) -> bool {
+        self.modal.is_visible()
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.modal.is_editing()
+    }
+
+    pub fn terminal_cursor(&self) -> Option<Position> {
+        self.modal.terminal_cursor()
+    }
+
+    pub fn action(&self) -> TemplateAction {
+        self.action
+    }
+
+    pub fn matches(&self) -> &[TemplateMatch] {
+        self.modal.results()
+    }
+
+    pub fn selected_match(&self) -> Option<&TemplateMatch> {
+        self.modal.selected()
+    }
+
+    /// Lists the templates of the vault. Shows a toast instead of the modal when the vault has no
+    /// templates.
+    fn open<'a>(&mut self, vault: &Vault, action: TemplateAction) -> Option<AppMessage<'a>> {
+        let config = match templates::load(vault) {
+            Ok(config) => config,
+            Err(error) => {
+                error!(?error, "failed to read templates config");
+                return Some(AppMessage::Toast(toast::Message::Create(
+                    toast::Toast::error(
+                        "Failed to read the templates config",
+                        Duration::from_secs(2),
+                    ),
+                )));
+            }
+        };
+
+        let folder = vault.path.join(config.folder.trim().trim_matches('/'));
+        self.templates = config
+            .templates(vault)
+            .into_iter()
+            .map(|template| {
+                let name = template
+                    .path()
+                    .strip_prefix(&folder)
+                    .unwrap_or(template.path())
+                    .with_extension("")
+                    .to_string_lossy()
+                    .replace('\\', "/");
+                (template, name)
+            })
+            .collect();
+
+        if self.templates.is_empty() {
+            return Some(AppMessage::Toast(toast::Message::Create(
+                toast::Toast::info("No templates found", Duration::from_secs(2)),
+            )));
+        }
+
+        self.action = action;
+        self.modal.query.clear();
+        self.modal.open();
+        self.rank();
+        None
+    }
+
+    /// Ranks the templates against the current query and selects the best match.
+    fn rank(&mut self) {
+        self.modal
+            .set_results(rank(&self.templates, &self.modal.query));
+    }
+}
+
+pub struct TemplateModal {
+    pub border_type: BorderType,
+    pub theme: Theme,
+}
+
+impl TemplateModal {
+    pub fn new(border_type: BorderType, theme: Theme) -> Self {
+        Self { border_type, theme }
+    }
+
+    fn match_item<'a>(template_match: &'a TemplateMatch, theme: &Theme, _: usize) -> ListItem<'a> {
+        ListItem::new(Line::from(highlighted(
+            &template_match.name,
+            &template_match.indices,
+            theme,
+        )))
+    }
+}
+
+impl StatefulWidget for TemplateModal {
+    type State = TemplateModalState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let title = match state.action {
+            TemplateAction::NewNote => "New note from template",
+            TemplateAction::Insert => "Insert template",
+        };
+        let results_title = match state.matches().len() {
+            1 => String::from("1 template"),
+            count => format!("{count} templates"),
+        };
+
+        QueryModal::new(
+            title,
+            results_title,
+            "No matching templates",
+            Self::match_item,
+            self.border_type,
+            self.theme,
+        )
+        .size(50, 60)
+        .render(area, buf, &mut state.modal);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use ratatui::crossterm::event::{KeyCode, KeyModifiers};
+
+    use super::*;
+
+    fn templates(names: &[&str]) -> Vec<(Note, String)> {
+        names
+            .iter()
+            .map(|name| {
+                let path = format!("/vault/Templates/{name}.md");
+                let note_name = name.rsplit('/').next().unwrap();
+                (
+                    Note::new_unchecked(note_name, Path::new(&path)),
+                    name.to_string(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_typing_filters_and_select_applies_template() {
+        let mut state = TemplateModalState {
+            action: TemplateAction::Insert,
+            templates: templates(&["Daily", "Work/Meeting", "Weekly review"]),
+            ..Default::default()
+        };
+        state.modal.open();
+        state.rank();
+        assert_eq!(state.matches().len(), 3);
+
+        let vault = Vault::default();
+        for c in "meet".chars() {
+            update(
+                Message::Modal(query_modal::Message::KeyEvent(KeyEvent::new(
+                    KeyCode::Char(c),
+                    KeyModifiers::NONE,
+                ))),
+                &vault,
+                &mut state,
+            );
+        }
+        let names: Vec<&str> = state.matches().iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["Work/Meeting"]);
+
+        let message = update(
+            Message::Modal(query_modal::Message::Submit),
+            &vault,
+            &mut state,
+        );
+        assert!(matches!(
+            message,
+            Some(AppMessage::ApplyTemplate(template, TemplateAction::Insert))
+                if template.name() == "Meeting"
+        ));
+        assert!(!state.is_visible());
+    }
+}